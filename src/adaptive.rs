@@ -0,0 +1,120 @@
+use crate::{battery, log, pressure};
+use std::time::Duration;
+
+// --adaptive：电池电量低或系统资源紧张时放慢 watch 模式的刷新，暂停开销大的模块，
+// 避免监视程序本身加重它所报告的问题；情况缓解后恢复正常的刷新间隔
+
+// 受限时刷新间隔放大的倍数
+const FACTOR: u32 = 4;
+
+// 受限时暂停定时刷新的模块：需要启动外部进程、联网或遍历 /proc 的模块
+// 信号、点击和控制套接字的请求仍会刷新
+const EXPENSIVE: &[&str] = &[
+    "containers",
+    "updates",
+    "weather",
+    "top",
+    "failed-units",
+    "script",
+];
+
+// 电量回升超过阈值这么多、压力降到阈值的一半以下后才恢复，避免在阈值附近来回切换
+const BATTERY_HYSTERESIS: i64 = 2;
+
+pub struct Adaptive {
+    battery_level: i64,
+    max_pressure: f64,
+    constrained: bool,
+}
+
+impl Adaptive {
+    pub fn new(battery_level: i64, max_pressure: f64) -> Self {
+        Adaptive {
+            battery_level,
+            max_pressure,
+            constrained: false,
+        }
+    }
+
+    // 根据放电中的电量（未放电时为 None）和最高的 PSI avg10 判断是否受限，返回原因
+    fn transition(&self, discharging: Option<i64>, pressure: f64) -> Option<String> {
+        let battery_low = discharging.is_some_and(|capacity| {
+            capacity <= self.battery_level
+                || self.constrained && capacity <= self.battery_level + BATTERY_HYSTERESIS
+        });
+        let pressured =
+            pressure > self.max_pressure || self.constrained && pressure > self.max_pressure / 2.0;
+        match (battery_low, pressured) {
+            (true, _) => Some(format!("battery at {}%", discharging.unwrap_or(0))),
+            (false, true) => Some(format!("pressure at {:.1}%", pressure)),
+            (false, false) => None,
+        }
+    }
+
+    // 每次检查时调用，状态改变时返回 true
+    pub fn check(&mut self, device: &str) -> bool {
+        let discharging = (battery::get_battery_status(device).ok().as_deref()
+            == Some("Discharging"))
+        .then(|| {
+            battery::get_battery_capacity(device)
+                .ok()
+                .and_then(|c| c.parse::<i64>().ok())
+        })
+        .flatten();
+        let pressure = pressure::RESOURCES
+            .iter()
+            .filter_map(|resource| pressure::read_avg10(resource).ok())
+            .fold(0.0, f64::max);
+        let reason = self.transition(discharging, pressure);
+        if reason.is_some() == self.constrained {
+            return false;
+        }
+        self.constrained = reason.is_some();
+        match reason {
+            Some(reason) => log::info!("{}, polling {}x slower", reason, FACTOR),
+            None => log::info!("resources recovered, polling at normal intervals"),
+        }
+        true
+    }
+
+    // 模块当前的刷新间隔，暂停时为 None
+    pub fn interval(&self, name: &str, interval: Duration) -> Option<Duration> {
+        if !self.constrained {
+            return Some(interval);
+        }
+        if EXPENSIVE.contains(&name) {
+            return None;
+        }
+        Some(interval * FACTOR)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slows_down_under_pressure() {
+        let mut adaptive = Adaptive::new(20, 30.0);
+        assert_eq!(adaptive.transition(Some(50), 10.0), None);
+        assert_eq!(
+            adaptive.transition(None, 45.0).as_deref(),
+            Some("pressure at 45.0%")
+        );
+        assert_eq!(
+            adaptive.transition(Some(18), 0.0).as_deref(),
+            Some("battery at 18%")
+        );
+        adaptive.constrained = true;
+        // 回升到阈值附近时保持受限
+        assert!(adaptive.transition(Some(21), 20.0).is_some());
+        assert_eq!(adaptive.transition(Some(25), 10.0), None);
+        assert_eq!(adaptive.transition(None, 0.0), None);
+
+        let second = Duration::from_secs(1);
+        assert_eq!(adaptive.interval("cpu", second), Some(second * 4));
+        assert_eq!(adaptive.interval("updates", second), None);
+        adaptive.constrained = false;
+        assert_eq!(adaptive.interval("updates", second), Some(second));
+    }
+}
@@ -0,0 +1,1619 @@
+use crate::{
+    audio, battery, clipboard, diskio, fifo, gpu, hwmon, locale, memory, module, pressure,
+    recorder, render, sysfs, timeutil, updates,
+};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+// 命令行参数的定义、校验和从环境变量补充的参数
+
+// 模块名或输出块名（如 volume），返回模块名
+fn module_name(value: &str) -> Result<String, String> {
+    module::find(value)
+        .map(|entry| entry.id.to_string())
+        .ok_or_else(|| format!("unknown module '{}'", value))
+}
+
+// 百分比参数，如 PSI 阈值：0 到 100
+fn percent(value: &str) -> Result<f64, String> {
+    let percent: f64 = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+    if !(0.0..=100.0).contains(&percent) {
+        return Err(format!("{} is not in 0..=100", value));
+    }
+    Ok(percent)
+}
+
+// 使用 clap 定义命令行参数
+pub fn build_cli() -> clap::Command {
+    clap::Command::new("Battery Info")
+        .version("1.0")
+        .about("Retrieve laptop battery status and level")
+        .arg(
+            clap::Arg::new("battery")
+                .long("battery")
+                .help("Output battery status and capacity")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("battery-state")
+                .long("battery-state")
+                .help("Output battery status only")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("battery-capacity")
+                .long("battery-capacity")
+                .help("Output battery capacity only")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("battery-time")
+                .long("battery-time")
+                .help("Output estimated time to empty or full")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("battery-power")
+                .long("battery-power")
+                .help("Output battery power draw in watts")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("battery-health")
+                .long("battery-health")
+                .help("Output battery health and wear level")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("battery-info")
+                .long("battery-info")
+                .help("Output the battery manufacturer, model, technology, cycle count and serial number from sysfs")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("battery-device")
+                .long("battery-device")
+                .value_name("DEVICE")
+                .help("Battery to read: all, BAT0, BAT1...")
+                .default_value("all"),
+        )
+        .arg(
+            clap::Arg::new("battery-backend")
+                .long("battery-backend")
+                .value_name("BACKEND")
+                .help("Read batteries from sysfs, the UPower D-Bus service or Termux:API (auto: termux on Android, otherwise sysfs)")
+                .value_parser(["auto", "sysfs", "upower", "termux"])
+                .default_value("auto"),
+        )
+        .arg(
+            clap::Arg::new("battery-rounding")
+                .long("battery-rounding")
+                .value_name("MODE")
+                .help("Compute the battery percent from energy_now/energy_full and round it down, to nearest or up (default: the driver's capacity)")
+                .value_parser(locale::Rounding::NAMES),
+        )
+        .arg(
+            clap::Arg::new("battery-smoothing")
+                .long("battery-smoothing")
+                .value_name("FACTOR")
+                .help("In watch mode, average battery power draw so the time left does not jump with load spikes (0-1, smaller is smoother, 1 disables)")
+                .value_parser(battery::parse_smoothing),
+        )
+        .arg(
+            clap::Arg::new("status-style")
+                .long("status-style")
+                .value_name("STYLE")
+                .help("How to show the battery status: full (Charging), short (CHR) or symbol (⚡)")
+                .value_parser(battery::StatusStyle::NAMES)
+                .default_value("full"),
+        )
+        .arg(
+            clap::Arg::new("charger-info")
+                .long("charger-info")
+                .help("Output the charger's negotiated wattage and protocol (e.g. 65W PD), flagged as underpowered when the battery still discharges")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("peripherals")
+                .long("peripherals")
+                .help("Output battery levels of wireless mice, keyboards, headsets and other peripherals reported by UPower, lowest first")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("charge-threshold")
+                .long("charge-threshold")
+                .help("Output the battery charge start/stop thresholds (ThinkPad, ASUS and other laptops that support them)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("ac")
+                .long("ac")
+                .help("Output whether an AC adapter or USB-C power supply is connected, and its wattage when the supply reports voltage and current")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("audio-device")
+                .long("audio-device")
+                .value_name("NAME")
+                .help("Read and adjust the volume of a PulseAudio/PipeWire sink, or an ALSA mixer element given as CARD/ELEMENT (e.g. DAC/PCM), instead of Master on the default card"),
+        )
+        .arg(
+            clap::Arg::new("audio-backend")
+                .long("audio-backend")
+                .value_name("BACKEND")
+                .help("Volume backend when no --audio-device is given: auto tries PulseAudio/PipeWire (pactl) and falls back to ALSA (amixer), pulse and alsa use only one")
+                .value_parser(audio::BACKENDS)
+                .default_value("auto"),
+        )
+        .arg(
+            clap::Arg::new("volume-fallback")
+                .long("volume-fallback")
+                .value_name("MODE")
+                .help("What --volume-level shows when the volume cannot be read: unknown prints Unknown, hide drops the block")
+                .value_parser(["unknown", "hide"])
+                .default_value("unknown"),
+        )
+        .arg(
+            clap::Arg::new("list-audio-devices")
+                .long("list-audio-devices")
+                .help("List sinks and ALSA mixer elements usable with --audio-device")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("volume-level")
+                .long("volume-level")
+                .help("Output volume level")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("show-device")
+                .long("show-device")
+                .help("With --volume-level, append the active output's name, e.g. Headphones or HDMI / DisplayPort")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("backlight")
+                .long("backlight")
+                .help("Output backlight percentage")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("backlight-format")
+                .long("backlight-format")
+                .value_name("FORMAT")
+                .help("What --backlight shows: rounded percent, raw value/max, or both")
+                .value_parser(["percent", "raw", "both"])
+                .default_value("percent"),
+        )
+        .arg(
+            clap::Arg::new("kbd-backlight")
+                .long("kbd-backlight")
+                .help("Output keyboard backlight percentage")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("memory")
+                .long("memory")
+                .help("Output Memory")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("memory-format")
+                .long("memory-format")
+                .value_name("FORMAT")
+                .help("What --memory shows: used/total, free, available or used percent")
+                .value_parser(["used", "free", "available", "percent"])
+                .default_value("used"),
+        )
+        .arg(
+            clap::Arg::new("memory-detail")
+                .long("memory-detail")
+                .value_name("DETAIL")
+                .help("Append zram/zswap usage, dirty pages or cached/buffers to --memory (comma-separated or repeated)")
+                .value_parser(memory::Detail::NAMES)
+                .value_delimiter(',')
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            clap::Arg::new("memory-detail-format")
+                .long("memory-detail-format")
+                .value_name("STYLE")
+                .help("Show --memory-detail compactly for a status bar or with full labels for a terminal")
+                .value_parser(["compact", "verbose"])
+                .default_value("compact"),
+        )
+        .arg(
+            clap::Arg::new("unit")
+                .long("unit")
+                .value_name("UNIT")
+                .help("Unit for memory sizes")
+                .value_parser(["kib", "mib", "gib", "auto"])
+                .default_value("auto"),
+        )
+        .arg(
+            clap::Arg::new("memory-decimals")
+                .long("memory-decimals")
+                .value_name("N")
+                .help("Decimal places for memory sizes in G or auto units")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1"),
+        )
+        .arg(
+            clap::Arg::new("memory-rounding")
+                .long("memory-rounding")
+                .value_name("MODE")
+                .help("How to round memory sizes: round, floor or ceil")
+                .value_parser(locale::Rounding::NAMES)
+                .default_value("round"),
+        )
+        .arg(
+            clap::Arg::new("daylight")
+                .long("daylight")
+                .help("Output time until next sunrise or sunset")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("location")
+                .long("location")
+                .value_name("LAT,LON")
+                .help("Location used by --daylight, e.g. 31.23,121.47"),
+        )
+        .arg(
+            clap::Arg::new("weather")
+                .long("weather")
+                .value_name("LOCATION")
+                .help("Output current temperature and conditions at LOCATION, a place name (e.g. Berlin) or LAT,LON; needs curl"),
+        )
+        .arg(
+            clap::Arg::new("weather-provider")
+                .long("weather-provider")
+                .value_name("PROVIDER")
+                .help("Weather source for --weather")
+                .value_parser(["open-meteo", "wttr"])
+                .default_value("open-meteo"),
+        )
+        .arg(
+            clap::Arg::new("weather-ttl")
+                .long("weather-ttl")
+                .value_name("SECONDS")
+                .help("Reuse a fetched weather report for SECONDS before fetching again; the last report is also used while offline")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("900"),
+        )
+        .arg(
+            clap::Arg::new("next-event")
+                .long("next-event")
+                .help("Output the next upcoming calendar event")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("calendar")
+                .long("calendar")
+                .value_name("PATH")
+                .help("ICS file or calendar directory (e.g. khal vdir) used by --next-event"),
+        )
+        .arg(
+            clap::Arg::new("config")
+                .long("config")
+                .value_name("PATH")
+                .help("Config file, defaults to $XDG_CONFIG_HOME/sys-montion/config.toml")
+                .global(true),
+        )
+        .arg(
+            clap::Arg::new("watch")
+                .long("watch")
+                .value_name("SECS")
+                .help("Keep running and refresh output every SECS seconds")
+                .value_parser(clap::value_parser!(u64).range(1..)),
+        )
+        .arg(
+            clap::Arg::new("signal")
+                .long("signal")
+                .value_name("N")
+                .help("In watch mode, refresh the module immediately on SIGRTMIN+N")
+                .value_parser(clap::value_parser!(i32)),
+        )
+        .arg(
+            clap::Arg::new("min-interval")
+                .long("min-interval")
+                .value_name("MS")
+                .help("In watch mode, print at most one line every MS milliseconds; changes in between are merged into the next line (lines are only printed when the output changes)")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            clap::Arg::new("notify")
+                .long("notify")
+                .help("In watch mode, send alerts when battery is low: desktop notifications, or the webhook, ntfy or command backends chosen in [[alerts]] of the config file")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("battery-warn")
+                .long("battery-warn")
+                .value_name("PCT")
+                .help("Battery level that triggers a warning notification")
+                .value_parser(clap::value_parser!(i64).range(0..=100))
+                .default_value("20"),
+        )
+        .arg(
+            clap::Arg::new("battery-critical")
+                .long("battery-critical")
+                .value_name("PCT")
+                .help("Battery level that triggers a critical notification")
+                .value_parser(clap::value_parser!(i64).range(0..=100))
+                .default_value("10"),
+        )
+        .arg(
+            clap::Arg::new("notify-repeat")
+                .long("notify-repeat")
+                .value_name("SECS")
+                .help("Repeat the critical notification every SECS seconds")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .default_value("300"),
+        )
+        .arg(
+            clap::Arg::new("battery-action")
+                .long("battery-action")
+                .value_name("CMD")
+                .help("In watch mode, run CMD through sh -c (e.g. systemctl suspend) once when the battery drops to --battery-action-level while discharging; it runs again only after the charger is connected or the level recovers")
+        )
+        .arg(
+            clap::Arg::new("battery-action-level")
+                .long("battery-action-level")
+                .value_name("PCT")
+                .help("Battery level that triggers --battery-action")
+                .value_parser(clap::value_parser!(i64).range(0..=100))
+                .default_value("5"),
+        )
+        .arg(
+            clap::Arg::new("battery-action-dry-run")
+                .long("battery-action-dry-run")
+                .help("Log the --battery-action command instead of running it")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("memory-low")
+                .long("memory-low")
+                .value_name("MIB")
+                .help("In watch mode, trigger when MemAvailable falls below MIB")
+                .value_parser(clap::value_parser!(i64).range(1..)),
+        )
+        .arg(
+            clap::Arg::new("memory-pressure")
+                .long("memory-pressure")
+                .value_name("PCT")
+                .help("In watch mode, trigger when memory PSI avg10 exceeds PCT")
+                .value_parser(percent),
+        )
+        .arg(
+            clap::Arg::new("memory-hook")
+                .long("memory-hook")
+                .value_name("CMD")
+                .help("Shell command to run when a memory rule triggers"),
+        )
+        .arg(
+            clap::Arg::new("adaptive")
+                .long("adaptive")
+                .help("In watch mode, refresh modules 4x less often and pause expensive ones (containers, updates, weather, top, failed-units, script) while discharging below --battery-warn or under CPU, memory or I/O pressure")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("adaptive-pressure")
+                .long("adaptive-pressure")
+                .value_name("PERCENT")
+                .help("PSI avg10 above which --adaptive slows polling")
+                .default_value("20")
+                .value_parser(percent),
+        )
+        .arg(
+            clap::Arg::new("fan-alert")
+                .long("fan-alert")
+                .help("In watch mode, notify when fans stay at max or stop while hot")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("fan-max-minutes")
+                .long("fan-max-minutes")
+                .value_name("MINUTES")
+                .help("Alert after fans have been at max speed for MINUTES")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .default_value("10"),
+        )
+        .arg(
+            clap::Arg::new("fan-curve")
+                .long("fan-curve")
+                .value_name("CELSIUS:RPM")
+                .help("Expected fan speed per temperature for --temp, e.g. 60:1500,80:3000; the block turns warning when the fastest fan is slower than expected for the hottest sensor, and critical when all fans stop")
+                .value_parser(hwmon::parse_fan_point)
+                .value_delimiter(',')
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            clap::Arg::new("fan-stall-temp")
+                .long("fan-stall-temp")
+                .value_name("CELSIUS")
+                .help("Alert when fans report 0 RPM above this temperature")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("70"),
+        )
+        .arg(
+            clap::Arg::new("track-usage")
+                .long("track-usage")
+                .help("In watch mode, record daily screen-on and on-battery time, and boot history")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("battery-history")
+                .long("battery-history")
+                .help("In watch mode, record battery level and power once a minute for `battery --history`")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("screen-time")
+                .long("screen-time")
+                .help("Output today's screen-on time")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("usage-report")
+                .long("usage-report")
+                .help("Output screen-on and on-battery time for the last 7 days")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Output format; csv and tsv print a header and then one row per update, env prints module=value lines (e.g. battery_capacity=73) for eval \"$(sys-montion ... --format env)\"")
+                .value_parser(["plain", "i3bar", "waybar", "csv", "tsv", "env"])
+                .default_value("plain"),
+        )
+        .arg(
+            clap::Arg::new("api-version")
+                .long("api-version")
+                .value_name("N")
+                .help("Pin the JSON schema of i3bar, waybar and --format-hook output to version N; every object carries a version field, and new versions only add fields")
+                .value_parser(clap::value_parser!(u32).range(1..=render::API_VERSION as i64)),
+        )
+        .arg(
+            clap::Arg::new("speak-format")
+                .long("speak-format")
+                .help("Output every module as a full English sentence (\"Battery at 73 percent, discharging.\") for piping into espeak, festival or a screen reader")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("format-hook")
+                .long("format-hook")
+                .value_name("COMMAND")
+                .help("Run COMMAND through sh -c on every update with one JSON object per metric (name, text, value, level) on stdin, and print its first output line as the plain status line, e.g. to hide the battery when it is full")
+                .conflicts_with("format"),
+        )
+        .arg(
+            clap::Arg::new("align")
+                .long("align")
+                .help("Pad numbers and fields to the widest width seen so far (e.g. VOL:   7% and VOL: 100%), so watch output in a terminal or monospace bar does not jitter")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("raw")
+                .long("raw")
+                .help("Print only the numeric value of each metric (e.g. 73 instead of Charging: 73%), without labels, units, icons or threshold markers")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("warn-below")
+                .long("warn-below")
+                .value_name("[MODULE=]N")
+                .help("Mark output as warning when the value is below N; MODULE=N applies to one module only (e.g. battery=20). Repeatable")
+                .value_parser(render::parse_threshold)
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            clap::Arg::new("critical-below")
+                .long("critical-below")
+                .value_name("[MODULE=]N")
+                .help("Mark output as critical when the value is below N; MODULE=N applies to one module only (e.g. battery=20). Repeatable")
+                .value_parser(render::parse_threshold)
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            clap::Arg::new("warn-above")
+                .long("warn-above")
+                .value_name("[MODULE=]N")
+                .help("Mark output as warning when the value is above N; MODULE=N applies to one module only (e.g. battery=20). Repeatable")
+                .value_parser(render::parse_threshold)
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            clap::Arg::new("critical-above")
+                .long("critical-above")
+                .value_name("[MODULE=]N")
+                .help("Mark output as critical when the value is above N; MODULE=N applies to one module only (e.g. battery=20). Repeatable")
+                .value_parser(render::parse_threshold)
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            clap::Arg::new("prompt-segment")
+                .long("prompt-segment")
+                .value_name("MODULE")
+                .help("Print MODULE (e.g. battery, cpu) for a shell prompt or starship custom command: no trailing newline, nothing on errors")
+                .value_parser(module_name),
+        )
+        .arg(
+            clap::Arg::new("prompt-shell")
+                .long("prompt-shell")
+                .value_name("SHELL")
+                .help("Wrap --color ansi escapes of --prompt-segment so zsh or bash prompts measure their width correctly")
+                .value_parser(["none", "zsh", "bash"])
+                .default_value("none"),
+        )
+        .arg(
+            clap::Arg::new("color")
+                .long("color")
+                .value_name("MODE")
+                .help("Colorize plain output with ANSI escapes or Pango markup")
+                .value_parser(["none", "ansi", "pango"])
+                .default_value("none"),
+        )
+        .arg(
+            clap::Arg::new("format-preset")
+                .long("format-preset")
+                .value_name("PRESET")
+                .help("Write plain output for polybar (%{F#..}), dzen2 (^fg()), yambar (tag|type|value) or tmux (#[fg=..]) instead of --color")
+                .value_parser(["plain", "polybar", "dzen2", "yambar", "tmux"])
+                .default_value("plain"),
+        )
+        .arg(
+            clap::Arg::new("max-width")
+                .long("max-width")
+                .value_name("[MODULE=]N")
+                .help("Shorten the longest modules (e.g. media titles) so plain output is at most N characters; MODULE=N truncates one module (e.g. media=30) in every format. Repeatable")
+                .value_parser(render::parse_max_width)
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            clap::Arg::new("scroll")
+                .long("scroll")
+                .help("In watch mode, scroll modules longer than their --max-width MODULE=N limit as a marquee instead of truncating them")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("tmux")
+                .long("tmux")
+                .help("Output a compact line for tmux status-right: tmux colors, space-separated, at most --max-width (default 80) characters")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("format-preset"),
+        )
+        .arg(
+            clap::Arg::new("color-normal")
+                .long("color-normal")
+                .value_name("#RRGGBB")
+                .help("Color used when no threshold is reached")
+                .value_parser(render::parse_color),
+        )
+        .arg(
+            clap::Arg::new("color-warning")
+                .long("color-warning")
+                .value_name("#RRGGBB")
+                .help("Color used at warning level")
+                .value_parser(render::parse_color)
+                .default_value("#FFFF00"),
+        )
+        .arg(
+            clap::Arg::new("color-critical")
+                .long("color-critical")
+                .value_name("#RRGGBB")
+                .help("Color used at critical level")
+                .value_parser(render::parse_color)
+                .default_value("#FF0000"),
+        )
+        .arg(
+            clap::Arg::new("icons")
+                .long("icons")
+                .value_name("SET")
+                .help("Prefix output with an icon from this set")
+                .value_parser(["none", "nerd", "emoji", "ascii"])
+                .default_value("none"),
+        )
+        .arg(
+            clap::Arg::new("icon")
+                .long("icon")
+                .value_name("GLYPH")
+                .help("Override the icon chosen by --icons"),
+        )
+        .arg(
+            clap::Arg::new("skip-missing")
+                .long("skip-missing")
+                .help("Silently omit modules whose device is missing (no battery, no backlight) from combined and watch output")
+                .action(clap::ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            clap::Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Explain why a module failed; one-shot mode exits with 3 (missing device), 4 (parse error), 5 (permission denied) or 6 (backend unavailable). In watch mode, -v logs detected devices and backends, -vv also logs wakeups")
+                .action(clap::ArgAction::Count)
+                .global(true),
+        )
+        .arg(
+            clap::Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Only log errors, not warnings")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("verbose")
+                .global(true),
+        )
+        .arg(
+            clap::Arg::new("log-file")
+                .long("log-file")
+                .value_name("PATH")
+                .help("Append log messages to PATH instead of stderr")
+                .global(true),
+        )
+        .arg(
+            clap::Arg::new("sysfs-root")
+                .long("sysfs-root")
+                .value_name("DIR")
+                .help("Read /sys and /proc under DIR, e.g. a host tree mounted into a container or a test fixture tree; also read from SYS_MONTION_SYSFS_ROOT")
+                .global(true),
+        )
+        .arg(
+            clap::Arg::new("locale")
+                .long("locale")
+                .value_name("LOCALE")
+                .help("Locale for number formatting; auto reads LC_ALL/LC_NUMERIC/LANG")
+                .default_value("C"),
+        )
+        .arg(
+            clap::Arg::new("lang")
+                .long("lang")
+                .value_name("LANG")
+                .help("Language for labels such as Charging and MUTED; auto reads LC_ALL/LC_MESSAGES/LANG. Translations in ~/.config/sys-montion/locale/LANG.po override the built-in ones")
+                .default_value("C"),
+        )
+        .arg(
+            clap::Arg::new("now")
+                .long("now")
+                .value_name("TIMESTAMP")
+                .help("Freeze the clock at TIMESTAMP (Unix seconds or YYYY-MM-DDTHH:MM:SSZ)")
+                .value_parser(timeutil::parse_timestamp),
+        )
+        .arg(
+            clap::Arg::new("stdin")
+                .long("stdin")
+                .help("Answer queries read from stdin, one per line (MODULE [json], e.g. battery or memory json), until stdin closes")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("output")
+                .long("output")
+                .value_name("TARGET")
+                .help("In watch mode, also write each status line to a named pipe (fifo:PATH, created if missing); repeatable")
+                .value_parser(fifo::parse_target)
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            clap::Arg::new("log-metrics")
+                .long("log-metrics")
+                .value_name("FILE")
+                .help("In watch mode, append every update to FILE as a timestamped sample, for long-term logging")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            clap::Arg::new("log-metrics-format")
+                .long("log-metrics-format")
+                .value_name("FORMAT")
+                .help("Sample format for --log-metrics: one JSON object per line, or CSV with a header")
+                .value_parser(recorder::FORMATS)
+                .default_value("jsonl"),
+        )
+        .arg(
+            clap::Arg::new("log-metrics-max-size")
+                .long("log-metrics-max-size")
+                .value_name("MIB")
+                .help("Rotate the --log-metrics file when it reaches MIB mebibytes, keeping FILE.1 to FILE.3")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .default_value("10"),
+        )
+        .arg(
+            clap::Arg::new("xsetroot")
+                .long("xsetroot")
+                .help("In watch mode, write output to the X11 root window name for dwm")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("check")
+                .long("check")
+                .help("Exit with 0 (ok), 1 (warning) or 2 (critical) by the --warn-*/--critical-* thresholds, 3 if a module cannot be read")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("systemd")
+                .long("systemd")
+                .help("In watch mode, notify systemd when ready, send watchdog pings and log with journald priorities")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("osd")
+                .long("osd")
+                .help("Show volume or backlight level as a progress-bar notification")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("dbus")
+                .long("dbus")
+                .help("In watch mode, expose battery, volume and brightness as the org.sysmontion.Monitor D-Bus service")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("sink-hook")
+                .long("sink-hook")
+                .value_name("CMD")
+                .help("In watch mode, shell command to run when the default audio sink changes"),
+        )
+        .arg(
+            clap::Arg::new("mounts")
+                .long("mounts")
+                .help("Output mounted removable drives and their free space")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("throttle")
+                .long("throttle")
+                .help("Output CPU thermal throttle events since boot")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("flaps")
+                .long("flaps")
+                .value_name("IFACE")
+                .help(
+                    "Output how often IFACE lost carrier in the last hour (tracked in watch mode)",
+                ),
+        )
+        .arg(
+            clap::Arg::new("vpn")
+                .long("vpn")
+                .help("Output active WireGuard or tun VPN interfaces, nothing when none is up")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("connectivity")
+                .long("connectivity")
+                .help("Output network connectivity: online, limited, captive portal or offline")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("ip")
+                .long("ip")
+                .value_name("IFACE")
+                .help("Output the primary address of IFACE, or of the default route interface")
+                .num_args(0..=1)
+                .default_missing_value("default"),
+        )
+        .arg(
+            clap::Arg::new("net-rate")
+                .long("net-rate")
+                .value_name("IFACE")
+                .help("Output receive/transmit throughput of IFACE, or of the default route interface")
+                .num_args(0..=1)
+                .default_missing_value("default"),
+        )
+        .arg(
+            clap::Arg::new("wifi-detail")
+                .long("wifi-detail")
+                .value_name("IFACE")
+                .help("Output SSID, band (2.4/5/6 GHz), bitrate and security of the Wi-Fi connection on IFACE, or on the first wireless interface; nothing when not connected")
+                .num_args(0..=1)
+                .default_missing_value("auto"),
+        )
+        .arg(
+            clap::Arg::new("diskio")
+                .long("diskio")
+                .value_name("DEVICE")
+                .help("Output read/write throughput of DEVICE (e.g. nvme0n1), or of all disks")
+                .num_args(0..=1)
+                .default_missing_value("all"),
+        )
+        .arg(
+            clap::Arg::new("disk-temp")
+                .long("disk-temp")
+                .value_name("DEVICE")
+                .help("Output the temperature of NVMe drive DEVICE (e.g. nvme0), or of all NVMe drives, flagging SMART health failures when smartctl can read them")
+                .num_args(0..=1)
+                .default_missing_value("all"),
+        )
+        .arg(
+            clap::Arg::new("temp")
+                .long("temp")
+                .value_name("SENSOR")
+                .help("Output the temperature of a hwmon sensor given as CHIP:LABEL (e.g. nvme:Composite, k10temp:Tctl) or CHIP, of the CPU cores with cpu, or of the hottest sensor with max (default); repeat to output several")
+                .num_args(0..=1)
+                .default_missing_value("max")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            clap::Arg::new("cpu-temp-agg")
+                .long("cpu-temp-agg")
+                .value_name("AGG")
+                .help("How --temp cpu combines the per-core sensors: max (hottest core, default), avg (average of the cores) or per-core (list every core)")
+                .value_parser(hwmon::CPU_TEMP_AGGREGATIONS)
+                .default_value("max"),
+        )
+        .arg(
+            clap::Arg::new("count")
+                .long("count")
+                .value_names(["DIR", "GLOB"])
+                .help("Output how many files in DIR match GLOB (default *), updated via inotify")
+                .num_args(1..=2),
+        )
+        .arg(
+            clap::Arg::new("script")
+                .long("script")
+                .value_name("COMMAND")
+                .help("Output the first line a shell command prints (config: name = \"script\", command = ...)"),
+        )
+        .arg(
+            clap::Arg::new("plugin")
+                .long("plugin")
+                .value_name("PATH")
+                .help("Output the text returned by the sys_montion_read function of shared library PATH (config: name = \"plugin\", path = ...). Repeatable")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            clap::Arg::new("top")
+                .long("top")
+                .value_name("N")
+                .help("Output the N processes using the most CPU or memory")
+                .num_args(0..=1)
+                .default_missing_value("1")
+                .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..)),
+        )
+        .arg(
+            clap::Arg::new("top-by")
+                .long("top-by")
+                .value_name("KEY")
+                .help("Sort --top by CPU usage or resident memory")
+                .value_parser(["cpu", "rss"])
+                .default_value("cpu"),
+        )
+        .arg(
+            clap::Arg::new("bluetooth")
+                .long("bluetooth")
+                .help("Output bluetooth adapter state and connected devices with battery")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("mic")
+                .long("mic")
+                .help("Output the default microphone's mute state and capture level")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("media")
+                .long("media")
+                .help("Output the artist, title and playback state of an MPRIS media player")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("media-player")
+                .long("media-player")
+                .value_name("NAME")
+                .help("MPRIS player for --media and the media command (default: the playing one)")
+                .global(true),
+        )
+        .arg(
+            clap::Arg::new("media-max-length")
+                .long("media-max-length")
+                .value_name("N")
+                .help("Truncate --media output to N characters, 0 for no limit")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("40"),
+        )
+        .arg(
+            clap::Arg::new("cpu")
+                .long("cpu")
+                .help("Output overall CPU usage since the last refresh or invocation")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("pressure")
+                .long("pressure")
+                .value_name("RESOURCE")
+                .help("Output the share of the last 10 seconds tasks stalled waiting for RESOURCE (PSI avg10)")
+                .value_parser(pressure::RESOURCES),
+        )
+        .arg(
+            clap::Arg::new("cgroup")
+                .long("cgroup")
+                .value_name("PATH")
+                .help("Output memory and CPU usage of a cgroup v2 group, PATH relative to /sys/fs/cgroup (e.g. user.slice)"),
+        )
+        .arg(
+            clap::Arg::new("containers")
+                .long("containers")
+                .help("Output the number of running Docker or Podman containers")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("failed-units")
+                .long("failed-units")
+                .help("Output OK, or how many systemd units (system and user) are in a failed state")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("updates")
+                .long("updates")
+                .help("Output the number of pending package updates")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("updates-backend")
+                .long("updates-backend")
+                .value_name("BACKEND")
+                .help("Package manager to check for --updates (auto: the first of checkupdates, apt-get, dnf found in PATH)")
+                .value_parser(updates::Backend::NAMES)
+                .default_value("auto"),
+        )
+        .arg(
+            clap::Arg::new("updates-ttl")
+                .long("updates-ttl")
+                .value_name("SECONDS")
+                .help("Reuse the last update count for SECONDS before checking again; it is also used while offline")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("3600"),
+        )
+        .arg(
+            clap::Arg::new("clipboard")
+                .long("clipboard")
+                .value_name("SELECTION")
+                .help("Output whether the clipboard or primary selection holds text or an image, and its size")
+                .value_parser(clipboard::SELECTIONS)
+                .num_args(0..=1)
+                .default_missing_value("clipboard"),
+        )
+        .arg(
+            clap::Arg::new("timer")
+                .long("timer")
+                .help("Output the time left on the timer started with `sys-montion timer start`")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("nightlight")
+                .long("nightlight")
+                .help("Output the screen color temperature set by gammastep, redshift or wlsunset, or off")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("workspace")
+                .long("workspace")
+                .help("Output the workspaces from the sway, i3 or Hyprland IPC, e.g. 1 [2] 3! (focused in brackets, urgent marked)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("window-title")
+                .long("window-title")
+                .help("Output the focused window's title from the sway, i3 or Hyprland IPC")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("locks")
+                .long("locks")
+                .help("Output CAPS, NUM or SCRL while Caps Lock, Num Lock or Scroll Lock is on, nothing otherwise")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("lid")
+                .long("lid")
+                .help("Output the laptop lid state, open or closed, from /proc/acpi/button/lid or logind")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("docked")
+                .long("docked")
+                .help("Output DOCK and the connected external displays (DRM connectors) while docked, nothing otherwise")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("idle")
+                .long("idle")
+                .help("Output the seconds since the last input, from the logind session's IdleHint (set by the desktop or e.g. swayidle idlehint)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("locked")
+                .long("locked")
+                .help("Output LOCKED while the logind session is locked, nothing otherwise")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("gpu-temp")
+                .long("gpu-temp")
+                .value_name("SENSOR")
+                .help("Output the GPU temperature from the amdgpu or nouveau hwmon sensors, or nvidia-smi for the NVIDIA driver: edge (default), junction (hotspot), mem, or all of them")
+                .value_parser(gpu::SENSORS)
+                .num_args(0..=1)
+                .default_missing_value("edge"),
+        )
+        .arg(
+            clap::Arg::new("gpu-device")
+                .long("gpu-device")
+                .value_name("CARD")
+                .help("DRM card for --gpu-temp, e.g. card1 for the discrete GPU of a hybrid-graphics laptop; defaults to the first card with a temperature sensor"),
+        )
+        .arg(
+            clap::Arg::new("containers-stats")
+                .long("containers-stats")
+                .help("With --containers, also show the running containers' total CPU and memory use")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("containers-socket")
+                .long("containers-socket")
+                .value_name("PATH")
+                .help("Container engine API socket (default: $DOCKER_HOST, the Docker socket, then Podman's)"),
+        )
+        .arg(
+            clap::Arg::new("cpu-freq")
+                .long("cpu-freq")
+                .help("Output average CPU frequency and governor, flagging capped frequencies")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("gamemode")
+                .long("gamemode")
+                .help("Output Feral gamemode state, CPU governor and GPU profile")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("privacy")
+                .long("privacy")
+                .help("Output REC: cam/mic while a process has a webcam open or a microphone is capturing")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("dnd")
+                .long("dnd")
+                .help("Output whether the notification daemon (dunst, mako or swaync) is in Do Not Disturb mode")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("caffeine")
+                .long("caffeine")
+                .help("Output whether `caffeine on` is blocking idle and suspend through logind")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("power-profile")
+                .long("power-profile")
+                .help("Output the active power profile from power-profiles-daemon")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("prometheus")
+                .long("prometheus")
+                .value_name("ADDR:PORT")
+                .help("Serve metrics on /metrics in Prometheus text format")
+                .num_args(0..=1)
+                .default_missing_value("127.0.0.1:9101"),
+        )
+        .arg(
+            clap::Arg::new("http")
+                .long("http")
+                .value_name("ADDR:PORT")
+                .help("Serve the selected modules as JSON on /metrics.json, and each module on /MODULE (e.g. /battery), reading them on every request; binding to a LAN address to reach it from other devices requires --http-token")
+                .num_args(0..=1)
+                .default_missing_value("127.0.0.1:8725"),
+        )
+        .arg(
+            clap::Arg::new("http-token")
+                .long("http-token")
+                .value_name("TOKEN")
+                .help("Require Authorization: Bearer TOKEN on --http requests; mandatory when --http listens on anything but localhost"),
+        )
+        .arg(
+            clap::Arg::new("http-cors")
+                .long("http-cors")
+                .value_name("ORIGIN")
+                .help("Send Access-Control-Allow-Origin: ORIGIN with --http responses so a browser dashboard on that origin can read them (e.g. http://localhost:3000, or * for any page)"),
+        )
+        .arg(
+            clap::Arg::new("mqtt")
+                .long("mqtt")
+                .value_name("HOST[:PORT]")
+                .help("Publish metrics to an MQTT broker every --watch seconds (default 60)"),
+        )
+        .arg(
+            clap::Arg::new("mqtt-prefix")
+                .long("mqtt-prefix")
+                .value_name("PREFIX")
+                .help("MQTT topic prefix; metrics go to PREFIX/HOSTNAME/METRIC")
+                .default_value("sys-montion"),
+        )
+        .arg(
+            clap::Arg::new("mqtt-user")
+                .long("mqtt-user")
+                .value_name("USER")
+                .help("MQTT username; the password is read from SYS_MONTION_MQTT_PASSWORD"),
+        )
+        .arg(
+            clap::Arg::new("mqtt-discovery")
+                .long("mqtt-discovery")
+                .help("Publish Home Assistant MQTT discovery configs")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .subcommand(
+            clap::Command::new("eject")
+                .about("Sync, unmount and power off a removable drive via udisks2")
+                .arg(
+                    clap::Arg::new("device")
+                        .value_name("DEVICE")
+                        .help("Device (/dev/sdb, sdb1) or mount point of the drive")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("config")
+                .about("Work with the config file")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::Command::new("check")
+                        .about("Validate the config file against this system and preview its output"),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("import")
+                .about("Convert an i3status, i3status-rust or slstatus config and print it as TOML")
+                .arg(
+                    clap::Arg::new("from")
+                        .long("from")
+                        .value_name("BAR")
+                        .help("Config format to import")
+                        .value_parser(["i3status", "i3status-rust", "slstatus"])
+                        .required(true),
+                )
+                .arg(
+                    clap::Arg::new("file")
+                        .value_name("CONFIG")
+                        .help("Path to the config file (slstatus: config.h)")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("capabilities")
+                .about("List every module and feature, whether it works on this host and which backend it uses")
+                .arg(
+                    clap::Arg::new("json")
+                        .long("json")
+                        .help("Print the list as JSON")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("doctor").about(
+                "Check battery, backlight, sensor and audio backends and write permissions, and suggest fixes; exits with 1 if a check fails",
+            ),
+        )
+        .subcommand(
+            clap::Command::new("all").about(
+                "Output every module that works on this host in a default order, skipping missing hardware; combine with --watch for a status line without a config file",
+            ),
+        )
+        .subcommand(
+            clap::Command::new("list").about(
+                "List detected batteries, backlights, sensors, network interfaces, audio devices and disks",
+            ),
+        )
+        .subcommand(
+            clap::Command::new("media")
+                .about("Control an MPRIS media player")
+                .arg(
+                    clap::Arg::new("action")
+                        .value_name("ACTION")
+                        .value_parser(["play-pause", "next", "prev"])
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("kbd-backlight")
+                .about("Adjust the keyboard backlight")
+                .subcommand_required(true)
+                .arg(notify_arg())
+                .subcommand(clap::Command::new("up").about("Raise the backlight one level"))
+                .subcommand(clap::Command::new("down").about("Lower the backlight one level"))
+                .subcommand(
+                    clap::Command::new("set")
+                        .about("Set the backlight to PERCENT")
+                        .arg(
+                            clap::Arg::new("percent")
+                                .value_name("PERCENT")
+                                .value_parser(clap::value_parser!(i64).range(0..=100))
+                                .required(true),
+                        ),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("charge-threshold")
+                .about("Change the battery charge thresholds")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::Command::new("set")
+                        .about("Start charging below START% and stop at STOP%")
+                        .arg(
+                            clap::Arg::new("start")
+                                .value_name("START")
+                                .value_parser(clap::value_parser!(i64).range(0..=100))
+                                .required(true),
+                        )
+                        .arg(
+                            clap::Arg::new("stop")
+                                .value_name("STOP")
+                                .value_parser(clap::value_parser!(i64).range(1..=100))
+                                .required(true),
+                        ),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("backlight")
+                .about("Adjust the screen backlight")
+                .subcommand_required(true)
+                .arg(
+                    clap::Arg::new("fade")
+                        .long("fade")
+                        .value_name("MS")
+                        .help("Ramp the brightness over MS milliseconds in 20 steps")
+                        .value_parser(clap::value_parser!(u64))
+                        .global(true),
+                )
+                .arg(notify_arg())
+                .subcommand(clap::Command::new("up").about("Raise the backlight by 5%"))
+                .subcommand(clap::Command::new("down").about("Lower the backlight by 5%"))
+                .subcommand(
+                    clap::Command::new("set")
+                        .about("Set the backlight to PERCENT")
+                        .arg(
+                            clap::Arg::new("percent")
+                                .value_name("PERCENT")
+                                .value_parser(clap::value_parser!(i32).range(0..=100))
+                                .required(true),
+                        )
+                        .arg(
+                            clap::Arg::new("all")
+                                .long("all")
+                                .help("Set every backlight device and every external monitor that supports DDC/CI (needs the i2c-dev module and access to /dev/i2c-*)")
+                                .action(clap::ArgAction::SetTrue)
+                                .conflicts_with("fade"),
+                        ),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("volume")
+                .about("Adjust the default output volume")
+                .arg(notify_arg())
+                .arg(
+                    clap::Arg::new("action")
+                        .value_name("ACTION")
+                        .value_parser(["up", "down", "mute"])
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("dnd")
+                .about("Control the notification daemon's Do Not Disturb mode")
+                .subcommand_required(true)
+                .subcommand(clap::Command::new("toggle").about("Turn Do Not Disturb on or off")),
+        )
+        .subcommand(
+            clap::Command::new("caffeine")
+                .about("Block idle and suspend with a systemd-logind inhibitor")
+                .arg(
+                    clap::Arg::new("action")
+                        .value_name("ACTION")
+                        .value_parser(["on", "off", "toggle"])
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("nightlight")
+                .about("Control the running gammastep, redshift or wlsunset")
+                .subcommand_required(true)
+                .subcommand(clap::Command::new("toggle").about("Turn the night light on or off")),
+        )
+        .subcommand(
+            clap::Command::new("timer")
+                .about("Start or stop the countdown shown by --timer")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::Command::new("start")
+                        .about("Count down DURATION (e.g. 25m, 1h), replacing a running timer")
+                        .arg(
+                            clap::Arg::new("duration")
+                                .value_name("DURATION")
+                                .value_parser(timeutil::parse_duration)
+                                .required(true),
+                        ),
+                )
+                .subcommand(clap::Command::new("stop").about("Stop and clear the timer")),
+        )
+        .subcommand(
+            clap::Command::new("power-profile")
+                .about("Switch the power-profiles-daemon profile")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::Command::new("set").about("Activate PROFILE").arg(
+                        clap::Arg::new("profile")
+                            .value_name("PROFILE")
+                            .value_parser(["performance", "balanced", "power-saver"])
+                            .required(true),
+                    ),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("generate")
+                .about("Generate files for installing sys-montion")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::Command::new("completions")
+                        .about("Print a shell completion script")
+                        .arg(
+                            clap::Arg::new("shell")
+                                .value_name("SHELL")
+                                .value_parser(["bash", "zsh", "fish"])
+                                .required(true),
+                        ),
+                )
+                .subcommand(clap::Command::new("man").about("Print the man page in roff format"))
+                .subcommand(
+                    clap::Command::new("udev-rules")
+                        .about("Print udev rules letting GROUP change backlight, LEDs and charge thresholds without root")
+                        .arg(
+                            clap::Arg::new("group")
+                                .long("group")
+                                .value_name("GROUP")
+                                .default_value("video"),
+                        ),
+                )
+                .subcommand(
+                    clap::Command::new("systemd-unit")
+                        .about("Print a systemd user service running sys-montion with ARGS (default --watch 5)")
+                        .arg(
+                            clap::Arg::new("args")
+                                .value_name("ARGS")
+                                .num_args(1..)
+                                .trailing_var_arg(true)
+                                .allow_hyphen_values(true),
+                        ),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("battery")
+                .about("Show recorded battery history (needs --battery-history)")
+                .arg(
+                    clap::Arg::new("history")
+                        .long("history")
+                        .value_name("WINDOW")
+                        .help("Time window to summarize, e.g. 30m, 1h or 2d")
+                        .value_parser(timeutil::parse_duration)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("uptime-history")
+                .about("Summarize reboots per week and the longest uptime (needs --track-usage)"),
+        )
+        .subcommand(
+            clap::Command::new("ctl")
+                .about("Control a running --watch instance over its socket")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::Command::new("refresh")
+                        .about("Refresh all modules, or only MODULE")
+                        .arg(clap::Arg::new("module").value_name("MODULE")),
+                )
+                .subcommand(
+                    clap::Command::new("get")
+                        .about("Print the cached output of MODULE")
+                        .arg(clap::Arg::new("module").value_name("MODULE").required(true))
+                        .arg(
+                            clap::Arg::new("json")
+                                .long("json")
+                                .help("Print name, text and value as JSON")
+                                .action(clap::ArgAction::SetTrue),
+                        ),
+                ),
+        )
+}
+
+// 控制子命令的 --notify 参数
+fn notify_arg() -> clap::Arg {
+    clap::Arg::new("notify")
+        .long("notify")
+        .help("Show the new level as a progress-bar notification")
+        .action(clap::ArgAction::SetTrue)
+        .global(true)
+}
+
+// 选项对应的环境变量，如 --battery-device -> SYS_MONTION_BATTERY_DEVICE
+fn env_name(long: &str) -> String {
+    format!("SYS_MONTION_{}", long.to_uppercase().replace('-', "_"))
+}
+
+// 从环境变量补充命令行参数，便于在 initramfs、kiosk 镜像等不便放置配置文件的环境中使用：
+// SYS_MONTION_MODULES=battery,volume,memory 在命令行没有选择模块时选择模块（模块名或输出块名），
+// 带值的模块从同名变量取值，如 SYS_MONTION_FLAPS=wlan0；
+// SYS_MONTION_<选项> 在命令行未指定该选项时作为其值，如 SYS_MONTION_MEMORY_FORMAT=percent，
+// 开关选项取 1、true 或 yes。返回补充后的参数和无法识别的模块名
+pub fn env_args(
+    cli: &clap::Command,
+    args: Vec<OsString>,
+    var: impl Fn(&str) -> Option<String>,
+) -> (Vec<OsString>, Vec<String>) {
+    let var = |name: &str| var(name).filter(|value| !value.is_empty());
+    let given = |long: &str| {
+        args.iter().skip(1).any(|arg| {
+            let arg = arg.to_string_lossy();
+            arg.strip_prefix("--")
+                .and_then(|arg| arg.strip_prefix(long))
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('='))
+        })
+    };
+    let mut extra = Vec::new();
+    let mut unknown = Vec::new();
+    for arg in cli.get_arguments() {
+        let Some(long) = arg.get_long() else {
+            continue;
+        };
+        if module::get(long).is_some() || given(long) {
+            continue;
+        }
+        let Some(value) = var(&env_name(long)) else {
+            continue;
+        };
+        if arg.get_action().takes_values() {
+            extra.push(format!("--{}={}", long, value));
+        } else if matches!(value.as_str(), "1" | "true" | "yes") {
+            extra.push(format!("--{}", long));
+        }
+    }
+    let modules = var("SYS_MONTION_MODULES").filter(|_| !module::names().any(given));
+    for name in modules
+        .iter()
+        .flat_map(|modules| modules.split(','))
+        .map(str::trim)
+    {
+        match module::find(name).map(|entry| entry.id) {
+            Some(module) => match var(&env_name(module)) {
+                Some(value) => extra.push(format!("--{}={}", module, value)),
+                None => extra.push(format!("--{}", module)),
+            },
+            None if !name.is_empty() => unknown.push(name.to_string()),
+            None => {}
+        }
+    }
+    // 补充的参数放在程序名之后，位于子命令之前
+    let mut args = args.into_iter();
+    let result = args
+        .next()
+        .into_iter()
+        .chain(extra.into_iter().map(OsString::from))
+        .chain(args)
+        .collect();
+    (result, unknown)
+}
+
+// 设备名参数在解析后按 `list` 子命令列出的设备检查，需要先设置 --sysfs-root；
+// 本机没有该类设备时不检查，由模块报告缺少设备
+fn check_device(
+    matches: &clap::ArgMatches,
+    arg: &str,
+    kind: &str,
+    special: &[&str],
+    devices: impl FnOnce() -> Vec<String>,
+) -> Result<(), String> {
+    if matches.value_source(arg) != Some(clap::parser::ValueSource::CommandLine) {
+        return Ok(());
+    }
+    let Some(name) = matches
+        .get_one::<String>(arg)
+        .filter(|name| !special.contains(&name.as_str()))
+    else {
+        return Ok(());
+    };
+    let devices = devices();
+    if devices.is_empty() || devices.contains(name) {
+        return Ok(());
+    }
+    Err(format!(
+        "unknown {} '{}' for '--{}', available: {} (see `sys-montion list`)",
+        kind,
+        name,
+        arg,
+        devices.join(", ")
+    ))
+}
+
+fn interfaces() -> Vec<String> {
+    let Ok(entries) = sysfs::read_dir("/sys/class/net") else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+// 检查 --battery-device、--diskio 和网卡参数中的设备是否存在
+pub fn check_devices(matches: &clap::ArgMatches) -> Result<(), String> {
+    // UPower、Termux 的电池名与 sysfs 不同
+    if battery::backend() == battery::Backend::Sysfs {
+        check_device(matches, "battery-device", "battery", &["all"], || {
+            battery::list_batteries().unwrap_or_default()
+        })?;
+    }
+    check_device(matches, "diskio", "disk", &["all"], || {
+        diskio::list_disks().unwrap_or_default()
+    })?;
+    for arg in ["ip", "net-rate", "flaps", "wifi-detail"] {
+        check_device(matches, arg, "interface", &["default", "auto"], interfaces)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_options_from_environment() {
+        let env = |name: &str| match name {
+            "SYS_MONTION_MODULES" => Some("battery, volume,flaps,clock".to_string()),
+            "SYS_MONTION_FLAPS" => Some("wlan0".to_string()),
+            "SYS_MONTION_MEMORY_FORMAT" => Some("percent".to_string()),
+            "SYS_MONTION_SCROLL" => Some("1".to_string()),
+            "SYS_MONTION_UNIT" => Some("gib".to_string()),
+            _ => None,
+        };
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect();
+        let (result, unknown) =
+            env_args(&build_cli(), args(&["sys-montion", "--unit", "mib"]), env);
+        assert_eq!(
+            result,
+            args(&[
+                "sys-montion",
+                "--memory-format=percent",
+                "--scroll",
+                "--battery",
+                "--volume-level",
+                "--flaps=wlan0",
+                "--unit",
+                "mib",
+            ])
+            .into_iter()
+            .collect::<Vec<OsString>>()
+        );
+        assert_eq!(unknown, ["clock"]);
+        // 命令行选择了模块时忽略 SYS_MONTION_MODULES
+        let (result, _) = env_args(&build_cli(), args(&["sys-montion", "--memory"]), env);
+        assert!(!result.contains(&OsString::from("--battery")));
+    }
+
+    #[test]
+    fn validates_arguments() {
+        let parse = |args: &[&str]| build_cli().try_get_matches_from(args);
+        assert!(parse(&["sys-montion", "--memory-pressure", "120"]).is_err());
+        assert!(parse(&["sys-montion", "--top", "0"]).is_err());
+        assert!(parse(&["sys-montion", "--notify-repeat", "0"]).is_err());
+        sysfs::use_fixtures();
+        let matches = parse(&["sys-montion", "--ip", "eth0", "--battery-device", "BAT0"]).unwrap();
+        assert_eq!(check_devices(&matches), Ok(()));
+        let matches = parse(&["sys-montion", "--net-rate", "eth9"]).unwrap();
+        assert!(check_devices(&matches)
+            .unwrap_err()
+            .contains("eth0, tun0, wg0, wlan0"));
+    }
+}
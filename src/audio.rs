@@ -0,0 +1,517 @@
+use crate::error::Error;
+use crate::event_loop::EventLoop;
+use crate::module::{Builtin, EventInterest, MetricValue};
+use crate::render::Block;
+use crate::watch::Wake;
+use crate::{locale, log, read_file, script};
+use std::io;
+use std::process::Stdio;
+use std::sync::OnceLock;
+
+pub const BACKENDS: [&str; 3] = ["auto", "pulse", "alsa"];
+
+// 输出音量的来源：默认 sink（不可用时为 Master）、只用 ALSA 的 Master、指定声卡的 ALSA 控件，
+// 或 PulseAudio/PipeWire 的 sink
+pub enum Device {
+    Default,
+    Master,
+    Alsa { card: String, element: String },
+    Sink(String),
+}
+
+impl Device {
+    // --audio-backend：auto 优先 pactl，pulse 只用默认 sink，alsa 只用默认声卡的 Master
+    pub fn from_backend(backend: &str) -> Self {
+        match backend {
+            "pulse" => Device::Sink("@DEFAULT_SINK@".to_string()),
+            "alsa" => Device::Master,
+            _ => Device::Default,
+        }
+    }
+
+    // "CARD/ELEMENT"（如 1/PCM、DAC/Speaker）为 ALSA 控件，其余为 sink 名称
+    pub fn parse(name: &str) -> Self {
+        match name.split_once('/') {
+            Some((card, element)) => Device::Alsa {
+                card: card.to_string(),
+                element: element.to_string(),
+            },
+            None => Device::Sink(name.to_string()),
+        }
+    }
+}
+
+static DEVICE: OnceLock<Device> = OnceLock::new();
+
+// 选择音量设备，未设置时使用默认声卡的 Master
+pub fn init(device: Device) {
+    let _ = DEVICE.set(device);
+}
+
+pub fn device() -> &'static Device {
+    DEVICE.get().unwrap_or(&Device::Default)
+}
+
+// 读取音量：静音时为 "MUTED"，否则为 "VOL: 65%"，超过 100%（PipeWire 允许放大）时为 "VOL: 130%!"
+// sink 使用 `pactl` 读取，百分比已按 PulseAudio 的音量曲线归一化；
+// ALSA 控件使用 `amixer -M` 读取，按控件的 dB 范围映射为与 alsamixer 一致的百分比，依赖 `alsa-utils`
+// 未指定设备时与 control 一致，优先读取默认 sink，pactl 不可用时读取 Master
+pub fn get_volume_level() -> Result<String, Error> {
+    match device() {
+        Device::Default => {
+            sink_volume("@DEFAULT_SINK@").or_else(|_| amixer_volume(&["get", "Master"], "Master"))
+        }
+        Device::Master => amixer_volume(&["get", "Master"], "Master"),
+        Device::Alsa { card, element } => amixer_volume(&["-c", card, "get", element], element),
+        Device::Sink(sink) => sink_volume(sink),
+    }
+}
+
+// amixer、pactl 未安装时说明所需的软件包和另一个后端
+fn command_error(program: &str, e: io::Error) -> Error {
+    if e.kind() != io::ErrorKind::NotFound {
+        return Error::BackendUnavailable(format!("{}: {}", program, e));
+    }
+    Error::BackendUnavailable(match program {
+        "amixer" => "alsa-utils not installed (or use --audio-backend pulse)".to_string(),
+        _ => format!("{} not installed (or use --audio-backend alsa)", program),
+    })
+}
+
+fn amixer_volume(args: &[&str], element: &str) -> Result<String, Error> {
+    let output = locale::command("amixer")
+        .arg("-M")
+        .args(args)
+        .output()
+        .map_err(|e| command_error("amixer", e))?;
+    if !output.status.success() {
+        return Err(Error::MissingDevice(format!(
+            "no {} control found",
+            element
+        )));
+    }
+    parse_volume(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| Error::Parse(format!("no {} volume in amixer output", element)))
+}
+
+// 如 "Front Left: Playback 39 [65%] [-12.00dB] [on]"
+fn parse_volume(output: &str) -> Option<String> {
+    for line in output.lines() {
+        if line.contains("[off]") {
+            return Some("MUTED".to_string());
+        }
+        if line.contains("Mono:") || line.contains("Front Left:") {
+            return parse_percent(line).map(format_volume);
+        }
+    }
+    None
+}
+
+// 超过 100% 时加 "!" 提示音量被放大，可能失真
+fn format_volume(level: u32) -> String {
+    match level {
+        0..=100 => format!("VOL: {}%", level),
+        _ => format!("VOL: {}%!", level),
+    }
+}
+
+fn sink_volume(sink: &str) -> Result<String, Error> {
+    let pactl = |arg: &str| {
+        let output = locale::command("pactl")
+            .args([arg, sink])
+            .output()
+            .map_err(|e| command_error("pactl", e))?;
+        if !output.status.success() {
+            return Err(Error::MissingDevice(format!("no sink named '{}'", sink)));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    };
+    // 输出形如 "Mute: yes"
+    if pactl("get-sink-mute")?.contains("yes") {
+        return Ok("MUTED".to_string());
+    }
+    let volume = pactl("get-sink-volume")?;
+    parse_percent(&volume)
+        .map(format_volume)
+        .ok_or_else(|| Error::Parse(format!("no volume for sink '{}'", sink)))
+}
+
+// 当前输出设备的名称，用于 --show-device：sink 为活动端口的描述（如 "Headphones"、"HDMI / DisplayPort"），
+// 没有端口时为 sink 的描述；ALSA 控件为控件名；通过 amixer 读取 Master 时为 None
+pub fn output_name() -> Option<String> {
+    let sink = match device() {
+        Device::Alsa { element, .. } => return Some(element.clone()),
+        Device::Master => return None,
+        Device::Sink(sink) => sink.clone(),
+        Device::Default => default_sink()?,
+    };
+    let output = locale::command("pactl")
+        .args(["list", "sinks"])
+        .output()
+        .ok()?;
+    parse_sink_label(&String::from_utf8_lossy(&output.stdout), &sink)
+}
+
+// 在 `pactl list sinks` 的输出中找到 Name 为 sink 的段落，取 Active Port 对应的端口描述
+// 端口行形如 "analog-output-headphones: Headphones (type: Headphones, priority: 9900, available)"
+fn parse_sink_label(list: &str, sink: &str) -> Option<String> {
+    let section = list.split("Sink #").find(|section| {
+        section
+            .lines()
+            .any(|line| line.trim() == format!("Name: {}", sink))
+    })?;
+    let field = |name: &str| {
+        section
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(name))
+            .map(str::trim)
+    };
+    let description = field("Description:").map(str::to_string);
+    let Some(port) = field("Active Port:") else {
+        return description;
+    };
+    section
+        .lines()
+        .find_map(|line| {
+            let label = line.trim().strip_prefix(port)?.strip_prefix(": ")?;
+            Some(label.split(" (").next().unwrap_or(label).to_string())
+        })
+        .or(description)
+}
+
+// 列出可用于 --audio-device 的设备：PulseAudio/PipeWire 的 sink 和各声卡的 ALSA 控件
+pub fn list_devices() -> Vec<String> {
+    let mut devices = Vec::new();
+    let output = |program: &str, args: &[&str]| {
+        locale::command(program)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    };
+    // 每行形如 "56\talsa_output.usb-FiiO_DAC-00.analog-stereo\tPipeWire\ts24le 2ch 48000Hz\tRUNNING"
+    if let Some(sinks) = output("pactl", &["list", "short", "sinks"]) {
+        devices.extend(
+            sinks
+                .lines()
+                .filter_map(|line| line.split('\t').nth(1))
+                .map(|sink| format!("sink  {}", sink)),
+        );
+    }
+    for card in alsa_cards() {
+        // 每行形如 "Simple mixer control 'Master',0"
+        let Some(controls) = output("amixer", &["-c", &card, "scontrols"]) else {
+            continue;
+        };
+        devices.extend(
+            controls
+                .lines()
+                .filter_map(|line| line.split('\'').nth(1))
+                .map(|element| format!("alsa  {}/{}", card, element)),
+        );
+    }
+    devices
+}
+
+// 从 /proc/asound/cards 读取声卡标识，如 " 1 [DAC            ]: USB-Audio - FiiO DAC"
+pub fn alsa_cards() -> Vec<String> {
+    let cards = read_file("/proc/asound/cards").unwrap_or_default();
+    cards
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once('[')?;
+            let (id, _) = rest.split_once(']')?;
+            Some(id.trim().to_string())
+        })
+        .collect()
+}
+
+// 从 "[60%]" 形式或 pactl 的 "65536 / 60% / ..." 形式中取出第一个百分比
+fn parse_percent(line: &str) -> Option<u32> {
+    let end = line.find('%')?;
+    let start = line[..end]
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |i| i + 1);
+    line[start..end].parse().ok()
+}
+
+// 通过 PulseAudio/PipeWire 读取默认输入设备的静音状态和音量
+fn pactl_source() -> Option<(bool, Option<u32>)> {
+    let pactl = |arg: &str| {
+        let output = locale::command("pactl")
+            .args([arg, "@DEFAULT_SOURCE@"])
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+    };
+    // 输出形如 "Mute: yes"
+    let muted = pactl("get-source-mute")?.contains("yes");
+    let level = pactl("get-source-volume").and_then(|volume| parse_percent(&volume));
+    Some((muted, level))
+}
+
+// 通过 ALSA Capture 控件读取静音状态和音量，如 "Front Left: Capture 39 [60%] [0.00dB] [on]"
+fn amixer_capture() -> Result<(bool, Option<u32>), Error> {
+    let output = locale::command("amixer")
+        .arg("get")
+        .arg("Capture")
+        .output()
+        .map_err(|e| command_error("amixer", e))?;
+    if !output.status.success() {
+        return Err(Error::MissingDevice("no Capture control found".to_string()));
+    }
+    parse_capture(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| Error::Parse("no Capture level in amixer output".to_string()))
+}
+
+fn parse_capture(output: &str) -> Option<(bool, Option<u32>)> {
+    let line = output
+        .lines()
+        .find(|line| line.contains("Mono:") || line.contains("Front Left:"))?;
+    Some((line.contains("[off]"), parse_percent(line)))
+}
+
+// 读取麦克风（默认输入设备）状态：静音时为 "MIC: muted"，否则为 "MIC: 60%"
+// 优先使用 PulseAudio/PipeWire 默认输入设备，否则读取 ALSA Capture 控件
+pub fn get_mic() -> Result<String, Error> {
+    let (muted, level) = match pactl_source() {
+        Some(source) => source,
+        None => amixer_capture()?,
+    };
+    Ok(match (muted, level) {
+        (true, _) => "MIC: muted".to_string(),
+        (false, Some(level)) => format!("MIC: {}%", level),
+        (false, None) => "MIC: on".to_string(),
+    })
+}
+
+// 调整输出音量：up/down 按 5% 调整，mute 切换静音
+// 未指定设备时优先使用 pactl 调整默认 sink，不可用时使用 amixer 调整 Master
+pub fn control(action: &str) -> Result<(), Error> {
+    let (pactl, amixer) = match action {
+        "up" => (["set-sink-volume", "+5%"], ["set", "5%+"]),
+        "down" => (["set-sink-volume", "-5%"], ["set", "5%-"]),
+        "mute" => (["set-sink-mute", "toggle"], ["set", "toggle"]),
+        _ => return Err(Error::Parse(format!("unknown volume action '{}'", action))),
+    };
+    let status = |program: &str, args: &[&str]| {
+        locale::command(program)
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+    };
+    let (card, element) = match device() {
+        Device::Sink(sink) => {
+            let pactl = status("pactl", &[pactl[0], sink, pactl[1]])
+                .map_err(|e| command_error("pactl", e))?;
+            if !pactl.success() {
+                return Err(Error::MissingDevice(format!("no sink named '{}'", sink)));
+            }
+            return Ok(());
+        }
+        Device::Alsa { card, element } => (Some(card.as_str()), element.as_str()),
+        Device::Master => (None, "Master"),
+        Device::Default => {
+            if status("pactl", &[pactl[0], "@DEFAULT_SINK@", pactl[1]])
+                .is_ok_and(|status| status.success())
+            {
+                return Ok(());
+            }
+            (None, "Master")
+        }
+    };
+    let mut args = Vec::new();
+    if let Some(card) = card {
+        args.extend(["-c", card]);
+    }
+    args.extend([amixer[0], element, amixer[1]]);
+    let amixer = status("amixer", &args).map_err(|e| command_error("amixer", e))?;
+    if !amixer.success() {
+        return Err(Error::MissingDevice(format!(
+            "no {} control found",
+            element
+        )));
+    }
+    Ok(())
+}
+
+// 读取 PulseAudio/PipeWire 默认输出设备名称
+fn default_sink() -> Option<String> {
+    let output = locale::command("pactl")
+        .arg("get-default-sink")
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// 默认输出设备切换时执行用户命令，新设备名称通过 SYS_MONTION_SINK 传入
+fn run_sink_hook(hook: &str, sink: &str) {
+    script::spawn(hook, &[("SYS_MONTION_SINK", sink.to_string())], "sink hook");
+}
+
+// 订阅混音器变化事件，音量变化或默认输出设备切换（插拔耳机、连接蓝牙）时发送唤醒信号
+// 优先使用 `pactl subscribe`（PulseAudio/PipeWire），否则使用 `alsactl monitor`（ALSA）
+// 默认设备切换检测和 sink_hook 仅在 PulseAudio/PipeWire 下可用
+pub fn subscribe(events: &mut EventLoop, sink_hook: Option<String>) {
+    let commands: [(&str, &[&str]); 2] = [("pactl", &["subscribe"]), ("alsactl", &["monitor"])];
+    for (program, args) in commands {
+        let mut sink = default_sink();
+        let sink_hook = sink_hook.clone();
+        let result = events.add_command(program, args, move |line, woken| {
+            // pactl 输出形如 "Event 'change' on sink #0"，只关心 sink、source、server 和 card 变化
+            // 插拔耳机时声卡的端口可用性变化，产生 card 事件
+            let relevant = program == "alsactl"
+                || line.contains("sink")
+                || line.contains("source")
+                || line.contains("server")
+                || line.contains("card");
+            if !relevant {
+                return;
+            }
+            // 默认设备变化会产生 server 事件
+            if line.contains("server") {
+                let current = default_sink();
+                if current != sink {
+                    if let (Some(hook), Some(name)) = (&sink_hook, &current) {
+                        run_sink_hook(hook, name);
+                    }
+                    sink = current;
+                }
+            }
+            woken.push(Wake::All);
+        });
+        if result.is_ok() {
+            log::info!("watching mixer events with {}", program);
+            return;
+        }
+    }
+}
+
+// volume-level 模块，--show-device 时在音量后加上输出设备名
+pub struct Volume {
+    hide: bool,
+    show_device: bool,
+}
+
+impl Builtin for Volume {
+    const ID: &'static str = "volume-level";
+    const BLOCK: &'static str = "volume";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Volume {
+            hide: matches.get_one::<String>("volume-fallback").unwrap() == "hide",
+            show_device: matches.get_flag("show-device"),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let level = match get_volume_level() {
+            Ok(level) => level,
+            // 空文本的块不输出
+            Err(e) if self.hide => {
+                log::debug!("hiding volume: {}", e);
+                return Ok(Block::new(Self::BLOCK, String::new()));
+            }
+            Err(e) => return Err(e),
+        };
+        let text = match self.show_device.then(output_name).flatten() {
+            Some(name) => format!("{} {}", level, name),
+            None => level,
+        };
+        Ok(Block::percent(Self::BLOCK, text))
+    }
+
+    fn interests(&self) -> EventInterest {
+        EventInterest::AUDIO
+    }
+}
+
+pub struct Mic;
+
+impl Builtin for Mic {
+    const ID: &'static str = "mic";
+    const BLOCK: &'static str = "mic";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Mic
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::percent(Self::BLOCK, get_mic()?))
+    }
+
+    fn interests(&self) -> EventInterest {
+        EventInterest::AUDIO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_capture_state() {
+        let amixer = "Simple mixer control 'Capture',0\n  \
+            Front Left: Capture 39 [60%] [0.00dB] [on]\n  \
+            Front Right: Capture 39 [60%] [0.00dB] [on]";
+        assert_eq!(parse_capture(amixer), Some((false, Some(60))));
+        assert_eq!(
+            parse_capture("  Mono: Capture 0 [0%] [-17.25dB] [off]"),
+            Some((true, Some(0)))
+        );
+        assert_eq!(
+            parse_percent("Volume: front-left: 42597 /  65% / -11.23 dB"),
+            Some(65)
+        );
+    }
+
+    #[test]
+    fn parses_volume_and_cards() {
+        let amixer = "Simple mixer control 'PCM',0\n  \
+            Front Left: Playback 39 [65%] [-12.00dB] [on]";
+        assert_eq!(parse_volume(amixer).as_deref(), Some("VOL: 65%"));
+        assert_eq!(
+            parse_volume("  Mono: Playback 0 [0%] [off]").as_deref(),
+            Some("MUTED")
+        );
+        let pactl =
+            "Volume: front-left: 85196 / 130% / 6.84 dB,   front-right: 85196 / 130% / 6.84 dB";
+        assert_eq!(
+            parse_percent(pactl).map(format_volume).as_deref(),
+            Some("VOL: 130%!")
+        );
+        assert!(matches!(
+            Device::parse("DAC/Speaker"),
+            Device::Alsa { card, element } if card == "DAC" && element == "Speaker"
+        ));
+        let sinks = "Sink #56\n\tState: RUNNING\n\tName: alsa_output.pci-0000_00_1f.3.analog-stereo\n\tDescription: Built-in Audio Analog Stereo\n\tPorts:\n\t\tanalog-output-speaker: Speakers (type: Speaker, priority: 10000, availability unknown)\n\t\tanalog-output-headphones: Headphones (type: Headphones, priority: 9900, available)\n\tActive Port: analog-output-headphones\nSink #57\n\tName: bluez_output.AC_80_0A.1\n\tDescription: WH-1000XM4\n";
+        assert_eq!(
+            parse_sink_label(sinks, "alsa_output.pci-0000_00_1f.3.analog-stereo").as_deref(),
+            Some("Headphones")
+        );
+        assert_eq!(
+            parse_sink_label(sinks, "bluez_output.AC_80_0A.1").as_deref(),
+            Some("WH-1000XM4")
+        );
+        crate::sysfs::use_fixtures();
+        assert_eq!(alsa_cards(), ["PCH", "DAC"]);
+    }
+
+    #[test]
+    fn explains_missing_mixer_tools() {
+        let missing = || io::Error::from(io::ErrorKind::NotFound);
+        assert_eq!(
+            command_error("amixer", missing()).to_string(),
+            "alsa-utils not installed (or use --audio-backend pulse)"
+        );
+        assert!(matches!(Device::from_backend("alsa"), Device::Master));
+    }
+}
@@ -0,0 +1,230 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{dbus, ddc, generate, sysfs};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+// 屏幕背光：amdgpu_bl1 的读取和调整，backlight set --all 时包括其他背光设备和 DDC/CI 显示器
+
+// 输出背光亮度：percent 为百分比，raw 为原始值/最大值，both 同时输出两者
+fn format_brightness(format: &str, current: i32, max: i32) -> String {
+    let percent = brightness_percent(current, max);
+    match format {
+        "raw" => format!("BL: {}/{}", current, max),
+        "both" => format!("BL: {}% ({}/{})", percent, current, max),
+        _ => format!("BL: {}%", percent),
+    }
+}
+
+// 读取当前亮度和最大亮度的原始值
+fn read_brightness() -> Result<(i32, i32), Error> {
+    let brightness_path = "/sys/class/backlight/amdgpu_bl1/brightness";
+    let max_brightness_path = "/sys/class/backlight/amdgpu_bl1/max_brightness";
+
+    let read = |path: &str| match sysfs::read_int(path) {
+        Ok(value) => i32::try_from(value).map_err(|e| Error::Parse(format!("{}: {}", path, e))),
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            Err(Error::Parse(format!("{}: {}", path, e)))
+        }
+        Err(e) => Err(Error::from(e)),
+    };
+    let current_brightness = read(brightness_path)?;
+    let max_brightness = read(max_brightness_path)?;
+    if max_brightness <= 0 {
+        return Err(Error::Parse(format!(
+            "{}: must be positive",
+            max_brightness_path
+        )));
+    }
+    Ok((current_brightness, max_brightness))
+}
+
+// 四舍五入到整数百分比，49.7% 显示为 50% 而不是 49%
+fn brightness_percent(current: i32, max: i32) -> i32 {
+    ((i64::from(current) * 200 + i64::from(max)) / (i64::from(max) * 2)) as i32
+}
+
+// 渐变调整亮度时的步数
+const FADE_STEPS: i32 = 20;
+
+// 写入亮度值：直接写 sysfs，没有写权限时改用 logind 的 SetBrightness（活动会话的普通用户即可调用）
+// 返回是否改用 logind 写入，渐变时后续步骤沿用同一方式
+fn write_brightness(device: &str, level: i32, logind: bool) -> Result<bool, Error> {
+    let path = Path::new("/sys/class/backlight")
+        .join(device)
+        .join("brightness");
+    if !logind {
+        match fs::write(sysfs::path(&path), level.to_string()) {
+            Ok(()) => return Ok(false),
+            Err(e) if e.kind() != io::ErrorKind::PermissionDenied => return Err(Error::from(e)),
+            Err(_) => {}
+        }
+    }
+    dbus::call(
+        dbus::Bus::System,
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1/session/auto",
+        "org.freedesktop.login1.Session.SetBrightness",
+        &["backlight", device, &level.to_string()],
+    )
+    .map_err(|e| {
+        Error::PermissionDenied(format!(
+            "cannot write {}: permission denied, and logind SetBrightness failed: {} ({})",
+            path.display(),
+            e,
+            generate::UDEV_HINT
+        ))
+    })?;
+    Ok(true)
+}
+
+// 调整背光：up/down 按 5% 调整，set 按百分比设置
+// fade 不为 None 时在这段时间内分 FADE_STEPS 步逐渐变化，避免亮度突变
+pub fn set_brightness(
+    action: &str,
+    percent: Option<i32>,
+    fade: Option<Duration>,
+) -> Result<String, Error> {
+    let (current, max) = read_brightness()?;
+    let step = (max / 20).max(1);
+    let level = match (action, percent) {
+        ("up", _) => current + step,
+        ("down", _) => current - step,
+        ("set", Some(percent)) => (i64::from(percent) * i64::from(max) / 100) as i32,
+        _ => {
+            return Err(Error::Parse(format!(
+                "invalid backlight action '{}'",
+                action
+            )))
+        }
+    }
+    .clamp(0, max);
+    let mut logind = false;
+    if let Some(fade) = fade.filter(|fade| !fade.is_zero()) {
+        let delay = fade / FADE_STEPS as u32;
+        let mut last = current;
+        for i in 1..FADE_STEPS {
+            let value = current + (level - current) * i / FADE_STEPS;
+            if value != last {
+                logind = write_brightness("amdgpu_bl1", value, logind)?;
+                last = value;
+            }
+            std::thread::sleep(delay);
+        }
+    }
+    write_brightness("amdgpu_bl1", level, logind)?;
+    Ok(format!("BL: {}%", brightness_percent(level, max)))
+}
+
+// backlight set --all：把所有背光设备（内置屏幕）和支持 DDC/CI 的外接显示器设为同一百分比，
+// 输出如 "BL: 40% (amdgpu_bl1, HDMI-A-1)"；个别显示器失败时在标准错误输出警告，全部失败时返回错误
+pub fn set_all_brightness(percent: i32) -> Result<String, Error> {
+    let mut devices: Vec<String> = sysfs::read_dir("/sys/class/backlight")
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    devices.sort();
+    let mut updated = Vec::new();
+    let mut last_error = None;
+    for device in &devices {
+        let max = Path::new("/sys/class/backlight")
+            .join(device)
+            .join("max_brightness");
+        let result = sysfs::read_int(&max).map_err(Error::from).and_then(|max| {
+            write_brightness(device, (i64::from(percent) * max / 100) as i32, false)
+        });
+        match result {
+            Ok(_) => updated.push(device.clone()),
+            Err(e) => {
+                eprintln!("warning: {}: {}", device, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    for (output, bus) in ddc::displays() {
+        match ddc::set_brightness(&bus, percent) {
+            Ok(_) => updated.push(output),
+            Err(e) => {
+                eprintln!("warning: {} ({}): {}", output, bus, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    if updated.is_empty() {
+        return Err(last_error.unwrap_or_else(|| {
+            Error::MissingDevice("no backlight or DDC/CI display found".to_string())
+        }));
+    }
+    Ok(format!("BL: {}% ({})", percent, updated.join(", ")))
+}
+
+pub fn get_brightness_percent() -> Result<i32, Error> {
+    let (current, max) = read_brightness()?;
+    Ok(brightness_percent(current, max))
+}
+
+// backlight 模块，--backlight-format 选择百分比或原始值
+pub struct Backlight {
+    format: String,
+}
+
+impl Builtin for Backlight {
+    const ID: &'static str = "backlight";
+    const BLOCK: &'static str = "backlight";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Backlight {
+            format: matches
+                .get_one::<String>("backlight-format")
+                .unwrap()
+                .clone(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let (current, max) = read_brightness()?;
+        Ok(Block::with_percent(
+            Self::BLOCK,
+            format_brightness(&self.format, current, max),
+            f64::from(brightness_percent(current, max)),
+        ))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        (!sysfs::exists("/sys/class/backlight/amdgpu_bl1"))
+            .then(|| "backlight amdgpu_bl1 not found".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_backlight() {
+        sysfs::use_fixtures();
+        // 128 * 100 / 255 = 50.2
+        assert_eq!(get_brightness_percent().unwrap(), 50);
+        assert_eq!(format_brightness("percent", 128, 255), "BL: 50%");
+        assert_eq!(format_brightness("raw", 128, 255), "BL: 128/255");
+        assert_eq!(format_brightness("both", 128, 255), "BL: 50% (128/255)");
+        // raw 格式的数值仍为百分比
+        let matches =
+            crate::args::build_cli().get_matches_from(["sys-montion", "--backlight-format", "raw"]);
+        let block = Backlight::new(&matches).read_block().unwrap();
+        assert_eq!(
+            (block.text.as_str(), block.value),
+            ("BL: 128/255", Some(50.0))
+        );
+        // 497 / 1000 = 49.7%，四舍五入为 50%
+        assert_eq!(brightness_percent(497, 1000), 50);
+        assert_eq!(brightness_percent(494, 1000), 49);
+    }
+}
@@ -0,0 +1,1050 @@
+use crate::error::Error;
+use crate::locale::Rounding;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{format_duration, generate, locale, read_file, sysfs, termux, upower};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+
+// 电池数据来源：sysfs 直接读取 power_supply，upower 通过 UPower D-Bus 服务读取
+// UPower 会处理固件的异常数值并合并多块电池；termux 通过 Termux:API 读取 Android 的电池状态
+#[derive(Clone, Copy, PartialEq)]
+pub enum Backend {
+    Sysfs,
+    Upower,
+    Termux,
+}
+
+impl Backend {
+    // `auto` 在 Termux 中使用 termux，否则使用 sysfs
+    pub fn parse(name: &str) -> Option<Backend> {
+        match name {
+            "auto" if termux::detected() => Some(Backend::Termux),
+            "auto" | "sysfs" => Some(Backend::Sysfs),
+            "upower" => Some(Backend::Upower),
+            "termux" => Some(Backend::Termux),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Sysfs => "sysfs",
+            Backend::Upower => "upower",
+            Backend::Termux => "termux",
+        }
+    }
+}
+
+static BACKEND: OnceLock<Backend> = OnceLock::new();
+
+// 选择电池后端，未设置时使用 sysfs
+pub fn init(backend: Backend) {
+    let _ = BACKEND.set(backend);
+}
+
+pub fn backend() -> Backend {
+    BACKEND.get().copied().unwrap_or(Backend::Sysfs)
+}
+
+// --battery-rounding：由 energy_now / energy_full 计算电量百分比时的取整方式
+// 未设置时单块电池直接读取 capacity，多块电池合并时向下取整
+static ROUNDING: OnceLock<Rounding> = OnceLock::new();
+
+pub fn set_rounding(rounding: Rounding) {
+    let _ = ROUNDING.set(rounding);
+}
+
+fn percent(now: i64, full: i64) -> String {
+    let rounding = ROUNDING.get().copied().unwrap_or(Rounding::Floor);
+    (rounding.apply(now as f64 * 100.0 / full as f64, 0) as i64).to_string()
+}
+
+// --battery-smoothing：watch 模式下对功率做指数加权平均，负载突变时剩余时间不会大幅跳动
+// 因子越小越平滑，1 表示不平滑；单次输出没有历史，不做平滑
+pub const DEFAULT_SMOOTHING: f64 = 0.2;
+
+static SMOOTHING: Mutex<Option<f64>> = Mutex::new(None);
+
+// 各指标的平均值及计算时的充电状态，状态变化（插拔电源）时重新开始
+static AVERAGES: Mutex<BTreeMap<String, (String, f64)>> = Mutex::new(BTreeMap::new());
+
+pub fn check_smoothing(factor: f64) -> Result<f64, String> {
+    if factor > 0.0 && factor <= 1.0 {
+        Ok(factor)
+    } else {
+        Err(format!(
+            "invalid smoothing factor {}, expected a number in (0, 1]",
+            factor
+        ))
+    }
+}
+
+pub fn parse_smoothing(value: &str) -> Result<f64, String> {
+    let factor = value
+        .parse::<f64>()
+        .map_err(|_| format!("invalid smoothing factor '{}'", value))?;
+    check_smoothing(factor)
+}
+
+// 由 watch 模式设置，重新加载配置时更新
+pub fn set_smoothing(factor: f64) {
+    *SMOOTHING.lock().unwrap() = Some(factor);
+}
+
+fn ewma(factor: f64, average: f64, value: f64) -> f64 {
+    factor * value + (1.0 - factor) * average
+}
+
+fn smooth(key: String, status: &str, value: f64) -> f64 {
+    let Some(factor) = *SMOOTHING.lock().unwrap() else {
+        return value;
+    };
+    let mut averages = AVERAGES.lock().unwrap();
+    let average = match averages.get(&key) {
+        Some((last, average)) if last == status => ewma(factor, *average, value),
+        _ => value,
+    };
+    averages.insert(key, (status.to_string(), average));
+    average
+}
+
+// 充电状态，sysfs、UPower 和 Termux 的状态名统一为 sysfs 的写法
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Status {
+    Charging,
+    Discharging,
+    Full,
+    NotCharging,
+    Unknown,
+}
+
+// --status-style：full 为完整单词，short 为三字母缩写，symbol 为单个符号
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StatusStyle {
+    Full,
+    Short,
+    Symbol,
+}
+
+impl StatusStyle {
+    pub const NAMES: [&'static str; 3] = ["full", "short", "symbol"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "full" => Some(StatusStyle::Full),
+            "short" => Some(StatusStyle::Short),
+            "symbol" => Some(StatusStyle::Symbol),
+            _ => None,
+        }
+    }
+}
+
+impl Status {
+    // 无法识别的状态（如部分固件的 "Not Charging"）按 Unknown 处理
+    pub fn parse(name: &str) -> Self {
+        match name {
+            "Charging" => Status::Charging,
+            "Discharging" => Status::Discharging,
+            "Full" => Status::Full,
+            "Not charging" => Status::NotCharging,
+            _ => Status::Unknown,
+        }
+    }
+
+    pub fn render(self, style: StatusStyle) -> &'static str {
+        match (style, self) {
+            (StatusStyle::Full, Status::Charging) => "Charging",
+            (StatusStyle::Full, Status::Discharging) => "Discharging",
+            (StatusStyle::Full, Status::Full) => "Full",
+            (StatusStyle::Full, Status::NotCharging) => "Not charging",
+            (StatusStyle::Full, Status::Unknown) => "Unknown",
+            (StatusStyle::Short, Status::Charging) => "CHR",
+            (StatusStyle::Short, Status::Discharging) => "DIS",
+            (StatusStyle::Short, Status::Full) => "FUL",
+            (StatusStyle::Short, Status::NotCharging) => "NCH",
+            (StatusStyle::Short, Status::Unknown) => "UNK",
+            (StatusStyle::Symbol, Status::Charging) => "⚡",
+            (StatusStyle::Symbol, Status::Discharging) => "↓",
+            (StatusStyle::Symbol, Status::Full) => "✓",
+            (StatusStyle::Symbol, Status::NotCharging) => "=",
+            (StatusStyle::Symbol, Status::Unknown) => "?",
+        }
+    }
+}
+
+fn battery_path(name: &str) -> PathBuf {
+    Path::new(POWER_SUPPLY_PATH).join(name)
+}
+
+// 列出所有电池设备（type 为 Battery），如 BAT0、BAT1
+pub fn list_batteries() -> Result<Vec<String>, io::Error> {
+    let mut batteries = Vec::new();
+    for entry in sysfs::read_dir(POWER_SUPPLY_PATH)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if read_file(battery_path(&name).join("type")).unwrap_or_default() == "Battery" {
+            batteries.push(name);
+        }
+    }
+    batteries.sort();
+    Ok(batteries)
+}
+
+// 根据 `--battery-device` 解析出需要读取的电池，`all` 表示全部电池
+fn resolve_devices(device: &str) -> Result<Vec<String>, Error> {
+    if device != "all" {
+        return Ok(vec![device.to_string()]);
+    }
+    let batteries = list_batteries()?;
+    if batteries.is_empty() {
+        return Err(Error::MissingDevice("no battery found".to_string()));
+    }
+    Ok(batteries)
+}
+
+// 读取电池目录下的整数值
+fn read_value(name: &str, file: &str) -> Option<i64> {
+    sysfs::read_int(battery_path(name).join(file)).ok()
+}
+
+// 把 charge_*（µAh）换算为能量（µWh）所用的电压（V），优先设计最低电压，否则用当前电压
+// 只有一块电池时各项单位一致，没有电压也能计算比例，按 1 处理；多块电池时无法换算则返回 None，
+// 避免把 µAh 和另一块电池的 µWh 相加
+fn charge_voltage(name: &str, single: bool) -> Option<f64> {
+    read_value(name, "voltage_min_design")
+        .or_else(|| read_value(name, "voltage_now"))
+        .filter(|voltage| *voltage > 0)
+        .map(|voltage| voltage as f64 / 1e6)
+        .or(single.then_some(1.0))
+}
+
+// 读取单个电池的当前/满电能量（µWh）
+// 多数设备提供 energy_*（µWh），部分设备只提供 charge_*（µAh），按电压换算
+fn read_energy(name: &str, single: bool) -> Option<(i64, i64)> {
+    if let (Some(now), Some(full)) = (
+        read_value(name, "energy_now"),
+        read_value(name, "energy_full"),
+    ) {
+        return Some((now, full));
+    }
+    let (now, full) = (
+        read_value(name, "charge_now")?,
+        read_value(name, "charge_full")?,
+    );
+    let voltage = charge_voltage(name, single)?;
+    Some((
+        (now as f64 * voltage) as i64,
+        (full as f64 * voltage) as i64,
+    ))
+}
+
+// 读取单个电池的当前功率（µW），只有 current_now（µA）时按电压换算：
+// charge_* 电池用与 read_energy 相同的电压，保证比例一致；energy_* 电池需要 voltage_now
+fn read_rate(name: &str, single: bool) -> Option<i64> {
+    if let Some(power) = read_value(name, "power_now") {
+        return Some(power.abs());
+    }
+    let current = read_value(name, "current_now")?.abs();
+    let voltage = if read_value(name, "energy_now").is_some() {
+        read_value(name, "voltage_now").filter(|voltage| *voltage > 0)? as f64 / 1e6
+    } else {
+        charge_voltage(name, single)?
+    };
+    Some((current as f64 * voltage) as i64)
+}
+
+// 读取电池电量，多块电池时按总能量计算合并百分比
+pub fn get_battery_capacity(device: &str) -> Result<String, Error> {
+    match backend() {
+        Backend::Upower => return upower::get_battery_capacity(device),
+        Backend::Termux => return termux::get_battery_capacity(),
+        Backend::Sysfs => {}
+    }
+    let devices = resolve_devices(device)?;
+    if devices.len() == 1 {
+        if let (Some(_), Some((now, full))) = (ROUNDING.get(), read_energy(&devices[0], true)) {
+            if full > 0 {
+                return Ok(percent(now, full));
+            }
+        }
+        return Ok(read_file(battery_path(&devices[0]).join("capacity"))?);
+    }
+
+    let mut total_now: i64 = 0;
+    let mut total_full: i64 = 0;
+    for name in &devices {
+        if let Some((now, full)) = read_energy(name, false) {
+            total_now += now;
+            total_full += full;
+        }
+    }
+
+    if total_full == 0 {
+        return Err(Error::MissingDevice(
+            "unable to read battery energy".to_string(),
+        ));
+    }
+
+    Ok(percent(total_now, total_full))
+}
+
+// 读取充电状态，多块电池时任一在充电即视为 Charging
+pub fn get_battery_status(device: &str) -> Result<String, Error> {
+    match backend() {
+        Backend::Upower => return upower::get_battery_status(device),
+        Backend::Termux => return termux::get_battery_status(),
+        Backend::Sysfs => {}
+    }
+    let devices = resolve_devices(device)?;
+    let mut statuses = Vec::new();
+    for name in &devices {
+        statuses.push(read_file(battery_path(name).join("status"))?);
+    }
+
+    for status in ["Charging", "Discharging"] {
+        if statuses.iter().any(|s| s == status) {
+            return Ok(status.to_string());
+        }
+    }
+    if statuses.iter().all(|s| s == "Full") {
+        return Ok("Full".to_string());
+    }
+
+    Ok(statuses.remove(0))
+}
+
+// 估算剩余时间：放电时为耗尽时间，充电时为充满时间
+pub fn get_battery_time(device: &str) -> Result<String, Error> {
+    match backend() {
+        Backend::Upower => return upower::get_battery_time(device),
+        Backend::Termux => return termux::get_battery_time(),
+        Backend::Sysfs => {}
+    }
+    let devices = resolve_devices(device)?;
+    let status = get_battery_status(device)?;
+
+    let mut total_now: i64 = 0;
+    let mut total_full: i64 = 0;
+    let mut total_rate: i64 = 0;
+    let single = devices.len() == 1;
+    for name in &devices {
+        if let (Some((now, full)), Some(rate)) =
+            (read_energy(name, single), read_rate(name, single))
+        {
+            total_now += now;
+            total_full += full;
+            total_rate += rate;
+        }
+    }
+
+    // 功率为 0 时（已充满、未充电）无法估算，直接返回状态
+    if total_rate == 0 {
+        return Ok(status);
+    }
+
+    let (remaining, suffix) = match status.as_str() {
+        "Charging" => (total_full - total_now, "until full"),
+        "Discharging" => (total_now, "left"),
+        _ => return Ok(status),
+    };
+
+    let rate = smooth(format!("time:{}", device), &status, total_rate as f64);
+    let seconds = (remaining.max(0) as f64 * 3600.0 / rate) as i64;
+    Ok(format!("{} {}", format_duration(seconds), suffix))
+}
+
+// 读取瞬时功率（瓦特），优先 power_now，否则用 voltage_now * current_now 计算
+pub fn read_power_watts(device: &str) -> Result<f64, Error> {
+    match backend() {
+        Backend::Upower => return upower::read_power_watts(device),
+        Backend::Termux => return termux::read_power_watts(),
+        Backend::Sysfs => {}
+    }
+    let devices = resolve_devices(device)?;
+    let mut total_watts = 0.0;
+    let mut found = false;
+    for name in &devices {
+        let watts = if let Some(power) = read_value(name, "power_now") {
+            power.abs() as f64 / 1e6
+        } else if let (Some(voltage), Some(current)) = (
+            read_value(name, "voltage_now"),
+            read_value(name, "current_now"),
+        ) {
+            (voltage as f64 / 1e6) * (current.abs() as f64 / 1e6)
+        } else {
+            continue;
+        };
+        total_watts += watts;
+        found = true;
+    }
+
+    if !found {
+        return Err(Error::MissingDevice(
+            "battery does not report power_now or voltage_now/current_now".to_string(),
+        ));
+    }
+
+    Ok(total_watts)
+}
+
+pub fn get_battery_power(device: &str) -> Result<String, Error> {
+    let status = get_battery_status(device).unwrap_or_default();
+    let watts = smooth(
+        format!("power:{}", device),
+        &status,
+        read_power_watts(device)?,
+    );
+    Ok(format!("{}W", locale::format_number(watts, 1)))
+}
+
+// 计算电池健康度：满电容量 / 设计容量，并输出损耗百分比
+pub fn get_battery_health(device: &str) -> Result<String, Error> {
+    match backend() {
+        Backend::Upower => return upower::get_battery_health(device),
+        Backend::Termux => return termux::get_battery_health(),
+        Backend::Sysfs => {}
+    }
+    let devices = resolve_devices(device)?;
+    let mut total_full: i64 = 0;
+    let mut total_design: i64 = 0;
+    let single = devices.len() == 1;
+    for name in &devices {
+        if let (Some(full), Some(design)) = (
+            read_value(name, "energy_full"),
+            read_value(name, "energy_full_design"),
+        ) {
+            total_full += full;
+            total_design += design;
+        } else if let (Some(full), Some(design), Some(voltage)) = (
+            read_value(name, "charge_full"),
+            read_value(name, "charge_full_design"),
+            charge_voltage(name, single),
+        ) {
+            total_full += (full as f64 * voltage) as i64;
+            total_design += (design as f64 * voltage) as i64;
+        }
+    }
+
+    if total_design == 0 {
+        return Err(Error::MissingDevice(
+            "battery does not report design capacity".to_string(),
+        ));
+    }
+
+    let health = total_full * 100 / total_design;
+    Ok(format!("{}% ({}% wear)", health, (100 - health).max(0)))
+}
+
+// 输出电池的厂商、型号、类型、循环次数和序列号，如 "BAT0: SMP 5B10W13975 Li-ion, 312 cycles, SN 1234"
+// 多块电池时用 " | " 分隔；只读 sysfs，评估二手笔记本电池时无需安装 upower 或 acpi
+// 不支持循环计数的驱动把 cycle_count 报告为 0，这时不输出
+pub fn get_battery_info(device: &str) -> Result<String, Error> {
+    let devices = resolve_devices(device)?;
+    let mut batteries = Vec::new();
+    for name in &devices {
+        let path = battery_path(name);
+        if !sysfs::exists(&path) {
+            return Err(Error::MissingDevice(format!("battery {} not found", name)));
+        }
+        let attribute = |file: &str| {
+            read_file(path.join(file))
+                .ok()
+                .filter(|value| !value.is_empty())
+        };
+        let model: Vec<String> = ["manufacturer", "model_name", "technology"]
+            .into_iter()
+            .filter_map(attribute)
+            .collect();
+        let mut fields = Vec::new();
+        if !model.is_empty() {
+            fields.push(model.join(" "));
+        }
+        if let Some(cycles) = read_value(name, "cycle_count").filter(|&cycles| cycles > 0) {
+            fields.push(format!("{} cycles", cycles));
+        }
+        if let Some(serial) = attribute("serial_number") {
+            fields.push(format!("SN {}", serial));
+        }
+        if fields.is_empty() {
+            fields.push("no information".to_string());
+        }
+        batteries.push(format!("{}: {}", name, fields.join(", ")));
+    }
+    Ok(batteries.join(" | "))
+}
+
+// 供电设备当前的功率（瓦特），voltage_now 单位为 µV，current_now 单位为 µA
+fn supply_watts(name: &str) -> Option<f64> {
+    let power =
+        read_value(name, "voltage_now")? as f64 * read_value(name, "current_now")? as f64 / 1e12;
+    (power > 0.0).then_some(power)
+}
+
+// 供电协议：usb_type 中方括号内为当前协商的类型，如 "C [PD] PD_PPS"
+// 没有 usb_type 的适配器为 AC
+fn supply_protocol(name: &str, kind: &str) -> &'static str {
+    let usb_type = read_file(battery_path(name).join("usb_type")).unwrap_or_default();
+    let active = usb_type
+        .split_whitespace()
+        .find_map(|t| t.strip_prefix('[')?.strip_suffix(']'));
+    match (kind, active) {
+        (_, Some("PD")) => "PD",
+        (_, Some("PD_PPS")) => "PPS",
+        (_, Some("C")) => "USB-C",
+        ("Mains", _) => "AC",
+        _ => "USB",
+    }
+}
+
+// 输出充电器的协商功率和协议，如 "PWR: 65W PD"；未接电源时为 "PWR: off"
+// 接通电源但电池仍在放电时说明充电器功率不足，加上 "(underpowered)"
+pub fn get_charger_info() -> Result<String, Error> {
+    let mut found = false;
+    let mut best: Option<(f64, &str)> = None;
+    for entry in sysfs::read_dir(POWER_SUPPLY_PATH)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        let kind = read_file(battery_path(&name).join("type")).unwrap_or_default();
+        if kind != "Mains" && kind != "USB" {
+            continue;
+        }
+        found = true;
+        if read_value(&name, "online") != Some(1) {
+            continue;
+        }
+        let watts = supply_watts(&name).unwrap_or(0.0);
+        if best.is_none_or(|(best, _)| watts > best) {
+            best = Some((watts, supply_protocol(&name, &kind)));
+        }
+    }
+    if !found {
+        return Err(Error::MissingDevice("no AC adapter found".to_string()));
+    }
+    let Some((watts, protocol)) = best else {
+        return Ok("PWR: off".to_string());
+    };
+    // 不提供电压和电流的适配器只输出协议
+    let mut text = if watts > 0.0 {
+        format!("PWR: {:.0}W {}", watts, protocol)
+    } else {
+        format!("PWR: {}", protocol)
+    };
+    if get_battery_status("all").is_ok_and(|status| status == "Discharging") {
+        text.push_str(" (underpowered)");
+    }
+    Ok(text)
+}
+
+// 输出外部电源状态：接通时为 "AC: on"，USB-PD 等供电提供电压和电流时为协商功率，如 "AC: 45W"
+// 适配器（type 为 Mains，如 AC、ADP1）和 USB 供电（type 为 USB，如 ucsi-source-psy-*）都计入
+pub fn get_ac() -> Result<String, Error> {
+    if backend() == Backend::Termux {
+        return termux::get_ac();
+    }
+    let mut found = false;
+    let mut online = false;
+    let mut watts: Option<f64> = None;
+    for entry in sysfs::read_dir(POWER_SUPPLY_PATH)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        let kind = read_file(battery_path(&name).join("type")).unwrap_or_default();
+        if kind != "Mains" && kind != "USB" {
+            continue;
+        }
+        found = true;
+        if read_value(&name, "online") != Some(1) {
+            continue;
+        }
+        online = true;
+        if let Some(power) = supply_watts(&name) {
+            *watts.get_or_insert(0.0) += power;
+        }
+    }
+    if !found {
+        return Err(Error::MissingDevice("no AC adapter found".to_string()));
+    }
+    Ok(match (online, watts) {
+        (true, Some(watts)) => format!("AC: {:.0}W", watts),
+        (true, None) => "AC: on".to_string(),
+        (false, _) => "AC: off".to_string(),
+    })
+}
+
+// 是否接通了外部电源，没有适配器时为 false
+pub fn on_ac() -> bool {
+    get_ac().is_ok_and(|ac| ac != "AC: off")
+}
+
+// 充电阈值：开始充电和停止充电的电量百分比
+// ThinkPad 同时提供两者，ASUS 等只提供停止充电阈值，此时 start 为 None
+fn read_thresholds(name: &str) -> Option<(Option<i64>, i64)> {
+    let end = read_value(name, "charge_control_end_threshold")?;
+    Some((read_value(name, "charge_control_start_threshold"), end))
+}
+
+fn format_thresholds((start, end): (Option<i64>, i64)) -> String {
+    match start {
+        Some(start) => format!("{}-{}%", start, end),
+        None => format!("{}%", end),
+    }
+}
+
+// 输出充电阈值，如 "CHG: 75-80%"，多块电池都支持时带电池名，如 "CHG: BAT0 75-80% BAT1 80%"
+pub fn get_charge_threshold(device: &str) -> Result<String, Error> {
+    let thresholds: Vec<(String, (Option<i64>, i64))> = resolve_devices(device)?
+        .into_iter()
+        .filter_map(|name| read_thresholds(&name).map(|t| (name, t)))
+        .collect();
+    let text = match thresholds.as_slice() {
+        [] => {
+            return Err(Error::MissingDevice(
+                "battery does not support charge thresholds".to_string(),
+            ))
+        }
+        [(_, t)] => format_thresholds(*t),
+        _ => thresholds
+            .iter()
+            .map(|(name, t)| format!("{} {}", name, format_thresholds(*t)))
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+    Ok(format!("CHG: {}", text))
+}
+
+fn write_threshold(name: &str, file: &str, value: i64) -> Result<(), Error> {
+    let path = battery_path(name).join(file);
+    fs::write(sysfs::path(&path), value.to_string()).map_err(|e| match e.kind() {
+        io::ErrorKind::PermissionDenied => Error::PermissionDenied(format!(
+            "cannot write {}: permission denied (run as root, or {})",
+            path.display(),
+            generate::UDEV_HINT
+        )),
+        _ => Error::from(e),
+    })
+}
+
+// 设置充电阈值，支持多块电池时全部设置；只支持停止阈值的电池忽略 start
+pub fn set_charge_threshold(device: &str, start: i64, end: i64) -> Result<String, Error> {
+    if !(0..=100).contains(&start) || !(1..=100).contains(&end) || start >= end {
+        return Err(Error::Parse(format!(
+            "invalid thresholds {}-{}: need 0 <= start < stop <= 100",
+            start, end
+        )));
+    }
+    let mut found = false;
+    for name in resolve_devices(device)? {
+        let Some((current_start, current_end)) = read_thresholds(&name) else {
+            continue;
+        };
+        found = true;
+        if current_start.is_none() {
+            write_threshold(&name, "charge_control_end_threshold", end)?;
+            continue;
+        }
+        // 内核要求 start < end，按新旧值的大小决定先写哪一个
+        if start >= current_end {
+            write_threshold(&name, "charge_control_end_threshold", end)?;
+            write_threshold(&name, "charge_control_start_threshold", start)?;
+        } else {
+            write_threshold(&name, "charge_control_start_threshold", start)?;
+            write_threshold(&name, "charge_control_end_threshold", end)?;
+        }
+    }
+    if !found {
+        return Err(Error::MissingDevice(
+            "battery does not support charge thresholds".to_string(),
+        ));
+    }
+    get_charge_threshold(device)
+}
+
+// 按 --status-style 输出充电状态，full 时保留原始状态名
+fn styled_status(device: &str, style: Option<StatusStyle>) -> Result<String, Error> {
+    let status = get_battery_status(device)?;
+    Ok(match style {
+        Some(StatusStyle::Full) | None => status,
+        Some(style) => Status::parse(&status).render(style).to_string(),
+    })
+}
+
+fn device_arg(matches: &clap::ArgMatches) -> String {
+    matches.get_one::<String>("battery-device").unwrap().clone()
+}
+
+fn style_arg(matches: &clap::ArgMatches) -> Option<StatusStyle> {
+    StatusStyle::parse(matches.get_one::<String>("status-style").unwrap())
+}
+
+// 找不到电池的原因；UPower 和 Termux:API 不可用时读取会报错，这里只检查 sysfs
+fn battery_unavailable(device: &str) -> Option<String> {
+    if backend() != Backend::Sysfs {
+        None
+    } else if device == "all" {
+        let found = list_batteries().is_ok_and(|b| !b.is_empty());
+        (!found).then(|| "no battery found".to_string())
+    } else {
+        (!sysfs::exists(Path::new("/sys/class/power_supply").join(device)))
+            .then(|| format!("battery {} not found", device))
+    }
+}
+
+// battery 模块：充电状态和电量，如 "Discharging: 80%"
+pub struct Battery {
+    device: String,
+    style: Option<StatusStyle>,
+}
+
+impl Builtin for Battery {
+    const ID: &'static str = "battery";
+    const BLOCK: &'static str = "battery";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Battery {
+            device: device_arg(matches),
+            style: style_arg(matches),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let capacity = get_battery_capacity(&self.device)?;
+        let status = styled_status(&self.device, self.style)?;
+        Ok(Block::percent(
+            Self::BLOCK,
+            format!("{}: {}%", status, capacity),
+        ))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        battery_unavailable(&self.device)
+    }
+}
+
+pub struct BatteryState {
+    device: String,
+    style: Option<StatusStyle>,
+}
+
+impl Builtin for BatteryState {
+    const ID: &'static str = "battery-state";
+    const BLOCK: &'static str = "battery";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        BatteryState {
+            device: device_arg(matches),
+            style: style_arg(matches),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(
+            Self::BLOCK,
+            styled_status(&self.device, self.style)?,
+        ))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        battery_unavailable(&self.device)
+    }
+}
+
+pub struct BatteryCapacity {
+    device: String,
+}
+
+impl Builtin for BatteryCapacity {
+    const ID: &'static str = "battery-capacity";
+    const BLOCK: &'static str = "battery";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        BatteryCapacity {
+            device: device_arg(matches),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let capacity = get_battery_capacity(&self.device)?;
+        Ok(Block::percent(Self::BLOCK, format!("{}%", capacity)))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        battery_unavailable(&self.device)
+    }
+}
+
+pub struct BatteryTime {
+    device: String,
+}
+
+impl Builtin for BatteryTime {
+    const ID: &'static str = "battery-time";
+    const BLOCK: &'static str = "battery";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        BatteryTime {
+            device: device_arg(matches),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_battery_time(&self.device)?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        battery_unavailable(&self.device)
+    }
+}
+
+pub struct BatteryPower {
+    device: String,
+}
+
+impl Builtin for BatteryPower {
+    const ID: &'static str = "battery-power";
+    const BLOCK: &'static str = "battery";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        BatteryPower {
+            device: device_arg(matches),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(
+            Self::BLOCK,
+            get_battery_power(&self.device)?,
+        ))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        battery_unavailable(&self.device)
+    }
+}
+
+pub struct BatteryHealth {
+    device: String,
+}
+
+impl Builtin for BatteryHealth {
+    const ID: &'static str = "battery-health";
+    const BLOCK: &'static str = "battery";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        BatteryHealth {
+            device: device_arg(matches),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::percent(
+            Self::BLOCK,
+            get_battery_health(&self.device)?,
+        ))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        battery_unavailable(&self.device)
+    }
+}
+
+pub struct BatteryInfo {
+    device: String,
+}
+
+impl Builtin for BatteryInfo {
+    const ID: &'static str = "battery-info";
+    const BLOCK: &'static str = "battery";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        BatteryInfo {
+            device: device_arg(matches),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_battery_info(&self.device)?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        get_battery_info(&self.device).err().map(|e| e.to_string())
+    }
+}
+
+pub struct ChargeThreshold {
+    device: String,
+}
+
+impl Builtin for ChargeThreshold {
+    const ID: &'static str = "charge-threshold";
+    const BLOCK: &'static str = "charge_threshold";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        ChargeThreshold {
+            device: device_arg(matches),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(
+            Self::BLOCK,
+            get_charge_threshold(&self.device)?,
+        ))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        get_charge_threshold(&self.device)
+            .err()
+            .map(|e| e.to_string())
+    }
+}
+
+pub struct Ac;
+
+impl Builtin for Ac {
+    const ID: &'static str = "ac";
+    const BLOCK: &'static str = "ac";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Ac
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, get_ac()?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        get_ac().err().map(|e| e.to_string())
+    }
+}
+
+pub struct ChargerInfo;
+
+impl Builtin for ChargerInfo {
+    const ID: &'static str = "charger-info";
+    const BLOCK: &'static str = "charger";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        ChargerInfo
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, get_charger_info()?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        get_charger_info().err().map(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 固件树中 BAT0 提供 energy_*（放电中），BAT1 只提供 charge_*（已充满），AC 和 USB-C 供电不是电池
+    fn setup() {
+        sysfs::use_fixtures();
+    }
+
+    #[test]
+    fn lists_only_batteries() {
+        setup();
+        assert_eq!(list_batteries().unwrap(), ["BAT0", "BAT1"]);
+    }
+
+    #[test]
+    fn single_battery_uses_capacity_file() {
+        setup();
+        assert_eq!(get_battery_capacity("BAT0").unwrap(), "80");
+        assert_eq!(get_battery_status("BAT0").unwrap(), "Discharging");
+        assert_eq!(
+            Status::parse("Not charging").render(StatusStyle::Short),
+            "NCH"
+        );
+        assert_eq!(Status::parse("Charging").render(StatusStyle::Symbol), "⚡");
+    }
+
+    #[test]
+    fn combines_batteries_by_energy() {
+        setup();
+        // BAT1 的 charge_* 按 voltage_min_design 11V 换算为能量：
+        // (40000000 + 2000000 * 11) * 100 / (50000000 + 2000000 * 11)
+        assert_eq!(get_battery_capacity("all").unwrap(), "86");
+        // 任一电池放电时整体视为放电
+        assert_eq!(get_battery_status("all").unwrap(), "Discharging");
+    }
+
+    #[test]
+    fn estimates_time_left() {
+        setup();
+        assert_eq!(get_battery_time("BAT0").unwrap(), "4h 0m left");
+        // (40000000 + 22000000) µWh / 10000000 µW
+        assert_eq!(get_battery_time("all").unwrap(), "6h 12m left");
+    }
+
+    #[test]
+    fn smooths_power_draw() {
+        // 功率从 10W 突增到 30W，平均值逐步上升
+        assert_eq!(ewma(0.2, 10.0, 30.0), 14.0);
+        assert_eq!(ewma(1.0, 10.0, 30.0), 30.0);
+        assert_eq!(parse_smoothing("0.3"), Ok(0.3));
+        assert!(parse_smoothing("0").is_err());
+        assert!(parse_smoothing("1.5").is_err());
+    }
+
+    #[test]
+    fn reads_power_and_health() {
+        setup();
+        assert_eq!(get_battery_power("BAT0").unwrap(), "10.0W");
+        assert_eq!(get_battery_health("BAT0").unwrap(), "83% (17% wear)");
+        assert_eq!(get_battery_health("BAT1").unwrap(), "80% (20% wear)");
+        // (50000000 + 22000000) * 100 / (60000000 + 27500000)
+        assert_eq!(get_battery_health("all").unwrap(), "82% (18% wear)");
+    }
+
+    #[test]
+    fn reads_battery_info() {
+        setup();
+        assert_eq!(
+            get_battery_info("all").unwrap(),
+            "BAT0: SMP 5B10W13975 Li-poly, 312 cycles, SN 1234 | BAT1: Li-ion"
+        );
+        assert!(matches!(
+            get_battery_info("BAT9"),
+            Err(Error::MissingDevice(_))
+        ));
+    }
+
+    #[test]
+    fn missing_battery_is_missing_device() {
+        setup();
+        assert!(matches!(
+            get_battery_capacity("BAT9"),
+            Err(Error::MissingDevice(_))
+        ));
+    }
+
+    #[test]
+    fn reads_usb_pd_wattage() {
+        setup();
+        // AC 未接通，USB-C 供电 20 V × 2.25 A
+        assert_eq!(get_ac().unwrap(), "AC: 45W");
+        // 固件树中 BAT0 仍在放电
+        assert_eq!(get_charger_info().unwrap(), "PWR: 45W PD (underpowered)");
+    }
+
+    #[test]
+    fn reads_charge_thresholds() {
+        setup();
+        assert_eq!(get_charge_threshold("all").unwrap(), "CHG: 75-80%");
+        assert!(matches!(
+            get_charge_threshold("BAT1"),
+            Err(Error::MissingDevice(_))
+        ));
+        assert!(matches!(
+            set_charge_threshold("BAT0", 80, 60),
+            Err(Error::Parse(_))
+        ));
+    }
+}
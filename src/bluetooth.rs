@@ -0,0 +1,139 @@
+use crate::dbus::{self, Bus};
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::sysfs;
+use std::io;
+
+const BLUETOOTH_PATH: &str = "/sys/class/bluetooth/";
+
+// 列出蓝牙适配器，如 hci0（排除 hci0:11 这类连接节点）
+// 没有加载蓝牙驱动时 /sys/class/bluetooth 不存在
+fn list_adapters() -> Result<Vec<String>, io::Error> {
+    let entries = match sysfs::read_dir(BLUETOOTH_PATH) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut adapters: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with("hci") && !name.contains(':'))
+        .collect();
+    adapters.sort();
+    Ok(adapters)
+}
+
+fn bluez_property(path: &str, interface: &str, property: &str) -> Result<String, io::Error> {
+    dbus::get_property(Bus::System, "org.bluez", path, interface, property)
+}
+
+// 解析 GVariant 文本中的字符串，如 "'WH-1000XM4'"
+fn parse_string(value: &str) -> String {
+    value
+        .trim_matches(|c| c == '\'' || c == '"')
+        .replace("\\'", "'")
+}
+
+// 解析 BlueZ 电量百分比，gdbus 把 byte 类型输出为 "byte 0x50"
+fn parse_percentage(value: &str) -> Option<u8> {
+    let number = value.strip_prefix("byte ").unwrap_or(value);
+    match number.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16).ok(),
+        None => number.parse().ok(),
+    }
+}
+
+// 读取适配器上已连接的设备，返回 (名称, 电量)
+fn connected_devices(adapter_path: &str) -> Result<Vec<(String, Option<u8>)>, io::Error> {
+    let mut devices = Vec::new();
+    for node in dbus::children(Bus::System, "org.bluez", adapter_path)? {
+        if !node.starts_with("dev_") {
+            continue;
+        }
+        let path = format!("{}/{}", adapter_path, node);
+        if !bluez_property(&path, "org.bluez.Device1", "Connected").is_ok_and(|v| v == "true") {
+            continue;
+        }
+        let name = bluez_property(&path, "org.bluez.Device1", "Alias")
+            .map(|alias| parse_string(&alias))
+            .unwrap_or(node);
+        // 只有支持电量上报的设备才有 Battery1 接口
+        let battery = bluez_property(&path, "org.bluez.Battery1", "Percentage")
+            .ok()
+            .and_then(|value| parse_percentage(&value));
+        devices.push((name, battery));
+    }
+    Ok(devices)
+}
+
+// 输出蓝牙状态：关闭时为 "BT: off"，否则列出已连接设备，如 "BT: WH-1000XM4 80%"
+pub fn get_bluetooth() -> Result<String, Error> {
+    let adapters = list_adapters()?;
+    if adapters.is_empty() {
+        return Err(Error::MissingDevice(
+            "no bluetooth adapter found".to_string(),
+        ));
+    }
+
+    let mut powered = false;
+    let mut devices = Vec::new();
+    for adapter in &adapters {
+        let path = format!("/org/bluez/{}", adapter);
+        let on = bluez_property(&path, "org.bluez.Adapter1", "Powered")
+            .map_err(|e| Error::BackendUnavailable(format!("unable to query BlueZ: {}", e)))?;
+        if on != "true" {
+            continue;
+        }
+        powered = true;
+        devices.extend(connected_devices(&path)?);
+    }
+
+    if !powered {
+        return Ok("BT: off".to_string());
+    }
+    if devices.is_empty() {
+        return Ok("BT: on".to_string());
+    }
+    let devices: Vec<String> = devices
+        .into_iter()
+        .map(|(name, battery)| match battery {
+            Some(percent) => format!("{} {}%", name, percent),
+            None => name,
+        })
+        .collect();
+    Ok(format!("BT: {}", devices.join(", ")))
+}
+
+pub struct Bluetooth;
+
+impl Builtin for Bluetooth {
+    const ID: &'static str = "bluetooth";
+    const BLOCK: &'static str = "bluetooth";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Bluetooth
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, get_bluetooth()?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        (!sysfs::exists("/sys/class/bluetooth")).then(|| "no bluetooth adapter found".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gvariant_values() {
+        assert_eq!(parse_string("'WH-1000XM4'"), "WH-1000XM4");
+        assert_eq!(parse_string("\"Bob's Buds\""), "Bob's Buds");
+        assert_eq!(parse_percentage("byte 0x50"), Some(80));
+        assert_eq!(parse_percentage("80"), Some(80));
+        assert_eq!(parse_percentage("garbage"), None);
+    }
+}
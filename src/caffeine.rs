@@ -0,0 +1,118 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::paths::runtime_path;
+use crate::read_file;
+use crate::render::Block;
+use rustix::process::{kill_process_group, Pid, Signal};
+use std::fs;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+// logind 的抑制锁在持有者关闭文件描述符时释放，命令行调用无法一直持有，
+// 因此由后台的 `systemd-inhibit ... sleep infinity` 持有，其 PID 记录在运行时目录中
+const PID_FILE: &str = "sys-montion-caffeine.pid";
+
+// 正在持有抑制锁的 systemd-inhibit 进程，PID 文件过期（进程已退出或 PID 被复用）时为 None
+fn holder() -> Option<i32> {
+    let pid: i32 = fs::read_to_string(runtime_path(PID_FILE).ok()?)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    read_file(format!("/proc/{}/cmdline", pid))
+        .ok()?
+        .starts_with("systemd-inhibit")
+        .then_some(pid)
+}
+
+// 输出是否阻止了自动休眠，如 "CAF: on"
+pub fn get_caffeine() -> Result<String, Error> {
+    Ok(format!(
+        "CAF: {}",
+        if holder().is_some() { "on" } else { "off" }
+    ))
+}
+
+fn enable() -> Result<(), Error> {
+    if holder().is_some() {
+        return Ok(());
+    }
+    let path = runtime_path(PID_FILE)?;
+    let mut child = Command::new("systemd-inhibit")
+        .args([
+            "--what=idle:sleep",
+            "--who=sys-montion",
+            "--why=Caffeine mode",
+            "--mode=block",
+            "sleep",
+            "infinity",
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // 脱离终端的进程组，关闭终端时不会收到 SIGHUP
+        .process_group(0)
+        .spawn()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                Error::BackendUnavailable("systemd-inhibit not found".to_string())
+            }
+            _ => Error::from(e),
+        })?;
+    // logind 不可用时 systemd-inhibit 会立即退出
+    thread::sleep(Duration::from_millis(100));
+    if let Ok(Some(status)) = child.try_wait() {
+        return Err(Error::BackendUnavailable(format!(
+            "systemd-inhibit exited ({}), is systemd-logind running?",
+            status
+        )));
+    }
+    fs::write(path, child.id().to_string())?;
+    Ok(())
+}
+
+fn disable() -> Result<(), Error> {
+    // systemd-inhibit 自成一个进程组，连同它启动的 sleep 一起结束
+    if let Some(pid) = holder().and_then(Pid::from_raw) {
+        kill_process_group(pid, Signal::TERM).map_err(io::Error::from)?;
+    }
+    if let Ok(path) = runtime_path(PID_FILE) {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+// 开启、关闭或切换：on、off、toggle，返回切换后的状态
+pub fn control(action: &str) -> Result<String, Error> {
+    match action {
+        "on" => enable()?,
+        "off" => disable()?,
+        "toggle" if holder().is_some() => disable()?,
+        "toggle" => enable()?,
+        _ => {
+            return Err(Error::Parse(format!(
+                "unknown caffeine action '{}'",
+                action
+            )))
+        }
+    }
+    get_caffeine()
+}
+
+pub struct Caffeine;
+
+impl Builtin for Caffeine {
+    const ID: &'static str = "caffeine";
+    const BLOCK: &'static str = "caffeine";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Caffeine
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_caffeine()?))
+    }
+}
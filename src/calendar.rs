@@ -0,0 +1,435 @@
+use crate::error::Error;
+use crate::module::{self, Builtin, MetricValue};
+use crate::render::Block;
+use crate::timeutil::{days_from_civil, local_utc_offset, now_secs, zone_utc_offset};
+use crate::{format_duration, log};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 事件时间所在的时区：UTC（以 Z 结尾）、本地时间（浮动时间和全天事件）或 TZID 指定的时区
+#[derive(Clone, PartialEq, Debug)]
+enum Zone {
+    Utc,
+    Local,
+    Named(String),
+}
+
+// 日历中的时间：墙上时间（把日期和时刻按 UTC 计算的秒数）和所在的时区
+#[derive(Clone, Debug)]
+struct Time {
+    wall: i64,
+    zone: Zone,
+}
+
+// 解析 ICS 时间值，如 "20261015T090000Z"、"20261015T090000" 或 "20261015"
+// key 为带参数的属性名，如 "DTSTART;TZID=Europe/Berlin"
+fn parse_ics_time(key: &str, value: &str) -> Option<Time> {
+    let field = |range: std::ops::Range<usize>| value.get(range)?.parse::<i64>().ok();
+    let days = days_from_civil(field(0..4)?, field(4..6)?, field(6..8)?);
+    let seconds = if value.len() >= 15 {
+        field(9..11)? * 3600 + field(11..13)? * 60 + field(13..15)?
+    } else {
+        0
+    };
+    let zone = if value.ends_with('Z') {
+        Zone::Utc
+    } else {
+        match key.split(';').find_map(|param| param.strip_prefix("TZID=")) {
+            Some(tz) => Zone::Named(tz.trim_matches('"').to_string()),
+            None => Zone::Local,
+        }
+    };
+    Some(Time {
+        wall: days * 86400 + seconds,
+        zone,
+    })
+}
+
+// 把墙上时间转换为 Unix 时间；lookup 返回 TZID 时区在某一时刻的 UTC 偏移，未知的时区按本地时间处理
+struct Zones<F> {
+    local_offset: i64,
+    lookup: F,
+}
+
+impl<F: FnMut(&str, i64) -> Option<i64>> Zones<F> {
+    fn offset(&mut self, zone: &Zone, timestamp: i64) -> i64 {
+        match zone {
+            Zone::Utc => 0,
+            Zone::Local => self.local_offset,
+            Zone::Named(tz) => (self.lookup)(tz, timestamp).unwrap_or(self.local_offset),
+        }
+    }
+
+    // 先按墙上时间估计偏移，再用估计的时刻重新查询，夏令时切换前后也能得到正确的偏移
+    fn timestamp(&mut self, time: &Time) -> i64 {
+        let estimate = time.wall - self.offset(&time.zone, time.wall);
+        time.wall - self.offset(&time.zone, estimate)
+    }
+}
+
+// 支持的 RRULE：FREQ=DAILY 或 WEEKLY，以及 INTERVAL、COUNT、UNTIL 和不带序号的 BYDAY（如 MO,WE,FR）
+struct Rule {
+    weekly: bool,
+    interval: i64,
+    count: Option<usize>,
+    until: Option<Time>,
+    // 星期几，周一为 0
+    weekdays: Vec<i64>,
+}
+
+// 解析 RRULE，不支持的规则返回原因
+fn parse_rule(value: &str) -> Result<Rule, String> {
+    let unsupported = |part: &str| format!("{} recurrence not expanded", part);
+    let mut rule = Rule {
+        weekly: false,
+        interval: 1,
+        count: None,
+        until: None,
+        weekdays: Vec::new(),
+    };
+    let mut freq = None;
+    for part in value.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            return Err(unsupported(part));
+        };
+        match key {
+            "FREQ" => freq = Some(value),
+            "INTERVAL" => {
+                rule.interval = value
+                    .parse()
+                    .ok()
+                    .filter(|interval| *interval > 0)
+                    .ok_or_else(|| unsupported(part))?
+            }
+            "COUNT" => rule.count = Some(value.parse().map_err(|_| unsupported(part))?),
+            "UNTIL" => {
+                rule.until = Some(parse_ics_time(key, value).ok_or_else(|| unsupported(part))?)
+            }
+            "BYDAY" => {
+                rule.weekdays = value
+                    .split(',')
+                    .map(|day| WEEKDAYS.iter().position(|&d| d == day).map(|i| i as i64))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| unsupported(part))?
+            }
+            "WKST" => {}
+            _ => return Err(unsupported(part)),
+        }
+    }
+    match freq {
+        Some("DAILY") => {}
+        Some("WEEKLY") => rule.weekly = true,
+        Some(freq) => return Err(unsupported(&format!("FREQ={}", freq))),
+        None => return Err(unsupported(value)),
+    }
+    Ok(rule)
+}
+
+const WEEKDAYS: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+// 1970-01-01 为周四
+fn weekday(wall: i64) -> i64 {
+    (wall.div_euclid(86400) + 3).rem_euclid(7)
+}
+
+// 按规则依次生成重复事件的墙上时间，第一个不早于 DTSTART，COUNT 在这里截断
+fn occurrences(start: i64, rule: &Rule) -> Box<dyn Iterator<Item = i64> + '_> {
+    let occurrences: Box<dyn Iterator<Item = i64>> = if rule.weekly {
+        let mut weekdays = if rule.weekdays.is_empty() {
+            vec![weekday(start)]
+        } else {
+            rule.weekdays.clone()
+        };
+        weekdays.sort();
+        // DTSTART 所在周的周一，保留一天中的时刻
+        let monday = start - weekday(start) * 86400;
+        let step = rule.interval * 7 * 86400;
+        Box::new(
+            (0..)
+                .flat_map(move |week| {
+                    let weekdays = weekdays.clone();
+                    weekdays
+                        .into_iter()
+                        .map(move |day| monday + week * step + day * 86400)
+                })
+                .filter(move |&wall| wall >= start),
+        )
+    } else {
+        // 每天重复时 BYDAY 用于筛选，如工作日重复的 FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR
+        Box::new(
+            (0..)
+                .map(move |day| start + day * rule.interval * 86400)
+                .filter(|&wall| rule.weekdays.is_empty() || rule.weekdays.contains(&weekday(wall))),
+        )
+    };
+    Box::new(occurrences.take(rule.count.unwrap_or(usize::MAX)))
+}
+
+// 一个 VEVENT；RECURRENCE-ID 表示它替换同一 UID 的重复事件中的某一次
+#[derive(Default)]
+struct Event {
+    uid: String,
+    summary: String,
+    start: Option<Time>,
+    rule: Option<String>,
+    exdates: Vec<Time>,
+    recurrence_id: Option<Time>,
+}
+
+// 重复事件在 now 之后的第一次，skipped 为 EXDATE 和被单独修改的各次（Unix 时间）
+fn next_occurrence<F: FnMut(&str, i64) -> Option<i64>>(
+    start: &Time,
+    rule: &Rule,
+    skipped: &[i64],
+    now: i64,
+    zones: &mut Zones<F>,
+) -> Option<i64> {
+    let until = rule.until.as_ref().map(|until| {
+        // 不带 Z 的 UNTIL 与 DTSTART 在同一时区
+        let zone = match until.zone {
+            Zone::Utc => Zone::Utc,
+            _ => start.zone.clone(),
+        };
+        zones.timestamp(&Time {
+            wall: until.wall,
+            zone,
+        })
+    });
+    for wall in occurrences(start.wall, rule) {
+        // 时区偏移不超过 14 小时，更早的各次不必换算
+        if wall < now - 14 * 3600 {
+            continue;
+        }
+        let time = zones.timestamp(&Time {
+            wall,
+            zone: start.zone.clone(),
+        });
+        if until.is_some_and(|until| time > until) {
+            return None;
+        }
+        if time > now && !skipped.contains(&time) {
+            return Some(time);
+        }
+    }
+    None
+}
+
+// 解析 ICS 内容，返回每个事件在 now 之后的开始时间和标题，以及无法正确处理的事件的说明
+// 重复事件展开 DAILY 和 WEEKLY 规则，其他规则只取 DTSTART；未知的 TZID 按本地时间处理
+fn parse_ics<F: FnMut(&str, i64) -> Option<i64>>(
+    content: &str,
+    now: i64,
+    zones: &mut Zones<F>,
+) -> (Vec<(i64, String)>, Vec<String>) {
+    // 展开折行：以空格或制表符开头的行是上一行的延续
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut event = Event::default();
+    for line in &lines {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key.split(';').next().unwrap_or("") {
+            "BEGIN" if value == "VEVENT" => event = Event::default(),
+            "UID" => event.uid = value.to_string(),
+            "DTSTART" => event.start = parse_ics_time(key, value),
+            "SUMMARY" => event.summary = value.replace("\\,", ",").replace("\\;", ";"),
+            "RRULE" => event.rule = Some(value.to_string()),
+            "EXDATE" => event.exdates.extend(
+                value
+                    .split(',')
+                    .filter_map(|value| parse_ics_time(key, value)),
+            ),
+            "RECURRENCE-ID" => event.recurrence_id = parse_ics_time(key, value),
+            "END" if value == "VEVENT" => events.push(std::mem::take(&mut event)),
+            _ => {}
+        }
+    }
+
+    let mut upcoming = Vec::new();
+    let mut warnings = Vec::new();
+    for event in &events {
+        let Some(start) = &event.start else {
+            continue;
+        };
+        if let Zone::Named(tz) = &start.zone {
+            if (zones.lookup)(tz, start.wall).is_none() {
+                warnings.push(format!(
+                    "{}: unknown time zone {}, using local time",
+                    event.summary, tz
+                ));
+            }
+        }
+        let rule = match event.rule.as_deref().map(parse_rule) {
+            Some(Ok(rule)) if event.recurrence_id.is_none() => Some(rule),
+            Some(Err(reason)) => {
+                warnings.push(format!("{}: {}", event.summary, reason));
+                None
+            }
+            _ => None,
+        };
+        let time = match rule {
+            Some(rule) => {
+                let skipped: Vec<i64> = events
+                    .iter()
+                    .filter(|other| other.uid == event.uid)
+                    .filter_map(|other| other.recurrence_id.as_ref())
+                    .chain(&event.exdates)
+                    .map(|time| zones.timestamp(time))
+                    .collect();
+                next_occurrence(start, &rule, &skipped, now, zones)
+            }
+            None => Some(zones.timestamp(start)).filter(|&time| time > now),
+        };
+        if let Some(time) = time {
+            upcoming.push((time, event.summary.clone()));
+        }
+    }
+    (upcoming, warnings)
+}
+
+// 递归收集目录下的 .ics 文件（兼容 khal 使用的 vdir 结构）
+fn collect_ics_files(path: &Path, files: &mut Vec<std::path::PathBuf>) -> Result<(), io::Error> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            collect_ics_files(&entry?.path(), files)?;
+        }
+    } else if path.extension().is_some_and(|ext| ext == "ics") {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+// 读取 ICS 文件或日历目录，输出下一个事件的标题和距今时间
+pub fn get_next_event(calendar: &str) -> Result<String, Error> {
+    let mut files = Vec::new();
+    collect_ics_files(Path::new(calendar), &mut files)?;
+    if files.is_empty() {
+        return Err(Error::MissingDevice(format!(
+            "no .ics files found in {}",
+            calendar
+        )));
+    }
+
+    let now = now_secs();
+    // 同一时区在同一小时内的偏移只查询一次
+    let mut offsets: HashMap<(String, i64), Option<i64>> = HashMap::new();
+    let mut zones = Zones {
+        local_offset: local_utc_offset(),
+        lookup: |tz: &str, timestamp: i64| {
+            *offsets
+                .entry((tz.to_string(), timestamp.div_euclid(3600)))
+                .or_insert_with(|| zone_utc_offset(tz, timestamp))
+        },
+    };
+
+    let mut next: Option<(i64, String)> = None;
+    for file in &files {
+        let content = fs::read_to_string(file)?;
+        let (upcoming, warnings) = parse_ics(&content, now, &mut zones);
+        for warning in warnings {
+            log::warning!("{}: {}", file.display(), warning);
+        }
+        for (start, summary) in upcoming {
+            if next.as_ref().is_none_or(|(t, _)| start < *t) {
+                next = Some((start, summary));
+            }
+        }
+    }
+
+    match next {
+        Some((start, summary)) => Ok(format!("{} in {}", summary, format_duration(start - now))),
+        None => Ok("No events".to_string()),
+    }
+}
+
+pub struct NextEvent {
+    calendar: Option<String>,
+}
+
+impl Builtin for NextEvent {
+    const ID: &'static str = "next-event";
+    const BLOCK: &'static str = "calendar";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        NextEvent {
+            calendar: matches.get_one::<String>("calendar").cloned(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let calendar = module::required(&self.calendar, "calendar", "--calendar PATH")?;
+        Ok(Block::new(Self::BLOCK, get_next_event(calendar)?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        match &self.calendar {
+            None => Some("needs --calendar PATH".to_string()),
+            Some(calendar) => {
+                (!Path::new(calendar).exists()).then(|| format!("{} not found", calendar))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> String {
+        fs::read_to_string(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/calendar/work.ics"),
+        )
+        .unwrap()
+    }
+
+    // 本地时间按 UTC 处理；Europe/Berlin 在 2026-10-25 01:00Z 结束夏令时
+    fn zones() -> Zones<impl FnMut(&str, i64) -> Option<i64>> {
+        Zones {
+            local_offset: 0,
+            lookup: |tz: &str, timestamp: i64| match tz {
+                "Europe/Berlin" if timestamp < 1792890000 => Some(7200),
+                "Europe/Berlin" => Some(3600),
+                _ => None,
+            },
+        }
+    }
+
+    #[test]
+    fn expands_recurring_events() {
+        // 2026-10-15T12:00:00Z，周四
+        let (upcoming, warnings) = parse_ics(&fixture(), 1792065600, &mut zones());
+        assert_eq!(
+            upcoming,
+            [
+                (1792137600, "Dentist".to_string()),
+                // 周五被 EXDATE 排除，周一被单独修改，下一次为周三 09:30 CEST
+                (1792567800, "Standup".to_string()),
+                (1792400400, "Standup (moved)".to_string()),
+                (1792083600, "Gym".to_string()),
+                (1792067400, "Lunch".to_string()),
+                (1792486800, "Offsite, all hands".to_string()),
+            ]
+        );
+        assert_eq!(
+            warnings,
+            [
+                "Monthly review: FREQ=MONTHLY recurrence not expanded",
+                "Offsite, all hands: unknown time zone W. Europe Standard Time, using local time",
+            ]
+        );
+
+        // 夏令时结束后的周一 09:30 CET
+        let (upcoming, _) = parse_ics(&fixture(), 1792756800, &mut zones());
+        assert!(upcoming.contains(&(1793003400, "Standup".to_string())));
+    }
+}
@@ -0,0 +1,309 @@
+use crate::render::json_escape;
+use crate::{audio, battery, containers, module, sysfs, updates};
+use std::env;
+
+// 一项功能在本机上的可用情况
+struct Capability {
+    name: &'static str,
+    kind: &'static str,
+    // 选中的后端
+    backend: Option<&'static str>,
+    // 不可用的原因
+    reason: Option<String>,
+    // 被放弃的后端及原因
+    rejected: Vec<(&'static str, String)>,
+}
+
+// 在 PATH 中查找外部命令
+pub fn command_exists(command: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(command).is_file()))
+}
+
+fn require_command(command: &str) -> Result<(), String> {
+    if command_exists(command) {
+        Ok(())
+    } else {
+        Err(format!("{} not found in PATH", command))
+    }
+}
+
+fn require_path(path: &str) -> Result<(), String> {
+    if sysfs::exists(path) {
+        Ok(())
+    } else {
+        Err(format!("{} does not exist", path))
+    }
+}
+
+fn require_env(var: &str) -> Result<(), String> {
+    match env::var_os(var) {
+        Some(value) if !value.is_empty() => Ok(()),
+        _ => Err(format!("{} is not set", var)),
+    }
+}
+
+// 各功能按优先级排列的候选后端及其检查结果
+fn backends(name: &str) -> Vec<(&'static str, Result<(), String>)> {
+    match name {
+        "battery" | "battery-state" | "battery-capacity" | "battery-time" | "battery-power"
+        | "battery-health" => match battery::backend() {
+            battery::Backend::Sysfs => vec![(
+                "sysfs power_supply",
+                require_path("/sys/class/power_supply"),
+            )],
+            battery::Backend::Upower => vec![("gdbus + UPower", require_command("gdbus"))],
+            battery::Backend::Termux => {
+                vec![("Termux:API", require_command("termux-battery-status"))]
+            }
+        },
+        "ac" => match battery::backend() {
+            battery::Backend::Termux => {
+                vec![("Termux:API", require_command("termux-battery-status"))]
+            }
+            _ => vec![(
+                "sysfs power_supply",
+                require_path("/sys/class/power_supply"),
+            )],
+        },
+        "charger-info" | "battery-info" => vec![(
+            "sysfs power_supply",
+            require_path("/sys/class/power_supply"),
+        )],
+        "charge-threshold" => vec![(
+            "sysfs charge_control thresholds",
+            require_path("/sys/class/power_supply"),
+        )],
+        "volume-level" => match audio::device() {
+            audio::Device::Sink(_) => vec![("pactl sink", require_command("pactl"))],
+            audio::Device::Default => vec![
+                ("pactl default sink", require_command("pactl")),
+                ("amixer", require_command("amixer")),
+            ],
+            _ => vec![("amixer", require_command("amixer"))],
+        },
+        "mic" => vec![
+            ("pactl default source", require_command("pactl")),
+            ("amixer Capture", require_command("amixer")),
+        ],
+        "backlight" => vec![(
+            "sysfs amdgpu_bl1",
+            require_path("/sys/class/backlight/amdgpu_bl1"),
+        )],
+        "kbd-backlight" => vec![("sysfs leds", require_path("/sys/class/leds"))],
+        "memory" => vec![("procfs meminfo", require_path("/proc/meminfo"))],
+        "daylight" => vec![("builtin solar position", Ok(()))],
+        "weather" => vec![("curl", require_command("curl"))],
+        "next-event" => vec![("ics files", Ok(()))],
+        "screen-time" | "usage-report" => vec![("state database", Ok(()))],
+        "mounts" => vec![("procfs mountinfo", require_path("/proc/self/mountinfo"))],
+        "throttle" => vec![
+            (
+                "sysfs thermal_throttle",
+                require_path("/sys/devices/system/cpu/cpu0/thermal_throttle"),
+            ),
+            ("msr", Err("MSR counters are not supported".to_string())),
+        ],
+        "cpu-freq" => vec![(
+            "sysfs cpufreq",
+            require_path("/sys/devices/system/cpu/cpu0/cpufreq"),
+        )],
+        "power-profile" => vec![("gdbus + power-profiles-daemon", require_command("gdbus"))],
+        "gamemode" => vec![("gdbus + sysfs cpufreq", require_command("gdbus"))],
+        "privacy" => vec![("procfs fd + asound", require_path("/proc/asound"))],
+        "caffeine" => vec![("systemd-inhibit", require_command("systemd-inhibit"))],
+        "dnd" => vec![
+            ("gdbus + dunst", require_command("gdbus")),
+            ("makoctl", require_command("makoctl")),
+            ("swaync-client", require_command("swaync-client")),
+        ],
+        "flaps" => vec![("sysfs carrier counters", require_path("/sys/class/net"))],
+        "bluetooth" => vec![("gdbus + BlueZ", require_command("gdbus"))],
+        "media" => vec![("gdbus + MPRIS", require_command("gdbus"))],
+        "vpn" => vec![("sysfs net tun/wireguard", require_path("/sys/class/net"))],
+        "connectivity" => vec![
+            ("NetworkManager connectivity", require_command("gdbus")),
+            ("procfs default route", require_path("/proc/net/route")),
+        ],
+        "ip" => vec![("procfs route/fib_trie", require_path("/proc/net/fib_trie"))],
+        "diskio" => vec![("procfs diskstats", require_path("/proc/diskstats"))],
+        "disk-temp" => vec![("sysfs nvme hwmon", require_path("/sys/class/nvme"))],
+        "temp" => vec![("sysfs hwmon", require_path("/sys/class/hwmon"))],
+        "count" => vec![("inotify", Ok(()))],
+        "cpu" => vec![("procfs stat", require_path("/proc/stat"))],
+        "pressure" => vec![("procfs pressure", require_path("/proc/pressure"))],
+        "updates" => ["pacman", "apt", "dnf"]
+            .iter()
+            .map(|name| {
+                let command = updates::Backend::parse(name).unwrap().command();
+                (*name, require_command(command))
+            })
+            .collect(),
+        "peripherals" => vec![("gdbus + UPower", require_command("gdbus"))],
+        "nightlight" => vec![
+            ("gammastep", require_command("gammastep")),
+            ("redshift", require_command("redshift")),
+            ("wlsunset", require_command("wlsunset")),
+        ],
+        "workspace" | "window-title" => vec![
+            (
+                "sway/i3 IPC",
+                require_env("SWAYSOCK").or_else(|_| require_env("I3SOCK")),
+            ),
+            ("Hyprland IPC", require_env("HYPRLAND_INSTANCE_SIGNATURE")),
+        ],
+        "locks" => vec![("sysfs leds", require_path("/sys/class/leds"))],
+        "lid" => vec![
+            ("ACPI lid button", require_path("/proc/acpi/button/lid")),
+            ("gdbus + logind", require_command("gdbus")),
+        ],
+        "docked" => vec![("sysfs drm connectors", require_path("/sys/class/drm"))],
+        "idle" | "locked" => vec![("gdbus + logind", require_command("gdbus"))],
+        "wifi-detail" => vec![("netlink nl80211", require_path("/sys/class/net"))],
+        "gpu-temp" => vec![
+            ("sysfs drm hwmon", require_path("/sys/class/drm")),
+            ("nvidia-smi", require_command("nvidia-smi")),
+        ],
+        "timer" => vec![("runtime dir", require_env("XDG_RUNTIME_DIR"))],
+        "clipboard" => vec![
+            ("wl-paste", require_command("wl-paste")),
+            ("x11 selections", require_env("DISPLAY")),
+        ],
+        "failed-units" => vec![
+            ("gdbus + systemd", require_command("gdbus")),
+            ("systemctl", require_command("systemctl")),
+        ],
+        "containers" => vec![(
+            "Docker/Podman API socket",
+            containers::find_socket()
+                .map(drop)
+                .ok_or_else(|| "no Docker or Podman socket found".to_string()),
+        )],
+        "cgroup" => vec![(
+            "cgroup v2",
+            require_path("/sys/fs/cgroup/cgroup.controllers"),
+        )],
+        "net-rate" => vec![("sysfs net statistics", require_path("/sys/class/net"))],
+        "top" => vec![("procfs pid stat/status", require_path("/proc/stat"))],
+        "volume-events" => vec![
+            ("pactl subscribe", require_command("pactl")),
+            ("alsactl monitor", require_command("alsactl")),
+        ],
+        "notifications" | "eject" => vec![("gdbus", require_command("gdbus"))],
+        "xsetroot" => vec![("x11", require_env("DISPLAY"))],
+        "control-socket" => vec![("unix socket", require_env("XDG_RUNTIME_DIR"))],
+        "dbus-service" => vec![("zbus session bus", require_env("DBUS_SESSION_BUS_ADDRESS"))],
+        "uevents" => vec![("netlink kobject-uevent", Ok(()))],
+        _ => Vec::new(),
+    }
+}
+
+// 除模块外的其他功能
+const FEATURES: &[&str] = &[
+    "volume-events",
+    "notifications",
+    "eject",
+    "xsetroot",
+    "control-socket",
+    "dbus-service",
+    "uevents",
+];
+
+fn probe(name: &'static str, kind: &'static str, matches: &clap::ArgMatches) -> Capability {
+    let mut capability = Capability {
+        name,
+        kind,
+        backend: None,
+        reason: None,
+        rejected: Vec::new(),
+    };
+    for (backend, result) in backends(name) {
+        match result {
+            Ok(()) if capability.backend.is_none() => capability.backend = Some(backend),
+            Ok(()) => {}
+            Err(reason) => capability.rejected.push((backend, reason)),
+        }
+    }
+    match capability.backend {
+        // 模块实际读取一次，确认能够初始化
+        Some(_) if kind == "module" => {
+            if let Err(e) = module::read(name, matches) {
+                capability.reason = Some(e.to_string());
+            }
+        }
+        Some(_) => {}
+        None => {
+            capability.reason = Some(
+                capability
+                    .rejected
+                    .first()
+                    .map_or("no backend".to_string(), |(_, reason)| reason.clone()),
+            )
+        }
+    }
+    capability
+}
+
+// 模块能否在本机上工作
+pub fn available(name: &'static str, matches: &clap::ArgMatches) -> bool {
+    probe(name, "module", matches).reason.is_none()
+}
+
+fn to_json(capabilities: &[Capability]) -> String {
+    let string = |s: &str| format!("\"{}\"", json_escape(s));
+    let items: Vec<String> = capabilities
+        .iter()
+        .map(|c| {
+            let rejected: Vec<String> = c
+                .rejected
+                .iter()
+                .map(|(backend, reason)| {
+                    format!(
+                        "{{\"backend\":{},\"reason\":{}}}",
+                        string(backend),
+                        string(reason)
+                    )
+                })
+                .collect();
+            format!(
+                "{{\"name\":{},\"kind\":{},\"available\":{},\"backend\":{},\"reason\":{},\"rejected\":[{}]}}",
+                string(c.name),
+                string(c.kind),
+                c.reason.is_none(),
+                c.backend.map_or("null".to_string(), string),
+                c.reason.as_deref().map_or("null".to_string(), string),
+                rejected.join(",")
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+// 列出所有模块和功能在本机上是否可用、选用的后端以及其他后端被放弃的原因
+pub fn report(matches: &clap::ArgMatches, json: bool) -> String {
+    let capabilities: Vec<Capability> = module::names()
+        .map(|name| probe(name, "module", matches))
+        .chain(FEATURES.iter().map(|&name| probe(name, "feature", matches)))
+        .collect();
+    if json {
+        return to_json(&capabilities);
+    }
+
+    let mut lines = Vec::new();
+    for c in &capabilities {
+        let status = if c.reason.is_none() {
+            "ok"
+        } else {
+            "unavailable"
+        };
+        let mut line = format!("{:<16} {:<11} {}", c.name, status, c.backend.unwrap_or("-"));
+        if let Some(reason) = &c.reason {
+            line += &format!(" ({})", reason);
+        }
+        lines.push(line);
+        for (backend, reason) in &c.rejected {
+            lines.push(format!("{:<28} rejected {}: {}", "", backend, reason));
+        }
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,108 @@
+use crate::error::Error;
+use crate::module::{self, Builtin, MetricValue};
+use crate::render::Block;
+use crate::{counters, format_size, read_file, sysfs};
+use std::io;
+use std::path::Path;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+// cgroup v2 目录，path 相对于 /sys/fs/cgroup，如 user.slice 或 system.slice/docker-<id>.scope，
+// "/" 表示根 cgroup
+fn cgroup_dir(path: &str) -> String {
+    let path = path
+        .strip_prefix(CGROUP_ROOT)
+        .unwrap_or(path)
+        .trim_matches('/');
+    if path.is_empty() {
+        return CGROUP_ROOT.to_string();
+    }
+    format!("{}/{}", CGROUP_ROOT, path)
+}
+
+// cpu.stat 中的 usage_usec：cgroup 内所有任务累计使用的 CPU 时间（微秒）
+fn parse_usage_usec(stat: &str) -> Option<u64> {
+    stat.lines()
+        .find_map(|line| line.strip_prefix("usage_usec "))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+fn read_usage_usec(dir: &str) -> Result<Vec<u64>, Error> {
+    let stat = read_file(Path::new(dir).join("cpu.stat"))?;
+    let usage = parse_usage_usec(&stat)
+        .ok_or_else(|| Error::Parse(format!("usage_usec not found in {}/cpu.stat", dir)))?;
+    Ok(vec![usage])
+}
+
+// 输出 cgroup 的内存占用（memory.current）和 CPU 占用率，如 "CG user.slice: 1.2G 35%"
+// CPU 占用率以单核为 100%，多核满载时可超过 100%
+pub fn get_cgroup(path: &str) -> Result<String, Error> {
+    let dir = cgroup_dir(path);
+    if !sysfs::exists(&dir) {
+        return Err(Error::MissingDevice(format!("cgroup {} not found", dir)));
+    }
+    let memory = match sysfs::read_int(Path::new(&dir).join("memory.current")) {
+        Ok(bytes) => format_size(bytes.max(0) as u64),
+        // 未启用 memory 控制器时没有 memory.current
+        Err(e) if e.kind() == io::ErrorKind::NotFound => "-".to_string(),
+        Err(e) => return Err(Error::from(e)),
+    };
+    let (elapsed, previous, current) =
+        counters::delta(&format!("cgroup:{}", dir), || read_usage_usec(&dir))?;
+    let used = current[0].saturating_sub(previous.first().copied().unwrap_or(current[0]));
+    let percent = used as f64 / (elapsed * 1e6) * 100.0;
+    let name = match dir.strip_prefix(CGROUP_ROOT) {
+        Some("") => "/",
+        _ => dir.rsplit('/').next().unwrap_or(&dir),
+    };
+    Ok(format!("CG {}: {} {:.0}%", name, memory, percent))
+}
+
+pub struct Cgroup {
+    path: Option<String>,
+}
+
+impl Builtin for Cgroup {
+    const ID: &'static str = "cgroup";
+    const BLOCK: &'static str = "cgroup";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Cgroup {
+            path: matches.get_one::<String>("cgroup").cloned(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let path = module::required(&self.path, "cgroup", "--cgroup PATH")?;
+        Ok(Block::numeric(Self::BLOCK, get_cgroup(path)?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        (!sysfs::exists("/sys/fs/cgroup/cgroup.controllers"))
+            .then(|| "cgroup v2 is not mounted".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_cgroup_files() {
+        sysfs::use_fixtures();
+        assert_eq!(cgroup_dir("/user.slice/"), "/sys/fs/cgroup/user.slice");
+        assert_eq!(cgroup_dir("/"), "/sys/fs/cgroup");
+        assert_eq!(
+            cgroup_dir("/sys/fs/cgroup/user.slice"),
+            "/sys/fs/cgroup/user.slice"
+        );
+        assert_eq!(
+            read_usage_usec(&cgroup_dir("user.slice")).unwrap(),
+            [81234567]
+        );
+        assert!(matches!(
+            get_cgroup("no-such.slice"),
+            Err(Error::MissingDevice(_))
+        ));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::error::Error;
+use crate::event_loop::EventLoop;
+use crate::watch::Wake;
+use crate::{audio, backlight, caffeine, dnd, kbd_backlight, log, media, script};
+use rustix::fd::AsFd;
+use std::fs::File;
+use std::io;
+
+// 从 JSON 对象中取出字段值的起始位置，允许冒号前后有空格（swaybar 的输出带空格）
+fn field<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let start = line.find(&format!("\"{}\"", key))? + key.len() + 2;
+    line[start..]
+        .trim_start()
+        .strip_prefix(':')
+        .map(str::trim_start)
+}
+
+// 取出字符串字段的值，字段不存在或为 null 时返回 None
+fn string_field(line: &str, key: &str) -> Option<String> {
+    let value = field(line, key)?.strip_prefix('"')?;
+    Some(value[..value.find('"')?].to_string())
+}
+
+// 解析 i3bar 点击事件，返回块名、instance（输出块时设置的模块名）和按键编号
+// 事件流是一个无限数组：第一行为 "["，之后每行一个对象，除第一个外以逗号开头
+pub fn parse_event(line: &str) -> Option<(String, Option<String>, u8)> {
+    let line = line.trim_start_matches(['[', ',', ' ']);
+    let name = string_field(line, "name")?;
+    let button = field(line, "button")?;
+    let end = button
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(button.len());
+    Some((
+        name,
+        string_field(line, "instance"),
+        button[..end].parse().ok()?,
+    ))
+}
+
+// 读取 i3bar 写到标准输入的点击事件
+pub fn register(events: &mut EventLoop) -> Result<(), io::Error> {
+    // 不经过 Stdin 的缓冲，否则读入缓冲区的事件不会再触发 epoll
+    let stdin = File::from(io::stdin().as_fd().try_clone_to_owned()?);
+    events.add_lines(stdin, |line, woken| {
+        if let Some((name, instance, button)) = parse_event(line) {
+            woken.push(Wake::Click {
+                name,
+                instance,
+                button,
+            });
+        }
+    })
+}
+
+// 块的默认动作：点击音量切换静音，滚轮调整音量和亮度，点击媒体块播放/暂停，点击免打扰和 caffeine 块切换
+fn default_action(block: &str, button: u8, player: Option<&str>) -> Option<Result<(), Error>> {
+    Some(match (block, button) {
+        ("volume", 1) => audio::control("mute"),
+        ("volume", 4) => audio::control("up"),
+        ("volume", 5) => audio::control("down"),
+        ("backlight", 4) => backlight::set_brightness("up", None, None).map(drop),
+        ("backlight", 5) => backlight::set_brightness("down", None, None).map(drop),
+        ("kbd_backlight", 4) => kbd_backlight::control("up", None).map(drop),
+        ("kbd_backlight", 5) => kbd_backlight::control("down", None).map(drop),
+        ("dnd", 1) => dnd::toggle().map(drop),
+        ("caffeine", 1) => caffeine::control("toggle").map(drop),
+        ("media", 1) => media::control(player, "play-pause"),
+        ("media", 2) => media::control(player, "prev"),
+        ("media", 3) => media::control(player, "next"),
+        _ => return None,
+    })
+}
+
+// 执行点击动作：配置了命令时通过 sh -c 运行，按键编号通过 SYS_MONTION_BUTTON 传入
+pub fn dispatch(block: &str, button: u8, command: Option<&str>, player: Option<&str>) {
+    if let Some(command) = command {
+        script::spawn(
+            command,
+            &[("SYS_MONTION_BUTTON", button.to_string())],
+            &format!("click action for {}", block),
+        );
+        return;
+    }
+    match default_action(block, button, player) {
+        Some(Err(e)) => log::error!("click action for {}: {}", block, e),
+        Some(Ok(())) => {}
+        None => log::debug!("no action for button {} on {}", button, block),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_click_events() {
+        assert_eq!(parse_event("["), None);
+        assert_eq!(
+            parse_event(r#"{"name":"volume","instance":null,"button":1,"x":1200,"y":10}"#),
+            Some(("volume".to_string(), None, 1))
+        );
+        assert_eq!(
+            parse_event(r#",{ "name": "backlight", "button": 5, "modifiers": [ ] }"#),
+            Some(("backlight".to_string(), None, 5))
+        );
+        assert_eq!(
+            parse_event(r#",{"name":"cpu","instance":"throttle","button":1}"#),
+            Some(("cpu".to_string(), Some("throttle".to_string()), 1))
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{format_size, locale};
+use std::env;
+use std::fmt::Display;
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, CreateWindowAux, WindowClass};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::{COPY_FROM_PARENT, CURRENT_TIME, NONE};
+
+pub const SELECTIONS: [&str; 2] = ["clipboard", "primary"];
+
+// 等待 X11 选区所有者响应的时间，所有者卡住时不阻塞刷新
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+// 文本类型按优先级排列，Wayland 为 MIME 类型，X11 为目标名
+const TEXT_TYPES: [&str; 5] = [
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "text/plain",
+    "STRING",
+    "TEXT",
+];
+
+// X11 选区所有者都会提供的元数据目标，不代表内容
+const META_TARGETS: [&str; 4] = ["TARGETS", "TIMESTAMP", "MULTIPLE", "SAVE_TARGETS"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Text,
+    Image,
+    Other,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Text => "text",
+            Kind::Image => "image",
+            Kind::Other => "data",
+        }
+    }
+}
+
+// 按提供的类型判断内容，图片优先（截图工具通常同时提供文件路径文本），返回用于读取大小的类型
+fn classify(types: &[String]) -> Option<(Kind, &str)> {
+    if let Some(image) = types.iter().find(|t| t.starts_with("image/")) {
+        return Some((Kind::Image, image));
+    }
+    if let Some(text) = TEXT_TYPES
+        .iter()
+        .find_map(|name| types.iter().find(|t| t == name))
+    {
+        return Some((Kind::Text, text));
+    }
+    types
+        .iter()
+        .find(|t| !META_TARGETS.contains(&t.as_str()))
+        .map(|t| (Kind::Other, t.as_str()))
+}
+
+// 运行 wl-paste，剪贴板为空时 wl-paste 以非零状态退出，此时返回 None
+fn wl_paste(primary: bool, args: &[&str]) -> Result<Option<Vec<u8>>, Error> {
+    let mut command = locale::command("wl-paste");
+    if primary {
+        command.arg("--primary");
+    }
+    let output = command.args(args).output().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => {
+            Error::BackendUnavailable("wl-paste is not installed (wl-clipboard)".to_string())
+        }
+        _ => Error::from(e),
+    })?;
+    Ok(output.status.success().then_some(output.stdout))
+}
+
+// Wayland：wl-clipboard 通过 data-control 协议读取，需要合成器支持（wlroots、KDE 等）
+fn wayland(primary: bool) -> Result<Option<(Kind, u64)>, Error> {
+    let Some(types) = wl_paste(primary, &["--list-types"])? else {
+        return Ok(None);
+    };
+    let types: Vec<String> = String::from_utf8_lossy(&types)
+        .lines()
+        .map(str::to_string)
+        .collect();
+    let Some((kind, target)) = classify(&types) else {
+        return Ok(None);
+    };
+    let size =
+        wl_paste(primary, &["--no-newline", "--type", target])?.map_or(0, |data| data.len() as u64);
+    Ok(Some((kind, size)))
+}
+
+fn x11_error(e: impl Display) -> Error {
+    Error::BackendUnavailable(format!("X11: {}", e))
+}
+
+fn intern(conn: &RustConnection, name: &str) -> Result<Atom, Error> {
+    Ok(conn
+        .intern_atom(false, name.as_bytes())
+        .map_err(x11_error)?
+        .reply()
+        .map_err(x11_error)?
+        .atom)
+}
+
+// 请求所有者把选区转换为 target 并写入窗口属性，所有者拒绝转换时返回 false
+fn convert(
+    conn: &RustConnection,
+    window: u32,
+    selection: Atom,
+    target: Atom,
+    property: Atom,
+) -> Result<bool, Error> {
+    conn.convert_selection(window, selection, target, property, CURRENT_TIME)
+        .map_err(x11_error)?;
+    conn.flush().map_err(x11_error)?;
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        while let Some(event) = conn.poll_for_event().map_err(x11_error)? {
+            if let Event::SelectionNotify(notify) = event {
+                if notify.requestor == window {
+                    return Ok(notify.property != NONE);
+                }
+            }
+        }
+        if Instant::now() >= deadline {
+            return Err(Error::BackendUnavailable(
+                "the selection owner did not respond".to_string(),
+            ));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+// X11：先读取 TARGETS 判断类型，再只读取属性长度，不传输内容
+// 大于服务器请求上限的内容使用 INCR 分段传输，此时属性值为大小的下限
+fn x11(primary: bool) -> Result<Option<(Kind, u64)>, Error> {
+    let (conn, screen) = x11rb::connect(None).map_err(x11_error)?;
+    let root = conn.setup().roots[screen].root;
+    let selection = match primary {
+        true => AtomEnum::PRIMARY.into(),
+        false => intern(&conn, "CLIPBOARD")?,
+    };
+    let owner = conn
+        .get_selection_owner(selection)
+        .map_err(x11_error)?
+        .reply()
+        .map_err(x11_error)?
+        .owner;
+    if owner == NONE {
+        return Ok(None);
+    }
+    let window = conn.generate_id().map_err(x11_error)?;
+    conn.create_window(
+        COPY_FROM_PARENT as u8,
+        window,
+        root,
+        0,
+        0,
+        1,
+        1,
+        0,
+        WindowClass::INPUT_ONLY,
+        COPY_FROM_PARENT,
+        &CreateWindowAux::new(),
+    )
+    .map_err(x11_error)?;
+    let property = intern(&conn, "SYS_MONTION_SELECTION")?;
+    if !convert(
+        &conn,
+        window,
+        selection,
+        intern(&conn, "TARGETS")?,
+        property,
+    )? {
+        return Ok(None);
+    }
+    let atoms: Vec<Atom> = conn
+        .get_property(true, window, property, AtomEnum::ATOM, 0, 1024)
+        .map_err(x11_error)?
+        .reply()
+        .map_err(x11_error)?
+        .value32()
+        .map_or(Vec::new(), |atoms| atoms.collect());
+    let mut types = Vec::new();
+    for atom in &atoms {
+        let reply = conn
+            .get_atom_name(*atom)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+        types.push(String::from_utf8_lossy(&reply.name).to_string());
+    }
+    let Some((kind, target)) = classify(&types) else {
+        return Ok(None);
+    };
+    let target = atoms[types.iter().position(|t| t == target).unwrap()];
+    if !convert(&conn, window, selection, target, property)? {
+        return Ok(None);
+    }
+    let reply = conn
+        .get_property(true, window, property, AtomEnum::ANY, 0, 1)
+        .map_err(x11_error)?
+        .reply()
+        .map_err(x11_error)?;
+    let size = if reply.type_ == intern(&conn, "INCR")? {
+        reply.value32().and_then(|mut v| v.next()).unwrap_or(0) as u64
+    } else {
+        (reply.value_len * u32::from(reply.format) / 8 + reply.bytes_after) as u64
+    };
+    Ok(Some((kind, size)))
+}
+
+fn describe(content: Option<(Kind, u64)>) -> String {
+    match content {
+        Some((kind, size)) => format!("CLIP: {} {}", kind.name(), format_size(size)),
+        None => "CLIP: empty".to_string(),
+    }
+}
+
+// 输出剪贴板（或 primary 选区）的内容类型和大小，如 "CLIP: text 1.2K"、"CLIP: image 340K"
+pub fn get_clipboard(selection: &str) -> Result<String, Error> {
+    let primary = selection == "primary";
+    let has = |var: &str| env::var_os(var).is_some_and(|value| !value.is_empty());
+    let content = if has("WAYLAND_DISPLAY") {
+        wayland(primary)?
+    } else if has("DISPLAY") {
+        x11(primary)?
+    } else {
+        return Err(Error::MissingDevice(
+            "no Wayland or X11 display".to_string(),
+        ));
+    };
+    Ok(describe(content))
+}
+
+pub struct Clipboard {
+    selection: String,
+}
+
+impl Builtin for Clipboard {
+    const ID: &'static str = "clipboard";
+    const BLOCK: &'static str = "clipboard";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Clipboard {
+            selection: matches
+                .get_one::<String>("clipboard")
+                .map_or("clipboard", String::as_str)
+                .to_string(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_clipboard(&self.selection)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_selection_types() {
+        let types = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let x11 = types(&["TARGETS", "TIMESTAMP", "STRING", "UTF8_STRING"]);
+        assert_eq!(classify(&x11), Some((Kind::Text, "UTF8_STRING")));
+        let screenshot = types(&["text/uri-list", "text/plain", "image/png"]);
+        assert_eq!(classify(&screenshot), Some((Kind::Image, "image/png")));
+        assert_eq!(classify(&types(&["TARGETS", "TIMESTAMP"])), None);
+        assert_eq!(describe(Some((Kind::Text, 1536))), "CLIP: text 1.5K");
+        assert_eq!(describe(None), "CLIP: empty");
+    }
+}
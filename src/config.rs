@@ -0,0 +1,399 @@
+use crate::event_loop::EventLoop;
+use crate::notify::{Backend, Routes};
+use crate::watch::Wake;
+use rustix::event::epoll::EventFlags;
+use rustix::fs::inotify::{self, CreateFlags, WatchFlags};
+use rustix::io::Errno;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::mem::MaybeUninit;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml::Spanned;
+
+// 配置文件，默认位于 $XDG_CONFIG_HOME/sys-montion/config.toml
+//
+// interval = 5
+// separator = " | "
+// skip_missing = true
+// warn_below = 20
+// max_width = 120
+// color_warning = "#FFAA00"
+// battery_smoothing = 0.2
+//
+// [[modules]]
+// name = "battery"
+// interval = 30
+// critical_below = 10
+// color_critical = "#FF00FF"
+//
+// [[modules]]
+// name = "volume-level"
+// signal = 2
+// on_click = "pavucontrol"
+//
+// [[modules]]
+// name = "media"
+// max_width = 30
+//
+// [[modules]]
+// name = "script"
+// command = "~/bin/weather.sh"
+// label = "W:"
+// interval = 600
+//
+// [[modules]]
+// name = "plugin"
+// path = "/usr/lib/sys-montion/libgpu.so"
+//
+// [[modules]]
+// name = "battery"
+// hide_if = "ac and value > 95"
+//
+// [[alerts]]
+// name = "fan"
+// backend = "ntfy"
+// topic = "my-server"
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // 模块默认刷新间隔（秒），未设置时使用 --watch 的值
+    pub interval: Option<u64>,
+    // 组合输出时模块之间的分隔符
+    pub separator: Option<String>,
+    // 省略缺少硬件的模块（台式机没有电池、背光），同 --skip-missing
+    pub skip_missing: bool,
+    // 整行最大宽度，同 --max-width N
+    pub max_width: Option<usize>,
+    // 阈值和颜色，同 --warn-below、--color-warning 等，命令行指定时以命令行为准
+    pub warn_below: Option<f64>,
+    pub critical_below: Option<f64>,
+    pub warn_above: Option<f64>,
+    pub critical_above: Option<f64>,
+    pub color_normal: Option<Spanned<String>>,
+    pub color_warning: Option<Spanned<String>>,
+    pub color_critical: Option<Spanned<String>>,
+    // watch 模式下电池功率和剩余时间的平滑因子，同 --battery-smoothing
+    pub battery_smoothing: Option<Spanned<f64>>,
+    pub modules: Vec<ModuleConfig>,
+    pub alerts: Vec<AlertConfig>,
+    // 配置文件路径和内容，用于在错误信息中定位行列
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    content: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleConfig {
+    // 模块名称，与命令行参数同名，如 battery、volume-level、memory
+    pub name: Spanned<String>,
+    pub interval: Option<Spanned<u64>>,
+    // 收到 SIGRTMIN+signal 时立即刷新该模块
+    pub signal: Option<Spanned<i32>>,
+    // script 模块：要运行的命令（通过 sh -c）及输出前缀
+    pub command: Option<Spanned<String>>,
+    pub label: Option<String>,
+    // plugin 模块：要加载的共享库
+    pub path: Option<Spanned<String>>,
+    // 该模块输出的最大宽度（字符数），同 --max-width MODULE=N
+    pub max_width: Option<usize>,
+    // 该模块的阈值，同 --warn-below MODULE=N 等，未设置的使用全局阈值
+    pub warn_below: Option<f64>,
+    pub critical_below: Option<f64>,
+    pub warn_above: Option<f64>,
+    pub critical_above: Option<f64>,
+    // 该模块各级别的颜色，同 color_normal 等，未设置的使用全局颜色
+    pub color_normal: Option<Spanned<String>>,
+    pub color_warning: Option<Spanned<String>>,
+    pub color_critical: Option<Spanned<String>>,
+    // i3bar 点击事件触发的命令（通过 sh -c），未设置时使用模块的默认动作
+    pub on_click: Option<String>,
+    pub on_middle_click: Option<String>,
+    pub on_right_click: Option<String>,
+    pub on_scroll_up: Option<String>,
+    pub on_scroll_down: Option<String>,
+    // watch 模式下的显示条件，语法见 visibility.rs
+    pub show_if: Option<Spanned<String>>,
+    pub hide_if: Option<Spanned<String>>,
+}
+
+impl ModuleConfig {
+    pub fn name(&self) -> &str {
+        self.name.get_ref()
+    }
+
+    pub fn interval(&self) -> Option<u64> {
+        self.interval.as_ref().map(|i| *i.get_ref())
+    }
+
+    pub fn signal(&self) -> Option<i32> {
+        self.signal.as_ref().map(|s| *s.get_ref())
+    }
+
+    pub fn command(&self) -> Option<&str> {
+        self.command.as_ref().map(|c| c.get_ref().as_str())
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_ref().map(|p| p.get_ref().as_str())
+    }
+
+    pub fn show_if(&self) -> Option<&str> {
+        self.show_if.as_ref().map(|c| c.get_ref().as_str())
+    }
+
+    pub fn hide_if(&self) -> Option<&str> {
+        self.hide_if.as_ref().map(|c| c.get_ref().as_str())
+    }
+
+    // 按 i3bar 的按键编号排列：左键、中键、右键、滚轮上、滚轮下
+    pub fn actions(&self) -> [Option<&str>; 5] {
+        [
+            &self.on_click,
+            &self.on_middle_click,
+            &self.on_right_click,
+            &self.on_scroll_up,
+            &self.on_scroll_down,
+        ]
+        .map(|action| action.as_deref())
+    }
+}
+
+// 告警（--notify、--memory-low、--fan-alert）的发送方式，同一告警可配置多条，都会发送
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertConfig {
+    // 告警名称：battery、memory 或 fan
+    pub name: Spanned<String>,
+    // desktop（默认）、webhook（url）、ntfy（topic，可用 url 指定服务器）或 command
+    pub backend: Spanned<String>,
+    pub url: Option<String>,
+    pub topic: Option<String>,
+    pub command: Option<String>,
+}
+
+const ALERTS: &[&str] = &["battery", "memory", "fan"];
+
+impl AlertConfig {
+    fn backend(&self) -> Result<Backend, String> {
+        let missing = |field: &str| format!("{} backend needs {}", self.backend.get_ref(), field);
+        Ok(match self.backend.get_ref().as_str() {
+            "desktop" => Backend::Desktop,
+            "webhook" => Backend::Webhook(self.url.clone().ok_or_else(|| missing("a url"))?),
+            "ntfy" => {
+                let topic = self.topic.as_ref().ok_or_else(|| missing("a topic"))?;
+                let server = self.url.as_deref().unwrap_or("https://ntfy.sh");
+                Backend::Ntfy(format!("{}/{}", server.trim_end_matches('/'), topic))
+            }
+            "command" => {
+                Backend::Command(self.command.clone().ok_or_else(|| missing("a command"))?)
+            }
+            other => {
+                return Err(format!(
+                    "unknown backend '{}', expected desktop, webhook, ntfy or command",
+                    other
+                ))
+            }
+        })
+    }
+}
+
+// 把字节偏移转换为 "path:行:列"，行列从 1 开始
+fn location(path: &std::path::Path, content: &str, offset: usize) -> String {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    format!("{}:{}:{}", path.display(), line, column)
+}
+
+impl Config {
+    // 配置文件中某个位置的 "path:行:列"，用于错误和警告信息
+    pub fn location(&self, span: Range<usize>) -> String {
+        location(&self.path, &self.content, span.start)
+    }
+
+    // 各告警的发送方式，load 时已检查过
+    pub fn alert_routes(&self) -> Routes {
+        Routes::new(
+            self.alerts
+                .iter()
+                .filter_map(|alert| Some((alert.name.get_ref().clone(), alert.backend().ok()?)))
+                .collect(),
+        )
+    }
+}
+
+// 配置文件路径：命令行指定的路径，否则为默认路径（文件不一定存在）
+pub fn path(path: Option<&str>) -> Option<PathBuf> {
+    match path {
+        Some(path) => Some(PathBuf::from(path)),
+        None => default_path(),
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    Some(crate::paths::config_dir().ok()?.join("config.toml"))
+}
+
+// 读取配置文件；未指定路径且默认配置文件不存在时返回空配置
+pub fn load(path: Option<&str>) -> Result<Config, io::Error> {
+    let (path, explicit) = match path {
+        Some(path) => (PathBuf::from(path), true),
+        None => match default_path() {
+            Some(path) => (path, false),
+            None => return Ok(Config::default()),
+        },
+    };
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !explicit => return Ok(Config::default()),
+        Err(e) => return Err(e),
+    };
+
+    let mut config: Config = toml::from_str(&content).map_err(|e| {
+        let at = match e.span() {
+            Some(span) => location(&path, &content, span.start),
+            None => path.display().to_string(),
+        };
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", at, e.message()),
+        )
+    })?;
+    config.path = path;
+    config.content = content;
+
+    let invalid = |span: Range<usize>, message: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", config.location(span), message),
+        )
+    };
+    let module_colors = config
+        .modules
+        .iter()
+        .flat_map(|m| [&m.color_normal, &m.color_warning, &m.color_critical]);
+    for color in [
+        &config.color_normal,
+        &config.color_warning,
+        &config.color_critical,
+    ]
+    .into_iter()
+    .chain(module_colors)
+    .flatten()
+    {
+        crate::render::parse_color(color.get_ref()).map_err(|e| invalid(color.span(), e))?;
+    }
+    if let Some(factor) = &config.battery_smoothing {
+        crate::battery::check_smoothing(*factor.get_ref())
+            .map_err(|e| invalid(factor.span(), e))?;
+    }
+    for module in &config.modules {
+        if crate::module::get(module.name()).is_none() {
+            return Err(invalid(
+                module.name.span(),
+                format!("unknown module '{}'", module.name()),
+            ));
+        }
+        if let Some(interval) = module.interval.as_ref().filter(|i| *i.get_ref() == 0) {
+            return Err(invalid(
+                interval.span(),
+                "interval must be at least 1".to_string(),
+            ));
+        }
+        match (&module.command, module.name()) {
+            (None, "script") => {
+                return Err(invalid(
+                    module.name.span(),
+                    "script module needs a command".to_string(),
+                ))
+            }
+            (Some(command), name) if name != "script" => {
+                return Err(invalid(
+                    command.span(),
+                    format!("command is only valid for script modules, not '{}'", name),
+                ))
+            }
+            _ => {}
+        }
+        match (&module.path, module.name()) {
+            (None, "plugin") => {
+                return Err(invalid(
+                    module.name.span(),
+                    "plugin module needs a path".to_string(),
+                ))
+            }
+            (Some(path), name) if name != "plugin" => {
+                return Err(invalid(
+                    path.span(),
+                    format!("path is only valid for plugin modules, not '{}'", name),
+                ))
+            }
+            _ => {}
+        }
+        if let Some(signal) = &module.signal {
+            crate::signals::check_offset(*signal.get_ref())
+                .map_err(|e| invalid(signal.span(), e.to_string()))?;
+        }
+        for condition in [&module.show_if, &module.hide_if].into_iter().flatten() {
+            crate::visibility::Condition::parse(condition.get_ref())
+                .map_err(|e| invalid(condition.span(), e))?;
+        }
+    }
+    for alert in &config.alerts {
+        if !ALERTS.contains(&alert.name.get_ref().as_str()) {
+            return Err(invalid(
+                alert.name.span(),
+                format!(
+                    "unknown alert '{}', expected battery, memory or fan",
+                    alert.name.get_ref()
+                ),
+            ));
+        }
+        alert
+            .backend()
+            .map_err(|e| invalid(alert.backend.span(), e))?;
+    }
+    Ok(config)
+}
+
+// 用 inotify 监听配置文件所在目录，配置文件保存后唤醒 watch 循环重新加载
+// 编辑器常先写临时文件再改名覆盖，所以监听目录而不是文件本身
+pub fn register(events: &mut EventLoop, path: &Path) -> Result<(), io::Error> {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let Some(name) = path.file_name().map(|name| name.to_os_string()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "not a file path",
+        ));
+    };
+    let fd = inotify::init(CreateFlags::CLOEXEC | CreateFlags::NONBLOCK)?;
+    inotify::add_watch(&fd, dir, WatchFlags::CLOSE_WRITE | WatchFlags::MOVED_TO)?;
+    let mut buf = [MaybeUninit::uninit(); 4096];
+    events.add(fd, EventFlags::IN, move |fd, woken| {
+        let mut reader = inotify::Reader::new(&*fd, &mut buf);
+        let mut changed = false;
+        loop {
+            match reader.next() {
+                Ok(event) => {
+                    changed |= event
+                        .file_name()
+                        .is_some_and(|file| file.to_bytes() == name.as_encoded_bytes());
+                }
+                Err(Errno::AGAIN) => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if changed {
+            woken.push(Wake::Reload);
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,178 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{counters, format_size};
+use std::cell::Cell;
+use std::env;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+
+// 单次请求的读写超时，stats 接口需要采样一次 CPU，Docker 通常在 1 秒内返回
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// 查找容器引擎的 API 套接字：DOCKER_HOST（unix://）、Docker、rootless Podman、rootful Podman
+pub fn find_socket() -> Option<String> {
+    if let Some(path) = env::var("DOCKER_HOST")
+        .ok()
+        .and_then(|host| host.strip_prefix("unix://").map(str::to_string))
+    {
+        return Some(path);
+    }
+    let mut candidates = vec!["/var/run/docker.sock".to_string()];
+    if let Some(dir) = env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        candidates.push(format!("{}/podman/podman.sock", dir.to_string_lossy()));
+    }
+    candidates.push("/run/podman/podman.sock".to_string());
+    candidates.into_iter().find(|path| Path::new(path).exists())
+}
+
+// 通过 Docker 兼容 API 发送 GET 请求，返回响应正文
+// 使用 HTTP/1.0，服务端在响应后关闭连接，不会使用分块编码
+fn get(socket: &str, path: &str) -> Result<String, Error> {
+    let mut stream = UnixStream::connect(socket).map_err(|e| match e.kind() {
+        io::ErrorKind::PermissionDenied => Error::PermissionDenied(format!(
+            "cannot connect to {}: permission denied (add your user to the docker group)",
+            socket
+        )),
+        _ => Error::BackendUnavailable(format!("cannot connect to {}: {}", socket, e)),
+    })?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    write!(stream, "GET {} HTTP/1.0\r\nHost: localhost\r\n\r\n", path)?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    parse_response(&response)
+}
+
+fn parse_response(response: &str) -> Result<String, Error> {
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| Error::Parse("invalid HTTP response from container engine".to_string()))?;
+    let status = head.lines().next().unwrap_or("");
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(Error::BackendUnavailable(format!(
+            "container engine returned '{}'",
+            status
+        )));
+    }
+    Ok(body.to_string())
+}
+
+// /containers/json 返回运行中容器的数组，取出每个容器的 "Id"
+// 带引号匹配，不会误匹配 ImageID、NetworkID 等字段
+fn parse_ids(json: &str) -> Vec<String> {
+    json.match_indices("\"Id\":")
+        .filter_map(|(start, _)| {
+            let value = json[start + 5..].trim_start().strip_prefix('"')?;
+            Some(value[..value.find('"')?].to_string())
+        })
+        .collect()
+}
+
+// 取出 key 之后第一个 "field": 数值
+fn json_number(json: &str, key: &str, field: &str) -> Option<u64> {
+    let object = &json[json.find(&format!("\"{}\":", key))?..];
+    let start = object.find(&format!("\"{}\":", field))? + field.len() + 3;
+    let value = object[start..].trim_start();
+    let end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+// 单个容器的累计 CPU 时间（纳秒）和当前内存用量（字节）
+// "cpu_stats" 带引号匹配，不会匹配到 "precpu_stats"
+fn parse_stats(json: &str) -> Option<(u64, u64)> {
+    Some((
+        json_number(json, "cpu_stats", "total_usage")?,
+        json_number(json, "memory_stats", "usage").unwrap_or(0),
+    ))
+}
+
+// 输出运行中的容器数，如 "CTR: 3"；stats 为 true 时附带这些容器的 CPU 和内存合计，如 "CTR: 3 (12% 1.2G)"
+// CPU 占用率以单核为 100%，与 --cgroup 相同
+pub fn get_containers(socket: Option<&str>, stats: bool) -> Result<String, Error> {
+    let socket = match socket {
+        Some(socket) => socket.to_string(),
+        None => find_socket()
+            .ok_or_else(|| Error::MissingDevice("no Docker or Podman socket found".to_string()))?,
+    };
+    let ids = parse_ids(&get(&socket, "/containers/json")?);
+    if !stats || ids.is_empty() {
+        return Ok(format!("CTR: {}", ids.len()));
+    }
+    // delta 可能采样两次，内存取最后一次采样的合计
+    let memory = Cell::new(0);
+    let (elapsed, previous, current) = counters::delta("containers", || {
+        let (mut cpu, mut total) = (0, 0);
+        for id in &ids {
+            let json = get(
+                &socket,
+                &format!("/containers/{}/stats?stream=false&one-shot=true", id),
+            )?;
+            let (used, bytes) = parse_stats(&json).ok_or_else(|| {
+                Error::Parse(format!("unexpected stats response for container {}", id))
+            })?;
+            cpu += used;
+            total += bytes;
+        }
+        memory.set(total);
+        Ok(vec![cpu])
+    })?;
+    // 容器退出后合计值会变小，此时按 0 计算
+    let used = current[0].saturating_sub(previous.first().copied().unwrap_or(current[0]));
+    let percent = used as f64 / (elapsed * 1e9) * 100.0;
+    Ok(format!(
+        "CTR: {} ({:.0}% {})",
+        ids.len(),
+        percent,
+        format_size(memory.get())
+    ))
+}
+
+pub struct Containers {
+    socket: Option<String>,
+    stats: bool,
+}
+
+impl Builtin for Containers {
+    const ID: &'static str = "containers";
+    const BLOCK: &'static str = "containers";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Containers {
+            socket: matches.get_one::<String>("containers-socket").cloned(),
+            stats: matches.get_flag("containers-stats"),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(
+            Self::BLOCK,
+            get_containers(self.socket.as_deref(), self.stats)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_engine_responses() {
+        let list = r#"[{"Id":"8dfafdbc3a40","Names":["/web"],"ImageID":"sha256:ab12","NetworkSettings":{"Networks":{"bridge":{"NetworkID":"7ea29fc1"}}}},{"Id": "9cd87a2c11f0","Names":["/db"]}]"#;
+        assert_eq!(parse_ids(list), ["8dfafdbc3a40", "9cd87a2c11f0"]);
+        let stats = r#"{"read":"2026-10-15T12:00:00Z","cpu_stats":{"cpu_usage":{"total_usage":5000000},"system_cpu_usage":9000},"precpu_stats":{"cpu_usage":{"total_usage":0}},"memory_stats":{"usage":1048576,"limit":2097152}}"#;
+        assert_eq!(parse_stats(stats), Some((5000000, 1048576)));
+        assert_eq!(
+            parse_response("HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n[]").unwrap(),
+            "[]"
+        );
+        assert!(matches!(
+            parse_response("HTTP/1.0 404 Not Found\r\n\r\n{}"),
+            Err(Error::BackendUnavailable(_))
+        ));
+    }
+}
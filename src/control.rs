@@ -0,0 +1,126 @@
+use crate::event_loop::EventLoop;
+use crate::log;
+use crate::paths;
+use crate::render::{json_escape, Block, API_VERSION};
+use crate::watch::Wake;
+use rustix::event::epoll::EventFlags;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+// watch 循环最近一次输出的各模块结果，按模块名索引
+pub type Cache = Rc<RefCell<HashMap<String, Block>>>;
+
+// 控制套接字路径：$XDG_RUNTIME_DIR/sys-montion.sock
+pub fn socket_path() -> Result<PathBuf, io::Error> {
+    paths::runtime_path("sys-montion.sock")
+}
+
+// 按模块名或输出块名（如 volume-level 或 volume）查找缓存
+fn lookup<'a>(cache: &'a HashMap<String, Block>, name: &str) -> Option<&'a Block> {
+    cache
+        .get(name)
+        .or_else(|| cache.values().find(|block| block.name == name))
+}
+
+pub fn to_json(block: &Block) -> String {
+    let value = block
+        .value
+        .map(|value| format!(",\"value\":{}", value))
+        .unwrap_or_default();
+    format!(
+        "{{\"name\":\"{}\",\"text\":\"{}\"{},\"version\":{}}}",
+        block.name,
+        json_escape(&block.text),
+        value,
+        API_VERSION
+    )
+}
+
+// 处理一行请求，返回一行响应，出错时以 "error: " 开头
+//   refresh [MODULE]   立即刷新全部或指定模块
+//   get MODULE [json]  返回缓存的模块输出
+fn handle(line: &str, cache: &Cache, woken: &mut Vec<Wake>) -> String {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        ["refresh"] => {
+            woken.push(Wake::All);
+            "ok".to_string()
+        }
+        ["refresh", name] => {
+            if lookup(&cache.borrow(), name).is_none() {
+                return format!("error: module '{}' is not running", name);
+            }
+            woken.push(Wake::Module(name.to_string()));
+            "ok".to_string()
+        }
+        ["get", name, rest @ ..] => {
+            let cache = cache.borrow();
+            match (lookup(&cache, name), rest) {
+                (None, _) => format!("error: module '{}' is not running", name),
+                (Some(block), []) => block.text.clone(),
+                (Some(block), ["json"]) => to_json(block),
+                _ => format!("error: invalid request '{}'", line),
+            }
+        }
+        _ => format!("error: invalid request '{}'", line),
+    }
+}
+
+fn serve_client(stream: UnixStream, cache: &Cache, woken: &mut Vec<Wake>) -> Result<(), io::Error> {
+    // 请求在事件循环中同步处理，客户端迟迟不发送时不能长时间阻塞刷新
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut line = String::new();
+    // 其他实例启动时只连接检测套接字是否在用，不发送请求
+    if BufReader::new(&stream).read_line(&mut line)? == 0 {
+        return Ok(());
+    }
+    let response = handle(line.trim(), cache, woken);
+    writeln!(&stream, "{}", response)
+}
+
+// 监听控制套接字，套接字仅当前用户可访问
+// 已有实例在运行时返回错误，残留的套接字文件会被替换
+pub fn serve(events: &mut EventLoop, cache: Cache) -> Result<(), io::Error> {
+    let path = socket_path()?;
+    if UnixStream::connect(&path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("{} is in use by another instance", path.display()),
+        ));
+    }
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+
+    events.add(listener, EventFlags::IN, move |listener, woken| {
+        // 接受失败（如对方已断开）不影响后续连接
+        if let Ok((stream, _)) = listener.accept() {
+            if let Err(e) = serve_client(stream, &cache, woken) {
+                log::warning!("handling control request: {}", e);
+            }
+        }
+        Ok(())
+    })
+}
+
+// 客户端：向运行中的 watch 实例发送一行请求并返回响应
+pub fn request(line: &str) -> Result<String, io::Error> {
+    let path = socket_path()?;
+    let stream = UnixStream::connect(&path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+    writeln!(&stream, "{}", line)?;
+    let mut response = String::new();
+    BufReader::new(&stream).read_line(&mut response)?;
+    let response = response.trim_end().to_string();
+    match response.strip_prefix("error: ") {
+        Some(message) => Err(io::Error::other(message.to_string())),
+        None => Ok(response),
+    }
+}
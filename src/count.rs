@@ -0,0 +1,128 @@
+use crate::error::Error;
+use crate::event_loop::EventLoop;
+use crate::module::{self, Builtin, EventInterest, MetricValue};
+use crate::render::Block;
+use crate::watch::Wake;
+use rustix::event::epoll::EventFlags;
+use rustix::fs::inotify::{self, CreateFlags, WatchFlags};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 简单的通配符匹配，支持 * 和 ?
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // 回溯位置：上一个 * 在模式中的位置，以及它当前匹配到的名称位置
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    p = sp + 1;
+                    n = sn + 1;
+                    star = Some((sp, sn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+// 统计目录下名称匹配 pattern 的文件数，如 Maildir 的 new/ 目录中的未读邮件
+// 与 shell 一致，模式不以 . 开头时不匹配隐藏文件
+pub fn get_count(dir: &str, pattern: &str) -> Result<String, Error> {
+    let entries = fs::read_dir(dir).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => Error::MissingDevice(format!("{} not found", dir)),
+        _ => Error::from(e),
+    })?;
+    let mut count = 0;
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') && !pattern.starts_with('.') {
+            continue;
+        }
+        if entry.file_type()?.is_file() && glob_match(pattern, &name) {
+            count += 1;
+        }
+    }
+    Ok(format!("COUNT: {}", count))
+}
+
+// 用 inotify 监听目录，文件增删或移入移出时唤醒 count 模块
+pub fn register(events: &mut EventLoop, dir: &str) -> Result<(), io::Error> {
+    let fd = inotify::init(CreateFlags::CLOEXEC)?;
+    inotify::add_watch(
+        &fd,
+        dir,
+        WatchFlags::CREATE | WatchFlags::DELETE | WatchFlags::MOVED_FROM | WatchFlags::MOVED_TO,
+    )?;
+    let mut buf = [0u8; 4096];
+    events.add(fd, EventFlags::IN, move |fd, woken| {
+        // 只关心有没有事件，不解析事件内容
+        rustix::io::read(&*fd, &mut buf)?;
+        woken.push(Wake::Module("count".to_string()));
+        Ok(())
+    })
+}
+
+// count 模块：--count DIR [GLOB]，GLOB 默认为 *
+pub struct Count {
+    dir: Option<String>,
+    pattern: String,
+}
+
+impl Builtin for Count {
+    const ID: &'static str = "count";
+    const BLOCK: &'static str = "count";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        let mut args = matches.get_many::<String>("count").into_iter().flatten();
+        Count {
+            dir: args.next().cloned(),
+            pattern: args.next().map_or("*", String::as_str).to_string(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let dir = module::required(&self.dir, "count", "--count DIR [GLOB]")?;
+        Ok(Block::numeric(Self::BLOCK, get_count(dir, &self.pattern)?))
+    }
+
+    fn interests(&self) -> EventInterest {
+        EventInterest::FILES
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        match &self.dir {
+            None => Some("needs --count DIR [GLOB]".to_string()),
+            Some(dir) => (!Path::new(dir).is_dir()).then(|| format!("{} not found", dir)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_globs() {
+        assert!(glob_match("*", "1697364000.M1P2.host"));
+        assert!(glob_match("*.eml", "a.eml"));
+        assert!(!glob_match("*.eml", "a.eml.tmp"));
+        assert!(glob_match("a?c*", "abcdef"));
+        assert!(glob_match("*:2,*S*", "1.host:2,RS"));
+        assert!(!glob_match("*:2,*S*", "1.host:2,R"));
+    }
+}
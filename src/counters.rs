@@ -0,0 +1,98 @@
+use crate::error::Error;
+use crate::log;
+use crate::paths::cache_path;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// 没有可用的上一次采样时，两次采样的间隔
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+// 超过这个时间（毫秒）的采样不再使用，避免算出很久以前的平均值
+const MAX_SAMPLE_AGE: u64 = 10 * 60 * 1000;
+
+// 各计数器的上一次采样：key -> (时间毫秒, 计数值)
+// 首次使用时从缓存文件加载，之后每次更新都写回文件，单次运行之间也能计算速率
+type Samples = BTreeMap<String, (u64, Vec<u64>)>;
+static SAMPLES: Mutex<Option<Samples>> = Mutex::new(None);
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+// 读取缓存文件，每行 "key\t时间毫秒\t值1\t值2..."
+fn load() -> Samples {
+    let Ok(path) = cache_path("counters.tsv") else {
+        return Samples::new();
+    };
+    let content = fs::read_to_string(path).unwrap_or_default();
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let key = fields.next()?.to_string();
+            let time = fields.next()?.parse().ok()?;
+            let values: Option<Vec<u64>> = fields.map(|v| v.parse().ok()).collect();
+            Some((key, (time, values?)))
+        })
+        .collect()
+}
+
+// 先写临时文件再重命名，多个实例同时写入时不会读到半个文件
+fn save(samples: &Samples) -> Result<(), io::Error> {
+    let path = cache_path("counters.tsv")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut content = String::new();
+    for (key, (time, values)) in samples {
+        content.push_str(key);
+        content.push('\t');
+        content.push_str(&time.to_string());
+        for value in values {
+            content.push('\t');
+            content.push_str(&value.to_string());
+        }
+        content.push('\n');
+    }
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, content)?;
+    fs::rename(tmp, path)
+}
+
+// 记录 key 的本次采样，返回上一次的采样及距今秒数；没有或已过期时返回 None
+fn update(key: &str, values: Vec<u64>) -> Option<(f64, Vec<u64>)> {
+    let mut samples = SAMPLES.lock().unwrap();
+    let samples = samples.get_or_insert_with(load);
+    let now = now_millis();
+    let previous = samples.insert(key.to_string(), (now, values));
+    if let Err(e) = save(samples) {
+        log::warning!("saving counter cache: {}", e);
+    }
+    let (time, values) = previous?;
+    let elapsed = now
+        .checked_sub(time)
+        .filter(|&ms| ms > 0 && ms <= MAX_SAMPLE_AGE)?;
+    Some((elapsed as f64 / 1000.0, values))
+}
+
+// 取得计算速率所需的两次采样，返回 (间隔秒数, 上一次, 本次)
+// 有新鲜的上一次采样（watch 模式的上次刷新或上一次单次运行）时直接使用，
+// 否则先采样一次，等待片刻后再采样
+pub fn delta(
+    key: &str,
+    sample: impl Fn() -> Result<Vec<u64>, Error>,
+) -> Result<(f64, Vec<u64>, Vec<u64>), Error> {
+    let current = sample()?;
+    if let Some((elapsed, previous)) = update(key, current.clone()) {
+        return Ok((elapsed, previous, current));
+    }
+    thread::sleep(SAMPLE_INTERVAL);
+    let next = sample()?;
+    let elapsed = update(key, next.clone()).map_or(SAMPLE_INTERVAL.as_secs_f64(), |(e, _)| e);
+    Ok((elapsed, current, next))
+}
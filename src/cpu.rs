@@ -0,0 +1,212 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{counters, locale, read_file, sysfs};
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+const CPU_PATH: &str = "/sys/devices/system/cpu";
+
+// 读取 CPU 目录下的整数值
+fn read_value(cpu: &str, file: &str) -> Option<i64> {
+    sysfs::read_int(Path::new(CPU_PATH).join(cpu).join(file)).ok()
+}
+
+// 列出 cpu0、cpu1 等 CPU 目录（排除 cpufreq、cpuidle 等）
+fn list_cpus() -> Result<Vec<String>, io::Error> {
+    let mut cpus = Vec::new();
+    for entry in sysfs::read_dir(CPU_PATH)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        let is_cpu = name
+            .strip_prefix("cpu")
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+        if is_cpu {
+            cpus.push(name);
+        }
+    }
+    cpus.sort();
+    Ok(cpus)
+}
+
+// 统计开机以来的温控降频次数（Intel thermal_throttle）
+// 同一物理核心的超线程共享 core 计数，同一封装的所有 CPU 共享 package 计数，需去重
+pub fn get_throttle() -> Result<String, Error> {
+    let mut cores: HashMap<(i64, i64), i64> = HashMap::new();
+    let mut packages: HashMap<i64, i64> = HashMap::new();
+    for name in list_cpus()? {
+        let Some(core_count) = read_value(&name, "thermal_throttle/core_throttle_count") else {
+            continue;
+        };
+        let package = read_value(&name, "topology/physical_package_id").unwrap_or(0);
+        let core = read_value(&name, "topology/core_id").unwrap_or(0);
+        cores.insert((package, core), core_count);
+        if let Some(count) = read_value(&name, "thermal_throttle/package_throttle_count") {
+            packages.insert(package, count);
+        }
+    }
+
+    if cores.is_empty() {
+        return Err(Error::MissingDevice(
+            "CPU does not report thermal_throttle counters".to_string(),
+        ));
+    }
+
+    Ok(format!(
+        "THR: {} core, {} pkg",
+        cores.values().sum::<i64>(),
+        packages.values().sum::<i64>()
+    ))
+}
+
+// 读取 CPU 调频策略，如 performance、powersave、schedutil
+pub fn read_governor() -> Result<String, io::Error> {
+    read_file(Path::new(CPU_PATH).join("cpu0/cpufreq/scaling_governor"))
+}
+
+// 输出所有 CPU 的平均当前频率和调频策略，如 "CPU: 3.2GHz (powersave)"
+// 任一 CPU 的 scaling_max_freq 被压低到硬件上限以下（温控、功耗限制）时标记为 throttled
+pub fn get_cpu_freq() -> Result<String, Error> {
+    let mut total: i64 = 0;
+    let mut count: i64 = 0;
+    let mut throttled = false;
+    for cpu in list_cpus()? {
+        let Some(freq) = read_value(&cpu, "cpufreq/scaling_cur_freq") else {
+            continue;
+        };
+        total += freq;
+        count += 1;
+        if let (Some(limit), Some(max)) = (
+            read_value(&cpu, "cpufreq/scaling_max_freq"),
+            read_value(&cpu, "cpufreq/cpuinfo_max_freq"),
+        ) {
+            throttled |= limit < max;
+        }
+    }
+
+    if count == 0 {
+        return Err(Error::MissingDevice(
+            "CPU frequency scaling not available".to_string(),
+        ));
+    }
+
+    // scaling_cur_freq 单位为 kHz
+    let ghz = locale::format_number(total as f64 / count as f64 / 1e6, 1);
+    let governor = read_governor()?;
+    Ok(if throttled {
+        format!("CPU: {}GHz ({}, throttled)", ghz, governor)
+    } else {
+        format!("CPU: {}GHz ({})", ghz, governor)
+    })
+}
+
+// 读取 /proc/stat 的总 CPU 时间和空闲时间（idle + iowait），不含 guest（已计入 user）
+fn read_cpu_times() -> Result<Vec<u64>, Error> {
+    let stat = read_file("/proc/stat")?;
+    let fields: Vec<u64> = stat
+        .lines()
+        .find_map(|line| line.strip_prefix("cpu "))
+        .ok_or_else(|| Error::Parse("cpu line not found in /proc/stat".to_string()))?
+        .split_whitespace()
+        .take(8)
+        .filter_map(|v| v.parse().ok())
+        .collect();
+    if fields.len() < 5 {
+        return Err(Error::Parse("short cpu line in /proc/stat".to_string()));
+    }
+    Ok(vec![fields.iter().sum(), fields[3] + fields[4]])
+}
+
+// 输出整体 CPU 占用率，如 "CPU: 23%"
+pub fn get_cpu_usage() -> Result<String, Error> {
+    let (_, previous, current) = counters::delta("cpu", read_cpu_times)?;
+    let before = |i: usize| previous.get(i).copied().unwrap_or(current[i]);
+    let total = current[0].saturating_sub(before(0));
+    let idle = current[1].saturating_sub(before(1));
+    let busy = (total.saturating_sub(idle) * 100)
+        .checked_div(total)
+        .unwrap_or(0);
+    Ok(format!("CPU: {}%", busy))
+}
+
+// CPU 不支持调频时 cpu-freq 和 gamemode 模块不可用
+pub fn cpufreq_unavailable() -> Option<String> {
+    (!sysfs::exists("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor"))
+        .then(|| "CPU frequency scaling not available".to_string())
+}
+
+pub struct Throttle;
+
+impl Builtin for Throttle {
+    const ID: &'static str = "throttle";
+    const BLOCK: &'static str = "cpu";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Throttle
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, get_throttle()?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        (!sysfs::exists("/sys/devices/system/cpu/cpu0/thermal_throttle"))
+            .then(|| "CPU does not report thermal_throttle counters".to_string())
+    }
+}
+
+pub struct CpuFreq;
+
+impl Builtin for CpuFreq {
+    const ID: &'static str = "cpu-freq";
+    const BLOCK: &'static str = "cpu";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        CpuFreq
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, get_cpu_freq()?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        cpufreq_unavailable()
+    }
+}
+
+pub struct Cpu;
+
+impl Builtin for Cpu {
+    const ID: &'static str = "cpu";
+    const BLOCK: &'static str = "cpu";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Cpu
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::percent(Self::BLOCK, get_cpu_usage()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_frequency() {
+        sysfs::use_fixtures();
+        // cpu0 的 scaling_max_freq 低于 cpuinfo_max_freq
+        assert_eq!(
+            get_cpu_freq().unwrap(),
+            "CPU: 1.6GHz (powersave, throttled)"
+        );
+    }
+
+    #[test]
+    fn reads_cpu_times() {
+        sysfs::use_fixtures();
+        // 10000 + 2000 + 80000 + 100 + 50，空闲 80000 + 100
+        assert_eq!(read_cpu_times().unwrap(), [92150, 80100]);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::error::Error;
+use crate::format_duration;
+use crate::module::{self, Builtin, MetricValue};
+use crate::render::Block;
+use crate::timeutil::now_secs;
+use std::f64::consts::PI;
+
+// J2000 历元对应的儒略日
+const J2000: f64 = 2451545.0;
+// Unix 纪元对应的儒略日
+const UNIX_EPOCH_JD: f64 = 2440587.5;
+
+fn sin_deg(deg: f64) -> f64 {
+    (deg * PI / 180.0).sin()
+}
+
+fn cos_deg(deg: f64) -> f64 {
+    (deg * PI / 180.0).cos()
+}
+
+// 解析形如 "31.23,121.47" 的经纬度（东经、北纬为正）
+pub fn parse_location(location: &str) -> Result<(f64, f64), Error> {
+    let invalid = || Error::Parse(format!("invalid location '{}', expected LAT,LON", location));
+    let (lat, lon) = location.split_once(',').ok_or_else(invalid)?;
+    let lat: f64 = lat.trim().parse().map_err(|_| invalid())?;
+    let lon: f64 = lon.trim().parse().map_err(|_| invalid())?;
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(invalid());
+    }
+    Ok((lat, lon))
+}
+
+// 按日出方程计算第 n 天（自 J2000 起）的太阳中天儒略日和赤纬正弦值
+fn solar_position(n: f64, lon: f64) -> (f64, f64) {
+    let mean_solar_noon = n - lon / 360.0;
+    let m = (357.5291 + 0.98560028 * mean_solar_noon).rem_euclid(360.0);
+    let c = 1.9148 * sin_deg(m) + 0.02 * sin_deg(2.0 * m) + 0.0003 * sin_deg(3.0 * m);
+    let lambda = (m + c + 180.0 + 102.9372).rem_euclid(360.0);
+    let transit = J2000 + mean_solar_noon + 0.0053 * sin_deg(m) - 0.0069 * sin_deg(2.0 * lambda);
+    (transit, sin_deg(lambda) * sin_deg(23.4397))
+}
+
+// 计算第 n 天的日出、日落时间（Unix 秒），极昼/极夜时返回 None
+fn sun_times(n: f64, lat: f64, lon: f64) -> Option<(f64, f64)> {
+    let (transit, sin_decl) = solar_position(n, lon);
+    let cos_decl = (1.0 - sin_decl * sin_decl).sqrt();
+    let cos_hour_angle = (sin_deg(-0.833) - sin_deg(lat) * sin_decl) / (cos_deg(lat) * cos_decl);
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+
+    let hour_angle = cos_hour_angle.acos() * 180.0 / PI;
+    let to_unix = |jd: f64| (jd - UNIX_EPOCH_JD) * 86400.0;
+    Some((
+        to_unix(transit - hour_angle / 360.0),
+        to_unix(transit + hour_angle / 360.0),
+    ))
+}
+
+// 下一次日出或日落的时间和名称，处于极昼或极夜时返回 Err(是否为极昼)
+fn next_event(lat: f64, lon: f64, now: f64) -> Result<(f64, &'static str), bool> {
+    // 检查昨天到明天的所有日出日落，取最近的下一次
+    let today = (now / 86400.0 + UNIX_EPOCH_JD - J2000 + 0.0008).ceil();
+    let mut next: Option<(f64, &str)> = None;
+    for n in [today - 1.0, today, today + 1.0] {
+        let Some((sunrise, sunset)) = sun_times(n, lat, lon) else {
+            continue;
+        };
+        for (time, label) in [(sunrise, "Sunrise"), (sunset, "Sunset")] {
+            if time > now && next.is_none_or(|(t, _)| time < t) {
+                next = Some((time, label));
+            }
+        }
+    }
+    // 太阳赤纬与纬度同侧为极昼
+    next.ok_or_else(|| lat * solar_position(today, lon).1 > 0.0)
+}
+
+// 当前是否为白天：下一次事件是日落，或处于极昼
+pub fn is_day(lat: f64, lon: f64) -> bool {
+    match next_event(lat, lon, now_secs() as f64) {
+        Ok((_, label)) => label == "Sunset",
+        Err(polar_day) => polar_day,
+    }
+}
+
+// 计算下一次日出或日落距今的时间
+pub fn get_daylight(location: &str) -> Result<String, Error> {
+    let (lat, lon) = parse_location(location)?;
+    let now = now_secs() as f64;
+    match next_event(lat, lon, now) {
+        Ok((time, label)) => Ok(format!(
+            "{} in {}",
+            label,
+            format_duration((time - now) as i64)
+        )),
+        Err(true) => Ok("Polar day".to_string()),
+        Err(false) => Ok("Polar night".to_string()),
+    }
+}
+
+pub struct Daylight {
+    location: Option<String>,
+}
+
+impl Builtin for Daylight {
+    const ID: &'static str = "daylight";
+    const BLOCK: &'static str = "daylight";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Daylight {
+            location: matches.get_one::<String>("location").cloned(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let location = module::required(&self.location, "location", "--location LAT,LON")?;
+        Ok(Block::new(Self::BLOCK, get_daylight(location)?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        self.location
+            .is_none()
+            .then(|| "needs --location LAT,LON".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 与已知的日出日落时间（timeanddate.com）相差不超过 2 分钟
+    fn assert_near(actual: Result<(f64, &str), bool>, expected: f64, label: &str) {
+        let (time, actual_label) = actual.unwrap();
+        assert_eq!(actual_label, label);
+        assert!(
+            (time - expected).abs() <= 120.0,
+            "{} off by {}s",
+            label,
+            time - expected
+        );
+    }
+
+    #[test]
+    fn computes_sunrise_and_sunset() {
+        // 伦敦 2024-06-21：日出 03:43Z，日落 20:21Z
+        let (lat, lon) = parse_location("51.5074,-0.1278").unwrap();
+        assert_near(next_event(lat, lon, 1718928000.0), 1718941380.0, "Sunrise");
+        assert_near(next_event(lat, lon, 1718942000.0), 1719001260.0, "Sunset");
+
+        // 上海 2024-12-21：日出 06:50 CST（前一天 22:50Z），日落 16:56 CST（08:56Z）
+        let (lat, lon) = parse_location("31.23,121.47").unwrap();
+        assert_near(next_event(lat, lon, 1734696000.0), 1734735000.0, "Sunrise");
+        assert_near(next_event(lat, lon, 1734736000.0), 1734771360.0, "Sunset");
+
+        // 特罗姆瑟夏至极昼、冬至极夜
+        assert_eq!(next_event(69.65, 18.96, 1718928000.0), Err(true));
+        assert_eq!(next_event(69.65, 18.96, 1734696000.0 + 86400.0), Err(false));
+        assert!(parse_location("91,0").is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use crate::locale;
+use std::io;
+
+#[derive(Clone, Copy)]
+pub enum Bus {
+    Session,
+    System,
+}
+
+// 使用 `gdbus` 调用 D-Bus 方法，返回 GVariant 文本形式的结果，如 "(<uint32 5>,)"
+// 依赖 `glib2`
+pub fn call(
+    bus: Bus,
+    dest: &str,
+    object_path: &str,
+    method: &str,
+    args: &[&str],
+) -> Result<String, io::Error> {
+    let bus = match bus {
+        Bus::Session => "--session",
+        Bus::System => "--system",
+    };
+    let output = locale::command("gdbus")
+        .args([
+            "call",
+            bus,
+            "--dest",
+            dest,
+            "--object-path",
+            object_path,
+            "--method",
+            method,
+        ])
+        .args(args)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// 读取属性，返回去掉 "(<...>,)" 外层后的值文本，如 "int32 1" 或 "objectpath '/org/...'"
+pub fn get_property(
+    bus: Bus,
+    dest: &str,
+    object_path: &str,
+    interface: &str,
+    property: &str,
+) -> Result<String, io::Error> {
+    let reply = call(
+        bus,
+        dest,
+        object_path,
+        "org.freedesktop.DBus.Properties.Get",
+        &[interface, property],
+    )?;
+    Ok(reply
+        .trim_start_matches("(<")
+        .trim_end_matches(">,)")
+        .to_string())
+}
+
+// 列出对象路径下的子节点名称，如 "/org/bluez/hci0" 下的 "dev_AA_BB_CC_DD_EE_FF"
+pub fn children(bus: Bus, dest: &str, object_path: &str) -> Result<Vec<String>, io::Error> {
+    let bus = match bus {
+        Bus::Session => "--session",
+        Bus::System => "--system",
+    };
+    let output = locale::command("gdbus")
+        .args([
+            "introspect",
+            bus,
+            "--dest",
+            dest,
+            "--object-path",
+            object_path,
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    // 子节点输出为缩进的 "node NAME {" 行，顶层的 "node /path {" 是对象本身
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.starts_with(' '))
+        .filter_map(|line| line.trim().strip_prefix("node ")?.strip_suffix(" {"))
+        .map(str::to_string)
+        .collect())
+}
@@ -0,0 +1,155 @@
+use crate::error::Error;
+use crate::{lid, sysfs};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::fd::AsRawFd;
+use std::path::Path;
+use std::time::Duration;
+
+// 外接显示器没有背光设备，亮度通过 DDC/CI 设置：经显示器的 I2C 总线（/dev/i2c-N，需要 i2c-dev 模块）
+// 收发 MCCS 命令，与 ddcutil 使用的协议相同
+
+const DRM_PATH: &str = "/sys/class/drm";
+
+// linux/i2c-dev.h 中设置从设备地址的 ioctl
+const I2C_SLAVE: libc::c_ulong = 0x0703;
+
+// 显示器 DDC/CI 的 7 位 I2C 地址，写入时为 0x6E；主机的源地址为 0x51，应答的校验从 0x50 算起
+const DDC_ADDRESS: u16 = 0x37;
+const DISPLAY: u8 = 0x6E;
+const HOST: u8 = 0x51;
+const HOST_CHECKSUM: u8 = 0x50;
+
+// MCCS 的亮度控制码
+const BRIGHTNESS: u8 = 0x10;
+
+// 显示器处理命令需要的时间，见 DDC/CI 规范
+const REPLY_DELAY: Duration = Duration::from_millis(40);
+const WRITE_DELAY: Duration = Duration::from_millis(50);
+
+fn checksum(start: u8, bytes: &[u8]) -> u8 {
+    bytes.iter().fold(start, |sum, byte| sum ^ byte)
+}
+
+// Get VCP Feature 请求：源地址、长度（0x80 | 1）、操作码 0x01、控制码和校验和
+fn get_request(code: u8) -> [u8; 5] {
+    let mut packet = [HOST, 0x82, 0x01, code, 0];
+    packet[4] = checksum(DISPLAY, &packet[..4]);
+    packet
+}
+
+// Set VCP Feature 请求：操作码 0x03、控制码、16 位的值（高字节在前）和校验和
+fn set_request(code: u8, value: u16) -> [u8; 7] {
+    let [high, low] = value.to_be_bytes();
+    let mut packet = [HOST, 0x84, 0x03, code, high, low, 0];
+    packet[6] = checksum(DISPLAY, &packet[..6]);
+    packet
+}
+
+// 解析 Get VCP Feature 的应答，返回（当前值，最大值）
+// 应答为源地址 0x6E、长度 0x88、操作码 0x02、结果码（0 为成功）、控制码、类型、最大值、当前值和校验和
+fn parse_reply(reply: &[u8], code: u8) -> Option<(u16, u16)> {
+    if reply.len() < 11
+        || reply[..3] != [DISPLAY, 0x88, 0x02]
+        || reply[3] != 0
+        || reply[4] != code
+        || checksum(HOST_CHECKSUM, &reply[..10]) != reply[10]
+    {
+        return None;
+    }
+    let max = u16::from_be_bytes([reply[6], reply[7]]);
+    let current = u16::from_be_bytes([reply[8], reply[9]]);
+    Some((current, max))
+}
+
+// 接口的 I2C 总线，如 i2c-5：amdgpu、nouveau 为 ddc 链接，i915 的 DP 接口为 i2c-N 子目录
+fn connector_bus(connector: &str) -> Option<String> {
+    let dir = Path::new(DRM_PATH).join(connector);
+    if let Ok(target) = fs::read_link(sysfs::path(dir.join("ddc"))) {
+        return Some(target.file_name()?.to_string_lossy().to_string());
+    }
+    sysfs::read_dir(&dir).ok()?.flatten().find_map(|entry| {
+        let name = entry.file_name().to_string_lossy().to_string();
+        name.starts_with("i2c-").then_some(name)
+    })
+}
+
+// 接有外接显示器的接口及其 I2C 总线，如 ("HDMI-A-1", "i2c-5")
+pub fn displays() -> Vec<(String, String)> {
+    lid::external_connectors()
+        .iter()
+        .filter_map(|connector| {
+            let bus = connector_bus(connector)?;
+            Some((lid::output_name(connector).to_string(), bus))
+        })
+        .collect()
+}
+
+fn open_bus(bus: &str) -> Result<fs::File, Error> {
+    let path = Path::new("/dev").join(bus);
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&path)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => Error::BackendUnavailable(format!(
+                "{} not found, load the i2c-dev module (modprobe i2c-dev)",
+                path.display()
+            )),
+            io::ErrorKind::PermissionDenied => Error::PermissionDenied(format!(
+                "cannot open {}: permission denied, add yourself to the i2c group",
+                path.display()
+            )),
+            _ => Error::from(e),
+        })?;
+    if unsafe {
+        libc::ioctl(
+            file.as_raw_fd(),
+            I2C_SLAVE as _,
+            libc::c_ulong::from(DDC_ADDRESS),
+        )
+    } < 0
+    {
+        return Err(Error::from(io::Error::last_os_error()));
+    }
+    Ok(file)
+}
+
+// 按百分比设置外接显示器的亮度，先读取显示器报告的最大值（通常为 100），返回设置的百分比
+pub fn set_brightness(bus: &str, percent: i32) -> Result<i32, Error> {
+    let percent = percent.clamp(0, 100);
+    let mut file = open_bus(bus)?;
+    file.write_all(&get_request(BRIGHTNESS))?;
+    std::thread::sleep(REPLY_DELAY);
+    let mut reply = [0u8; 11];
+    file.read_exact(&mut reply)?;
+    let (_, max) = parse_reply(&reply, BRIGHTNESS)
+        .ok_or_else(|| Error::Parse(format!("{}: no valid DDC/CI brightness reply", bus)))?;
+    let value = (i64::from(percent) * i64::from(max) / 100) as u16;
+    file.write_all(&set_request(BRIGHTNESS, value))?;
+    std::thread::sleep(WRITE_DELAY);
+    Ok(percent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_and_parses_packets() {
+        assert_eq!(get_request(BRIGHTNESS), [0x51, 0x82, 0x01, 0x10, 0xAC]);
+        assert_eq!(
+            set_request(BRIGHTNESS, 50),
+            [0x51, 0x84, 0x03, 0x10, 0x00, 0x32, 0x9A]
+        );
+        let mut reply = [
+            0x6E, 0x88, 0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32, 0,
+        ];
+        reply[10] = checksum(HOST_CHECKSUM, &reply[..10]);
+        assert_eq!(parse_reply(&reply, BRIGHTNESS), Some((50, 100)));
+        reply[3] = 0x01;
+        assert_eq!(parse_reply(&reply, BRIGHTNESS), None);
+        sysfs::use_fixtures();
+        assert_eq!(displays(), [("HDMI-A-1".to_string(), "i2c-7".to_string())]);
+    }
+}
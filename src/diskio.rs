@@ -0,0 +1,111 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{counters, locale, read_file, sysfs};
+use std::collections::BTreeMap;
+use std::io;
+
+// /proc/diskstats 的扇区固定为 512 字节，与设备实际扇区大小无关
+const SECTOR_SIZE: u64 = 512;
+
+// 整块磁盘：/sys/block 下除 loop、ram、zram 以外的设备
+pub fn list_disks() -> Result<Vec<String>, io::Error> {
+    let mut disks = Vec::new();
+    for entry in sysfs::read_dir("/sys/block")? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if !["loop", "ram", "zram"].iter().any(|p| name.starts_with(p)) {
+            disks.push(name);
+        }
+    }
+    disks.sort();
+    Ok(disks)
+}
+
+// 读取 /proc/diskstats，返回设备 -> (已读字节, 已写字节)
+// 每行：主设备号 次设备号 名称 读次数 读合并 读扇区 读耗时 写次数 写合并 写扇区 ...
+fn read_diskstats() -> Result<BTreeMap<String, (u64, u64)>, io::Error> {
+    let stats = read_file("/proc/diskstats")?;
+    Ok(stats
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let read: u64 = fields.get(5)?.parse().ok()?;
+            let written: u64 = fields.get(9)?.parse().ok()?;
+            Some((
+                fields.get(2)?.to_string(),
+                (read * SECTOR_SIZE, written * SECTOR_SIZE),
+            ))
+        })
+        .collect())
+}
+
+// 读取设备当前的累计读写字节数，device 为 "all" 时合计所有整块磁盘
+fn read_counters(device: &str) -> Result<(u64, u64), Error> {
+    let stats = read_diskstats()?;
+    let devices = if device == "all" {
+        list_disks()?
+    } else {
+        vec![device.to_string()]
+    };
+    let mut total = None;
+    for name in &devices {
+        if let Some((read, written)) = stats.get(name) {
+            let (r, w) = total.unwrap_or((0, 0));
+            total = Some((r + read, w + written));
+        }
+    }
+    total.ok_or_else(|| Error::MissingDevice(format!("no disk {} in /proc/diskstats", device)))
+}
+
+// 输出磁盘读写速率，如 "DISK: R 1.2 MB/s W 0.3 MB/s"
+pub fn get_diskio(device: &str) -> Result<String, Error> {
+    let (elapsed, previous, current) = counters::delta(&format!("diskio:{}", device), || {
+        let (read, written) = read_counters(device)?;
+        Ok(vec![read, written])
+    })?;
+    let rate = |i: usize| {
+        let before = previous.get(i).copied().unwrap_or(current[i]);
+        let mb = current[i].saturating_sub(before) as f64 / 1e6 / elapsed;
+        locale::format_number(mb, 1)
+    };
+    Ok(format!("DISK: R {} MB/s W {} MB/s", rate(0), rate(1)))
+}
+
+pub struct DiskIo {
+    device: String,
+}
+
+impl Builtin for DiskIo {
+    const ID: &'static str = "diskio";
+    const BLOCK: &'static str = "diskio";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        DiskIo {
+            device: matches
+                .get_one::<String>("diskio")
+                .map_or("all", String::as_str)
+                .to_string(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, get_diskio(&self.device)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_whole_disks() {
+        sysfs::use_fixtures();
+        // sda 和 nvme0n1 计入，分区和 loop0 不计入
+        assert_eq!(
+            read_counters("all").unwrap(),
+            ((2000 + 3000) * 512, (4000 + 1000) * 512)
+        );
+        assert_eq!(read_counters("sda1").unwrap(), (1500 * 512, 3500 * 512));
+        assert!(matches!(read_counters("sdz"), Err(Error::MissingDevice(_))));
+    }
+}
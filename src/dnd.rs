@@ -0,0 +1,99 @@
+use crate::dbus::{self, Bus};
+use crate::error::Error;
+use crate::locale;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use std::io;
+
+const DUNST: &str = "org.freedesktop.Notifications";
+const DUNST_PATH: &str = "/org/freedesktop/Notifications";
+const DUNST_INTERFACE: &str = "org.dunstproject.cmd0";
+// mako 的免打扰模式需要在配置中定义 [mode=do-not-disturb] invisible=1
+const MAKO_MODE: &str = "do-not-disturb";
+
+// 运行命令并返回输出，命令不存在或失败（通常是对应的通知守护进程未运行）时返回错误
+fn run(program: &str, args: &[&str]) -> Result<String, io::Error> {
+    let output = locale::command(program).args(args).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn dunst_paused() -> Result<bool, io::Error> {
+    let value = dbus::get_property(Bus::Session, DUNST, DUNST_PATH, DUNST_INTERFACE, "paused")?;
+    Ok(value == "true")
+}
+
+// `makoctl mode` 每行输出一个当前启用的模式
+fn mako_dnd() -> Result<bool, io::Error> {
+    Ok(run("makoctl", &["mode"])?
+        .lines()
+        .any(|mode| mode.trim() == MAKO_MODE))
+}
+
+// `swaync-client -D` 输出 true 或 false
+fn swaync_dnd() -> Result<bool, io::Error> {
+    Ok(run("swaync-client", &["--get-dnd", "--skip-wait"])? == "true")
+}
+
+// 查询一种通知守护进程是否处于免打扰状态
+type Query = fn() -> Result<bool, io::Error>;
+
+// 依次查询 dunst（D-Bus）、mako 和 SwayNotificationCenter，使用第一个正在运行的
+fn query() -> Result<(&'static str, bool), Error> {
+    let daemons: [(&str, Query); 3] = [
+        ("dunst", dunst_paused),
+        ("mako", mako_dnd),
+        ("swaync", swaync_dnd),
+    ];
+    for (daemon, state) in daemons {
+        if let Ok(dnd) = state() {
+            return Ok((daemon, dnd));
+        }
+    }
+    Err(Error::BackendUnavailable(
+        "no supported notification daemon (dunst, mako, swaync) is running".to_string(),
+    ))
+}
+
+// 输出免打扰状态，如 "DND: on"
+pub fn get_dnd() -> Result<String, Error> {
+    let (_, dnd) = query()?;
+    Ok(format!("DND: {}", if dnd { "on" } else { "off" }))
+}
+
+// 切换免打扰状态，返回切换后的状态
+pub fn toggle() -> Result<String, Error> {
+    let (daemon, dnd) = query()?;
+    let result = match daemon {
+        "dunst" => dbus::call(
+            Bus::Session,
+            DUNST,
+            DUNST_PATH,
+            "org.freedesktop.DBus.Properties.Set",
+            &[DUNST_INTERFACE, "paused", &format!("<{}>", !dnd)],
+        ),
+        "mako" => run("makoctl", &["mode", "-t", MAKO_MODE]),
+        _ => run("swaync-client", &["--toggle-dnd", "--skip-wait"]),
+    };
+    result.map_err(|e| Error::BackendUnavailable(format!("{}: {}", daemon, e)))?;
+    Ok(format!("DND: {}", if dnd { "off" } else { "on" }))
+}
+
+pub struct Dnd;
+
+impl Builtin for Dnd {
+    const ID: &'static str = "dnd";
+    const BLOCK: &'static str = "dnd";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Dnd
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_dnd()?))
+    }
+}
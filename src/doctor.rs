@@ -0,0 +1,322 @@
+use crate::capabilities::command_exists;
+use crate::{audio, battery, generate, hwmon, kbd_backlight, locale, paths, sysfs};
+use rustix::fs::{access, Access};
+use std::env;
+use std::path::Path;
+
+const BACKLIGHT_PATH: &str = "/sys/class/backlight/amdgpu_bl1";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Status {
+    Ok,
+    // 能工作但有限制，如只能通过 logind 写入
+    Warn,
+    Fail,
+}
+
+// 一项检查的结果，hint 为可以照做的修复建议
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+    hint: Option<String>,
+}
+
+fn ok(name: &'static str, detail: impl Into<String>) -> Check {
+    Check {
+        name,
+        status: Status::Ok,
+        detail: detail.into(),
+        hint: None,
+    }
+}
+
+fn problem(name: &'static str, status: Status, detail: impl Into<String>, hint: &str) -> Check {
+    Check {
+        name,
+        status,
+        detail: detail.into(),
+        hint: Some(hint.to_string()),
+    }
+}
+
+fn writable(path: &Path) -> bool {
+    access(sysfs::path(path), Access::WRITE_OK).is_ok()
+}
+
+// 运行命令，成功时返回标准输出
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    locale::command(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn check_battery() -> Check {
+    match battery::backend() {
+        battery::Backend::Sysfs => match battery::list_batteries() {
+            Ok(batteries) if !batteries.is_empty() => ok("battery", batteries.join(", ")),
+            _ => problem(
+                "battery",
+                Status::Fail,
+                "no battery in /sys/class/power_supply",
+                "expected on desktops; in a container pass the host tree with --sysfs-root",
+            ),
+        },
+        battery::Backend::Upower if command_exists("gdbus") => ok("battery", "UPower via gdbus"),
+        battery::Backend::Upower => problem(
+            "battery",
+            Status::Fail,
+            "gdbus not found",
+            "install glib2 (gdbus) or use --battery-backend sysfs",
+        ),
+        battery::Backend::Termux if command_exists("termux-battery-status") => {
+            ok("battery", "Termux:API")
+        }
+        battery::Backend::Termux => problem(
+            "battery",
+            Status::Fail,
+            "termux-battery-status not found",
+            "pkg install termux-api, and install the Termux:API app",
+        ),
+    }
+}
+
+fn check_backlight() -> Check {
+    let brightness = Path::new(BACKLIGHT_PATH).join("brightness");
+    if !sysfs::exists(&brightness) {
+        return problem(
+            "backlight",
+            Status::Fail,
+            format!("{} not found", BACKLIGHT_PATH),
+            "run `sys-montion list` to see the backlights on this machine",
+        );
+    }
+    if !writable(&brightness) {
+        return problem(
+            "backlight",
+            Status::Warn,
+            "brightness is read-only, changes go through logind",
+            generate::UDEV_HINT,
+        );
+    }
+    ok("backlight", "amdgpu_bl1, writable")
+}
+
+fn check_kbd_backlight() -> Check {
+    let Ok(device) = kbd_backlight::find_device() else {
+        return problem(
+            "kbd-backlight",
+            Status::Fail,
+            "no *kbd_backlight LED",
+            "the keyboard has no backlight, or its driver (e.g. thinkpad_acpi) is not loaded",
+        );
+    };
+    if !writable(
+        &Path::new("/sys/class/leds")
+            .join(&device)
+            .join("brightness"),
+    ) {
+        return problem(
+            "kbd-backlight",
+            Status::Warn,
+            format!("{} is read-only, changes go through logind", device),
+            generate::UDEV_HINT,
+        );
+    }
+    ok("kbd-backlight", format!("{}, writable", device))
+}
+
+fn check_charge_threshold() -> Check {
+    let batteries = battery::list_batteries().unwrap_or_default();
+    let files: Vec<_> = batteries
+        .iter()
+        .map(|name| {
+            Path::new("/sys/class/power_supply")
+                .join(name)
+                .join("charge_control_end_threshold")
+        })
+        .filter(|path| sysfs::exists(path))
+        .collect();
+    if files.is_empty() {
+        return problem(
+            "charge-threshold",
+            Status::Fail,
+            "no battery supports charge_control_end_threshold",
+            "needs a vendor driver such as thinkpad_acpi, asus-wmi or huawei-wmi",
+        );
+    }
+    if !files.iter().all(|path| writable(path)) {
+        return problem(
+            "charge-threshold",
+            Status::Warn,
+            "thresholds are read-only",
+            generate::UDEV_HINT,
+        );
+    }
+    ok("charge-threshold", "writable")
+}
+
+fn check_hwmon() -> Check {
+    let chips = hwmon::list_chips().unwrap_or_default();
+    let temps = chips
+        .iter()
+        .flat_map(|chip| &chip.sensors)
+        .filter(|sensor| sensor.starts_with("temp"))
+        .count();
+    if temps == 0 {
+        return problem(
+            "hwmon",
+            Status::Fail,
+            "no temperature sensors in /sys/class/hwmon",
+            "load the sensor drivers, e.g. run `sudo sensors-detect` or modprobe coretemp / k10temp",
+        );
+    }
+    let names: Vec<&str> = chips.iter().map(|chip| chip.name.as_str()).collect();
+    ok(
+        "hwmon",
+        format!("{} temperature sensors ({})", temps, names.join(", ")),
+    )
+}
+
+fn check_alsa() -> Check {
+    let cards = audio::alsa_cards();
+    if cards.is_empty() {
+        return problem(
+            "alsa",
+            Status::Fail,
+            "no sound cards in /proc/asound/cards",
+            "check that the sound driver is loaded (`lspci -k`, `dmesg | grep snd`)",
+        );
+    }
+    ok("alsa", cards.join(", "))
+}
+
+fn check_amixer() -> Check {
+    if !command_exists("amixer") {
+        return problem(
+            "amixer",
+            Status::Fail,
+            "amixer not found in PATH",
+            "install alsa-utils",
+        );
+    }
+    if run("amixer", &["-M", "get", "Master"]).is_none() {
+        return problem(
+            "amixer",
+            Status::Warn,
+            "`amixer get Master` failed",
+            "the default card has no Master control; pick one with --audio-device (see --list-audio-devices)",
+        );
+    }
+    ok("amixer", "Master control found")
+}
+
+fn check_pulse() -> Check {
+    if !command_exists("pactl") {
+        return problem(
+            "pulse",
+            Status::Warn,
+            "pactl not found in PATH, falling back to amixer",
+            "install pulseaudio-utils (also works with PipeWire's pipewire-pulse)",
+        );
+    }
+    let Some(info) = run("pactl", &["info"]) else {
+        return problem(
+            "pulse",
+            Status::Fail,
+            "cannot connect to the PulseAudio/PipeWire server",
+            "start pipewire-pulse or pulseaudio for this user (systemctl --user status pipewire-pulse)",
+        );
+    };
+    let server = info
+        .lines()
+        .find_map(|line| line.strip_prefix("Server Name:"))
+        .map_or("connected", str::trim);
+    ok("pulse", server)
+}
+
+fn check_dbus() -> Check {
+    if env::var_os("DBUS_SESSION_BUS_ADDRESS").is_none_or(|value| value.is_empty()) {
+        return problem(
+            "dbus",
+            Status::Warn,
+            "DBUS_SESSION_BUS_ADDRESS is not set",
+            "media, notifications and dnd need the session bus; run inside your graphical session",
+        );
+    }
+    if !Path::new("/run/dbus/system_bus_socket").exists() {
+        return problem(
+            "dbus",
+            Status::Warn,
+            "system bus socket not found",
+            "UPower, BlueZ and logind need the system bus (is dbus running?)",
+        );
+    }
+    ok("dbus", "session and system bus")
+}
+
+fn check_input() -> Check {
+    let readable = std::fs::read_dir("/dev/input").is_ok_and(|entries| {
+        entries.flatten().any(|entry| {
+            entry.file_name().to_string_lossy().starts_with("event")
+                && access(entry.path(), Access::READ_OK).is_ok()
+        })
+    });
+    if !readable {
+        return problem(
+            "input",
+            Status::Warn,
+            "cannot read /dev/input, --locks only updates on the refresh interval",
+            "sudo usermod -aG input $USER, then log in again",
+        );
+    }
+    ok("input", "/dev/input readable")
+}
+
+fn check_runtime_dir() -> Check {
+    match paths::runtime_dir() {
+        Ok(dir) if access(&dir, Access::WRITE_OK).is_ok() => {
+            ok("runtime-dir", dir.display().to_string())
+        }
+        _ => problem(
+            "runtime-dir",
+            Status::Fail,
+            "XDG_RUNTIME_DIR is not set or not writable",
+            "timer, caffeine, ctl and the control socket need it; it is set by systemd-logind on login",
+        ),
+    }
+}
+
+// 逐项检查各后端，输出结果和修复建议；有检查失败时返回 false
+pub fn report() -> (String, bool) {
+    let checks = [
+        check_battery(),
+        check_backlight(),
+        check_kbd_backlight(),
+        check_charge_threshold(),
+        check_hwmon(),
+        check_alsa(),
+        check_amixer(),
+        check_pulse(),
+        check_dbus(),
+        check_input(),
+        check_runtime_dir(),
+    ];
+    let mut lines = Vec::new();
+    for check in &checks {
+        let status = match check.status {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        };
+        lines.push(format!("{:<4} {:<16} {}", status, check.name, check.detail));
+        if let Some(hint) = &check.hint {
+            lines.push(format!("{:<21} -> {}", "", hint));
+        }
+    }
+    let healthy = checks.iter().all(|check| check.status != Status::Fail);
+    (lines.join("\n"), healthy)
+}
@@ -0,0 +1,66 @@
+use std::io;
+
+// 模块读取错误，按原因区分，一次性输出模式下映射为不同的退出码
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    // 设备或 sysfs/procfs 节点不存在（无电池、无背光等）
+    #[error("{0}")]
+    MissingDevice(String),
+    // 读取到的内容无法解析
+    #[error("{0}")]
+    Parse(String),
+    #[error("{0}")]
+    PermissionDenied(String),
+    // 依赖的外部命令或服务不可用（amixer、pactl、gdbus 等）
+    #[error("{0}")]
+    BackendUnavailable(String),
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        let message = e.to_string();
+        match e.kind() {
+            io::ErrorKind::NotFound => Error::MissingDevice(message),
+            io::ErrorKind::PermissionDenied => Error::PermissionDenied(message),
+            io::ErrorKind::InvalidData | io::ErrorKind::InvalidInput => Error::Parse(message),
+            _ => Error::BackendUnavailable(message),
+        }
+    }
+}
+
+impl Error {
+    // 退出码避开 1（通用错误）、2（参数错误）和 33（i3blocks 的 urgent）
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::MissingDevice(_) => 3,
+            Error::Parse(_) => 4,
+            Error::PermissionDenied(_) => 5,
+            Error::BackendUnavailable(_) => 6,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::MissingDevice(_) => "missing device",
+            Error::Parse(_) => "parse error",
+            Error::PermissionDenied(_) => "permission denied",
+            Error::BackendUnavailable(_) => "backend unavailable",
+        }
+    }
+
+    // --verbose 时输出的排查提示
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Error::MissingDevice(_) => {
+                "the device, sysfs node or required option does not exist on this machine"
+            }
+            Error::Parse(_) => "the kernel or a tool reported a value in an unexpected format",
+            Error::PermissionDenied(_) => {
+                "check file permissions or group membership (e.g. video); `sys-montion generate udev-rules` prints the udev rules for writes"
+            }
+            Error::BackendUnavailable(_) => {
+                "a required tool or service (amixer, pactl, gdbus, udisks2) is missing or failed"
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::watch::Wake;
+use crate::{locale, log};
+use rustix::buffer::spare_capacity;
+use rustix::event::{epoll, Timespec};
+use rustix::fd::{AsFd, BorrowedFd, OwnedFd};
+use rustix::io::Errno;
+use std::io::{self, Read};
+use std::process::{Child, ChildStdout, Stdio};
+use std::thread;
+use std::time::Duration;
+
+// 命令的标准输出，事件源移除时结束命令
+struct CommandOutput {
+    child: Child,
+    stdout: ChildStdout,
+}
+
+impl AsFd for CommandOutput {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.stdout.as_fd()
+    }
+}
+
+impl Read for CommandOutput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stdout.read(buf)
+    }
+}
+
+impl Drop for CommandOutput {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// 事件源就绪时调用，把需要刷新的模块追加到 woken；返回错误时移除该事件源
+type Handler = Box<dyn FnMut(&mut Vec<Wake>) -> io::Result<()>>;
+
+// watch 模式的单线程事件循环
+// uevent、挂载表、inotify、混音器事件、信号和控制套接字都注册到同一个 epoll，
+// 与模块定时刷新一起等待，常驻运行时不再为每个事件源各开一个线程
+pub struct EventLoop {
+    epoll: OwnedFd,
+    handlers: Vec<Option<Handler>>,
+}
+
+impl EventLoop {
+    pub fn new() -> Result<Self, io::Error> {
+        Ok(EventLoop {
+            epoll: epoll::create(epoll::CreateFlags::CLOEXEC)?,
+            handlers: Vec::new(),
+        })
+    }
+
+    // 注册事件源，source 由事件循环持有，移除时随之关闭
+    pub fn add<S: AsFd + 'static>(
+        &mut self,
+        mut source: S,
+        flags: epoll::EventFlags,
+        mut handler: impl FnMut(&mut S, &mut Vec<Wake>) -> io::Result<()> + 'static,
+    ) -> Result<(), io::Error> {
+        let data = epoll::EventData::new_u64(self.handlers.len() as u64);
+        epoll::add(&self.epoll, &source, data, flags)?;
+        self.handlers
+            .push(Some(Box::new(move |woken| handler(&mut source, woken))));
+        Ok(())
+    }
+
+    // 按行读取事件源（如命令输出或标准输入），每读到一行调用一次 handler
+    // 读到文件末尾后移除该事件源
+    pub fn add_lines<S: AsFd + Read + 'static>(
+        &mut self,
+        source: S,
+        mut handler: impl FnMut(&str, &mut Vec<Wake>) + 'static,
+    ) -> Result<(), io::Error> {
+        // 一次读取可能只包含半行，剩余部分留到下次
+        let mut pending = Vec::new();
+        let mut buf = [0u8; 4096];
+        self.add(source, epoll::EventFlags::IN, move |source, woken| {
+            let len = source.read(&mut buf)?;
+            if len == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            pending.extend_from_slice(&buf[..len]);
+            while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                handler(String::from_utf8_lossy(&line).trim_end(), woken);
+            }
+            Ok(())
+        })
+    }
+
+    // 运行持续输出事件的命令（如 `pactl subscribe`），每读到一行调用一次 handler
+    // 命令退出后移除该事件源
+    pub fn add_command(
+        &mut self,
+        program: &str,
+        args: &[&str],
+        handler: impl FnMut(&str, &mut Vec<Wake>) + 'static,
+    ) -> Result<(), io::Error> {
+        let mut child = locale::command(program)
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let Some(stdout) = child.stdout.take() else {
+            return Err(io::Error::other(format!("{}: no stdout", program)));
+        };
+        self.add_lines(CommandOutput { child, stdout }, handler)
+    }
+
+    // 等待事件或超时，就绪事件源产生的唤醒事件追加到 woken
+    pub fn wait(&mut self, timeout: Duration, woken: &mut Vec<Wake>) {
+        let mut events = Vec::with_capacity(16);
+        let timespec = Timespec::try_from(timeout).ok();
+        match epoll::wait(&self.epoll, spare_capacity(&mut events), timespec.as_ref()) {
+            Ok(_) => {}
+            // 信号处理函数会打断等待，信号本身随后经由管道送达
+            Err(Errno::INTR) => return,
+            Err(e) => {
+                log::error!("waiting for events: {}", e);
+                thread::sleep(timeout);
+                return;
+            }
+        }
+        for event in events {
+            let data = event.data;
+            let index = data.u64() as usize;
+            let Some(handler) = self.handlers.get_mut(index).and_then(Option::as_mut) else {
+                continue;
+            };
+            // 事件源已关闭（如 pactl 退出）时丢弃，关闭描述符后 epoll 自动移除它
+            if let Err(e) = handler(woken) {
+                log::debug!("event source {} closed: {}", index, e);
+                self.handlers[index] = None;
+            }
+        }
+    }
+}
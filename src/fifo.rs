@@ -0,0 +1,134 @@
+use crate::log;
+use std::ffi::CString;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+// 解析 --output 参数：目前只支持 fifo:<路径>
+pub fn parse_target(value: &str) -> Result<PathBuf, String> {
+    match value.strip_prefix("fifo:") {
+        Some(path) if !path.is_empty() => Ok(PathBuf::from(path)),
+        _ => Err(format!("invalid output '{}', expected fifo:PATH", value)),
+    }
+}
+
+// 输出到命名管道：没有读者时丢弃输出，读者断开后在下一次写入时重新打开
+pub struct Fifo {
+    path: PathBuf,
+    file: Option<File>,
+    // 每次重新打开后先写入的内容，如 i3bar 协议头
+    header: Option<String>,
+}
+
+fn mkfifo(path: &Path) -> Result<(), io::Error> {
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contains NUL"))?;
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+impl Fifo {
+    // 路径不存在时创建命名管道，已存在但不是命名管道时报错
+    pub fn create(path: PathBuf, header: Option<String>) -> Result<Self, io::Error> {
+        match path.metadata() {
+            Ok(metadata) if metadata.file_type().is_fifo() => {}
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a FIFO", path.display()),
+                ))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => mkfifo(&path)?,
+            Err(e) => return Err(e),
+        }
+        Ok(Fifo {
+            path,
+            file: None,
+            header,
+        })
+    }
+
+    // 以非阻塞方式打开，没有读者时 open 返回 ENXIO，不会阻塞 watch 循环
+    fn open(&mut self) -> Option<&mut File> {
+        if self.file.is_none() {
+            let mut file = match OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(&self.path)
+            {
+                Ok(file) => file,
+                Err(e) => {
+                    if e.raw_os_error() != Some(libc::ENXIO) {
+                        log::warning!("opening {}: {}", self.path.display(), e);
+                    }
+                    return None;
+                }
+            };
+            if let Some(header) = &self.header {
+                if writeln!(file, "{}", header).is_err() {
+                    return None;
+                }
+            }
+            self.file = Some(file);
+        }
+        self.file.as_mut()
+    }
+
+    // 读者是否已连接；没有读者时每次输出都尝试打开，新连接的读者无需等到输出变化
+    pub fn is_open(&self) -> bool {
+        self.file.is_some()
+    }
+
+    pub fn write_line(&mut self, line: &str) {
+        let Some(file) = self.open() else {
+            return;
+        };
+        match writeln!(file, "{}", line) {
+            Ok(()) => {}
+            // 读者跟不上、管道已满时丢弃这一行
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                log::debug!("{} is full, dropping a line", self.path.display())
+            }
+            // 读者已断开（EPIPE），下次写入时重新打开
+            Err(e) => {
+                log::debug!("writing {}: {}", self.path.display(), e);
+                self.file = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn writes_to_reader_and_reopens() {
+        let path = std::env::temp_dir().join(format!("sys-montion-fifo-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut fifo = Fifo::create(path.clone(), Some("header".to_string())).unwrap();
+        // 没有读者时不阻塞
+        fifo.write_line("dropped");
+        for line in ["first", "second"] {
+            let reader = OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(&path)
+                .unwrap();
+            fifo.write_line(line);
+            let mut lines = BufReader::new(reader).lines();
+            assert_eq!(lines.next().unwrap().unwrap(), "header");
+            assert_eq!(lines.next().unwrap().unwrap(), line);
+            drop(lines);
+            // 读者断开后第一次写入失败，之后重新打开
+            fifo.write_line("lost");
+        }
+        assert!(parse_target("stdout").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
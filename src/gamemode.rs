@@ -0,0 +1,69 @@
+use crate::cpu;
+use crate::dbus::{self, Bus};
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{read_file, sysfs};
+use std::path::Path;
+
+// 通过 D-Bus 查询 Feral gamemode 当前注册的游戏数量，gamemoded 未运行时视为 0
+fn client_count() -> i64 {
+    dbus::get_property(
+        Bus::Session,
+        "com.feralinteractive.GameMode",
+        "/com/feralinteractive/GameMode",
+        "com.feralinteractive.GameMode",
+        "ClientCount",
+    )
+    .ok()
+    .and_then(|value| value.rsplit(' ').next()?.parse().ok())
+    .unwrap_or(0)
+}
+
+// 读取 GPU 性能档位（amdgpu 的 power_dpm_force_performance_level，如 auto、high）
+fn read_gpu_profile() -> Option<String> {
+    let mut cards: Vec<String> = sysfs::read_dir("/sys/class/drm/")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with("card") && !name.contains('-'))
+        .collect();
+    cards.sort();
+    cards.iter().find_map(|card| {
+        read_file(
+            Path::new("/sys/class/drm")
+                .join(card)
+                .join("device/power_dpm_force_performance_level"),
+        )
+        .ok()
+    })
+}
+
+// 输出 gamemode 状态、CPU 调频策略和 GPU 档位，如 "GM: on performance/high"
+pub fn get_gamemode() -> Result<String, Error> {
+    let state = if client_count() > 0 { "on" } else { "off" };
+    let governor = cpu::read_governor()?;
+    Ok(match read_gpu_profile() {
+        Some(profile) => format!("GM: {} {}/{}", state, governor, profile),
+        None => format!("GM: {} {}", state, governor),
+    })
+}
+
+pub struct GameMode;
+
+impl Builtin for GameMode {
+    const ID: &'static str = "gamemode";
+    const BLOCK: &'static str = "gamemode";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        GameMode
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_gamemode()?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        cpu::cpufreq_unavailable()
+    }
+}
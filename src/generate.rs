@@ -0,0 +1,431 @@
+use clap::{Arg, Command};
+
+// 补全脚本和 man 手册都从 args::build_cli() 的参数定义生成，运行时输出，打包时无需构建脚本
+const BIN: &str = env!("CARGO_PKG_NAME");
+
+fn help(arg: &Arg) -> String {
+    arg.get_help().map(|h| h.to_string()).unwrap_or_default()
+}
+
+fn about(command: &Command) -> String {
+    command
+        .get_about()
+        .map(|a| a.to_string())
+        .unwrap_or_default()
+}
+
+fn takes_value(arg: &Arg) -> bool {
+    arg.get_action().takes_values()
+}
+
+fn possible_values(arg: &Arg) -> Vec<String> {
+    arg.get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect()
+}
+
+fn value_name(arg: &Arg) -> String {
+    arg.get_value_names()
+        .and_then(|names| names.first())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| arg.get_id().to_string().to_uppercase())
+}
+
+fn options(command: &Command) -> impl Iterator<Item = &Arg> {
+    command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+}
+
+// 递归列出命令及其路径，如 ["ctl", "get"]
+fn walk<'a>(command: &'a Command, path: Vec<&'a str>, out: &mut Vec<(Vec<&'a str>, &'a Command)>) {
+    out.push((path.clone(), command));
+    for sub in command.get_subcommands() {
+        let mut sub_path = path.clone();
+        sub_path.push(sub.get_name());
+        walk(sub, sub_path, out);
+    }
+}
+
+fn commands(root: &Command) -> Vec<(Vec<&str>, &Command)> {
+    let mut out = Vec::new();
+    walk(root, Vec::new(), &mut out);
+    out
+}
+
+fn bash(root: &Command) -> String {
+    let commands = commands(root);
+    let mut script = format!(
+        "_sys_montion() {{
+    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\" prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"
+    local cmd=\"{}\" opts i
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case \"$cmd ${{COMP_WORDS[i]}}\" in
+",
+        BIN
+    );
+    let nested: Vec<String> = commands
+        .iter()
+        .filter(|(path, _)| !path.is_empty())
+        .map(|(path, _)| format!("\"{} {}\"", BIN, path.join(" ")))
+        .collect();
+    script.push_str(&format!(
+        "            {}) cmd=\"$cmd ${{COMP_WORDS[i]}}\" ;;\n        esac\n    done\n    case \"$prev\" in\n",
+        nested.join("|")
+    ));
+    // 需要参数值的选项：有固定取值时补全取值，否则补全文件名
+    let mut seen = Vec::new();
+    for (_, command) in &commands {
+        for arg in options(command).filter(|arg| takes_value(arg)) {
+            let Some(long) = arg.get_long() else {
+                continue;
+            };
+            if seen.contains(&long) {
+                continue;
+            }
+            seen.push(long);
+            let values = possible_values(arg);
+            let reply = if values.is_empty() {
+                "compgen -f -- \"$cur\"".to_string()
+            } else {
+                format!("compgen -W \"{}\" -- \"$cur\"", values.join(" "))
+            };
+            script.push_str(&format!(
+                "        --{}) COMPREPLY=($({})); return ;;\n",
+                long, reply
+            ));
+        }
+    }
+    script.push_str("    esac\n    case \"$cmd\" in\n");
+    for (path, command) in &commands {
+        let mut words: Vec<String> = options(command)
+            .filter_map(|arg| arg.get_long().map(|long| format!("--{}", long)))
+            .collect();
+        words.extend(
+            command
+                .get_subcommands()
+                .map(|sub| sub.get_name().to_string()),
+        );
+        for arg in command.get_positionals() {
+            words.extend(possible_values(arg));
+        }
+        let name = std::iter::once(BIN)
+            .chain(path.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ");
+        script.push_str(&format!(
+            "        \"{}\") opts=\"{}\" ;;\n",
+            name,
+            words.join(" ")
+        ));
+    }
+    script.push_str(&format!(
+        "    esac\n    COMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))\n}}\ncomplete -F _sys_montion {}\n",
+        BIN
+    ));
+    script
+}
+
+fn zsh_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+fn zsh_function(path: &[&str]) -> String {
+    std::iter::once("_sys_montion")
+        .chain(path.iter().copied())
+        .collect::<Vec<_>>()
+        .join("_")
+        .replace('-', "_")
+}
+
+fn zsh(root: &Command) -> String {
+    let mut script = format!("#compdef {}\n", BIN);
+    for (path, command) in commands(root) {
+        let mut specs = Vec::new();
+        for arg in options(command) {
+            let Some(long) = arg.get_long() else {
+                continue;
+            };
+            let description = zsh_escape(&help(arg));
+            let spec = if takes_value(arg) {
+                let values = possible_values(arg);
+                let action = if values.is_empty() {
+                    "_files".to_string()
+                } else {
+                    format!("({})", values.join(" "))
+                };
+                format!(
+                    "'--{}=[{}]:{}:{}'",
+                    long,
+                    description,
+                    value_name(arg),
+                    action
+                )
+            } else {
+                format!("'--{}[{}]'", long, description)
+            };
+            specs.push(spec);
+            if let Some(short) = arg.get_short() {
+                specs.push(format!("'-{}[{}]'", short, description));
+            }
+        }
+        for arg in command.get_positionals() {
+            let values = possible_values(arg);
+            let action = if values.is_empty() {
+                "_files".to_string()
+            } else {
+                format!("({})", values.join(" "))
+            };
+            specs.push(format!("':{}:{}'", value_name(arg), action));
+        }
+        let function = zsh_function(&path);
+        script.push_str(&format!("\n{}() {{\n", function));
+        if command.has_subcommands() {
+            specs.push("'1: :->command'".to_string());
+            specs.push("'*::arg:->args'".to_string());
+            script.push_str("    local line state\n");
+            script.push_str(&format!(
+                "    _arguments -C \\\n        {}\n",
+                specs.join(" \\\n        ")
+            ));
+            script.push_str("    case $state in\n        command)\n            _values 'command'");
+            for sub in command.get_subcommands() {
+                script.push_str(&format!(
+                    " \\\n                '{}[{}]'",
+                    sub.get_name(),
+                    zsh_escape(&about(sub))
+                ));
+            }
+            script.push_str("\n            ;;\n        args)\n            case $line[1] in\n");
+            for sub in command.get_subcommands() {
+                let mut sub_path = path.clone();
+                sub_path.push(sub.get_name());
+                script.push_str(&format!(
+                    "                {}) {} ;;\n",
+                    sub.get_name(),
+                    zsh_function(&sub_path)
+                ));
+            }
+            script.push_str("            esac\n            ;;\n    esac\n}\n");
+        } else if specs.is_empty() {
+            script.push_str("    _message 'no more arguments'\n}\n");
+        } else {
+            script.push_str(&format!(
+                "    _arguments \\\n        {}\n}}\n",
+                specs.join(" \\\n        ")
+            ));
+        }
+    }
+    script.push_str("\n_sys_montion \"$@\"\n");
+    script
+}
+
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn fish(root: &Command) -> String {
+    let mut script = String::new();
+    for (path, command) in commands(root) {
+        // 顶层只在输入子命令前补全，子命令在其路径上的各级都已出现后补全
+        let condition = if path.is_empty() {
+            "__fish_use_subcommand".to_string()
+        } else {
+            path.iter()
+                .map(|name| format!("__fish_seen_subcommand_from {}", name))
+                .collect::<Vec<_>>()
+                .join("; and ")
+        };
+        for arg in options(command) {
+            let Some(long) = arg.get_long() else {
+                continue;
+            };
+            // 全局选项在各子命令中重复出现，只在顶层输出一次
+            if arg.is_global_set() && !path.is_empty() {
+                continue;
+            }
+            let mut line = format!("complete -c {}", BIN);
+            if !arg.is_global_set() {
+                line.push_str(&format!(" -n '{}'", condition));
+            }
+            if let Some(short) = arg.get_short() {
+                line.push_str(&format!(" -s {}", short));
+            }
+            line.push_str(&format!(" -l {}", long));
+            if takes_value(arg) {
+                line.push_str(" -r");
+                let values = possible_values(arg);
+                if !values.is_empty() {
+                    line.push_str(&format!(" -f -a '{}'", values.join(" ")));
+                }
+            }
+            line.push_str(&format!(" -d '{}'\n", fish_escape(&help(arg))));
+            script.push_str(&line);
+        }
+        for sub in command.get_subcommands() {
+            script.push_str(&format!(
+                "complete -c {} -n '{}' -f -a {} -d '{}'\n",
+                BIN,
+                condition,
+                sub.get_name(),
+                fish_escape(&about(sub))
+            ));
+        }
+        for arg in command.get_positionals() {
+            let values = possible_values(arg);
+            if !values.is_empty() {
+                script.push_str(&format!(
+                    "complete -c {} -n '{}' -f -a '{}'\n",
+                    BIN,
+                    condition,
+                    values.join(" ")
+                ));
+            }
+        }
+    }
+    script
+}
+
+// 生成 shell 补全脚本：bash、zsh 或 fish
+pub fn completions(shell: &str, mut root: Command) -> String {
+    root.build();
+    match shell {
+        "zsh" => zsh(&root),
+        "fish" => fish(&root),
+        _ => bash(&root),
+    }
+}
+
+// 转义 roff 文本：反斜杠、连字符，以及行首的 . 和 '
+fn roff_escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+fn man_option(arg: &Arg) -> String {
+    let mut names = Vec::new();
+    if let Some(short) = arg.get_short() {
+        names.push(format!("\\fB\\-{}\\fR", short));
+    }
+    if let Some(long) = arg.get_long() {
+        names.push(format!("\\fB\\-\\-{}\\fR", roff_escape(long)));
+    }
+    let mut entry = format!(".TP\n{}", names.join(", "));
+    if takes_value(arg) {
+        entry.push_str(&format!(" \\fI{}\\fR", value_name(arg)));
+    }
+    entry.push('\n');
+    entry.push_str(&roff_escape(&help(arg)));
+    let values = possible_values(arg);
+    if !values.is_empty() {
+        entry.push_str(&format!(
+            " [possible values: {}]",
+            roff_escape(&values.join(", "))
+        ));
+    }
+    if let Some(default) = arg
+        .get_default_values()
+        .first()
+        .filter(|_| takes_value(arg))
+    {
+        entry.push_str(&format!(
+            " [default: {}]",
+            roff_escape(&default.to_string_lossy())
+        ));
+    }
+    entry.push('\n');
+    entry
+}
+
+// 生成 man(1) 手册页，每个子命令单独一段
+pub fn man_page(mut root: Command) -> String {
+    root.build();
+    let mut page = format!(
+        ".TH {} 1 \"\" \"{} {}\"\n.SH NAME\n{} \\- {}\n.SH SYNOPSIS\n\\fB{}\\fR [\\fIOPTIONS\\fR] [\\fICOMMAND\\fR]\n",
+        BIN.to_uppercase(),
+        BIN,
+        root.get_version().unwrap_or_default(),
+        BIN,
+        roff_escape(&about(&root)),
+        BIN
+    );
+    page.push_str(".SH OPTIONS\n");
+    for arg in options(&root) {
+        page.push_str(&man_option(arg));
+    }
+    page.push_str(".SH COMMANDS\n");
+    for (path, command) in commands(&root).into_iter().skip(1) {
+        if command.has_subcommands() || path.contains(&"help") {
+            continue;
+        }
+        let mut synopsis = format!("\\fB{}\\fR", roff_escape(&path.join(" ")));
+        for arg in command.get_positionals() {
+            synopsis.push_str(&format!(" \\fI{}\\fR", value_name(arg)));
+        }
+        page.push_str(&format!(
+            ".TP\n{}\n{}\n",
+            synopsis,
+            roff_escape(&about(command))
+        ));
+        for arg in options(command).filter(|arg| !arg.is_global_set()) {
+            page.push_str(&format!(".RS\n{}.RE\n", man_option(arg)));
+        }
+    }
+    page
+}
+
+// 写入 sysfs 失败（EACCES）时附在错误信息后的提示
+pub const UDEV_HINT: &str = "run `sys-montion generate udev-rules` to allow writes without root";
+
+// udev 规则：设备出现时把背光、LED 亮度和充电阈值文件交给 group 并允许组写入
+pub fn udev_rules(group: &str) -> String {
+    format!(
+        "# Save as /etc/udev/rules.d/90-{bin}.rules, then run:
+#   sudo udevadm control --reload && sudo udevadm trigger
+#   sudo usermod -aG {group} $USER
+# and log out and back in so the new group membership takes effect.
+
+# Screen backlight (backlight up/down/set)
+ACTION==\"add\", SUBSYSTEM==\"backlight\", RUN+=\"/bin/chgrp {group} /sys%p/brightness\", RUN+=\"/bin/chmod g+w /sys%p/brightness\"
+
+# Keyboard backlight and other LEDs (kbd-backlight)
+ACTION==\"add\", SUBSYSTEM==\"leds\", RUN+=\"/bin/chgrp {group} /sys%p/brightness\", RUN+=\"/bin/chmod g+w /sys%p/brightness\"
+
+# Battery charge thresholds (--charge-threshold), only on batteries that support them
+ACTION==\"add\", SUBSYSTEM==\"power_supply\", ATTR{{type}}==\"Battery\", TEST==\"charge_control_end_threshold\", RUN+=\"/bin/sh -c 'chgrp {group} /sys%p/charge_control_*_threshold && chmod g+w /sys%p/charge_control_*_threshold'\"
+",
+        bin = BIN,
+        group = group
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::build_cli;
+
+    #[test]
+    fn generates_from_cli_definition() {
+        let bash = completions("bash", build_cli());
+        assert!(bash.contains("        --format) COMPREPLY=($(compgen -W \"plain i3bar waybar csv tsv env\" -- \"$cur\")); return ;;\n"));
+        assert!(bash.contains("        \"sys-montion ctl get\") opts=\"--json"));
+        let man = man_page(build_cli());
+        assert!(man
+            .contains(".TP\n\\fBctl get\\fR \\fIMODULE\\fR\nPrint the cached output of MODULE\n"));
+        assert!(
+            man.contains("[possible values: plain, i3bar, waybar, csv, tsv, env] [default: plain]")
+        );
+        let rules = udev_rules("wheel");
+        assert!(rules.contains("SUBSYSTEM==\"leds\", RUN+=\"/bin/chgrp wheel /sys%p/brightness\""));
+    }
+}
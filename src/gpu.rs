@@ -0,0 +1,187 @@
+use crate::error::Error;
+use crate::hwmon::{self, TempSensor};
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{locale, read_file, sysfs};
+use std::path::Path;
+
+const DRM_PATH: &str = "/sys/class/drm";
+
+pub const SENSORS: [&str; 4] = ["edge", "junction", "mem", "all"];
+
+// PCI 厂商 ID：NVIDIA 专有驱动不注册 hwmon，温度通过 nvidia-smi 读取
+const NVIDIA_VENDOR: &str = "0x10de";
+
+// 列出显卡，如 card0、card1（排除 card0-eDP-1 等输出接口）
+fn list_cards() -> Vec<String> {
+    let mut cards: Vec<String> = sysfs::read_dir(DRM_PATH)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| name.starts_with("card") && !name.contains('-'))
+                .collect()
+        })
+        .unwrap_or_default();
+    cards.sort();
+    cards
+}
+
+// amdgpu 的传感器为 edge、junction（热点）和 mem，nouveau 只有一个 temp1
+fn hwmon_temps(card: &str) -> Vec<TempSensor> {
+    let dir = Path::new(DRM_PATH).join(card).join("device/hwmon");
+    sysfs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .flat_map(|entry| hwmon::chip_temps(&dir.join(entry.file_name())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// 通过 nvidia-smi 读取 GPU 核心温度，按 PCI 地址对应到显卡
+// nvidia-smi 的地址形如 00000000:01:00.0，sysfs 中为 0000:01:00.0
+fn nvidia_temp(card: &str) -> Option<TempSensor> {
+    let device = Path::new(DRM_PATH).join(card).join("device");
+    if read_file(device.join("vendor")).ok()? != NVIDIA_VENDOR {
+        return None;
+    }
+    let address = std::fs::canonicalize(sysfs::path(&device)).ok()?;
+    let address = address.file_name()?.to_string_lossy().to_lowercase();
+    let output = locale::command("nvidia-smi")
+        .args([
+            "--query-gpu=pci.bus_id,temperature.gpu",
+            "--format=csv,noheader,nounits",
+        ])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            let (bus_id, temp) = line.split_once(',')?;
+            if !bus_id.trim().to_lowercase().ends_with(&address) {
+                return None;
+            }
+            Some(TempSensor {
+                chip: "nvidia".to_string(),
+                label: "edge".to_string(),
+                celsius: temp.trim().parse().ok()?,
+            })
+        })
+}
+
+fn card_temps(card: &str) -> Vec<TempSensor> {
+    let sensors = hwmon_temps(card);
+    if !sensors.is_empty() {
+        return sensors;
+    }
+    nvidia_temp(card).into_iter().collect()
+}
+
+// 选择传感器：没有 edge 标签的驱动（nouveau、nvidia）只有一个核心温度，作为 edge
+fn select<'a>(sensors: &'a [TempSensor], sensor: &str) -> Vec<&'a TempSensor> {
+    match sensor {
+        "all" => sensors.iter().collect(),
+        "edge" => sensors
+            .iter()
+            .find(|s| s.label == "edge")
+            .or(sensors.first())
+            .into_iter()
+            .collect(),
+        _ => sensors.iter().filter(|s| s.label == sensor).collect(),
+    }
+}
+
+// 输出显卡温度，如 "GPU: 52°C"；sensor 为 all 时带标签，如 "GPU: edge 52°C junction 61°C mem 58°C"
+// 未指定显卡时使用第一块有温度传感器的显卡，双显卡笔记本可用 --gpu-device 选择独显
+pub fn get_gpu_temp(device: Option<&str>, sensor: &str) -> Result<String, Error> {
+    let (card, sensors) = match device {
+        Some(card) => {
+            if !sysfs::exists(Path::new(DRM_PATH).join(card)) {
+                return Err(Error::MissingDevice(format!("no GPU named {}", card)));
+            }
+            (card.to_string(), card_temps(card))
+        }
+        None => list_cards()
+            .into_iter()
+            .map(|card| {
+                let sensors = card_temps(&card);
+                (card, sensors)
+            })
+            .find(|(_, sensors)| !sensors.is_empty())
+            .ok_or_else(|| Error::MissingDevice("no GPU temperature sensor found".to_string()))?,
+    };
+    let selected = select(&sensors, sensor);
+    if selected.is_empty() {
+        return Err(Error::MissingDevice(format!(
+            "{} has no {} temperature sensor",
+            card, sensor
+        )));
+    }
+    let readings: Vec<String> = match sensor {
+        "all" => selected
+            .iter()
+            .map(|s| format!("{} {:.0}°C", s.label, s.celsius))
+            .collect(),
+        _ => vec![format!("{:.0}°C", selected[0].celsius)],
+    };
+    Ok(format!("GPU: {}", readings.join(" ")))
+}
+
+pub struct GpuTemp {
+    device: Option<String>,
+    sensor: String,
+}
+
+impl GpuTemp {
+    fn read(&self) -> Result<String, Error> {
+        get_gpu_temp(self.device.as_deref(), &self.sensor)
+    }
+}
+
+impl Builtin for GpuTemp {
+    const ID: &'static str = "gpu-temp";
+    const BLOCK: &'static str = "gpu_temp";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        GpuTemp {
+            device: matches.get_one::<String>("gpu-device").cloned(),
+            sensor: matches
+                .get_one::<String>("gpu-temp")
+                .map_or("edge", String::as_str)
+                .to_string(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, self.read()?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        self.read().err().map(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_gpu_sensors() {
+        sysfs::use_fixtures();
+        assert_eq!(get_gpu_temp(None, "edge").unwrap(), "GPU: 52°C");
+        assert_eq!(
+            get_gpu_temp(Some("card1"), "junction").unwrap(),
+            "GPU: 61°C"
+        );
+        assert_eq!(
+            get_gpu_temp(None, "all").unwrap(),
+            "GPU: edge 52°C junction 61°C mem 58°C"
+        );
+        assert!(matches!(
+            get_gpu_temp(Some("card0"), "edge"),
+            Err(Error::MissingDevice(_))
+        ));
+    }
+}
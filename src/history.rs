@@ -0,0 +1,167 @@
+use crate::error::Error;
+use crate::timeutil::now_secs;
+use crate::{battery, format_duration, locale, paths};
+use std::fs;
+use std::io;
+
+// 保留的采样条数，每分钟一条时约为两天
+const KEEP_SAMPLES: usize = 2880;
+
+// 两次采样的最小间隔（秒）
+const SAMPLE_INTERVAL: i64 = 60;
+
+// 迷你图的最大宽度（字符）
+const SPARKLINE_WIDTH: usize = 24;
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+// 一次采样：时间、电量百分比和功率（瓦特，电池不提供时为 None）
+#[derive(Debug, PartialEq)]
+struct Sample {
+    time: i64,
+    capacity: f64,
+    watts: Option<f64>,
+}
+
+// 读取电池历史，每行格式为 "时间\t电量\t功率"，功率未知时为 "-"
+fn load() -> Result<Vec<Sample>, io::Error> {
+    let content = match fs::read_to_string(paths::state_path("battery-history.tsv")?) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(parse(&content))
+}
+
+fn parse(content: &str) -> Vec<Sample> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [time, capacity, watts] = fields[..] else {
+                return None;
+            };
+            Some(Sample {
+                time: time.parse().ok()?,
+                capacity: capacity.parse().ok()?,
+                watts: watts.parse().ok(),
+            })
+        })
+        .collect()
+}
+
+// 只保留最近 KEEP_SAMPLES 条，文件大小固定
+fn save(samples: &[Sample]) -> Result<(), io::Error> {
+    let path = paths::state_path("battery-history.tsv")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let skip = samples.len().saturating_sub(KEEP_SAMPLES);
+    let content: String = samples[skip..]
+        .iter()
+        .map(|sample| {
+            let watts = sample
+                .watts
+                .map_or("-".to_string(), |w| format!("{:.2}", w));
+            format!("{}\t{}\t{}\n", sample.time, sample.capacity, watts)
+        })
+        .collect();
+    fs::write(path, content)
+}
+
+// watch 模式下每次刷新调用，距上一条采样不足 SAMPLE_INTERVAL 秒时跳过
+pub fn record(battery_device: &str) -> Result<(), Error> {
+    let mut samples = load()?;
+    let now = now_secs();
+    if samples
+        .last()
+        .is_some_and(|last| now - last.time < SAMPLE_INTERVAL)
+    {
+        return Ok(());
+    }
+    let capacity = battery::get_battery_capacity(battery_device)?;
+    samples.push(Sample {
+        time: now,
+        capacity: capacity
+            .parse()
+            .map_err(|_| Error::Parse(format!("invalid battery capacity '{}'", capacity)))?,
+        watts: battery::read_power_watts(battery_device).ok(),
+    });
+    save(&samples)?;
+    Ok(())
+}
+
+// 把窗口等分为 SPARKLINE_WIDTH 段，每段取平均电量，按 0-100% 映射到柱高
+// 没有采样的时间段（如关机或未运行 watch）显示为空格
+fn sparkline(samples: &[&Sample], start: i64, window: i64) -> String {
+    let mut buckets = vec![(0.0, 0); SPARKLINE_WIDTH];
+    for sample in samples {
+        let index = ((sample.time - start) * SPARKLINE_WIDTH as i64 / window)
+            .clamp(0, SPARKLINE_WIDTH as i64 - 1) as usize;
+        buckets[index].0 += sample.capacity;
+        buckets[index].1 += 1;
+    }
+    buckets
+        .iter()
+        .map(|&(sum, count)| {
+            if count == 0 {
+                return ' ';
+            }
+            let level = (sum / count as f64).clamp(0.0, 100.0) / 100.0;
+            BARS[((level * (BARS.len() - 1) as f64).round()) as usize]
+        })
+        .collect()
+}
+
+fn summarize(samples: &[Sample], window: i64, now: i64) -> Result<String, Error> {
+    let start = now - window;
+    let samples: Vec<&Sample> = samples.iter().filter(|s| s.time >= start).collect();
+    let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+        return Err(Error::MissingDevice(format!(
+            "no battery history in the last {} (record it with --watch --battery-history)",
+            format_duration(window)
+        )));
+    };
+    let mut text = format!(
+        "{} {:.0}%→{:.0}%",
+        sparkline(&samples, start, window),
+        first.capacity,
+        last.capacity
+    );
+    let hours = (last.time - first.time) as f64 / 3600.0;
+    if hours > 0.0 {
+        let rate = (last.capacity - first.capacity) / hours;
+        text.push_str(&format!(" ({}%/h", locale::format_number(rate, 1)));
+        let watts: Vec<f64> = samples.iter().filter_map(|s| s.watts).collect();
+        if !watts.is_empty() {
+            let average = watts.iter().sum::<f64>() / watts.len() as f64;
+            text.push_str(&format!(", {}W avg", locale::format_number(average, 1)));
+        }
+        text.push(')');
+    }
+    Ok(text)
+}
+
+// 输出时间窗口内的电量迷你图和平均变化速度，如 "▇▇▆▆▅▅▄ 80%→72% (-8.0%/h, 9.5W avg)"
+pub fn get_battery_history(window: i64) -> Result<String, Error> {
+    summarize(&load()?, window, now_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_discharge() {
+        let samples = parse("1000\t90\t10.00\n2800\t85\t-\n4600\t80\t8.00\n");
+        assert_eq!(
+            summarize(&samples, 3600, 4600).unwrap(),
+            format!(
+                "▇{}▇{}▇ 90%→80% (-10.0%/h, 9.0W avg)",
+                " ".repeat(11),
+                " ".repeat(10)
+            )
+        );
+        assert!(summarize(&samples, 60, 10000).is_err());
+    }
+}
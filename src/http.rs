@@ -0,0 +1,265 @@
+use crate::control::to_json;
+use crate::log;
+use crate::module::{self, Module};
+use crate::render::{json_escape, API_VERSION};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+// --http：每次请求时读取模块并返回 JSON，供局域网内其他设备上的小部件（手机面板、Stream Deck 插件）获取
+//   /metrics.json   选择的全部模块（未选择时为 `all` 子命令的模块）
+//   /MODULE         单个模块，模块名或输出块名，如 /battery、/memory、/volume
+// 只提供 names 中的模块，剪贴板、窗口标题等未选择的模块返回 404
+// 监听非回环地址时必须设置 --http-token，请求须带 Authorization: Bearer TOKEN，否则返回 401
+// 模块实例按名称保留，CPU 占用率等需要两次采样的模块在两次请求之间计算
+struct Server {
+    names: Vec<&'static str>,
+    modules: HashMap<String, Box<dyn Module>>,
+    matches: Arc<clap::ArgMatches>,
+    // --http-cors：允许跨域读取的来源，默认不发送 Access-Control-Allow-Origin
+    cors: Option<String>,
+    // --http-token：请求须携带的令牌
+    token: Option<String>,
+}
+
+// 逐字节比较，耗时与令牌在哪一位不同无关
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+// 地址解析出的所有地址都是回环地址（127.0.0.1、::1、localhost）
+fn is_loopback(addr: &str) -> Result<bool, io::Error> {
+    let mut addrs = addr.to_socket_addrs()?.peekable();
+    Ok(addrs.peek().is_some() && addrs.all(|addr| addr.ip().is_loopback()))
+}
+
+fn error_json(name: &str, message: &str) -> String {
+    format!(
+        "{{\"name\":\"{}\",\"error\":\"{}\",\"version\":{}}}",
+        json_escape(name),
+        json_escape(message),
+        API_VERSION
+    )
+}
+
+impl Server {
+    // 读取一个模块，未知或未选择的模块返回 None
+    fn read(&mut self, name: &str) -> Option<Result<String, String>> {
+        let id = *self.names.iter().find(|&&id| id == name).or_else(|| {
+            let entry = module::find(name)?;
+            self.names.iter().find(|&&id| id == entry.id)
+        })?;
+        if !self.modules.contains_key(id) {
+            let module = module::builtin(id, &self.matches)?;
+            self.modules.insert(id.to_string(), module);
+        }
+        Some(match self.modules.get_mut(id).unwrap().read() {
+            Ok(block) => Ok(to_json(&block)),
+            Err(e) => Err(e.to_string()),
+        })
+    }
+
+    // 读取失败的模块以 {"name":...,"error":...} 列出，便于小部件显示原因
+    fn metrics(&mut self) -> String {
+        let mut modules = Vec::new();
+        for name in self.names.clone() {
+            match self.read(name) {
+                Some(Ok(json)) => modules.push(json),
+                Some(Err(e)) => modules.push(error_json(name, &e)),
+                None => {}
+            }
+        }
+        format!(
+            "{{\"version\":{},\"modules\":[{}]}}",
+            API_VERSION,
+            modules.join(",")
+        )
+    }
+
+    // 返回状态行和 JSON 响应体
+    fn respond(&mut self, path: &str) -> (&'static str, String) {
+        let path = path.split('?').next().unwrap_or(path);
+        match path.strip_prefix('/') {
+            Some("metrics.json") => ("200 OK", self.metrics()),
+            Some(name) if !name.is_empty() => match self.read(name) {
+                Some(Ok(json)) => ("200 OK", json),
+                Some(Err(e)) => ("503 Service Unavailable", error_json(name, &e)),
+                None => (
+                    "404 Not Found",
+                    error_json(name, &format!("unknown module '{}'", name)),
+                ),
+            },
+            _ => ("404 Not Found", error_json("", "not found")),
+        }
+    }
+
+    // 处理一个请求，authorization 为 Authorization 请求头的值
+    fn handle(
+        &mut self,
+        request_line: &str,
+        authorization: Option<&str>,
+    ) -> (&'static str, String) {
+        let mut words = request_line.split_whitespace();
+        match (words.next(), words.next()) {
+            // 浏览器带 Authorization 跨域请求前先发送 OPTIONS 预检
+            (Some("OPTIONS"), Some(_)) if self.cors.is_some() => ("204 No Content", String::new()),
+            (Some("GET"), Some(_)) if !self.authorized(authorization) => (
+                "401 Unauthorized",
+                error_json("", "missing or wrong bearer token"),
+            ),
+            (Some("GET"), Some(path)) => self.respond(path),
+            _ => (
+                "405 Method Not Allowed",
+                error_json("", "only GET is supported"),
+            ),
+        }
+    }
+
+    fn authorized(&self, authorization: Option<&str>) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|given| same_token(given.trim(), token))
+    }
+
+    fn serve_client(&mut self, stream: TcpStream) -> Result<(), io::Error> {
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let mut reader = BufReader::new(&stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // 读完请求头，只保留 Authorization
+        let mut authorization = None;
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("authorization") {
+                    authorization = Some(value.trim().to_string());
+                }
+            }
+            header.clear();
+        }
+
+        let (status, body) = self.handle(&request_line, authorization.as_deref());
+        // 浏览器中运行的面板跨域请求时需要 Access-Control-Allow-Origin，只对 --http-cors 指定的来源发送，
+        // 否则任意网页都能读取本机的模块
+        let cors = self
+            .cors
+            .as_ref()
+            .map(|origin| {
+                format!(
+                    "Access-Control-Allow-Origin: {}\r\nAccess-Control-Allow-Headers: Authorization\r\n",
+                    origin
+                )
+            })
+            .unwrap_or_default();
+        write!(
+            &stream,
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Cache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            cors,
+            body
+        )
+    }
+}
+
+// 运行 HTTP 服务，逐个处理请求；names 为 /metrics.json 中的模块及顺序
+pub fn run(
+    addr: &str,
+    names: Vec<&'static str>,
+    matches: &Arc<clap::ArgMatches>,
+) -> Result<(), io::Error> {
+    let token = matches.get_one::<String>("http-token").cloned();
+    if token.is_none() && !is_loopback(addr)? {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "refusing to serve metrics beyond localhost without --http-token",
+        ));
+    }
+    let listener = TcpListener::bind(addr)?;
+    let mut server = Server {
+        names,
+        modules: HashMap::new(),
+        matches: Arc::clone(matches),
+        cors: matches.get_one::<String>("http-cors").cloned(),
+        token,
+    };
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| server.serve_client(stream));
+        if let Err(e) = result {
+            log::warning!("serving HTTP request: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sysfs;
+
+    #[test]
+    fn serves_modules_as_json() {
+        sysfs::use_fixtures();
+        let mut server = Server {
+            names: vec!["memory"],
+            modules: HashMap::new(),
+            matches: Arc::new(crate::args::build_cli().get_matches_from(["sys-montion"])),
+            cors: None,
+            token: None,
+        };
+        assert_eq!(
+            server.respond("/metrics.json"),
+            (
+                "200 OK",
+                r#"{"version":2,"modules":[{"name":"memory","text":"MEM: 3.8G/15.3G (25%)","value":25,"version":2}]}"#
+                    .to_string()
+            )
+        );
+        assert_eq!(server.respond("/memory?t=1").0, "200 OK");
+        assert_eq!(server.respond("/nothing").0, "404 Not Found");
+        // 未选择的模块不提供
+        assert_eq!(server.respond("/clipboard").0, "404 Not Found");
+        assert_eq!(server.respond("/cpu").0, "404 Not Found");
+        assert_eq!(server.respond("/").0, "404 Not Found");
+    }
+
+    #[test]
+    fn requires_token_beyond_localhost() {
+        assert!(is_loopback("127.0.0.1:8725").unwrap());
+        assert!(is_loopback("[::1]:8725").unwrap());
+        assert!(!is_loopback("0.0.0.0:8725").unwrap());
+        assert!(!is_loopback("192.168.1.10:8725").unwrap());
+
+        sysfs::use_fixtures();
+        let mut server = Server {
+            names: vec!["memory"],
+            modules: HashMap::new(),
+            matches: Arc::new(crate::args::build_cli().get_matches_from(["sys-montion"])),
+            cors: None,
+            token: Some("s3cret".to_string()),
+        };
+        let request = "GET /memory HTTP/1.1\r\n";
+        assert_eq!(server.handle(request, None).0, "401 Unauthorized");
+        assert_eq!(
+            server.handle(request, Some("Bearer wrong")).0,
+            "401 Unauthorized"
+        );
+        assert_eq!(server.handle(request, Some("Bearer s3cret")).0, "200 OK");
+        assert_eq!(
+            server
+                .handle("POST /memory HTTP/1.1", Some("Bearer s3cret"))
+                .0,
+            "405 Method Not Allowed"
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::{Block, Level};
+use crate::{read_file, sysfs};
+use std::io;
+use std::path::{Path, PathBuf};
+
+const HWMON_PATH: &str = "/sys/class/hwmon";
+
+// 风扇读数
+pub struct Fan {
+    pub rpm: i64,
+    // 转速达到 fan*_max，或对应 pwm 输出为 255
+    pub at_max: bool,
+}
+
+// 列出所有 hwmon 设备目录，如 /sys/class/hwmon/hwmon0
+fn hwmon_dirs() -> Result<Vec<PathBuf>, io::Error> {
+    let mut dirs = Vec::new();
+    for entry in sysfs::read_dir(HWMON_PATH)? {
+        dirs.push(Path::new(HWMON_PATH).join(entry?.file_name()));
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+// 列出目录下匹配 `{prefix}N_input` 的传感器编号
+fn sensor_indexes(dir: &Path, prefix: &str) -> Vec<String> {
+    let Ok(entries) = sysfs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut indexes: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let index = name.strip_prefix(prefix)?.strip_suffix("_input")?;
+            index
+                .chars()
+                .all(|c| c.is_ascii_digit())
+                .then(|| index.to_string())
+        })
+        .collect();
+    indexes.sort();
+    indexes
+}
+
+fn read_value(path: impl AsRef<Path>) -> Option<i64> {
+    sysfs::read_int(path).ok()
+}
+
+// 一个 hwmon 设备：目录名（如 hwmon0）、驱动名称（如 coretemp）和传感器（如 temp1、fan1）
+pub struct Chip {
+    pub id: String,
+    pub name: String,
+    pub sensors: Vec<String>,
+}
+
+pub fn list_chips() -> Result<Vec<Chip>, io::Error> {
+    let mut chips = Vec::new();
+    for dir in hwmon_dirs()? {
+        let mut sensors = Vec::new();
+        for prefix in ["temp", "fan"] {
+            sensors.extend(
+                sensor_indexes(&dir, prefix)
+                    .into_iter()
+                    .map(|index| format!("{}{}", prefix, index)),
+            );
+        }
+        chips.push(Chip {
+            id: dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            name: read_file(dir.join("name")).unwrap_or_default(),
+            sensors,
+        });
+    }
+    Ok(chips)
+}
+
+// 读取所有风扇转速
+pub fn read_fans() -> Result<Vec<Fan>, io::Error> {
+    let mut fans = Vec::new();
+    for dir in hwmon_dirs()? {
+        for index in sensor_indexes(&dir, "fan") {
+            let Some(rpm) = read_value(dir.join(format!("fan{}_input", index))) else {
+                continue;
+            };
+            let max = read_value(dir.join(format!("fan{}_max", index)));
+            let pwm = read_value(dir.join(format!("pwm{}", index)));
+            let at_max = max.is_some_and(|max| max > 0 && rpm >= max) || pwm == Some(255);
+            fans.push(Fan { rpm, at_max });
+        }
+    }
+    Ok(fans)
+}
+
+// --fan-curve 的一点 "温度:转速"，如 60:2000 表示 60°C 及以上时风扇应至少 2000 RPM
+pub fn parse_fan_point(spec: &str) -> Result<(f64, i64), String> {
+    let invalid = || format!("invalid fan curve point '{}', expected CELSIUS:RPM", spec);
+    let (celsius, rpm) = spec.split_once(':').ok_or_else(invalid)?;
+    let celsius = celsius.trim().parse().map_err(|_| invalid())?;
+    let rpm = rpm.trim().parse().map_err(|_| invalid())?;
+    Ok((celsius, rpm))
+}
+
+// 按风扇曲线检查：取温度已达到的各点中最高的转速要求，最快的风扇低于要求时为 Warning，
+// 风扇全部停转时为 Critical（多为积灰或风扇故障）；返回级别和说明，如 "fan 0 RPM"
+pub fn check_fan_curve(
+    curve: &[(f64, i64)],
+    celsius: f64,
+    fans: &[Fan],
+) -> Option<(Level, String)> {
+    let expected = curve
+        .iter()
+        .filter(|(temp, _)| celsius >= *temp)
+        .map(|(_, rpm)| *rpm)
+        .max()?;
+    let fastest = fans.iter().map(|fan| fan.rpm).max()?;
+    if fastest >= expected {
+        return None;
+    }
+    let level = if fastest == 0 {
+        Level::Critical
+    } else {
+        Level::Warning
+    };
+    Some((level, format!("fan {} RPM", fastest)))
+}
+
+// 温度传感器：所在设备的驱动名称（如 nvme、k10temp）、标签和温度（摄氏度）
+// 标签取 temp*_label（如 Tctl、Composite），没有时为 tempN
+pub struct TempSensor {
+    pub chip: String,
+    pub label: String,
+    pub celsius: f64,
+}
+
+// 读取一个 hwmon 设备目录下的所有温度传感器
+pub fn chip_temps(dir: &Path) -> Vec<TempSensor> {
+    let chip = read_file(dir.join("name")).unwrap_or_default();
+    let mut sensors = Vec::new();
+    for index in sensor_indexes(dir, "temp") {
+        let Some(temp) = read_value(dir.join(format!("temp{}_input", index))) else {
+            continue;
+        };
+        let label = read_file(dir.join(format!("temp{}_label", index)))
+            .unwrap_or_else(|_| format!("temp{}", index));
+        sensors.push(TempSensor {
+            chip: chip.clone(),
+            label,
+            celsius: temp as f64 / 1000.0,
+        });
+    }
+    sensors
+}
+
+pub fn read_temps() -> Result<Vec<TempSensor>, io::Error> {
+    Ok(hwmon_dirs()?
+        .iter()
+        .flat_map(|dir| chip_temps(dir))
+        .collect())
+}
+
+fn hottest<'a>(sensors: impl Iterator<Item = &'a TempSensor>) -> Option<f64> {
+    sensors.map(|sensor| sensor.celsius).reduce(f64::max)
+}
+
+// 读取所有温度传感器中的最高温度（摄氏度）
+pub fn read_max_temp() -> Result<f64, io::Error> {
+    hottest(read_temps()?.iter())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no temperature sensor found"))
+}
+
+// 按 "设备:标签" 选择传感器，如 nvme:Composite、k10temp:Tctl 或 k10temp:temp1
+// 只写设备名时取该设备最热的传感器，max 取所有传感器中最热的
+fn select_temp(sensors: &[TempSensor], spec: &str) -> Option<f64> {
+    match spec.split_once(':') {
+        _ if spec == "max" => hottest(sensors.iter()),
+        Some((chip, label)) => sensors
+            .iter()
+            .find(|sensor| sensor.chip == chip && sensor.label == label)
+            .map(|sensor| sensor.celsius),
+        None => hottest(sensors.iter().filter(|sensor| sensor.chip == spec)),
+    }
+}
+
+// --cpu-temp-agg：--temp cpu 显示最热核心、各核心的平均值，或逐个列出各核心
+pub const CPU_TEMP_AGGREGATIONS: [&str; 3] = ["max", "avg", "per-core"];
+
+// CPU 温度传感器的驱动：Intel coretemp、AMD k10temp 和 zenpower，树莓派等 ARM 的 cpu_thermal
+const CPU_CHIPS: [&str; 4] = ["coretemp", "k10temp", "zenpower", "cpu_thermal"];
+
+// CPU 各核心（coretemp 的 Core N）或各 CCD（k10temp 的 TccdN）的传感器，没有时为 CPU 的全部传感器
+fn cpu_cores(sensors: &[TempSensor]) -> Vec<&TempSensor> {
+    let cpu: Vec<&TempSensor> = sensors
+        .iter()
+        .filter(|sensor| CPU_CHIPS.contains(&sensor.chip.as_str()))
+        .collect();
+    let cores: Vec<&TempSensor> = cpu
+        .iter()
+        .copied()
+        .filter(|sensor| sensor.label.starts_with("Core ") || sensor.label.starts_with("Tccd"))
+        .collect();
+    if cores.is_empty() {
+        cpu
+    } else {
+        cores
+    }
+}
+
+// 逐核心列出时的短标签，如 Core 3 -> C3、Tccd1 -> CCD1
+fn core_label(label: &str) -> String {
+    if let Some(index) = label.strip_prefix("Core ") {
+        format!("C{}", index)
+    } else if let Some(index) = label.strip_prefix("Tccd") {
+        format!("CCD{}", index)
+    } else {
+        label.to_string()
+    }
+}
+
+// --temp cpu 的读数，如 max/avg 为 "64°C"，per-core 为 "C0 58°C C1 64°C"
+fn cpu_temp(sensors: &[TempSensor], aggregation: &str) -> Option<String> {
+    let cores = cpu_cores(sensors);
+    if cores.is_empty() {
+        return None;
+    }
+    Some(match aggregation {
+        "avg" => format!(
+            "{:.0}°C",
+            cores.iter().map(|core| core.celsius).sum::<f64>() / cores.len() as f64
+        ),
+        "per-core" => cores
+            .iter()
+            .map(|core| format!("{} {:.0}°C", core_label(&core.label), core.celsius))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => format!("{:.0}°C", hottest(cores.into_iter())?),
+    })
+}
+
+// 输出温度：单个传感器为 "TEMP: 72°C"，多个时带标签，如 "TEMP: Tctl 72°C Composite 45°C"
+// cpu 为 CPU 核心的温度，按 cpu_aggregation 汇总
+pub fn get_temp(specs: &[&str], cpu_aggregation: &str) -> Result<String, Error> {
+    let sensors = read_temps()?;
+    let mut readings = Vec::new();
+    for &spec in specs {
+        if spec == "cpu" {
+            let reading = cpu_temp(&sensors, cpu_aggregation).ok_or_else(|| {
+                Error::MissingDevice("no CPU temperature sensor found".to_string())
+            })?;
+            readings.push(match specs {
+                [_] => reading,
+                _ => format!("cpu {}", reading),
+            });
+            continue;
+        }
+        let celsius = select_temp(&sensors, spec).ok_or_else(|| {
+            Error::MissingDevice(format!("no temperature sensor matching '{}'", spec))
+        })?;
+        readings.push(match specs {
+            [_] => format!("{:.0}°C", celsius),
+            _ => format!(
+                "{} {:.0}°C",
+                spec.rsplit(':').next().unwrap_or(spec),
+                celsius
+            ),
+        });
+    }
+    Ok(format!("TEMP: {}", readings.join(" ")))
+}
+
+// temp 模块：--temp 选择的传感器，设置了 --fan-curve 时附加风扇检查的结果
+pub struct Temp {
+    specs: Vec<String>,
+    aggregation: String,
+    curve: Vec<(f64, i64)>,
+}
+
+impl Builtin for Temp {
+    const ID: &'static str = "temp";
+    const BLOCK: &'static str = "temp";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Temp {
+            specs: matches
+                .get_many::<String>("temp")
+                .map_or(vec!["max".to_string()], |specs| specs.cloned().collect()),
+            aggregation: matches.get_one::<String>("cpu-temp-agg").unwrap().clone(),
+            curve: matches
+                .get_many::<(f64, i64)>("fan-curve")
+                .map_or(Vec::new(), |points| points.copied().collect()),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let specs: Vec<&str> = self.specs.iter().map(String::as_str).collect();
+        let text = get_temp(&specs, &self.aggregation)?;
+        let fans = read_fans().unwrap_or_default();
+        let check = read_max_temp()
+            .ok()
+            .and_then(|celsius| check_fan_curve(&self.curve, celsius, &fans));
+        Ok(match check {
+            Some((level, note)) => Block {
+                level,
+                ..Block::numeric(Self::BLOCK, format!("{} {}", text, note))
+            },
+            None => Block::numeric(Self::BLOCK, text),
+        })
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        read_temps()
+            .map_or(true, |sensors| sensors.is_empty())
+            .then(|| "no hwmon temperature sensors".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sysfs;
+
+    #[test]
+    fn reads_fans() {
+        sysfs::use_fixtures();
+        let fans = read_fans().unwrap();
+        let readings: Vec<(i64, bool)> = fans.iter().map(|fan| (fan.rpm, fan.at_max)).collect();
+        assert_eq!(readings, [(2400, true), (1200, false)]);
+    }
+
+    #[test]
+    fn checks_fan_curve() {
+        let curve = [(60.0, 1500), (80.0, 3000)];
+        let fans = |rpm| [Fan { rpm, at_max: false }];
+        assert!(check_fan_curve(&curve, 50.0, &fans(0)).is_none());
+        assert!(check_fan_curve(&curve, 70.0, &fans(2000)).is_none());
+        assert!(matches!(
+            check_fan_curve(&curve, 85.0, &fans(2000)),
+            Some((Level::Warning, _))
+        ));
+        assert!(matches!(
+            check_fan_curve(&curve, 72.0, &fans(0)),
+            Some((Level::Critical, note)) if note == "fan 0 RPM"
+        ));
+        assert_eq!(parse_fan_point("60:1500"), Ok((60.0, 1500)));
+        assert!(parse_fan_point("60").is_err());
+    }
+
+    #[test]
+    fn reads_max_temp_across_devices() {
+        sysfs::use_fixtures();
+        assert_eq!(read_max_temp().unwrap(), 71.5);
+    }
+
+    #[test]
+    fn selects_temperature_sensors() {
+        sysfs::use_fixtures();
+        assert_eq!(get_temp(&["max"], "max").unwrap(), "TEMP: 72°C");
+        assert_eq!(
+            get_temp(&["k10temp:Tctl", "thinkpad:temp1"], "max").unwrap(),
+            "TEMP: Tctl 72°C temp1 45°C"
+        );
+        assert_eq!(get_temp(&["thinkpad"], "max").unwrap(), "TEMP: 45°C");
+        assert!(matches!(
+            get_temp(&["nvme:Composite"], "max"),
+            Err(Error::MissingDevice(_))
+        ));
+    }
+
+    #[test]
+    fn aggregates_cpu_cores() {
+        let sensor = |chip: &str, label: &str, celsius| TempSensor {
+            chip: chip.to_string(),
+            label: label.to_string(),
+            celsius,
+        };
+        let sensors = [
+            sensor("coretemp", "Package id 0", 66.0),
+            sensor("coretemp", "Core 0", 58.0),
+            sensor("coretemp", "Core 1", 64.0),
+            sensor("nvme", "Composite", 80.0),
+        ];
+        assert_eq!(cpu_temp(&sensors, "max").unwrap(), "64°C");
+        assert_eq!(cpu_temp(&sensors, "avg").unwrap(), "61°C");
+        assert_eq!(cpu_temp(&sensors, "per-core").unwrap(), "C0 58°C C1 64°C");
+        // k10temp 没有 Tccd 时使用 Tctl
+        assert_eq!(
+            cpu_temp(&[sensor("k10temp", "Tctl", 71.5)], "per-core").unwrap(),
+            "Tctl 72°C"
+        );
+        assert!(cpu_temp(&sensors[3..], "max").is_none());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::log;
+use crate::paths;
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+
+// 内置的简体中文翻译，按原文（gettext 的 msgid）排列
+const ZH: &[(&str, &str)] = &[
+    ("Charging", "充电中"),
+    ("Discharging", "放电中"),
+    ("Not charging", "未充电"),
+    ("Full", "已充满"),
+    ("Unknown", "未知"),
+    ("MUTED", "静音"),
+    ("Playing", "播放中"),
+    ("Paused", "已暂停"),
+    ("Stopped", "已停止"),
+    ("Sunrise in", "日出还有"),
+    ("Sunset in", "日落还有"),
+    ("Polar day", "极昼"),
+    ("Polar night", "极夜"),
+    ("on", "开"),
+    ("off", "关"),
+    ("BAT", "电池"),
+    ("AC", "电源"),
+    ("VOL", "音量"),
+    ("MIC", "麦克风"),
+    ("BL", "亮度"),
+    ("KBD", "键盘灯"),
+    ("MEM", "内存"),
+    ("CPU", "处理器"),
+    ("TEMP", "温度"),
+    ("NET", "网络"),
+    ("BT", "蓝牙"),
+    ("DND", "勿扰"),
+    ("REC", "录制"),
+    ("Weather", "天气"),
+    ("Clear", "晴"),
+    ("Mainly clear", "晴间少云"),
+    ("Partly cloudy", "多云"),
+    ("Overcast", "阴"),
+    ("Fog", "雾"),
+    ("Drizzle", "毛毛雨"),
+    ("Rain", "雨"),
+    ("Rain showers", "阵雨"),
+    ("Snow", "雪"),
+    ("Snow showers", "阵雪"),
+    ("Thunderstorm", "雷暴"),
+];
+
+// 当前语言的翻译表，按原文长度从长到短排列，"Not charging" 先于 "Charging" 匹配
+static CATALOG: OnceLock<Vec<(String, String)>> = OnceLock::new();
+
+// 解析 gettext .po 文件中单行的 msgid/msgstr 对，忽略注释、多行字符串和空翻译
+fn parse_po(content: &str) -> Vec<(String, String)> {
+    let unquote = |value: &str| {
+        let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+        Some(value.replace("\\\"", "\"").replace("\\\\", "\\"))
+    };
+    let mut messages = Vec::new();
+    let mut msgid = None;
+    for line in content.lines() {
+        if let Some(value) = line.strip_prefix("msgid ") {
+            msgid = unquote(value);
+        } else if let Some(value) = line.strip_prefix("msgstr ") {
+            if let (Some(id), Some(text)) = (msgid.take(), unquote(value)) {
+                if !id.is_empty() && !text.is_empty() {
+                    messages.push((id, text));
+                }
+            }
+        }
+    }
+    messages
+}
+
+// 选择输出语言，`auto` 表示读取 LC_ALL / LC_MESSAGES / LANG
+// 内置翻译之上叠加 $XDG_CONFIG_HOME/sys-montion/locale/<语言>.po 中的翻译
+pub fn init(lang: &str) {
+    let lang = if lang == "auto" {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|key| env::var(key).ok().filter(|v| !v.is_empty()))
+            .unwrap_or_else(|| "C".to_string())
+    } else {
+        lang.to_string()
+    };
+    // zh_CN.UTF-8 依次查找 zh_CN.po 和 zh.po
+    let name = lang.split(['.', '@']).next().unwrap_or("");
+    let language = name.split(['_', '-']).next().unwrap_or("");
+    let mut catalog: Vec<(String, String)> = match language {
+        "zh" => ZH
+            .iter()
+            .map(|(id, text)| (id.to_string(), text.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    };
+    if let Some(dir) = paths::config_dir().ok().map(|dir| dir.join("locale")) {
+        let file = [name, language]
+            .iter()
+            .filter(|name| !name.is_empty())
+            .map(|name| dir.join(format!("{}.po", name)))
+            .find(|path| path.exists());
+        if let Some(path) = file {
+            match fs::read_to_string(&path) {
+                Ok(content) => {
+                    for (id, text) in parse_po(&content) {
+                        catalog.retain(|(existing, _)| *existing != id);
+                        catalog.push((id, text));
+                    }
+                }
+                Err(e) => log::warning!("reading {}: {}", path.display(), e),
+            }
+        }
+    }
+    catalog.sort_by_key(|(id, _)| std::cmp::Reverse(id.chars().count()));
+    let _ = CATALOG.set(catalog);
+}
+
+fn is_word_char(c: Option<char>) -> bool {
+    c.is_some_and(|c| c.is_alphanumeric() || c == '_')
+}
+
+// 按整词替换为当前语言的译文，如 "Charging: 80%" -> "充电中: 80%"
+// 模块读取和解析始终使用英文，只在输出时翻译，图标和阈值判断不受语言影响
+pub fn localize(text: &str) -> String {
+    let Some(catalog) = CATALOG.get().filter(|catalog| !catalog.is_empty()) else {
+        return text.to_string();
+    };
+    translate(catalog, text)
+}
+
+fn translate(catalog: &[(String, String)], text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    'outer: while !rest.is_empty() {
+        let before = result.chars().next_back();
+        if !is_word_char(before) {
+            for (id, translation) in catalog {
+                if let Some(after) = rest.strip_prefix(id.as_str()) {
+                    if !is_word_char(after.chars().next()) {
+                        result.push_str(translation);
+                        rest = after;
+                        continue 'outer;
+                    }
+                }
+            }
+        }
+        let c = rest.chars().next().unwrap();
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_whole_words() {
+        let mut catalog: Vec<(String, String)> = ZH
+            .iter()
+            .map(|(id, text)| (id.to_string(), text.to_string()))
+            .collect();
+        catalog.sort_by_key(|(id, _)| std::cmp::Reverse(id.chars().count()));
+        assert_eq!(translate(&catalog, "Discharging: 80%"), "放电中: 80%");
+        assert_eq!(translate(&catalog, "VOL: MUTED"), "音量: 静音");
+        assert_eq!(
+            translate(&catalog, "Weather: 12°C Partly cloudy"),
+            "天气: 12°C 多云"
+        );
+        // 不替换单词的一部分
+        assert_eq!(translate(&catalog, "ACME online"), "ACME online");
+    }
+
+    #[test]
+    fn parses_po_files() {
+        let po = "# 德语\nmsgid \"\"\nmsgstr \"Content-Type: text/plain\\n\"\n\nmsgid \"Charging\"\nmsgstr \"Lädt\"\n\n#, fuzzy\nmsgid \"Full\"\nmsgstr \"\"\n";
+        assert_eq!(
+            parse_po(po),
+            vec![("Charging".to_string(), "Lädt".to_string())]
+        );
+    }
+}
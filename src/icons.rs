@@ -0,0 +1,62 @@
+use crate::battery::{Status, StatusStyle};
+use crate::render::Block;
+
+// Nerd Font 电池图标，依次为 0%~100%（每 10% 一级）
+const NERD_BATTERY: [&str; 11] = [
+    "\u{f008e}",
+    "\u{f007a}",
+    "\u{f007b}",
+    "\u{f007c}",
+    "\u{f007d}",
+    "\u{f007e}",
+    "\u{f007f}",
+    "\u{f0080}",
+    "\u{f0081}",
+    "\u{f0082}",
+    "\u{f0079}",
+];
+
+// 根据图标集和指标状态选择前缀图标
+// 电池图标随电量和充电状态变化，音量图标随音量和静音状态变化
+pub fn icon(set: &str, block: &Block) -> Option<String> {
+    let value = block.value.unwrap_or(0.0).clamp(0.0, 100.0);
+    // --status-style short/symbol 时状态为 "CHR" 或 "⚡"
+    let charging = block.text.contains("Charging") && !block.text.contains("Discharging")
+        || [StatusStyle::Short, StatusStyle::Symbol]
+            .iter()
+            .any(|&style| block.text.starts_with(Status::Charging.render(style)));
+    let muted = block.text.contains("MUTED");
+
+    let icon = match (set, block.name) {
+        ("nerd", "battery") if charging => "\u{f0084}",
+        ("nerd", "battery") => NERD_BATTERY[(value / 10.0).round() as usize],
+        ("nerd", "volume") if muted => "\u{f0581}",
+        ("nerd", "volume") if value < 34.0 => "\u{f057f}",
+        ("nerd", "volume") if value < 67.0 => "\u{f0580}",
+        ("nerd", "volume") => "\u{f057e}",
+        ("nerd", "backlight") => "\u{f00e0}",
+        ("nerd", "memory") => "\u{f035b}",
+
+        ("emoji", "battery") if charging => "🔌",
+        ("emoji", "battery") if value < 20.0 => "🪫",
+        ("emoji", "battery") => "🔋",
+        ("emoji", "volume") if muted => "🔇",
+        ("emoji", "volume") if value < 34.0 => "🔈",
+        ("emoji", "volume") if value < 67.0 => "🔉",
+        ("emoji", "volume") => "🔊",
+        ("emoji", "backlight") => "🔆",
+        ("emoji", "memory") => "🧠",
+
+        ("ascii", "battery") if charging => "[+]",
+        ("ascii", "battery") if value < 20.0 => "[ ]",
+        ("ascii", "battery") if value < 60.0 => "[=]",
+        ("ascii", "battery") => "[#]",
+        ("ascii", "volume") if muted => "<x",
+        ("ascii", "volume") => "<)",
+        ("ascii", "backlight") => "*",
+        ("ascii", "memory") => "#",
+
+        _ => return None,
+    };
+    Some(icon.to_string())
+}
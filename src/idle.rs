@@ -0,0 +1,111 @@
+use crate::dbus::{self, Bus};
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::timeutil::now_secs;
+
+// 空闲和锁屏状态来自 logind 会话的 IdleHint、IdleSinceHint 和 LockedHint 属性，
+// 由桌面环境或空闲守护进程设置（GNOME、KDE，sway 下为 swayidle 的 idlehint 选项）
+// auto 为调用者所在的会话，从 systemd 用户服务中运行时为该用户的图形会话
+const SESSION: &str = "/org/freedesktop/login1/session/auto";
+const INTERFACE: &str = "org.freedesktop.login1.Session";
+
+fn session_property(property: &str) -> Result<String, Error> {
+    dbus::get_property(
+        Bus::System,
+        "org.freedesktop.login1",
+        SESSION,
+        INTERFACE,
+        property,
+    )
+    .map_err(|e| Error::BackendUnavailable(format!("cannot read logind session: {}", e)))
+}
+
+fn parse_bool(value: &str) -> Result<bool, Error> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(Error::Parse(format!("unexpected boolean {}", value))),
+    }
+}
+
+// IdleSinceHint 为 CLOCK_REALTIME 的微秒时间戳，gdbus 输出为 "uint64 1700000000000000"
+fn parse_usec(value: &str) -> Result<i64, Error> {
+    value
+        .rsplit(' ')
+        .next()
+        .and_then(|usec| usec.parse::<i64>().ok())
+        .ok_or_else(|| Error::Parse(format!("unexpected timestamp {}", value)))
+}
+
+// 空闲秒数：会话未空闲时为 0
+fn idle_seconds(idle: bool, since_usec: i64, now: i64) -> i64 {
+    if !idle || since_usec == 0 {
+        return 0;
+    }
+    (now - since_usec / 1_000_000).max(0)
+}
+
+// 输出自上次输入以来的秒数，如 "IDLE: 312s"，活动时为 "IDLE: 0s"
+pub fn get_idle() -> Result<String, Error> {
+    let idle = parse_bool(&session_property("IdleHint")?)?;
+    let since = parse_usec(&session_property("IdleSinceHint")?)?;
+    Ok(format!("IDLE: {}s", idle_seconds(idle, since, now_secs())))
+}
+
+// 会话锁屏时输出 "LOCKED"，否则为空
+pub fn get_locked() -> Result<String, Error> {
+    Ok(if parse_bool(&session_property("LockedHint")?)? {
+        "LOCKED".to_string()
+    } else {
+        String::new()
+    })
+}
+
+pub struct Idle;
+
+impl Builtin for Idle {
+    const ID: &'static str = "idle";
+    const BLOCK: &'static str = "idle";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Idle
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, get_idle()?))
+    }
+}
+
+pub struct Locked;
+
+impl Builtin for Locked {
+    const ID: &'static str = "locked";
+    const BLOCK: &'static str = "locked";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Locked
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_locked()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_idle_time() {
+        assert_eq!(
+            parse_usec("uint64 1700000000000000").unwrap(),
+            1700000000000000
+        );
+        assert!(parse_usec("'soon'").is_err());
+        assert_eq!(idle_seconds(true, 1700000000000000, 1700000312), 312);
+        assert_eq!(idle_seconds(false, 1700000000000000, 1700000312), 0);
+        assert!(parse_bool("true").unwrap());
+        assert!(parse_bool("1").is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use std::fs;
+use std::io;
+
+// 从其他状态栏配置中识别出的模块
+struct Imported {
+    interval: Option<u64>,
+    // (模块名, 刷新间隔)
+    modules: Vec<(&'static str, Option<u64>)>,
+    // 无法转换的条目，以注释形式输出
+    skipped: Vec<String>,
+}
+
+impl Imported {
+    fn new() -> Self {
+        Imported {
+            interval: None,
+            modules: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, source: &str, module: Option<&'static str>, interval: Option<u64>) {
+        match module {
+            Some(module) => self.modules.push((module, interval)),
+            None => self.skipped.push(source.to_string()),
+        }
+    }
+
+    // 输出为本工具的 TOML 配置
+    fn to_toml(&self, from: &str) -> String {
+        let mut out = format!("# Imported from {} by `sys-montion import`\n", from);
+        if let Some(interval) = self.interval {
+            out += &format!("interval = {}\n", interval);
+        }
+        for (name, interval) in &self.modules {
+            out += &format!("\n[[modules]]\nname = \"{}\"\n", name);
+            if let Some(interval) = interval {
+                out += &format!("interval = {}\n", interval);
+            }
+        }
+        if !self.skipped.is_empty() {
+            out += "\n# Not supported, skipped:\n";
+            for entry in &self.skipped {
+                out += &format!("#   {}\n", entry);
+            }
+        }
+        out
+    }
+}
+
+// i3status：`order += "battery all"` 决定输出顺序，`general { interval = 5 }` 为刷新间隔
+fn from_i3status(content: &str) -> Imported {
+    let mut imported = Imported::new();
+    let mut in_general = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with("general") && line.ends_with('{') {
+            in_general = true;
+        } else if line.starts_with('}') {
+            in_general = false;
+        } else if in_general {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "interval" {
+                    imported.interval = value.trim().parse().ok();
+                }
+            }
+        } else if let Some(entry) = line.strip_prefix("order") {
+            let Some(entry) = entry.trim_start().strip_prefix("+=") else {
+                continue;
+            };
+            let entry = entry.trim().trim_matches('"');
+            let module = match entry.split_whitespace().next().unwrap_or("") {
+                "battery" => Some("battery"),
+                "volume" => Some("volume-level"),
+                "memory" => Some("memory"),
+                _ => None,
+            };
+            imported.push(entry, module, None);
+        }
+    }
+    imported
+}
+
+// i3status-rust：TOML 中的 [[block]] 列表，每个 block 可有自己的 interval
+fn from_i3status_rust(content: &str) -> Result<Imported, io::Error> {
+    let table: toml::Table = toml::from_str(content)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.message().to_string()))?;
+    let mut imported = Imported::new();
+    let blocks = table.get("block").and_then(|b| b.as_array());
+    for block in blocks.into_iter().flatten() {
+        let name = block.get("block").and_then(|b| b.as_str()).unwrap_or("");
+        let interval = block
+            .get("interval")
+            .and_then(|i| i.as_integer())
+            .and_then(|i| u64::try_from(i).ok());
+        let module = match name {
+            "battery" => Some("battery"),
+            "sound" => Some("volume-level"),
+            "backlight" => Some("backlight"),
+            "memory" => Some("memory"),
+            _ => None,
+        };
+        imported.push(name, module, interval);
+    }
+    Ok(imported)
+}
+
+// slstatus：config.h 中的 `{ battery_perc, "%s%%", "BAT0" },` 数组和以毫秒为单位的 interval
+fn from_slstatus(content: &str) -> Imported {
+    let mut imported = Imported::new();
+    for line in content.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("const unsigned int interval") {
+            let ms = rest
+                .trim_start_matches([' ', '='])
+                .trim_end_matches(';')
+                .trim();
+            imported.interval = ms.parse::<u64>().ok().map(|ms| (ms / 1000).max(1));
+            continue;
+        }
+        let Some(entry) = line.strip_prefix('{') else {
+            continue;
+        };
+        let function = entry.split(',').next().unwrap_or("").trim();
+        if function.is_empty()
+            || !function
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            continue;
+        }
+        let module = match function {
+            "battery_perc" => Some("battery-capacity"),
+            "battery_state" => Some("battery-state"),
+            "battery_remaining" => Some("battery-time"),
+            "ram_used" | "ram_perc" | "ram_free" | "ram_total" => Some("memory"),
+            "vol_perc" => Some("volume-level"),
+            _ => None,
+        };
+        imported.push(function, module, None);
+    }
+    imported
+}
+
+// 读取其他状态栏的配置文件并转换为本工具的配置
+pub fn import(from: &str, path: &str) -> Result<String, io::Error> {
+    let content = fs::read_to_string(path)?;
+    let imported = match from {
+        "i3status" => from_i3status(&content),
+        "i3status-rust" => from_i3status_rust(&content)?,
+        "slstatus" => from_slstatus(&content),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported source '{}'", from),
+            ))
+        }
+    };
+    Ok(imported.to_toml(from))
+}
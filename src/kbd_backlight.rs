@@ -0,0 +1,129 @@
+use crate::dbus::{self, Bus};
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{generate, read_file, sysfs};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const LEDS_PATH: &str = "/sys/class/leds";
+
+// 查找键盘背光设备，如 tpacpi::kbd_backlight、asus::kbd_backlight
+pub fn find_device() -> Result<String, Error> {
+    let mut devices: Vec<String> = sysfs::read_dir(LEDS_PATH)
+        .map_err(|_| Error::MissingDevice("no LED class devices".to_string()))?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.contains("kbd_backlight"))
+        .collect();
+    devices.sort();
+    devices
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::MissingDevice("no keyboard backlight found".to_string()))
+}
+
+fn device_path(device: &str) -> PathBuf {
+    Path::new(LEDS_PATH).join(device)
+}
+
+// 读取当前亮度和最大亮度（原始级数，多数键盘只有 2~3 级）
+fn read_levels(device: &str) -> Result<(i64, i64), Error> {
+    let read = |file: &str| {
+        let path = device_path(device).join(file);
+        read_file(&path)?
+            .parse::<i64>()
+            .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))
+    };
+    let max = read("max_brightness")?;
+    if max <= 0 {
+        return Err(Error::Parse(format!(
+            "{}: must be positive",
+            device_path(device).join("max_brightness").display()
+        )));
+    }
+    Ok((read("brightness")?, max))
+}
+
+pub fn get_kbd_backlight() -> Result<String, Error> {
+    let (brightness, max) = read_levels(&find_device()?)?;
+    Ok(format!("KBD: {}%", brightness * 100 / max))
+}
+
+// 设置亮度：直接写 sysfs，没有写权限时改用 logind 的 SetBrightness（活动会话的普通用户即可调用）
+fn write_level(device: &str, level: i64) -> Result<(), Error> {
+    let path = device_path(device).join("brightness");
+    match fs::write(sysfs::path(&path), level.to_string()) {
+        Ok(()) => return Ok(()),
+        Err(e) if e.kind() != io::ErrorKind::PermissionDenied => return Err(Error::from(e)),
+        Err(_) => {}
+    }
+    dbus::call(
+        Bus::System,
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1/session/auto",
+        "org.freedesktop.login1.Session.SetBrightness",
+        &["leds", device, &level.to_string()],
+    )
+    .map_err(|e| {
+        Error::PermissionDenied(format!(
+            "cannot write {}: permission denied, and logind SetBrightness failed: {} ({})",
+            path.display(),
+            e,
+            generate::UDEV_HINT
+        ))
+    })?;
+    Ok(())
+}
+
+// 调整键盘背光：up/down 按一级调整，set 按百分比设置
+pub fn control(action: &str, percent: Option<i64>) -> Result<String, Error> {
+    let device = find_device()?;
+    let (brightness, max) = read_levels(&device)?;
+    let level = match (action, percent) {
+        ("up", _) => brightness + 1,
+        ("down", _) => brightness - 1,
+        ("set", Some(percent)) => (percent * max + 50) / 100,
+        _ => {
+            return Err(Error::Parse(format!(
+                "invalid kbd-backlight action '{}'",
+                action
+            )))
+        }
+    }
+    .clamp(0, max);
+    write_level(&device, level)?;
+    Ok(format!("KBD: {}%", level * 100 / max))
+}
+
+pub struct KbdBacklight;
+
+impl Builtin for KbdBacklight {
+    const ID: &'static str = "kbd-backlight";
+    const BLOCK: &'static str = "kbd_backlight";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        KbdBacklight
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::percent(Self::BLOCK, get_kbd_backlight()?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        find_device().err().map(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_keyboard_backlight() {
+        sysfs::use_fixtures();
+        assert_eq!(find_device().unwrap(), "tpacpi::kbd_backlight");
+        assert_eq!(get_kbd_backlight().unwrap(), "KBD: 50%");
+    }
+}
@@ -0,0 +1,158 @@
+use crate::dbus::{self, Bus};
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{read_file, sysfs};
+use std::path::Path;
+
+const LID_PATH: &str = "/proc/acpi/button/lid";
+const DRM_PATH: &str = "/sys/class/drm";
+
+// ACPI dock 驱动为每个扩展坞注册 dock.N，docked 为 1 时已接入
+const DOCK_PATH: &str = "/sys/devices/platform";
+
+// 笔记本内置屏幕的接口类型，其余接口接有显示器时视为外接
+const INTERNAL: [&str; 3] = ["eDP", "LVDS", "DSI"];
+
+// 解析 "state:      open" 中的状态
+fn parse_state(text: &str) -> Option<&str> {
+    let state = text.strip_prefix("state:")?.trim();
+    matches!(state, "open" | "closed").then_some(state)
+}
+
+// 输出笔记本盖子状态 "LID: open" 或 "LID: closed"
+// 读取 /proc/acpi/button/lid/*/state，没有 ACPI 按钮时退回 logind 的 LidClosed 属性
+pub fn get_lid() -> Result<String, Error> {
+    let from_acpi = sysfs::read_dir(LID_PATH).ok().and_then(|entries| {
+        entries.flatten().find_map(|entry| {
+            let text = read_file(Path::new(LID_PATH).join(entry.file_name()).join("state")).ok()?;
+            parse_state(&text).map(str::to_string)
+        })
+    });
+    let state = match from_acpi {
+        Some(state) => state,
+        None => {
+            let value = dbus::get_property(
+                Bus::System,
+                "org.freedesktop.login1",
+                "/org/freedesktop/login1",
+                "org.freedesktop.login1.Manager",
+                "LidClosed",
+            )
+            .map_err(|_| Error::MissingDevice("no laptop lid found".to_string()))?;
+            match value.as_str() {
+                "true" => "closed".to_string(),
+                "false" => "open".to_string(),
+                _ => {
+                    return Err(Error::Parse(format!(
+                        "unexpected LidClosed value {}",
+                        value
+                    )))
+                }
+            }
+        }
+    };
+    Ok(format!("LID: {}", state))
+}
+
+// 接有显示器的外接输出接口，如 card1-HDMI-A-1，按名称排序
+pub fn external_connectors() -> Vec<String> {
+    let mut connectors: Vec<String> = sysfs::read_dir(DRM_PATH)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| {
+                    let Some((_, output)) = name.split_once('-') else {
+                        return false;
+                    };
+                    !INTERNAL.iter().any(|internal| output.starts_with(internal))
+                        && read_file(Path::new(DRM_PATH).join(name).join("status"))
+                            .is_ok_and(|status| status == "connected")
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    connectors.sort();
+    connectors
+}
+
+// 去掉显卡前缀的接口名，如 card1-HDMI-A-1 -> HDMI-A-1
+pub fn output_name(connector: &str) -> &str {
+    connector
+        .split_once('-')
+        .map_or(connector, |(_, output)| output)
+}
+
+fn acpi_docked() -> bool {
+    sysfs::read_dir(DOCK_PATH).is_ok_and(|entries| {
+        entries.flatten().any(|entry| {
+            entry.file_name().to_string_lossy().starts_with("dock.")
+                && read_file(Path::new(DOCK_PATH).join(entry.file_name()).join("docked"))
+                    .is_ok_and(|docked| docked == "1")
+        })
+    })
+}
+
+// 接入扩展坞或外接显示器时输出 "DOCK: HDMI-A-1 DP-2"（没有外接显示器的扩展坞只输出 "DOCK"），否则为空
+pub fn get_docked() -> Result<String, Error> {
+    if !sysfs::exists(DRM_PATH) {
+        return Err(Error::MissingDevice("no DRM devices".to_string()));
+    }
+    let outputs: Vec<String> = external_connectors()
+        .iter()
+        .map(|connector| output_name(connector).to_string())
+        .collect();
+    if !outputs.is_empty() {
+        return Ok(format!("DOCK: {}", outputs.join(" ")));
+    }
+    Ok(if acpi_docked() {
+        "DOCK".to_string()
+    } else {
+        String::new()
+    })
+}
+
+pub struct Lid;
+
+impl Builtin for Lid {
+    const ID: &'static str = "lid";
+    const BLOCK: &'static str = "lid";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Lid
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_lid()?))
+    }
+}
+
+pub struct Docked;
+
+impl Builtin for Docked {
+    const ID: &'static str = "docked";
+    const BLOCK: &'static str = "dock";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Docked
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_docked()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_lid_and_dock() {
+        sysfs::use_fixtures();
+        assert_eq!(get_lid().unwrap(), "LID: open");
+        assert_eq!(parse_state("state:      closed"), Some("closed"));
+        assert_eq!(parse_state("open"), None);
+        assert_eq!(get_docked().unwrap(), "DOCK: HDMI-A-1");
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{audio, battery, diskio, hwmon, net, read_file, sysfs};
+
+// 列出目录下的设备名，如 /sys/class/backlight 下的 amdgpu_bl1
+fn class_devices(dir: &str) -> Vec<String> {
+    let Ok(entries) = sysfs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+fn network_interfaces() -> Vec<String> {
+    class_devices("/sys/class/net")
+        .into_iter()
+        .map(|iface| {
+            let state = read_file(net::iface_path(&iface).join("operstate"))
+                .unwrap_or_else(|_| "unknown".to_string());
+            format!("{:<16} {}", iface, state)
+        })
+        .collect()
+}
+
+fn hwmon_sensors() -> Vec<String> {
+    hwmon::list_chips()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|chip| {
+            format!(
+                "{:<16} {:<12} {}",
+                chip.id,
+                chip.name,
+                chip.sensors.join(" ")
+            )
+        })
+        .collect()
+}
+
+// 列出检测到的设备及其标识，用于填写 --battery-device、--ip、--diskio 等参数
+pub fn report() -> String {
+    let sections = [
+        (
+            "Batteries (--battery-device)",
+            battery::list_batteries().unwrap_or_default(),
+        ),
+        ("Backlights", class_devices("/sys/class/backlight")),
+        ("Hwmon sensors", hwmon_sensors()),
+        (
+            "Network interfaces (--ip, --net-rate, --flaps)",
+            network_interfaces(),
+        ),
+        ("Audio devices (--audio-device)", audio::list_devices()),
+        ("Disks (--diskio)", diskio::list_disks().unwrap_or_default()),
+    ];
+    let mut lines = Vec::new();
+    for (title, devices) in sections {
+        lines.push(format!("{}:", title));
+        if devices.is_empty() {
+            lines.push("  none found".to_string());
+        }
+        lines.extend(devices.iter().map(|device| format!("  {}", device)));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_devices() {
+        sysfs::use_fixtures();
+        let report = report();
+        assert!(report.contains("Batteries (--battery-device):\n  BAT0\n  BAT1\n"));
+        assert!(report.contains("Backlights:\n  amdgpu_bl1\n"));
+        assert!(report.contains("\n  hwmon0           thinkpad     temp1 fan1 fan2\n"));
+        assert!(report.contains("\n  eth0             up\n"));
+        assert!(report.ends_with("Disks (--diskio):\n  nvme0n1\n  sda"));
+    }
+}
@@ -0,0 +1,141 @@
+use std::env;
+use std::process::Command;
+use std::sync::OnceLock;
+
+// 数字格式：小数点和千位分隔符
+struct NumberFormat {
+    decimal: char,
+    group: Option<char>,
+}
+
+static NUMBER_FORMAT: OnceLock<NumberFormat> = OnceLock::new();
+
+// 根据 locale 名称（如 de_DE.UTF-8）选择数字格式，未知 locale 按 C 处理
+fn number_format(locale: &str) -> NumberFormat {
+    let name = locale.split(['.', '@']).next().unwrap_or("");
+    let language = name.split(['_', '-']).next().unwrap_or("");
+    let (decimal, group) = match (name, language) {
+        ("de_CH", _) | ("it_CH", _) => ('.', Some('\'')),
+        (_, "en" | "zh" | "ja" | "ko" | "th" | "he") => ('.', Some(',')),
+        (_, "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el") => (',', Some('.')),
+        (_, "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "uk" | "hu") => {
+            (',', Some('\u{202f}'))
+        }
+        _ => ('.', None),
+    };
+    NumberFormat { decimal, group }
+}
+
+// 初始化数字格式，`auto` 表示读取 LC_ALL / LC_NUMERIC / LANG
+pub fn init(locale: &str) {
+    let locale = if locale == "auto" {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .find_map(|key| env::var(key).ok().filter(|v| !v.is_empty()))
+            .unwrap_or_else(|| "C".to_string())
+    } else {
+        locale.to_string()
+    };
+    let _ = NUMBER_FORMAT.set(number_format(&locale));
+}
+
+// 需要解析输出的外部命令（amixer、pactl、gdbus 等）固定在 C locale 下运行，
+// 输出中的关键字和数字格式不随用户语言变化
+pub fn command(program: &str) -> Command {
+    let mut command = Command::new(program);
+    command.env("LC_ALL", "C");
+    command
+}
+
+// 数值取整方式：round 四舍五入，floor 向下取整，ceil 向上取整
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Rounding {
+    Round,
+    Floor,
+    Ceil,
+}
+
+impl Rounding {
+    pub const NAMES: [&'static str; 3] = ["round", "floor", "ceil"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "round" => Some(Rounding::Round),
+            "floor" => Some(Rounding::Floor),
+            "ceil" => Some(Rounding::Ceil),
+            _ => None,
+        }
+    }
+
+    // 按保留的小数位数取整，如 floor 保留 1 位时 7.96 -> 7.9
+    pub fn apply(self, value: f64, decimals: usize) -> f64 {
+        let scale = 10f64.powi(decimals as i32);
+        // 消除 2.3 * 10 = 22.999... 之类的浮点误差，避免 floor 少取一位
+        let scaled = (value * scale * 1e9).round() / 1e9;
+        let rounded = match self {
+            Rounding::Round => scaled.round(),
+            Rounding::Floor => scaled.floor(),
+            Rounding::Ceil => scaled.ceil(),
+        };
+        rounded / scale
+    }
+}
+
+fn current() -> &'static NumberFormat {
+    NUMBER_FORMAT.get_or_init(|| number_format("C"))
+}
+
+fn group_digits(digits: &str, group: Option<char>) -> String {
+    let Some(group) = group else {
+        return digits.to_string();
+    };
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(group);
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+// 按当前 locale 格式化整数，如 de_DE 下 15320 -> "15.320"
+pub fn format_int(value: i64) -> String {
+    let digits = group_digits(&value.unsigned_abs().to_string(), current().group);
+    if value < 0 {
+        format!("-{}", digits)
+    } else {
+        digits
+    }
+}
+
+// 按当前 locale 格式化小数，如 de_DE 下 12.34 保留 1 位 -> "12,3"
+pub fn format_number(value: f64, decimals: usize) -> String {
+    let format = current();
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (int_part, frac_part) = formatted.split_once('.').unwrap_or((&formatted, ""));
+    let mut result = String::new();
+    if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        result.push('-');
+    }
+    result.push_str(&group_digits(int_part, format.group));
+    if !frac_part.is_empty() {
+        result.push(format.decimal);
+        result.push_str(frac_part);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_values() {
+        assert_eq!(Rounding::Floor.apply(7.96, 1), 7.9);
+        assert_eq!(Rounding::Ceil.apply(7.91, 1), 8.0);
+        assert_eq!(Rounding::Round.apply(72.5, 0), 73.0);
+        assert_eq!(Rounding::Floor.apply(2.3, 1), 2.3);
+        assert_eq!(Rounding::Ceil.apply(99.2, 0), 100.0);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::error::Error;
+use crate::event_loop::EventLoop;
+use crate::module::{Builtin, EventInterest, MetricValue};
+use crate::render::Block;
+use crate::watch::Wake;
+use crate::{log, read_file, sysfs};
+use rustix::event::epoll::EventFlags;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+const LEDS_PATH: &str = "/sys/class/leds";
+
+// 每个键盘的锁定键指示灯为 inputN::capslock 等 LED 设备，按显示顺序排列
+const LOCKS: [(&str, &str); 3] = [
+    ("capslock", "CAPS"),
+    ("numlock", "NUM"),
+    ("scrolllock", "SCRL"),
+];
+
+// input_event 中的 EV_LED 事件类型
+const EV_LED: u16 = 0x11;
+
+// input_event 为 struct timeval 加 type（u16）、code（u16）和 value（i32），64 位系统上为 24 字节
+const EVENT_SIZE: usize = std::mem::size_of::<libc::input_event>();
+
+// 输出处于开启状态的锁定键，如 "CAPS NUM"，都未开启时为空
+// 接了多个键盘时任一键盘的指示灯亮即视为开启
+pub fn get_locks() -> Result<String, Error> {
+    let devices: Vec<String> = sysfs::read_dir(LEDS_PATH)
+        .map_err(|_| Error::MissingDevice("no LED class devices".to_string()))?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    let mut found = false;
+    let mut active = Vec::new();
+    for (suffix, label) in LOCKS {
+        let leds: Vec<&String> = devices
+            .iter()
+            .filter(|name| name.ends_with(&format!("::{}", suffix)))
+            .collect();
+        found |= !leds.is_empty();
+        if leds.iter().any(|name| {
+            read_file(Path::new(LEDS_PATH).join(name).join("brightness"))
+                .is_ok_and(|brightness| brightness != "0")
+        }) {
+            active.push(label);
+        }
+    }
+    if !found {
+        return Err(Error::MissingDevice(
+            "no keyboard lock indicators found".to_string(),
+        ));
+    }
+    Ok(active.join(" "))
+}
+
+// 监听带指示灯的键盘的 evdev 事件，锁定键切换时内核发出 EV_LED 事件
+// LED 的 sysfs 属性不支持 poll，也不产生 uevent；读取 /dev/input 通常需要 input 组权限
+pub fn register(events: &mut EventLoop) -> Result<(), io::Error> {
+    let mut registered = 0;
+    for entry in sysfs::read_dir("/sys/class/input")?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with("event") {
+            continue;
+        }
+        // capabilities/led 为十六进制位图，0 表示没有指示灯
+        let leds = read_file(
+            Path::new("/sys/class/input")
+                .join(&name)
+                .join("device/capabilities/led"),
+        )
+        .unwrap_or_default();
+        if leds.is_empty() || leds == "0" {
+            continue;
+        }
+        let file = match File::open(format!("/dev/input/{}", name)) {
+            Ok(file) => file,
+            Err(e) => {
+                log::debug!("cannot open /dev/input/{}: {}", name, e);
+                continue;
+            }
+        };
+        let mut buf = [0u8; EVENT_SIZE * 16];
+        events.add(file, EventFlags::IN, move |file, woken| {
+            let len = file.read(&mut buf)?;
+            if len == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            if buf[..len].chunks_exact(EVENT_SIZE).any(|event| {
+                let kind = &event[EVENT_SIZE - 8..];
+                u16::from_ne_bytes([kind[0], kind[1]]) == EV_LED
+            }) {
+                woken.push(Wake::All);
+            }
+            Ok(())
+        })?;
+        registered += 1;
+    }
+    if registered == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "no readable keyboard in /dev/input (add your user to the input group)",
+        ));
+    }
+    Ok(())
+}
+
+pub struct Locks;
+
+impl Builtin for Locks {
+    const ID: &'static str = "locks";
+    const BLOCK: &'static str = "locks";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Locks
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_locks()?))
+    }
+
+    fn interests(&self) -> EventInterest {
+        EventInterest::LOCKS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_active_locks() {
+        sysfs::use_fixtures();
+        // 固件树中 Caps Lock 关闭、Num Lock 开启
+        assert_eq!(get_locks().unwrap(), "NUM");
+    }
+}
@@ -0,0 +1,120 @@
+use crate::timeutil;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+// 日志级别，数值越大越详细
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    // syslog 优先级，journald 按行首的 <N> 识别
+    fn priority(self) -> u8 {
+        match self {
+            Level::Error => 3,
+            Level::Warn => 4,
+            Level::Info => 6,
+            Level::Debug => 7,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+        }
+    }
+}
+
+// 默认只输出错误和警告，-q 只输出错误，-v 增加 info，-vv 增加 debug
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Warn as u8);
+// 指定 --log-file 时写入文件（追加），并记录本地时区偏移用于时间戳
+static FILE: OnceLock<(Mutex<File>, i64)> = OnceLock::new();
+// 由 systemd 启动时 stderr 接入 journald，行首写优先级而不是程序名
+static JOURNAL: AtomicBool = AtomicBool::new(false);
+
+pub fn init(verbosity: u8, quiet: bool, file: Option<&str>) -> Result<(), io::Error> {
+    let level = match (quiet, verbosity) {
+        (true, _) => Level::Error,
+        (false, 0) => Level::Warn,
+        (false, 1) => Level::Info,
+        (false, _) => Level::Debug,
+    };
+    LEVEL.store(level as u8, Ordering::Relaxed);
+    if let Some(path) = file {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let _ = FILE.set((Mutex::new(file), timeutil::local_utc_offset()));
+    }
+    Ok(())
+}
+
+pub fn use_journal() {
+    JOURNAL.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+// 每条日志一行：stderr 上为 "sys-montion: warn: ..."，journald 中为 "<4>..."，日志文件中另带本地时间
+pub fn write(level: Level, args: fmt::Arguments) {
+    if !enabled(level) {
+        return;
+    }
+    match FILE.get() {
+        Some((file, offset)) => {
+            let local = timeutil::now_secs() + offset;
+            let (year, month, day) = timeutil::civil_from_days(local.div_euclid(86400));
+            let seconds = local.rem_euclid(86400);
+            let _ = writeln!(
+                file.lock().unwrap(),
+                "{:04}-{:02}-{:02} {:02}:{:02}:{:02} {}: {}",
+                year,
+                month,
+                day,
+                seconds / 3600,
+                seconds / 60 % 60,
+                seconds % 60,
+                level.name(),
+                args
+            );
+        }
+        None if JOURNAL.load(Ordering::Relaxed) => eprintln!("<{}>{}", level.priority(), args),
+        None => eprintln!("sys-montion: {}: {}", level.name(), args),
+    }
+}
+
+macro_rules! error {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Error, format_args!($($arg)*))
+    };
+}
+
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Warn, format_args!($($arg)*))
+    };
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Info, format_args!($($arg)*))
+    };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Debug, format_args!($($arg)*))
+    };
+}
+
+pub(crate) use {debug, error, info, warning};
@@ -1,21 +1,137 @@
-use clap;
+mod adaptive;
+mod args;
+mod audio;
+mod backlight;
+mod battery;
+mod bluetooth;
+mod caffeine;
+mod calendar;
+mod capabilities;
+mod cgroup;
+mod click;
+mod clipboard;
+mod config;
+mod containers;
+mod control;
+mod count;
+mod counters;
+mod cpu;
+mod daylight;
+mod dbus;
+mod ddc;
+mod diskio;
+mod dnd;
+mod doctor;
+mod error;
+mod event_loop;
+mod fifo;
+mod gamemode;
+mod generate;
+mod gpu;
+mod history;
+mod http;
+mod hwmon;
+mod i18n;
+mod icons;
+mod idle;
+mod import;
+mod kbd_backlight;
+mod lid;
+mod list;
+mod locale;
+mod locks;
+mod log;
+mod media;
+mod memory;
+mod metrics;
+mod module;
+mod mounts;
+mod mqtt;
+mod net;
+mod nightlight;
+mod notify;
+mod nvme;
+mod paths;
+mod plugin;
+mod power_profile;
+mod pressure;
+mod privacy;
+mod prometheus;
+mod query;
+mod recorder;
+mod render;
+mod script;
+mod service;
+mod signals;
+mod sleep;
+mod speak;
+mod sysfs;
+mod systemd;
+mod termux;
+mod timer;
+mod timeutil;
+mod top;
+mod uevent;
+mod updates;
+mod upower;
+mod uptime;
+mod usage;
+mod visibility;
+mod watch;
+mod weather;
+mod wifi;
+mod wm;
+mod xsetroot;
+
+use error::Error;
+use module::{MetricValue, Module};
+use render::{Block, Level, Renderer, Thresholds};
+use std::env;
 use std::fs;
 use std::io;
-use std::process::Command;
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::time::Duration;
+
+// 通用读取文件函数，/sys 和 /proc 路径经过 sysfs 根目录映射
+fn read_file(path: impl AsRef<Path>) -> Result<String, io::Error> {
+    fs::read_to_string(sysfs::path(path)).map(|s| s.trim().to_string())
+}
 
-// 通用读取文件函数
-fn read_file(path: &str) -> Result<String, io::Error> {
-    fs::read_to_string(path).map(|s| s.trim().to_string())
+// 格式化时长，如 "2h 14m"，超过一天时为 "3d 4h"
+fn format_duration(seconds: i64) -> String {
+    let minutes = seconds.max(0) / 60;
+    if minutes >= 24 * 60 {
+        format!("{}d {}h", minutes / (24 * 60), minutes / 60 % 24)
+    } else {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    }
 }
 
-// 读取电池电量
-fn get_battery_capacity(battery_path: &str) -> Result<String, io::Error> {
-    read_file(&(battery_path.to_string() + "capacity"))
+// 格式化字节数，如 "12.3G"
+fn format_size(bytes: u64) -> String {
+    format_size_with(bytes, 1, locale::Rounding::Round)
 }
 
-// 读取充电状态
-fn get_battery_status(battery_path: &str) -> Result<String, io::Error> {
-    read_file(&(battery_path.to_string() + "status"))
+// 同 format_size，指定小数位数和取整方式（--memory-decimals、--memory-rounding）
+fn format_size_with(bytes: u64, decimals: usize, rounding: locale::Rounding) -> String {
+    let units = ["B", "K", "M", "G", "T", "P"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}{}", bytes, units[0])
+    } else {
+        format!(
+            "{}{}",
+            locale::format_number(rounding.apply(size, decimals), decimals),
+            units[unit]
+        )
+    }
 }
 
 // 打印帮助信息
@@ -25,180 +141,835 @@ fn print_help() {
         --battery        Output battery status and capacity.
         --battery-state  Output battery status only.
         --battery-level  Output battery capacity only.
+        --battery-time   Output estimated time to empty or full.
+        --battery-power  Output battery power draw in watts.
+        --battery-health Output battery health and wear level.
+        --battery-info   Output battery manufacturer, model, technology, cycles and serial.
+        --battery-device Battery to read: all (default), BAT0, BAT1...
+        --battery-backend Battery data source: auto (default), sysfs, upower or termux.
+        --battery-rounding Compute battery percent from energy and round it: floor, round or ceil.
+        --battery-smoothing Smoothing factor for battery power and time left in watch mode (default 0.2, 1 = off).
+        --status-style   Battery status as a word (full, default), CHR-style abbreviation (short) or symbol.
+        --charge-threshold Output battery charge start/stop thresholds.
+        --peripherals    Output battery levels of wireless mice, keyboards and headsets (UPower).
+        --ac             Output whether external power is connected, with wattage if known.
+        --charger-info   Output the charger's negotiated wattage and protocol (e.g. 65W PD).
         --volume-level   Output volume level.
-        --backlight      Output backlight"
-    );
-}
+        --show-device    With --volume-level, also show the output device (e.g. Headphones, HDMI).
+        --audio-backend  Volume backend: auto (default, pactl then amixer), pulse, alsa.
+        --volume-fallback unknown (default) or hide the volume when it cannot be read.
+        --mic            Output microphone mute state and level.
+        --audio-device   Volume source: a PulseAudio sink or ALSA CARD/ELEMENT.
+        --list-audio-devices List names accepted by --audio-device.
+        --backlight      Output backlight
+        --backlight-format Backlight output: percent (default), raw, both.
+        --kbd-backlight  Output keyboard backlight.
+        --memory-format  Memory output: used (default), free, available, percent.
+        --memory-detail  Add zram, zswap, dirty or cache details to --memory (comma-separated).
+        --memory-detail-format compact (default) or verbose labels for --memory-detail.
+        --unit           Memory unit: kib, mib, gib, auto (default).
+        --memory-decimals Decimal places for G and auto memory sizes (default 1).
+        --memory-rounding Rounding for memory sizes: round (default), floor or ceil.
+        --daylight       Output time until next sunrise/sunset.
+        --location       Location for --daylight as LAT,LON
+        --weather LOC    Output temperature and conditions at LOC (a place name or LAT,LON).
+        --weather-provider Weather source: open-meteo (default) or wttr.
+        --weather-ttl    Seconds to reuse a fetched weather report (default: 900).
+        --next-event     Output the next upcoming calendar event.
+        --calendar       ICS file or calendar directory for --next-event
+        --watch          Keep running and refresh every N seconds.
+        --signal         In watch mode, refresh on SIGRTMIN+N (SIGUSR1 refreshes all).
+        --min-interval   In watch mode, print at most one line every MS milliseconds.
+        --config         Config file (default ~/.config/sys-montion/config.toml).
+        --notify         In watch mode, notify when battery is low.
+        --battery-warn   Battery warning threshold (default 20).
+        --battery-critical Battery critical threshold (default 10).
+        --battery-action CMD In watch mode, run CMD once when discharging below --battery-action-level.
+        --battery-action-level Battery level for --battery-action (default 5).
+        --battery-action-dry-run Only log what --battery-action would run.
+        --memory-low     In watch mode, trigger when MemAvailable < N MiB.
+        --memory-pressure In watch mode, trigger when memory PSI avg10 > N%.
+        --memory-hook    Command to run when a memory rule triggers.
+        --fan-alert      In watch mode, notify about stuck or stalled fans.
+        --adaptive       In watch mode, poll slower while battery is low or PSI is high.
+        --adaptive-pressure Pressure (PSI avg10 %) that slows polling (default 20).
+        --fan-max-minutes Alert after fans run at max for N minutes (default 10).
+        --fan-curve C:RPM,... Mark --temp as warning/critical when fans spin slower than expected.
+        --fan-stall-temp Alert when fans stop above this °C (default 70).
+        --track-usage    In watch mode, record screen-on, on-battery time and boots.
+        --battery-history In watch mode, record battery level and power once a minute.
+        --screen-time    Output today's screen-on time.
+        --usage-report   Output a weekly usage summary.
+        --format         Output format: plain (default), i3bar, waybar, csv, tsv, env.
+        --raw            Print only numeric values, without labels or units.
+        --align          Pad numbers and fields to a stable width while watching.
+        --api-version N  Version of the JSON fields in i3bar, waybar and --format-hook output (default: latest).
+        --speak-format   Output full English sentences for espeak, festival or screen readers.
+        --format-hook    Command that turns the metrics (JSON lines on stdin) into the status line.
+        --warn-below     Mark output as warning below this value.
+        --critical-below Mark output as critical below this value.
+        --warn-above     Mark output as warning above this value.
+        --critical-above Mark output as critical above this value.
+                         MODULE=N sets a threshold for one module (e.g. battery=20, repeatable).
+        --check          Exit 0/1/2 for normal/warning/critical by the thresholds, 3 on errors.
+        --color          Colorize plain output: none (default), ansi, pango.
+        --prompt-segment MODULE Print one module for a shell prompt, without newline.
+        --prompt-shell   Escape --color ansi for zsh or bash prompts (default none).
+        --format-preset  Plain output syntax for: plain (default), polybar, dzen2, yambar, tmux.
+        --max-width      Shorten the longest modules so plain output fits N characters;
+                         MODULE=N limits one module (repeatable).
+        --scroll         In watch mode, scroll modules longer than MODULE=N instead of truncating.
+        --tmux           Compact tmux status-right line (tmux preset, max width 80).
+        --color-normal   Color used below all thresholds (#RRGGBB).
+        --color-warning  Color used at warning level (default #FFFF00).
+        --color-critical Color used at critical level (default #FF0000).
+        --icons          Icon set: none (default), nerd, emoji, ascii.
+        --icon           Use this glyph instead of the icon set's choice.
+        --skip-missing   Omit modules whose hardware is missing from combined output.
+        --verbose, -v    Explain module errors; -v/-vv log more in watch mode.
+        --quiet, -q      Only log errors.
+        --log-file       Append log messages to this file instead of stderr.
+        --sysfs-root     Read /sys and /proc under this directory (also SYS_MONTION_SYSFS_ROOT).
+        --locale         Number formatting locale: C (default), auto, de_DE...
+        --lang           Output language for labels: C (default, English), auto, zh_CN...
+        --now            Pretend the current time is this Unix time or UTC date.
+        --xsetroot       In watch mode, set the X11 root window name (dwm).
+        --output fifo:PATH In watch mode, also write each line to a named pipe.
+        --log-metrics    In watch mode, append timestamped samples to this file.
+        --log-metrics-format jsonl (default) or csv for --log-metrics.
+        --log-metrics-max-size Rotate the --log-metrics file at N MiB (default 10).
+        --stdin          Answer one query per line from stdin, e.g. battery or memory json.
+        --osd            Also show volume/backlight as an OSD notification.
+        --systemd        In watch mode, use sd_notify, the watchdog and journald priorities.
+        --dbus           In watch mode, expose values as the org.sysmontion.Monitor service.
+        --sink-hook      In watch mode, command to run when the default sink changes.
+        --mounts         Output mounted removable drives and their free space.
+        --diskio [DEV]   Output disk read/write throughput of DEV (default: all disks).
+        --disk-temp [DEV] Output NVMe temperature and SMART health of DEV (default: all).
+        --temp [SENSOR]  Output temperature of hwmon SENSOR (chip:label), the CPU cores (cpu),
+                         or the hottest (max); repeatable.
+        --cpu-temp-agg   For --temp cpu: max (default), avg or per-core.
+        --gpu-temp [SENSOR] Output GPU temperature: edge (default), junction, mem or all.
+        --gpu-device     GPU for --gpu-temp, e.g. card1 (default: the first with a sensor).
+        --count DIR [GLOB] Output the number of files in DIR matching GLOB (e.g. Maildir new/).
+        --script         Output the first line printed by a shell command.
+        --plugin PATH    Output the text of a module loaded from a shared library; repeatable.
+        --throttle       Output CPU thermal throttle events since boot.
+        --cpu-freq       Output average CPU frequency and governor.
+        --cpu            Output overall CPU usage.
+        --pressure cpu|memory|io Output the PSI stall percentage (avg10) of a resource.
+        --cgroup PATH    Output memory and CPU usage of a cgroup v2 group, e.g. user.slice.
+        --containers     Output the number of running Docker/Podman containers.
+        --containers-stats Also show the containers' total CPU and memory use.
+        --containers-socket Container engine API socket (default: auto-detect).
+        --failed-units   Output OK or the number of failed systemd units.
+        --updates        Output the number of pending package updates.
+        --clipboard      Output the clipboard (or primary selection) content type and size.
+        --timer          Output the time left on the timer started with `timer start`.
+        --nightlight     Output the color temperature set by gammastep, redshift or wlsunset.
+        --workspace      Output sway/i3/Hyprland workspaces, with the focused one in brackets.
+        --window-title   Output the title of the focused window (sway/i3/Hyprland).
+        --locks          Output CAPS, NUM or SCRL while the lock key is on.
+        --lid            Output LID: open or LID: closed.
+        --docked         Output the external displays while docked, or nothing.
+        --idle           Output the seconds since the last input (logind IdleHint).
+        --locked         Output LOCKED while the session is locked, nothing otherwise.
+        --updates-backend Package manager: auto (default), pacman, apt, dnf.
+        --updates-ttl    Seconds to reuse the last update count (default: 3600).
+        --flaps          Output link drops of an interface in the last hour.
+        --vpn            Output active WireGuard/tun interfaces, or nothing.
+        --connectivity   Output online, limited, captive portal or offline.
+        --ip [IFACE]     Output the address of IFACE (default: the default route's).
+        --net-rate [IFACE] Output receive/transmit throughput of IFACE.
+        --wifi-detail [IFACE] Output SSID, band, bitrate and security of the Wi-Fi link.
+        --gamemode       Output gamemode state, CPU governor and GPU profile.
+        --privacy        Show when the webcam or microphone is in use.
+        --dnd            Output Do Not Disturb state of dunst, mako or swaync.
+        --caffeine       Output whether idle and suspend are being inhibited.
+        --power-profile  Output the active power-profiles-daemon profile.
+        --top [N]        Output the N (default 1) processes using the most CPU or memory.
+        --top-by         Sort --top by cpu (default) or rss.
+        --bluetooth      Output bluetooth power state and connected devices.
+        --media          Output the artist and title playing in an MPRIS player.
+        --media-player   MPRIS player to use, e.g. spotify (default: the one playing).
+        --media-max-length Truncate --media output to N characters (default 40, 0 = no limit).
+        --prometheus     Serve /metrics for Prometheus (default 127.0.0.1:9101).
+        --http [ADDR]    Serve /metrics.json and /MODULE as JSON (default 127.0.0.1:8725).
+        --http-token     Bearer token required by --http (needed beyond localhost).
+        --http-cors      Allow browser pages on ORIGIN to read --http responses.
+        --mqtt           Publish metrics to an MQTT broker (HOST[:PORT]).
+        --mqtt-prefix    MQTT topic prefix (default sys-montion).
+        --mqtt-user      MQTT username; password from SYS_MONTION_MQTT_PASSWORD.
+        --mqtt-discovery Also publish Home Assistant discovery configs.
 
-// 读取音量
-// 使用 `amixer` 读取，依赖 `alsa-utils`
-fn get_volume_level() -> Result<String, io::Error> {
-    let output = Command::new("amixer").arg("get").arg("Master").output()?;
-    let output_str = String::from_utf8_lossy(&output.stdout);
-
-    for line in output_str.lines() {
-        if line.contains("[off]") {
-            return Ok("MUTED".to_string());
-        }
-        if line.contains("Mono:") || line.contains("Front Left:") {
-            // 从形如 "[65%]" 的字符串中提取音量百分比
-            if let Some(start) = line.find('[') {
-                if let Some(end) = line.find('%') {
-                    let mut rst = "VOL: ".to_string();
-                    rst.push_str(&line[start + 1..end + 1].to_string());
-                    return Ok(rst);
-                }
-            }
-        }
-    }
+Commands:
+        eject <DEVICE>   Sync, unmount and power off a removable drive.
+        config check     Validate the config file and preview its output.
+        import --from i3status|i3status-rust|slstatus <CONFIG>
+                         Convert another status bar's config to this tool's config.
+        capabilities [--json]     List modules and features available on this host.
+        doctor                    Check backends and permissions, with suggested fixes.
+        list             List detected devices and their names for device options.
+        all              Output every module that works on this host, in a default order.
+        media play-pause|next|prev Control the MPRIS player.
+        kbd-backlight [--notify] up|down|set <PERCENT> Adjust the keyboard backlight.
+        charge-threshold set <START> <STOP>
+                         Set the battery charge thresholds (needs write access).
+        backlight [--fade <MS>] [--notify] up|down|set [--all] <PERCENT>
+                         Adjust the screen backlight, ramping over MS milliseconds.
+                         --all also sets the other backlights and DDC/CI monitors.
+        volume [--notify] up|down|mute Adjust or mute the default output.
+                         --notify on these commands shows the new level as an OSD.
+        dnd toggle       Turn notification Do Not Disturb on or off.
+        caffeine on|off|toggle    Block or allow idle and suspend.
+        power-profile set performance|balanced|power-saver
+                         Switch the power profile.
+        battery --history <WINDOW>
+                         Sparkline and discharge rate over e.g. 1h (needs --battery-history).
+        uptime-history   Summarize reboots per week and the longest uptime.
+        ctl refresh [MODULE]      Ask a running --watch instance to refresh.
+        ctl get MODULE [--json]   Print a module's cached value from --watch.
 
-    Ok("Unknown".to_string())
+Environment:
+        SYS_MONTION_MODULES=battery,volume,memory
+                         Modules to output when none are given on the command line.
+        SYS_MONTION_<OPTION>=VALUE
+                         Default for --option, e.g. SYS_MONTION_MEMORY_FORMAT=percent;
+                         switches take 1, true or yes."
+    );
 }
 
-fn get_brightness() -> Result<String, io::Error> {
-    let brightness_path = "/sys/class/backlight/amdgpu_bl1/brightness";
-    let max_brightness_path = "/sys/class/backlight/amdgpu_bl1/max_brightness";
-
-    let current_brightness = read_file(brightness_path)?;
-    let current_brightness: i32 = current_brightness.parse().unwrap_or(0);
+// `all` 子命令的模块及顺序：系统负载、温度在前，音量、背光等居中，电池在最后
+// 不包含需要参数（weather、count）、联网（updates）或较慢（containers）的模块
+const ALL_MODULES: &[&str] = &[
+    "cpu",
+    "memory",
+    "temp",
+    "gpu-temp",
+    "net-rate",
+    "volume-level",
+    "backlight",
+    "kbd-backlight",
+    "bluetooth",
+    "power-profile",
+    "battery",
+];
 
-    let max_brightness = read_file(max_brightness_path)?;
-    let max_brightness: i32 = max_brightness.parse().unwrap_or(1);
-
-    let brightness_percentage = (current_brightness * 100) / max_brightness;
-
-    Ok(format!("BL: {}%", brightness_percentage))
+// 根据命令行参数选择要输出的模块，按参数出现的顺序排列
+// 模块参数可以是开关或带值参数（如 --flaps wlan0）
+fn selected_modules(matches: &clap::ArgMatches) -> Vec<&'static str> {
+    let mut modules: Vec<&'static str> = module::names()
+        .filter(|name| matches.value_source(name) == Some(clap::parser::ValueSource::CommandLine))
+        .collect();
+    modules.sort_by_key(|name| matches.index_of(name));
+    modules
 }
 
-fn get_memory() -> Result<String, io::Error> {
-    let meminfo_path = "/proc/meminfo";
-    let meminfo = read_file(meminfo_path)?;
+// 读取失败时代替模块输出的块
+fn unknown_block(name: &str) -> Block {
+    match module::get(name) {
+        Some(entry) => Block {
+            id: entry.id,
+            ..Block::new(entry.block, "Unknown".to_string())
+        },
+        None => Block::new("battery", "Unknown".to_string()),
+    }
+}
 
-    let mut total_memory: i64 = 0;
-    // let mut free_memory: i64 = 0;
-    let mut available_memory: i64 = 0;
+// 控制子命令调整音量/亮度后，读取新的值并显示 OSD 进度条
+fn notify_level(module: &str, matches: &clap::ArgMatches) {
+    let result = module::read(module, matches).and_then(|block| {
+        let value = block.value.unwrap_or(0.0) as i64;
+        notify::send_osd(block.name, &block.text, value).map_err(Error::from)
+    });
+    if let Err(e) = result {
+        eprintln!("Error sending OSD notification: {}", e);
+    }
+}
 
-    // 逐行解析 meminfo 文件
-    for line in meminfo.lines() {
-        if line.starts_with("MemTotal:") {
-            total_memory = parse_meminfo_value(line);
-        // } else if line.starts_with("MemFree:") {
-        //    free_memory = parse_meminfo_value(line);
-        } else if line.starts_with("MemAvailable:") {
-            available_memory = parse_meminfo_value(line);
-        }
+// 输出模块读取错误，--verbose 时附带错误类型、退出码和排查提示
+fn report_error(name: &str, error: &Error, matches: &clap::ArgMatches) {
+    eprintln!("Error reading {}: {}", name, error);
+    if matches.get_count("verbose") > 0 {
+        eprintln!(
+            "  {} (exit code {}): {}",
+            error.kind(),
+            error.exit_code(),
+            error.hint()
+        );
     }
+}
 
-    if total_memory == 0 {
-        return Ok("Unable to retrieve memory info".to_string());
+// 把模块的读取结果转换为输出，读取失败时输出 "Unknown"
+// skip_missing 时缺少硬件的模块直接省略，不输出错误
+fn module_output(name: &str, result: MetricValue, skip_missing: bool) -> Option<Block> {
+    match result {
+        Ok(block) => Some(block),
+        Err(Error::MissingDevice(_)) if skip_missing => None,
+        Err(e) => {
+            log::error!("reading {}: {} ({})", name, e, e.kind());
+            log::info!("{}", e.hint());
+            Some(unknown_block(name))
+        }
     }
+}
 
-    // 计算内存使用量百分比： (total_memory - available_memory) / total_memory * 100
-    // let used_memory = total_memory - available_memory;
-    let used_memory = (total_memory - available_memory) / 1024;
-    // let used_percentage = (used_memory * 100) / total_memory;
+// 按刷新计划创建模块实例，跳过未知的模块
+fn create_modules(
+    schedules: &[watch::Schedule],
+    matches: &Arc<clap::ArgMatches>,
+) -> Vec<Box<dyn Module>> {
+    schedules
+        .iter()
+        .filter_map(|schedule| module::create(schedule, matches))
+        .collect()
+}
 
-    Ok(format!("MEM: {}M", used_memory))
+// 配置文件中各模块的刷新计划，未设置间隔的模块使用 default_interval
+fn config_schedules(config: &config::Config, default_interval: u64) -> Vec<watch::Schedule<'_>> {
+    config
+        .modules
+        .iter()
+        .map(|module| watch::Schedule {
+            name: module.name(),
+            interval: module.interval().unwrap_or(default_interval).max(1),
+            signal: module.signal(),
+            command: module.command(),
+            path: module.path(),
+            label: module.label.as_deref(),
+            actions: module.actions(),
+            show_if: module.show_if(),
+            hide_if: module.hide_if(),
+        })
+        .collect()
 }
 
-fn parse_meminfo_value(line: &str) -> i64 {
-    line.split_whitespace()
-        .nth(1)
-        .unwrap_or("0")
-        .parse()
-        .unwrap_or(0)
+// 命令行选择的模块，都使用同一个间隔和刷新信号
+fn cli_schedules(matches: &clap::ArgMatches, interval: u64) -> Vec<watch::Schedule<'_>> {
+    let schedule = |name, path| watch::Schedule {
+        name,
+        interval,
+        signal: matches.get_one::<i32>("signal").copied(),
+        command: None,
+        path,
+        label: None,
+        actions: [None; 5],
+        show_if: None,
+        hide_if: None,
+    };
+    // `all` 只保留本机上能读取的模块
+    let names = if matches.subcommand_name() == Some("all") {
+        ALL_MODULES
+            .iter()
+            .copied()
+            .filter(|name| capabilities::available(name, matches))
+            .collect()
+    } else {
+        selected_modules(matches)
+    };
+    names
+        .into_iter()
+        .flat_map(|name| match name {
+            // 每个 --plugin 路径一个模块
+            "plugin" => matches
+                .get_many::<String>("plugin")
+                .into_iter()
+                .flatten()
+                .map(|path| schedule(name, Some(path.as_str())))
+                .collect(),
+            _ => vec![schedule(name, None)],
+        })
+        .collect()
 }
 
 fn main() -> io::Result<()> {
-    let battery_path = "/sys/class/power_supply/BAT0/";
-
-    // 使用 clap 解析命令行参数
-    let matches = clap::Command::new("Battery Info")
-        .version("1.0")
-        .about("Retrieve laptop battery status and level")
-        .arg(
-            clap::Arg::new("battery")
-                .long("battery")
-                .help("Output battery status and capacity")
-                .action(clap::ArgAction::SetTrue),
-        )
-        .arg(
-            clap::Arg::new("battery-state")
-                .long("battery-state")
-                .help("Output battery status only")
-                .action(clap::ArgAction::SetTrue),
-        )
-        .arg(
-            clap::Arg::new("battery-capacity")
-                .long("battery-capacity")
-                .help("Output battery capacity only")
-                .action(clap::ArgAction::SetTrue),
-        )
-        .arg(
-            clap::Arg::new("volume-level")
-                .long("volume-level")
-                .help("Output volume level")
-                .action(clap::ArgAction::SetTrue),
-        )
-        .arg(
-            clap::Arg::new("backlight")
-                .long("backlight")
-                .help("Output backlight percentage")
-                .action(clap::ArgAction::SetTrue),
-        )
-        .arg(
-            clap::Arg::new("memory")
-                .long("memory")
-                .help("Output Memory")
-                .action(clap::ArgAction::SetTrue),
-        )
-        .get_matches();
+    let cli = args::build_cli();
+    let (args, unknown_modules) =
+        args::env_args(&cli, env::args_os().collect(), |name| env::var(name).ok());
+    let matches = Arc::new(cli.get_matches_from(args));
+    let log_file = matches.get_one::<String>("log-file");
+    if let Err(e) = log::init(
+        matches.get_count("verbose"),
+        matches.get_flag("quiet"),
+        log_file.map(String::as_str),
+    ) {
+        eprintln!("Error opening log file {}: {}", log_file.unwrap(), e);
+        process::exit(1);
+    }
+    if matches.get_flag("systemd") {
+        log::use_journal();
+    }
+    for name in &unknown_modules {
+        log::warning!("SYS_MONTION_MODULES: unknown module '{}'", name);
+    }
+    if let Some(root) = matches.get_one::<String>("sysfs-root") {
+        if let Err(e) = sysfs::set_root(root) {
+            eprintln!("Error: --sysfs-root: {}", e);
+            process::exit(1);
+        }
+    }
+    if let Some(("eject", sub)) = matches.subcommand() {
+        let device = sub.get_one::<String>("device").unwrap();
+        if let Err(e) = mounts::eject(device) {
+            eprintln!("Error ejecting {}: {}", device, e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(("import", sub)) = matches.subcommand() {
+        let from = sub.get_one::<String>("from").unwrap();
+        let file = sub.get_one::<String>("file").unwrap();
+        match import::import(from, file) {
+            Ok(config) => print!("{}", config),
+            Err(e) => {
+                eprintln!("Error importing {}: {}", file, e);
+                process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    battery::init(
+        battery::Backend::parse(matches.get_one::<String>("battery-backend").unwrap()).unwrap(),
+    );
+    if let Err(e) = args::check_devices(&matches) {
+        args::build_cli()
+            .error(clap::error::ErrorKind::InvalidValue, e)
+            .exit();
+    }
+    if let Some(mode) = matches.get_one::<String>("battery-rounding") {
+        battery::set_rounding(locale::Rounding::parse(mode).unwrap());
+    }
+    memory::init(
+        *matches.get_one::<usize>("memory-decimals").unwrap(),
+        locale::Rounding::parse(matches.get_one::<String>("memory-rounding").unwrap()).unwrap(),
+    );
+    audio::init(matches.get_one::<String>("audio-device").map_or_else(
+        || audio::Device::from_backend(matches.get_one::<String>("audio-backend").unwrap()),
+        |name| audio::Device::parse(name),
+    ));
+    if matches.get_flag("list-audio-devices") {
+        for device in audio::list_devices() {
+            println!("{}", device);
+        }
+        return Ok(());
+    }
+    if let Some(("capabilities", sub)) = matches.subcommand() {
+        println!("{}", capabilities::report(&matches, sub.get_flag("json")));
+        return Ok(());
+    }
+    if let Some(("doctor", _)) = matches.subcommand() {
+        let (report, healthy) = doctor::report();
+        println!("{}", report);
+        if !healthy {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(("list", _)) = matches.subcommand() {
+        println!("{}", list::report());
+        return Ok(());
+    }
+    if let Some(("media", sub)) = matches.subcommand() {
+        let player = matches
+            .get_one::<String>("media-player")
+            .map(String::as_str);
+        let action = sub.get_one::<String>("action").unwrap();
+        if let Err(e) = media::control(player, action) {
+            eprintln!("Error: {}", e);
+            process::exit(e.exit_code());
+        }
+        return Ok(());
+    }
+    if let Some(("kbd-backlight", sub)) = matches.subcommand() {
+        let (action, args) = sub.subcommand().unwrap();
+        let percent = args.try_get_one::<i64>("percent").ok().flatten().copied();
+        match kbd_backlight::control(action, percent) {
+            Ok(level) => {
+                println!("{}", level);
+                if sub.get_flag("notify") {
+                    notify_level("kbd-backlight", &matches);
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(e.exit_code());
+            }
+        }
+        return Ok(());
+    }
+    if let Some(("charge-threshold", sub)) = matches.subcommand() {
+        let (_, args) = sub.subcommand().unwrap();
+        let device = matches.get_one::<String>("battery-device").unwrap();
+        let start = *args.get_one::<i64>("start").unwrap();
+        let stop = *args.get_one::<i64>("stop").unwrap();
+        match battery::set_charge_threshold(device, start, stop) {
+            Ok(thresholds) => println!("{}", thresholds),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(e.exit_code());
+            }
+        }
+        return Ok(());
+    }
+    if let Some(("backlight", sub)) = matches.subcommand() {
+        let (action, args) = sub.subcommand().unwrap();
+        let percent = args.try_get_one::<i32>("percent").ok().flatten().copied();
+        let fade = sub
+            .get_one::<u64>("fade")
+            .copied()
+            .map(Duration::from_millis);
+        let all = args.try_get_one::<bool>("all").ok().flatten() == Some(&true);
+        // --fade 为全局选项，写在 set 之前时 conflicts_with 检查不到
+        if all && fade.is_some() {
+            args::build_cli()
+                .error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "the argument '--all' cannot be used with '--fade <MS>'",
+                )
+                .exit();
+        }
+        let result = match percent {
+            Some(percent) if all => backlight::set_all_brightness(percent),
+            _ => backlight::set_brightness(action, percent, fade),
+        };
+        match result {
+            Ok(level) => {
+                println!("{}", level);
+                if sub.get_flag("notify") {
+                    notify_level("backlight", &matches);
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(e.exit_code());
+            }
+        }
+        return Ok(());
+    }
+    if let Some(("volume", sub)) = matches.subcommand() {
+        let action = sub.get_one::<String>("action").unwrap();
+        if let Err(e) = audio::control(action) {
+            eprintln!("Error: {}", e);
+            process::exit(e.exit_code());
+        }
+        if sub.get_flag("notify") {
+            notify_level("volume-level", &matches);
+        }
+        return Ok(());
+    }
+    if let Some(("dnd", _)) = matches.subcommand() {
+        match dnd::toggle() {
+            Ok(state) => println!("{}", state),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(e.exit_code());
+            }
+        }
+        return Ok(());
+    }
+    if let Some(("caffeine", sub)) = matches.subcommand() {
+        let action = sub.get_one::<String>("action").unwrap();
+        match caffeine::control(action) {
+            Ok(state) => println!("{}", state),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(e.exit_code());
+            }
+        }
+        return Ok(());
+    }
+    if let Some(("nightlight", _)) = matches.subcommand() {
+        match nightlight::toggle() {
+            Ok(state) => println!("{}", state),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(e.exit_code());
+            }
+        }
+        return Ok(());
+    }
+    if let Some(("timer", sub)) = matches.subcommand() {
+        let result = match sub.subcommand() {
+            Some(("start", args)) => timer::start(*args.get_one::<i64>("duration").unwrap()),
+            _ => timer::stop(),
+        };
+        match result {
+            Ok(state) => println!("{}", state),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(e.exit_code());
+            }
+        }
+        return Ok(());
+    }
+    if let Some(("power-profile", sub)) = matches.subcommand() {
+        let (_, args) = sub.subcommand().unwrap();
+        let profile = args.get_one::<String>("profile").unwrap();
+        if let Err(e) = power_profile::set_power_profile(profile) {
+            eprintln!("Error: {}", e);
+            process::exit(e.exit_code());
+        }
+        return Ok(());
+    }
+    if let Some(("generate", sub)) = matches.subcommand() {
+        let (kind, args) = sub.subcommand().unwrap();
+        if kind == "completions" {
+            let shell = args.get_one::<String>("shell").unwrap();
+            print!("{}", generate::completions(shell, args::build_cli()));
+            return Ok(());
+        }
+        if kind == "man" {
+            print!("{}", generate::man_page(args::build_cli()));
+            return Ok(());
+        }
+        if kind == "udev-rules" {
+            print!(
+                "{}",
+                generate::udev_rules(args.get_one::<String>("group").unwrap())
+            );
+            return Ok(());
+        }
+        let watch_args: Vec<String> = match args.get_many::<String>("args") {
+            Some(values) => values.cloned().collect(),
+            None => vec!["--watch".to_string(), "5".to_string()],
+        };
+        let exe = env::current_exe()?;
+        print!(
+            "{}",
+            systemd::unit_file(&exe.to_string_lossy(), &watch_args)
+        );
+        return Ok(());
+    }
+    if let Some(("battery", sub)) = matches.subcommand() {
+        match history::get_battery_history(*sub.get_one::<i64>("history").unwrap()) {
+            Ok(history) => println!("{}", history),
+            Err(e) => {
+                eprintln!("Error reading battery history: {}", e);
+                process::exit(e.exit_code());
+            }
+        }
+        return Ok(());
+    }
+    if let Some(("uptime-history", _)) = matches.subcommand() {
+        match uptime::get_uptime_history() {
+            Ok(history) => println!("{}", history),
+            Err(e) => {
+                eprintln!("Error reading uptime history: {}", e);
+                process::exit(e.exit_code());
+            }
+        }
+        return Ok(());
+    }
+    if let Some(("ctl", sub)) = matches.subcommand() {
+        let request = match sub.subcommand() {
+            Some(("refresh", args)) => match args.get_one::<String>("module") {
+                Some(module) => format!("refresh {}", module),
+                None => "refresh".to_string(),
+            },
+            Some(("get", args)) => {
+                let module = args.get_one::<String>("module").unwrap();
+                if args.get_flag("json") {
+                    format!("get {} json", module)
+                } else {
+                    format!("get {}", module)
+                }
+            }
+            _ => unreachable!("subcommand_required"),
+        };
+        match control::request(&request) {
+            Ok(response) if response != "ok" => println!("{}", response),
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    locale::init(matches.get_one::<String>("locale").unwrap());
+    i18n::init(matches.get_one::<String>("lang").unwrap());
+    if let Some(&now) = matches.get_one::<i64>("now") {
+        timeutil::set_clock(Box::new(timeutil::FixedClock(now)));
+    }
 
-    // 根据不同参数输出信息
-    if matches.get_flag("battery") {
-        let capacity = get_battery_capacity(battery_path).unwrap_or_else(|e| {
-            eprintln!("Error reading battery capacity: {}", e);
-            "Unknown".to_string()
-        });
-        let status = get_battery_status(battery_path).unwrap_or_else(|e| {
-            eprintln!("Error reading battery status: {}", e);
-            "Unknown".to_string()
-        });
-        println!("{}: {}%", status, capacity);
-    } else if matches.get_flag("battery-state") {
-        let status = get_battery_status(battery_path).unwrap_or_else(|e| {
-            eprintln!("Error reading battery status: {}", e);
-            "Unknown".to_string()
-        });
-        println!("{}", status);
-    } else if matches.get_flag("battery-capacity") {
-        let capacity = get_battery_capacity(battery_path).unwrap_or_else(|e| {
-            eprintln!("Error reading battery capacity: {}", e);
-            "Unknown".to_string()
+    if let Some(("config", _)) = matches.subcommand() {
+        let path = matches.get_one::<String>("config").map(String::as_str);
+        let config = config::load(path).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            process::exit(1);
         });
-        println!("{}%", capacity);
-    } else if matches.get_flag("volume-level") {
-        let volume_level = get_volume_level().unwrap_or_else(|e| {
-            eprintln!("Error reading volume level: {}", e);
-            "Unknown".to_string()
-        });
-        println!("{}", volume_level);
-    } else if matches.get_flag("backlight") {
-        let backlight_percentage = get_brightness().unwrap_or_else(|e| {
-            eprintln!("Error reading backlight: {}", e);
-            "Unknown".to_string()
-        });
-        println!("{}", backlight_percentage);
-    } else if matches.get_flag("memory") {
-        let memory = get_memory().unwrap_or_else(|e| {
-            eprintln!("Error reading backlight: {}", e);
-            "Unknown".to_string()
-        });
-        println!("{}", memory);
-    } else {
-        // 未指定参数时打印帮助信息
-        print_help();
+        for module in &config.modules {
+            let reason =
+                module::get(module.name()).and_then(|entry| entry.create(&matches).unavailable());
+            if let Some(reason) = reason {
+                println!(
+                    "{}: warning: module '{}': {}",
+                    config.location(module.name.span()),
+                    module.name(),
+                    reason
+                );
+            }
+        }
+        println!("config ok, {} modules", config.modules.len());
+        let skip_missing = matches.get_flag("skip-missing") || config.skip_missing;
+        let mut modules = create_modules(&config_schedules(&config, 1), &matches);
+        let results = module::read_parallel(modules.iter_mut().collect());
+        let blocks: Vec<Block> = modules
+            .iter()
+            .zip(results)
+            .filter_map(|(module, result)| module_output(module.id(), result, skip_missing))
+            .collect();
+        let renderer = Renderer::from_matches(&matches, &config);
+        let separator = config
+            .separator
+            .as_deref()
+            .unwrap_or(renderer.default_separator());
+        println!("{}", renderer.render_line(&blocks, separator));
+        return Ok(());
+    }
+    let config = match config::load(matches.get_one::<String>("config").map(String::as_str)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error reading config: {}", e);
+            process::exit(1);
+        }
+    };
+    if matches.get_flag("stdin") {
+        if let Err(e) = query::run(&matches) {
+            eprintln!("Error reading queries: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(addr) = matches.get_one::<String>("prometheus") {
+        let battery_device = matches.get_one::<String>("battery-device").unwrap();
+        if let Err(e) = prometheus::run(addr, battery_device) {
+            eprintln!("Error serving metrics on {}: {}", addr, e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(addr) = matches.get_one::<String>("http") {
+        // 没有选择模块时与 `all` 子命令相同，只包含本机上能读取的模块
+        let mut names = selected_modules(&matches);
+        if names.is_empty() {
+            names = ALL_MODULES
+                .iter()
+                .copied()
+                .filter(|name| capabilities::available(name, &matches))
+                .collect();
+        }
+        if let Err(e) = http::run(addr, names, &matches) {
+            eprintln!("Error serving HTTP on {}: {}", addr, e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(addr) = matches.get_one::<String>("mqtt") {
+        let options = mqtt::Options {
+            addr: addr.clone(),
+            prefix: matches.get_one::<String>("mqtt-prefix").unwrap().clone(),
+            username: matches.get_one::<String>("mqtt-user").cloned(),
+            discovery: matches.get_flag("mqtt-discovery"),
+            interval: Duration::from_secs(matches.get_one::<u64>("watch").copied().unwrap_or(60)),
+        };
+        mqtt::run(
+            &options,
+            matches.get_one::<String>("battery-device").unwrap(),
+        );
+        return Ok(());
     }
+    // 在提示符中每次都会运行：不换行，出错时不输出任何内容，只以退出码表示
+    if let Some(name) = matches.get_one::<String>("prompt-segment") {
+        let block = match module::read(name, &matches) {
+            Ok(block) => block,
+            Err(e) => process::exit(e.exit_code()),
+        };
+        let renderer = Renderer::from_matches(&matches, &config);
+        let shell = matches.get_one::<String>("prompt-shell").unwrap();
+        print!("{}", renderer.prompt_segment(&block, shell));
+        return io::Write::flush(&mut io::stdout());
+    }
+    let Some(&interval) = matches.get_one::<u64>("watch") else {
+        let renderer = Renderer::from_matches(&matches, &config);
+        let separator = config
+            .separator
+            .clone()
+            .unwrap_or_else(|| renderer.default_separator().to_string());
+        let skip_missing = matches.get_flag("skip-missing")
+            || config.skip_missing
+            || matches.subcommand_name() == Some("all");
+        // 命令行指定了模块时输出这些模块，否则输出配置文件中的组合状态行
+        let mut schedules = cli_schedules(&matches, 1);
+        if schedules.is_empty() {
+            schedules = config_schedules(&config, 1);
+        }
+        let mut modules = create_modules(&schedules, &matches);
+        if !modules.is_empty() {
+            // 读取失败的模块输出 "Unknown"，其余模块照常输出，最后统一报告所有错误
+            let mut errors = Vec::new();
+            let mut blocks = Vec::new();
+            let results = module::read_parallel(modules.iter_mut().collect());
+            for (module, result) in modules.iter().zip(results) {
+                let name = module.id();
+                match result {
+                    Ok(block) => blocks.push(block),
+                    Err(Error::MissingDevice(_)) if skip_missing => {}
+                    Err(e) => {
+                        blocks.push(unknown_block(name));
+                        errors.push((name, e));
+                    }
+                }
+            }
+            // 按键绑定中调整音量/亮度后调用，显示 OSD 进度条
+            if matches.get_flag("osd") {
+                for block in blocks
+                    .iter()
+                    .filter(|b| matches!(b.name, "volume" | "backlight"))
+                {
+                    let value = block.value.unwrap_or(0.0) as i64;
+                    if let Err(e) = notify::send_osd(block.name, &block.text, value) {
+                        eprintln!("Error sending OSD notification: {}", e);
+                    }
+                }
+            }
+            println!("{}", renderer.render_line(&blocks, &separator));
+            for (name, e) in &errors {
+                report_error(name, e, &matches);
+            }
+            // 与 Nagios 插件相同：0 正常、1 警告、2 严重，读取失败时为 3（未知）
+            if matches.get_flag("check") {
+                let thresholds = Thresholds::from_matches(&matches, &config);
+                let level = blocks
+                    .iter()
+                    .map(|block| thresholds.block_level(block))
+                    .max()
+                    .unwrap_or(Level::Normal);
+                process::exit(if errors.is_empty() { level as i32 } else { 3 });
+            }
+            // 以第一个错误的类型对应的退出码退出
+            if let Some((_, e)) = errors.first() {
+                process::exit(e.exit_code());
+            }
+        } else {
+            // 未指定参数时打印帮助信息
+            print_help();
+        }
+        return Ok(());
+    };
 
+    watch::run(&matches, config, interval);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(8040), "2h 14m");
+        assert_eq!(format_duration(3 * 86400 + 4 * 3600), "3d 4h");
+        assert_eq!(format_duration(-5), "0h 0m");
+    }
+}
@@ -0,0 +1,175 @@
+use crate::dbus::{self, Bus};
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::{truncate, Block};
+use std::io;
+
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+// 列出会话总线上的 MPRIS 播放器，返回去掉前缀的名称，如 "spotify"、"firefox.instance_1_42"
+fn list_players() -> Result<Vec<String>, io::Error> {
+    let reply = dbus::call(
+        Bus::Session,
+        "org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        "org.freedesktop.DBus.ListNames",
+        &[],
+    )?;
+    let mut players: Vec<String> = reply
+        .split('\'')
+        .filter_map(|name| name.strip_prefix(MPRIS_PREFIX))
+        .map(str::to_string)
+        .collect();
+    players.sort();
+    Ok(players)
+}
+
+fn playback_status(player: &str) -> Option<String> {
+    dbus::get_property(
+        Bus::Session,
+        &format!("{}{}", MPRIS_PREFIX, player),
+        MPRIS_PATH,
+        PLAYER_INTERFACE,
+        "PlaybackStatus",
+    )
+    .ok()
+    .map(|status| status.trim_matches('\'').to_string())
+}
+
+// 选择播放器：指定名称时取名称匹配的第一个，否则优先正在播放的
+fn select_player(wanted: Option<&str>) -> Result<String, Error> {
+    let players = list_players()
+        .map_err(|e| Error::BackendUnavailable(format!("unable to list MPRIS players: {}", e)))?;
+    let player = match wanted {
+        Some(wanted) => players.into_iter().find(|p| {
+            p == wanted
+                || p.strip_prefix(wanted)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        }),
+        None => players
+            .iter()
+            .find(|p| playback_status(p).as_deref() == Some("Playing"))
+            .or(players.first())
+            .cloned(),
+    };
+    player.ok_or_else(|| match wanted {
+        Some(wanted) => Error::MissingDevice(format!("media player {} is not running", wanted)),
+        None => Error::MissingDevice("no MPRIS media player running".to_string()),
+    })
+}
+
+// 从 Metadata 的 GVariant 文本中取出字符串字段
+// 如 {'xesam:title': <'Song'>, 'xesam:artist': <['Artist']>}，数组取第一个元素
+fn metadata_field(metadata: &str, key: &str) -> Option<String> {
+    let rest = &metadata[metadata.find(&format!("'{}': <", key))? + key.len() + 5..];
+    let rest = rest.strip_prefix('[').unwrap_or(rest);
+    let quote = rest.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let mut value = String::new();
+    let mut chars = rest[1..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.extend(chars.next()),
+            c if c == quote => break,
+            c => value.push(c),
+        }
+    }
+    (!value.is_empty()).then_some(value)
+}
+
+// 输出当前播放的 "艺术家 - 标题" 和播放状态，如 "MEDIA: Artist - Title [paused]"
+pub fn get_media(player: Option<&str>, max_length: usize) -> Result<String, Error> {
+    let player = select_player(player)?;
+    let status = playback_status(&player).unwrap_or_default();
+    if status == "Stopped" {
+        return Ok("MEDIA: stopped".to_string());
+    }
+
+    let metadata = dbus::get_property(
+        Bus::Session,
+        &format!("{}{}", MPRIS_PREFIX, player),
+        MPRIS_PATH,
+        PLAYER_INTERFACE,
+        "Metadata",
+    )?;
+    let title = metadata_field(&metadata, "xesam:title").unwrap_or_else(|| player.clone());
+    let track = match metadata_field(&metadata, "xesam:artist") {
+        Some(artist) => format!("{} - {}", artist, title),
+        None => title,
+    };
+
+    let track = truncate(&track, max_length);
+    Ok(match status.as_str() {
+        "Paused" => format!("MEDIA: {} [paused]", track),
+        _ => format!("MEDIA: {}", track),
+    })
+}
+
+// 控制播放器：action 为 play-pause、next 或 prev
+pub fn control(player: Option<&str>, action: &str) -> Result<(), Error> {
+    let player = select_player(player)?;
+    let method = match action {
+        "play-pause" => "PlayPause",
+        "next" => "Next",
+        "prev" => "Previous",
+        _ => return Err(Error::Parse(format!("unknown media action '{}'", action))),
+    };
+    dbus::call(
+        Bus::Session,
+        &format!("{}{}", MPRIS_PREFIX, player),
+        MPRIS_PATH,
+        &format!("{}.{}", PLAYER_INTERFACE, method),
+        &[],
+    )?;
+    Ok(())
+}
+
+pub struct Media {
+    player: Option<String>,
+    max_length: usize,
+}
+
+impl Builtin for Media {
+    const ID: &'static str = "media";
+    const BLOCK: &'static str = "media";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Media {
+            player: matches.get_one::<String>("media-player").cloned(),
+            max_length: *matches.get_one::<usize>("media-max-length").unwrap(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(
+            Self::BLOCK,
+            get_media(self.player.as_deref(), self.max_length)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_metadata() {
+        let metadata = "{'mpris:trackid': <objectpath '/1'>, \
+             'xesam:artist': <['Daft Punk', 'Pharrell']>, \
+             'xesam:title': <\"Get Lucky (Radio Edit)\">, 'xesam:album': <'It\\'s'>}";
+        assert_eq!(
+            metadata_field(metadata, "xesam:artist").as_deref(),
+            Some("Daft Punk")
+        );
+        assert_eq!(
+            metadata_field(metadata, "xesam:title").as_deref(),
+            Some("Get Lucky (Radio Edit)")
+        );
+        assert_eq!(
+            metadata_field(metadata, "xesam:album").as_deref(),
+            Some("It's")
+        );
+        assert_eq!(metadata_field(metadata, "xesam:url"), None);
+    }
+}
@@ -0,0 +1,390 @@
+use crate::error::Error;
+use crate::locale::{self, Rounding};
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{format_size_with, pressure, read_file, sysfs};
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+// G 和自动单位的小数位数及取整方式，未设置时保留 1 位并四舍五入
+static SIZE_FORMAT: OnceLock<(usize, Rounding)> = OnceLock::new();
+
+pub fn init(decimals: usize, rounding: Rounding) {
+    let _ = SIZE_FORMAT.set((decimals, rounding));
+}
+
+fn size_format() -> (usize, Rounding) {
+    SIZE_FORMAT.get().copied().unwrap_or((1, Rounding::Round))
+}
+
+// 内存输出格式：已用/总量、空闲、可用或已用百分比
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MemoryFormat {
+    Used,
+    Free,
+    Available,
+    Percent,
+}
+
+impl MemoryFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "used" => Some(MemoryFormat::Used),
+            "free" => Some(MemoryFormat::Free),
+            "available" => Some(MemoryFormat::Available),
+            "percent" => Some(MemoryFormat::Percent),
+            _ => None,
+        }
+    }
+}
+
+// 内存大小单位，auto 按大小自动选择 K/M/G
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Unit {
+    Kib,
+    Mib,
+    Gib,
+    Auto,
+}
+
+impl Unit {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "kib" => Some(Unit::Kib),
+            "mib" => Some(Unit::Mib),
+            "gib" => Some(Unit::Gib),
+            "auto" => Some(Unit::Auto),
+            _ => None,
+        }
+    }
+
+    // 格式化 KiB 数值
+    fn format(self, kib: i64) -> String {
+        let (decimals, rounding) = size_format();
+        match self {
+            Unit::Kib => format!("{}K", locale::format_int(kib)),
+            Unit::Mib => format!("{}M", locale::format_int(kib / 1024)),
+            Unit::Gib => format!(
+                "{}G",
+                locale::format_number(rounding.apply(kib as f64 / 1048576.0, decimals), decimals)
+            ),
+            Unit::Auto => format_size_with(kib.max(0) as u64 * 1024, decimals, rounding),
+        }
+    }
+}
+
+// 返回输出文本和已用内存的百分比
+pub fn get_memory(format: MemoryFormat, unit: Unit) -> Result<(String, i64), Error> {
+    let meminfo = read_file("/proc/meminfo")?;
+
+    let mut total_memory: i64 = 0;
+    let mut free_memory: i64 = 0;
+    let mut available_memory: Option<i64> = None;
+
+    // 逐行解析 meminfo 文件
+    for line in meminfo.lines() {
+        if line.starts_with("MemTotal:") {
+            total_memory = parse_meminfo_value(line);
+        } else if line.starts_with("MemFree:") {
+            free_memory = parse_meminfo_value(line);
+        } else if line.starts_with("MemAvailable:") {
+            available_memory = Some(parse_meminfo_value(line));
+        }
+    }
+
+    if total_memory == 0 {
+        return Err(Error::Parse(
+            "MemTotal not found in /proc/meminfo".to_string(),
+        ));
+    }
+    // 3.14 之前的内核没有 MemAvailable，退回到 MemFree
+    let available_memory = available_memory.unwrap_or(free_memory);
+
+    // 已用内存 = MemTotal - MemAvailable，缓存等可回收内存不计为已用
+    let used_memory = total_memory - available_memory;
+    let used_percentage = used_memory * 100 / total_memory;
+
+    let text = match format {
+        MemoryFormat::Used => format!(
+            "MEM: {}/{} ({}%)",
+            unit.format(used_memory),
+            unit.format(total_memory),
+            used_percentage
+        ),
+        MemoryFormat::Free => format!("MEM: {} free", unit.format(free_memory)),
+        MemoryFormat::Available => format!("MEM: {} available", unit.format(available_memory)),
+        MemoryFormat::Percent => format!("MEM: {}%", used_percentage),
+    };
+    Ok((text, used_percentage))
+}
+
+// --memory-detail 附加的内存细节
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Detail {
+    // zram 设备中存储的数据量和压缩后的大小（/sys/block/zram*/mm_stat）
+    Zram,
+    // zswap 缓存的数据量和压缩后的大小（meminfo 的 Zswapped/Zswap，5.19 起提供）
+    Zswap,
+    // 等待写回磁盘的脏页
+    Dirty,
+    // 页缓存和块设备缓冲区
+    Cache,
+}
+
+impl Detail {
+    pub const NAMES: [&'static str; 4] = ["zram", "zswap", "dirty", "cache"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "zram" => Some(Detail::Zram),
+            "zswap" => Some(Detail::Zswap),
+            "dirty" => Some(Detail::Dirty),
+            "cache" => Some(Detail::Cache),
+            _ => None,
+        }
+    }
+}
+
+// 所有 zram 设备的原始数据量和压缩后大小（KiB），没有 zram 设备时为 None
+// mm_stat 的前两个字段为 orig_data_size 和 compr_data_size（字节）
+fn read_zram() -> Result<Option<(i64, i64)>, io::Error> {
+    let mut total = None;
+    for entry in sysfs::read_dir("/sys/block")? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if !name.starts_with("zram") {
+            continue;
+        }
+        let Ok(stat) = read_file(Path::new("/sys/block").join(&name).join("mm_stat")) else {
+            continue;
+        };
+        let fields: Vec<i64> = stat
+            .split_whitespace()
+            .take(2)
+            .filter_map(|field| field.parse().ok())
+            .collect();
+        if let [orig, compr] = fields[..] {
+            let (total_orig, total_compr) = total.get_or_insert((0, 0));
+            *total_orig += orig / 1024;
+            *total_compr += compr / 1024;
+        }
+    }
+    Ok(total)
+}
+
+fn compression(stored: i64, compressed: i64) -> String {
+    if compressed == 0 {
+        return "-".to_string();
+    }
+    format!(
+        "{}x",
+        locale::format_number(stored as f64 / compressed as f64, 1)
+    )
+}
+
+// 输出内存细节：紧凑格式适合状态栏，如 "zram 1.2G→300M dirty 12M"
+// 详细格式带完整说明，如 "zram: 1.2G stored in 300M (4.0x); dirty: 12M, writeback: 0K"
+// 本机不支持的细节（如没有 zram 设备）不输出
+pub fn get_memory_details(details: &[Detail], verbose: bool, unit: Unit) -> Result<String, Error> {
+    let meminfo = read_file("/proc/meminfo")?;
+    let value = |key: &str| {
+        meminfo
+            .lines()
+            .find(|line| line.starts_with(key))
+            .map(parse_meminfo_value)
+    };
+    let mut parts = Vec::new();
+    for detail in details {
+        let part = match detail {
+            Detail::Zram | Detail::Zswap => {
+                let sizes = match detail {
+                    Detail::Zram => read_zram()?,
+                    _ => value("Zswapped:").zip(value("Zswap:")),
+                };
+                let name = if *detail == Detail::Zram {
+                    "zram"
+                } else {
+                    "zswap"
+                };
+                sizes.map(|(stored, compressed)| {
+                    if verbose {
+                        format!(
+                            "{}: {} stored in {} ({})",
+                            name,
+                            unit.format(stored),
+                            unit.format(compressed),
+                            compression(stored, compressed)
+                        )
+                    } else {
+                        format!(
+                            "{} {}→{}",
+                            name,
+                            unit.format(stored),
+                            unit.format(compressed)
+                        )
+                    }
+                })
+            }
+            Detail::Dirty => value("Dirty:").map(|dirty| {
+                if verbose {
+                    format!(
+                        "dirty: {}, writeback: {}",
+                        unit.format(dirty),
+                        unit.format(value("Writeback:").unwrap_or(0))
+                    )
+                } else {
+                    format!("dirty {}", unit.format(dirty))
+                }
+            }),
+            Detail::Cache => value("Cached:").map(|cached| {
+                let buffers = unit.format(value("Buffers:").unwrap_or(0));
+                if verbose {
+                    format!("cached: {}, buffers: {}", unit.format(cached), buffers)
+                } else {
+                    format!("cache {} buf {}", unit.format(cached), buffers)
+                }
+            }),
+        };
+        parts.extend(part);
+    }
+    Ok(parts.join(if verbose { "; " } else { " " }))
+}
+
+fn parse_meminfo_value(line: &str) -> i64 {
+    line.split_whitespace()
+        .nth(1)
+        .unwrap_or("0")
+        .parse()
+        .unwrap_or(0)
+}
+
+// 读取 /proc/meminfo 中的一项（KiB），如 "MemAvailable:"
+fn read_meminfo_kib(key: &str) -> Result<i64, io::Error> {
+    let meminfo = read_file("/proc/meminfo")?;
+    meminfo
+        .lines()
+        .find(|line| line.starts_with(key))
+        .map(parse_meminfo_value)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("{} not found", key)))
+}
+
+// 读取 MemAvailable（KiB）
+pub fn get_available_kib() -> Result<i64, io::Error> {
+    read_meminfo_kib("MemAvailable:")
+}
+
+// 读取 MemTotal（KiB）
+pub fn get_total_kib() -> Result<i64, io::Error> {
+    read_meminfo_kib("MemTotal:")
+}
+
+// 读取 PSI 内存压力（/proc/pressure/memory 中 some 行的 avg10 百分比）
+pub fn get_memory_pressure() -> Result<f64, io::Error> {
+    pressure::read_avg10("memory")
+}
+
+// memory 模块，--memory-detail 的各项附加在后面
+pub struct Memory {
+    format: MemoryFormat,
+    unit: Unit,
+    details: Vec<Detail>,
+    verbose: bool,
+}
+
+impl Builtin for Memory {
+    const ID: &'static str = "memory";
+    const BLOCK: &'static str = "memory";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Memory {
+            format: MemoryFormat::parse(matches.get_one::<String>("memory-format").unwrap())
+                .unwrap(),
+            unit: Unit::parse(matches.get_one::<String>("unit").unwrap()).unwrap(),
+            details: matches
+                .get_many::<String>("memory-detail")
+                .into_iter()
+                .flatten()
+                .filter_map(|name| Detail::parse(name))
+                .collect(),
+            verbose: matches.get_one::<String>("memory-detail-format").unwrap() == "verbose",
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let (mut text, percent) = get_memory(self.format, self.unit)?;
+        if !self.details.is_empty() {
+            let extra = get_memory_details(&self.details, self.verbose, self.unit)?;
+            if !extra.is_empty() {
+                text.push_str(if self.verbose { "; " } else { " " });
+                text.push_str(&extra);
+            }
+        }
+        // 各种格式的数值都是已用内存的百分比
+        Ok(Block::with_percent(Self::BLOCK, text, percent as f64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sysfs;
+
+    #[test]
+    fn parses_meminfo() {
+        sysfs::use_fixtures();
+        assert_eq!(get_total_kib().unwrap(), 16000000);
+        assert_eq!(get_available_kib().unwrap(), 12000000);
+    }
+
+    #[test]
+    fn formats_memory() {
+        sysfs::use_fixtures();
+        // 已用 16000000 - 12000000 = 4000000 KiB
+        assert_eq!(
+            get_memory(MemoryFormat::Used, Unit::Auto).unwrap(),
+            ("MEM: 3.8G/15.3G (25%)".to_string(), 25)
+        );
+        assert_eq!(
+            get_memory(MemoryFormat::Used, Unit::Mib).unwrap().0,
+            "MEM: 3906M/15625M (25%)"
+        );
+        assert_eq!(
+            get_memory(MemoryFormat::Free, Unit::Kib).unwrap().0,
+            "MEM: 2000000K free"
+        );
+        assert_eq!(
+            get_memory(MemoryFormat::Available, Unit::Gib).unwrap().0,
+            "MEM: 11.4G available"
+        );
+        assert_eq!(
+            get_memory(MemoryFormat::Percent, Unit::Auto).unwrap().0,
+            "MEM: 25%"
+        );
+    }
+
+    #[test]
+    fn formats_details() {
+        sysfs::use_fixtures();
+        let details = [Detail::Zram, Detail::Zswap, Detail::Dirty, Detail::Cache];
+        assert_eq!(
+            get_memory_details(&details, false, Unit::Mib).unwrap(),
+            "zram 1200M→300M zswap 800M→200M dirty 12M cache 5859M buf 488M"
+        );
+        assert_eq!(
+            get_memory_details(&details[..1], true, Unit::Mib).unwrap(),
+            "zram: 1200M stored in 300M (4.0x)"
+        );
+    }
+
+    #[test]
+    fn parses_pressure() {
+        sysfs::use_fixtures();
+        assert_eq!(get_memory_pressure().unwrap(), 1.5);
+    }
+
+    #[test]
+    fn parses_meminfo_value() {
+        assert_eq!(parse_meminfo_value("MemTotal:       16000000 kB"), 16000000);
+        assert_eq!(parse_meminfo_value("garbage"), 0);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{backlight, battery, hwmon, memory};
+
+// 一项数值指标，供 Prometheus 和 MQTT 导出使用
+pub struct Metric {
+    pub name: &'static str,
+    pub help: &'static str,
+    // 单位，如 "%"、"W"、"B"、"°C"，无单位时为空
+    pub unit: &'static str,
+    // 区分多个样本的标签名，如风扇编号 "fan"
+    pub label: &'static str,
+    // (标签值, 数值)；只有一个样本时标签值为空
+    pub samples: Vec<(String, f64)>,
+}
+
+fn single(
+    name: &'static str,
+    help: &'static str,
+    unit: &'static str,
+    value: Option<f64>,
+) -> Metric {
+    Metric {
+        name,
+        help,
+        unit,
+        label: "",
+        samples: value.map(|v| vec![(String::new(), v)]).unwrap_or_default(),
+    }
+}
+
+// 读取所有指标，读取失败的指标没有样本
+pub fn collect(battery_device: &str) -> Vec<Metric> {
+    let capacity = battery::get_battery_capacity(battery_device)
+        .ok()
+        .and_then(|c| c.parse().ok());
+    let charging = battery::get_battery_status(battery_device)
+        .ok()
+        .map(|status| if status == "Charging" { 1.0 } else { 0.0 });
+    let power = battery::read_power_watts(battery_device).ok();
+
+    let total = memory::get_total_kib().ok();
+    let available = memory::get_available_kib().ok();
+    let used = total.zip(available).map(|(t, a)| ((t - a) * 1024) as f64);
+    let available = available.map(|a| (a * 1024) as f64);
+    let pressure = memory::get_memory_pressure().ok();
+
+    let backlight = backlight::get_brightness_percent().ok().map(f64::from);
+    let temp = hwmon::read_max_temp().ok();
+    let fans = hwmon::read_fans()
+        .unwrap_or_default()
+        .iter()
+        .enumerate()
+        .map(|(i, fan)| (i.to_string(), fan.rpm as f64))
+        .collect();
+
+    vec![
+        single(
+            "battery_capacity",
+            "Battery charge in percent.",
+            "%",
+            capacity,
+        ),
+        single(
+            "battery_charging",
+            "Whether the battery is charging.",
+            "",
+            charging,
+        ),
+        single(
+            "battery_power_watts",
+            "Battery power draw in watts.",
+            "W",
+            power,
+        ),
+        single(
+            "memory_used_bytes",
+            "Memory in use (MemTotal - MemAvailable).",
+            "B",
+            used,
+        ),
+        single(
+            "memory_available_bytes",
+            "MemAvailable in bytes.",
+            "B",
+            available,
+        ),
+        single(
+            "memory_pressure_percent",
+            "Memory PSI some avg10.",
+            "%",
+            pressure,
+        ),
+        single(
+            "backlight_percent",
+            "Backlight brightness in percent.",
+            "%",
+            backlight,
+        ),
+        single("cpu_temp_celsius", "Highest hwmon temperature.", "°C", temp),
+        Metric {
+            name: "fan_rpm",
+            help: "Fan speed in RPM.",
+            unit: "RPM",
+            label: "fan",
+            samples: fans,
+        },
+    ]
+}
@@ -0,0 +1,327 @@
+use crate::error::Error;
+use crate::plugin::Plugin;
+use crate::render::Block;
+use crate::script::Script;
+use crate::watch::Schedule;
+use crate::{
+    audio, backlight, battery, bluetooth, caffeine, calendar, cgroup, clipboard, containers, count,
+    cpu, daylight, diskio, dnd, gamemode, gpu, hwmon, idle, kbd_backlight, lid, locks, media,
+    memory, mounts, net, nightlight, nvme, power_profile, pressure, privacy, systemd, timer, top,
+    updates, upower, usage, weather, wifi, wm,
+};
+use std::ops::BitOr;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+
+// 模块一次读取的结果
+pub type MetricValue = Result<Block, Error>;
+
+// 模块关心的系统事件，watch 模式据此注册对应的事件源，事件到达时立即刷新
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventInterest(u8);
+
+impl EventInterest {
+    pub const NONE: Self = EventInterest(0);
+    // 挂载表变化（/proc/self/mountinfo）
+    pub const MOUNTS: Self = EventInterest(1);
+    // 混音器事件（pactl subscribe / alsactl monitor）
+    pub const AUDIO: Self = EventInterest(2);
+    // --count 目录中的文件变化（inotify）
+    pub const FILES: Self = EventInterest(4);
+    // 窗口管理器的工作区和窗口事件（sway/Hyprland IPC）
+    pub const WM: Self = EventInterest(8);
+    // 键盘锁定键指示灯（evdev EV_LED）
+    pub const LOCKS: Self = EventInterest(16);
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for EventInterest {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        EventInterest(self.0 | other.0)
+    }
+}
+
+// 一个可输出的模块：按名称创建，watch 模式下实例常驻，可在两次读取之间保留状态
+// 内置模块实现 Builtin 并登记在 REGISTRY 中，由下面的通用实现得到这个 trait
+pub trait Module: Send {
+    // 命令行参数和配置文件中的名称，如 volume-level
+    fn id(&self) -> &str;
+    fn read(&mut self) -> MetricValue;
+    fn interests(&self) -> EventInterest {
+        EventInterest::NONE
+    }
+    // 模块在本机上不能工作的原因（缺少硬件或参数），用于 config 子命令的检查
+    fn unavailable(&self) -> Option<String> {
+        None
+    }
+}
+
+// 内置模块：创建时从命令行参数取出自己的选项，每个模块在各自的源文件中实现
+pub trait Builtin: Send + Sized + 'static {
+    // 模块名，即命令行参数和配置文件中的名称，如 volume-level
+    const ID: &'static str;
+    // 输出块名，用于颜色、图标和 waybar class，多个模块可以共用，如 volume
+    const BLOCK: &'static str;
+
+    fn new(matches: &clap::ArgMatches) -> Self;
+    fn read_block(&mut self) -> MetricValue;
+    fn interests(&self) -> EventInterest {
+        EventInterest::NONE
+    }
+    fn unavailable(&self) -> Option<String> {
+        None
+    }
+}
+
+impl<T: Builtin> Module for T {
+    fn id(&self) -> &str {
+        T::ID
+    }
+
+    // 输出块带上模块名
+    fn read(&mut self) -> MetricValue {
+        let block = self.read_block()?;
+        Ok(Block { id: T::ID, ..block })
+    }
+
+    fn interests(&self) -> EventInterest {
+        Builtin::interests(self)
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        Builtin::unavailable(self)
+    }
+}
+
+// 需要额外参数的模块在缺少参数时报错，如 "no location, use --location LAT,LON"
+pub fn required<'a>(value: &'a Option<String>, arg: &str, usage: &str) -> Result<&'a str, Error> {
+    value
+        .as_deref()
+        .ok_or_else(|| Error::MissingDevice(format!("no {}, use {}", arg, usage)))
+}
+
+// REGISTRY 中的一项
+pub struct Entry {
+    pub id: &'static str,
+    pub block: &'static str,
+    new: fn(&clap::ArgMatches) -> Box<dyn Module>,
+}
+
+impl Entry {
+    pub fn create(&self, matches: &clap::ArgMatches) -> Box<dyn Module> {
+        (self.new)(matches)
+    }
+}
+
+fn boxed<T: Builtin>(matches: &clap::ArgMatches) -> Box<dyn Module> {
+    Box::new(T::new(matches))
+}
+
+const fn entry<T: Builtin>() -> Entry {
+    Entry {
+        id: T::ID,
+        block: T::BLOCK,
+        new: boxed::<T>,
+    }
+}
+
+// 所有内置模块，按此顺序列出（capabilities）；同一命令行中的模块按参数出现的顺序输出
+pub const REGISTRY: &[Entry] = &[
+    entry::<battery::Battery>(),
+    entry::<battery::BatteryState>(),
+    entry::<battery::BatteryCapacity>(),
+    entry::<battery::BatteryTime>(),
+    entry::<battery::BatteryPower>(),
+    entry::<battery::BatteryHealth>(),
+    entry::<battery::BatteryInfo>(),
+    entry::<battery::ChargeThreshold>(),
+    entry::<battery::Ac>(),
+    entry::<audio::Volume>(),
+    entry::<backlight::Backlight>(),
+    entry::<memory::Memory>(),
+    entry::<daylight::Daylight>(),
+    entry::<weather::Weather>(),
+    entry::<calendar::NextEvent>(),
+    entry::<usage::ScreenTime>(),
+    entry::<usage::UsageReport>(),
+    entry::<mounts::Mounts>(),
+    entry::<cpu::Throttle>(),
+    entry::<gamemode::GameMode>(),
+    entry::<privacy::Privacy>(),
+    entry::<dnd::Dnd>(),
+    entry::<caffeine::Caffeine>(),
+    entry::<net::Flaps>(),
+    entry::<top::Top>(),
+    entry::<bluetooth::Bluetooth>(),
+    entry::<media::Media>(),
+    entry::<audio::Mic>(),
+    entry::<kbd_backlight::KbdBacklight>(),
+    entry::<cpu::CpuFreq>(),
+    entry::<power_profile::PowerProfile>(),
+    entry::<net::Vpn>(),
+    entry::<net::Connectivity>(),
+    entry::<net::Ip>(),
+    entry::<diskio::DiskIo>(),
+    entry::<nvme::DiskTemp>(),
+    entry::<hwmon::Temp>(),
+    entry::<count::Count>(),
+    entry::<Script>(),
+    entry::<Plugin>(),
+    entry::<cpu::Cpu>(),
+    entry::<net::NetRate>(),
+    entry::<pressure::Pressure>(),
+    entry::<cgroup::Cgroup>(),
+    entry::<containers::Containers>(),
+    entry::<systemd::FailedUnits>(),
+    entry::<updates::Updates>(),
+    entry::<clipboard::Clipboard>(),
+    entry::<timer::Timer>(),
+    entry::<upower::Peripherals>(),
+    entry::<battery::ChargerInfo>(),
+    entry::<nightlight::Nightlight>(),
+    entry::<wm::Workspace>(),
+    entry::<wm::WindowTitle>(),
+    entry::<locks::Locks>(),
+    entry::<gpu::GpuTemp>(),
+    entry::<lid::Lid>(),
+    entry::<lid::Docked>(),
+    entry::<idle::Idle>(),
+    entry::<idle::Locked>(),
+    entry::<wifi::WifiDetail>(),
+];
+
+// 所有内置模块的名称
+pub fn names() -> impl Iterator<Item = &'static str> {
+    REGISTRY.iter().map(|entry| entry.id)
+}
+
+// 按模块名查找
+pub fn get(id: &str) -> Option<&'static Entry> {
+    REGISTRY.iter().find(|entry| entry.id == id)
+}
+
+// 按模块名或输出块名（如 volume-level 或 volume）查找，块名对应多个模块时取第一个
+pub fn find(name: &str) -> Option<&'static Entry> {
+    get(name).or_else(|| REGISTRY.iter().find(|entry| entry.block == name))
+}
+
+// 按刷新计划创建模块实例，未知的模块名返回 None
+pub fn create(schedule: &Schedule, matches: &clap::ArgMatches) -> Option<Box<dyn Module>> {
+    let entry = get(schedule.name)?;
+    Some(match (schedule.command, schedule.path) {
+        (Some(command), _) => Box::new(Script::command(command, schedule.label)),
+        (None, Some(path)) => Box::new(Plugin::load(path)),
+        (None, None) => entry.create(matches),
+    })
+}
+
+// 按模块名或输出块名创建内置模块，用于 --stdin 等按名称查询的场景
+pub fn builtin(name: &str, matches: &clap::ArgMatches) -> Option<Box<dyn Module>> {
+    Some(find(name)?.create(matches))
+}
+
+// 读取一次模块，用于 --prompt-segment、OSD 等只读取一次的场景
+pub fn read(name: &str, matches: &clap::ArgMatches) -> MetricValue {
+    get(name)
+        .ok_or_else(|| Error::MissingDevice(format!("unknown module '{}'", name)))?
+        .create(matches)
+        .read()
+}
+
+// 读取一个模块，模块 panic 时按读取失败处理（输出 "Unknown"），不影响其他模块和整个状态栏
+fn read_guarded(module: &mut Box<dyn Module>) -> MetricValue {
+    let id = module.id().to_string();
+    panic::catch_unwind(AssertUnwindSafe(|| module.read()))
+        .unwrap_or_else(|_| Err(Error::BackendUnavailable(format!("{} panicked", id))))
+}
+
+// 并行读取多个模块（每个模块一个线程），结果与 modules 的顺序一致
+// 音量、D-Bus 等模块需要启动子进程，串行读取时耗时会累加
+pub fn read_parallel(modules: Vec<&mut Box<dyn Module>>) -> Vec<MetricValue> {
+    if modules.len() <= 1 {
+        return modules.into_iter().map(read_guarded).collect();
+    }
+    thread::scope(|scope| {
+        let handles: Vec<_> = modules
+            .into_iter()
+            .map(|module| scope.spawn(move || read_guarded(module)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle.join().unwrap_or_else(|_| {
+                    Err(Error::BackendUnavailable("module panicked".to_string()))
+                })
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn creates_modules_by_name() {
+        let matches = Arc::new(crate::args::build_cli().get_matches_from(["sys-montion"]));
+        let schedule = |name| Schedule {
+            name,
+            interval: 1,
+            signal: None,
+            command: None,
+            path: None,
+            label: None,
+            actions: [None; 5],
+            show_if: None,
+            hide_if: None,
+        };
+        let volume = create(&schedule("volume-level"), &matches).unwrap();
+        assert_eq!(volume.id(), "volume-level");
+        assert!(volume.interests().contains(EventInterest::AUDIO));
+        assert!(!volume.interests().contains(EventInterest::MOUNTS));
+        assert!(create(&schedule("no-such-module"), &matches).is_none());
+        assert_eq!(builtin("volume", &matches).unwrap().id(), "volume-level");
+        assert!(create(&schedule("mounts"), &matches)
+            .unwrap()
+            .interests()
+            .contains(EventInterest::MOUNTS));
+        // 每个模块名只登记一次，没有模块参数时也能创建
+        for (i, entry) in REGISTRY.iter().enumerate() {
+            let first = REGISTRY.iter().position(|other| other.id == entry.id);
+            assert_eq!(first, Some(i), "{} registered twice", entry.id);
+            assert_eq!(entry.create(&matches).id(), entry.id);
+        }
+    }
+
+    struct Panicking;
+
+    impl Module for Panicking {
+        fn id(&self) -> &str {
+            "panicking"
+        }
+
+        fn read(&mut self) -> MetricValue {
+            panic!("bug in module")
+        }
+    }
+
+    #[test]
+    fn survives_panicking_modules() {
+        let mut modules: Vec<Box<dyn Module>> = vec![
+            Box::new(Panicking),
+            Box::new(Script::command("echo 42", None)),
+        ];
+        let results = read_parallel(modules.iter_mut().collect());
+        assert!(
+            matches!(&results[0], Err(Error::BackendUnavailable(e)) if e == "panicking panicked")
+        );
+        assert_eq!(results[1].as_ref().unwrap().text, "42");
+        assert!(read_parallel(modules.iter_mut().take(1).collect())[0].is_err());
+    }
+}
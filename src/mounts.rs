@@ -0,0 +1,239 @@
+use crate::dbus::{self, Bus};
+use crate::error::Error;
+use crate::event_loop::EventLoop;
+use crate::module::{Builtin, EventInterest, MetricValue};
+use crate::render::Block;
+use crate::watch::Wake;
+use crate::{format_size, read_file, sysfs};
+use rustix::event::epoll::EventFlags;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+
+// 块设备挂载点
+pub struct Mount {
+    pub source: String,
+    pub mount_point: String,
+}
+
+// mountinfo 中空格等字符以八进制转义，如 "\040"
+fn unescape(field: &str) -> String {
+    let mut result = String::new();
+    let mut chars = field.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let code: String = chars.by_ref().take(3).collect();
+            if let Ok(byte) = u8::from_str_radix(&code, 8) {
+                result.push(byte as char);
+                continue;
+            }
+            result.push(c);
+            result.push_str(&code);
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+// 判断块设备是否可移动：设备或其所属磁盘的 removable 为 1
+fn is_removable(source: &str) -> bool {
+    let Some(name) = source.strip_prefix("/dev/") else {
+        return false;
+    };
+    let path = Path::new("/sys/class/block").join(name);
+    read_file(path.join("removable")).is_ok_and(|s| s == "1")
+        || read_file(path.join("../removable")).is_ok_and(|s| s == "1")
+}
+
+// 读取 /proc/self/mountinfo 中所有块设备的挂载点
+fn list_block_mounts() -> Result<Vec<Mount>, io::Error> {
+    let mountinfo = read_file(MOUNTINFO_PATH)?;
+    let mut mounts = Vec::new();
+    for line in mountinfo.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // 可选字段之后以 "-" 分隔，其后依次为文件系统类型和挂载源
+        let Some(sep) = fields.iter().position(|&f| f == "-") else {
+            continue;
+        };
+        let (Some(mount_point), Some(source)) = (fields.get(4), fields.get(sep + 2)) else {
+            continue;
+        };
+        if source.starts_with("/dev/") {
+            mounts.push(Mount {
+                source: unescape(source),
+                mount_point: unescape(mount_point),
+            });
+        }
+    }
+    Ok(mounts)
+}
+
+// 找出可移动设备的挂载点
+// 除 removable 标记外，挂载在 /media 或 /run/media 下的设备也视为可移动设备
+pub fn list_removable() -> Result<Vec<Mount>, io::Error> {
+    let mut mounts = list_block_mounts()?;
+    mounts.retain(|mount| {
+        mount.mount_point.starts_with("/media/")
+            || mount.mount_point.starts_with("/run/media/")
+            || is_removable(&mount.source)
+    });
+    Ok(mounts)
+}
+
+// 读取挂载点的可用空间（字节）
+pub fn free_space(mount_point: &str) -> Result<u64, io::Error> {
+    let stat = rustix::fs::statvfs(mount_point)?;
+    Ok(stat.f_bavail * stat.f_frsize)
+}
+
+// 输出已挂载的可移动设备（挂载点目录名）及其可用空间，如 "USB: STICK 12.3G free"
+pub fn get_mounts() -> Result<String, Error> {
+    let mounts = list_removable()?;
+    if mounts.is_empty() {
+        return Ok("USB: none".to_string());
+    }
+    let entries: Vec<String> = mounts
+        .iter()
+        .map(|mount| {
+            let name = mount.mount_point.rsplit('/').next().unwrap_or_default();
+            match free_space(&mount.mount_point) {
+                Ok(free) => format!("{} {} free", name, format_size(free)),
+                Err(_) => name.to_string(),
+            }
+        })
+        .collect();
+    Ok(format!("USB: {}", entries.join(" | ")))
+}
+
+// 监听挂载表变化，内核在 mountinfo 变化时触发 POLLPRI
+pub fn register(events: &mut EventLoop) -> Result<(), io::Error> {
+    events.add(
+        File::open(sysfs::path(MOUNTINFO_PATH))?,
+        EventFlags::PRI,
+        |_, woken| {
+            woken.push(Wake::All);
+            Ok(())
+        },
+    )
+}
+
+// 分区所属的磁盘名，如 sdb1 -> sdb，nvme0n1p1 -> nvme0n1；本身是磁盘时原样返回
+fn disk_of(name: &str) -> String {
+    let path = Path::new("/sys/class/block").join(name);
+    if !sysfs::exists(path.join("partition")) {
+        return name.to_string();
+    }
+    fs::canonicalize(sysfs::path(path.join("..")))
+        .ok()
+        .and_then(|p| Some(p.file_name()?.to_string_lossy().to_string()))
+        .unwrap_or_else(|| name.to_string())
+}
+
+// udisks2 对象路径中非字母数字字符转义为 _xx
+fn udisks_escape(name: &str) -> String {
+    name.bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() {
+                (b as char).to_string()
+            } else {
+                format!("_{:02x}", b)
+            }
+        })
+        .collect()
+}
+
+fn udisks_call(object_path: &str, method: &str, args: &[&str]) -> Result<String, io::Error> {
+    dbus::call(
+        Bus::System,
+        "org.freedesktop.UDisks2",
+        object_path,
+        method,
+        args,
+    )
+}
+
+// 安全弹出设备：同步缓存、卸载该磁盘的所有分区并断电
+// 参数可以是设备（/dev/sdb1、sdb、/dev/disk/by-label/STICK）或挂载点
+pub fn eject(target: &str) -> Result<(), io::Error> {
+    let mounts = list_block_mounts()?;
+    let device = match mounts.iter().find(|m| m.mount_point == target) {
+        Some(mount) => mount.source.clone(),
+        None if target.starts_with('/') => target.to_string(),
+        None => format!("/dev/{}", target),
+    };
+    let device = fs::canonicalize(&device)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", device, e)))?;
+    let name = device
+        .strip_prefix("/dev")
+        .ok()
+        .map(|p| p.to_string_lossy().to_string())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a block device", device.display()),
+            )
+        })?;
+    let disk = disk_of(&name);
+
+    rustix::fs::sync();
+
+    for mount in &mounts {
+        let Some(part) = mount.source.strip_prefix("/dev/") else {
+            continue;
+        };
+        if disk_of(part) != disk {
+            continue;
+        }
+        let path = format!(
+            "/org/freedesktop/UDisks2/block_devices/{}",
+            udisks_escape(part)
+        );
+        udisks_call(&path, "org.freedesktop.UDisks2.Filesystem.Unmount", &["{}"])
+            .map_err(|e| io::Error::other(format!("unmounting {}: {}", mount.mount_point, e)))?;
+    }
+
+    // 通过块设备的 Drive 属性找到对应的驱动器对象，如 "(<objectpath '/org/.../drives/...'>,)"
+    let block = format!(
+        "/org/freedesktop/UDisks2/block_devices/{}",
+        udisks_escape(&disk)
+    );
+    let reply = udisks_call(
+        &block,
+        "org.freedesktop.DBus.Properties.Get",
+        &["org.freedesktop.UDisks2.Block", "Drive"],
+    )?;
+    let drive = reply
+        .split('\'')
+        .nth(1)
+        .filter(|path| *path != "/")
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no drive found for /dev/{}", disk),
+            )
+        })?;
+    udisks_call(drive, "org.freedesktop.UDisks2.Drive.PowerOff", &["{}"])?;
+    Ok(())
+}
+
+pub struct Mounts;
+
+impl Builtin for Mounts {
+    const ID: &'static str = "mounts";
+    const BLOCK: &'static str = "mounts";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Mounts
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_mounts()?))
+    }
+
+    fn interests(&self) -> EventInterest {
+        EventInterest::MOUNTS
+    }
+}
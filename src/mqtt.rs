@@ -0,0 +1,200 @@
+use crate::log;
+use crate::metrics::{self, Metric};
+use crate::read_file;
+use crate::render::json_escape;
+use std::env;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+// MQTT 3.1.1 最小客户端：只实现 CONNECT、QoS 0 PUBLISH 和 DISCONNECT
+pub struct Options {
+    // 服务器地址，如 "homeassistant.local:1883"
+    pub addr: String,
+    // 主题前缀，指标发布到 "{prefix}/{node}/{name}"
+    pub prefix: String,
+    pub username: Option<String>,
+    // 是否发布 Home Assistant MQTT discovery 配置
+    pub discovery: bool,
+    pub interval: Duration,
+}
+
+// 追加 MQTT 剩余长度（变长编码）
+fn push_length(packet: &mut Vec<u8>, mut len: usize) {
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+}
+
+// 追加带 2 字节长度前缀的字符串
+fn push_string(body: &mut Vec<u8>, s: &str) {
+    body.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    body.extend_from_slice(s.as_bytes());
+}
+
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    push_length(&mut packet, body.len());
+    packet.extend_from_slice(body);
+    packet
+}
+
+struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    // 连接服务器，密码从环境变量 SYS_MONTION_MQTT_PASSWORD 读取，避免出现在进程参数中
+    fn connect(options: &Options, client_id: &str) -> Result<Self, io::Error> {
+        let addr = if options.addr.contains(':') {
+            options.addr.clone()
+        } else {
+            format!("{}:1883", options.addr)
+        };
+        let mut stream = TcpStream::connect(addr)?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+
+        let password = env::var("SYS_MONTION_MQTT_PASSWORD").ok();
+        let mut flags = 0x02; // clean session
+        if options.username.is_some() {
+            flags |= 0x80;
+            if password.is_some() {
+                flags |= 0x40;
+            }
+        }
+        // 保活时间设为发布间隔的两倍，期间每次发布都会刷新
+        let keep_alive = (options.interval.as_secs() * 2).min(u16::MAX as u64) as u16;
+
+        let mut body = Vec::new();
+        push_string(&mut body, "MQTT");
+        body.push(4);
+        body.push(flags);
+        body.extend_from_slice(&keep_alive.to_be_bytes());
+        push_string(&mut body, client_id);
+        if let Some(username) = &options.username {
+            push_string(&mut body, username);
+            if let Some(password) = &password {
+                push_string(&mut body, password);
+            }
+        }
+        stream.write_all(&packet(0x10, &body))?;
+
+        let mut connack = [0u8; 4];
+        stream.read_exact(&mut connack)?;
+        if connack[0] != 0x20 || connack[3] != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("broker refused connection (code {})", connack[3]),
+            ));
+        }
+        Ok(Client { stream })
+    }
+
+    fn publish(&mut self, topic: &str, payload: &str, retain: bool) -> Result<(), io::Error> {
+        let mut body = Vec::new();
+        push_string(&mut body, topic);
+        body.extend_from_slice(payload.as_bytes());
+        let header = if retain { 0x31 } else { 0x30 };
+        self.stream.write_all(&packet(header, &body))
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.stream.write_all(&[0xE0, 0x00]);
+    }
+}
+
+fn sample_id(metric: &Metric, label: &str) -> String {
+    if label.is_empty() {
+        metric.name.to_string()
+    } else {
+        format!("{}_{}", metric.name, label)
+    }
+}
+
+// Home Assistant discovery 配置：每个样本注册为同一设备下的一个传感器
+fn discovery_payload(node: &str, state_topic: &str, metric: &Metric, id: &str) -> String {
+    let device_class = match metric.unit {
+        "W" => ",\"device_class\":\"power\"",
+        "B" => ",\"device_class\":\"data_size\"",
+        "°C" => ",\"device_class\":\"temperature\"",
+        "%" if metric.name == "battery_capacity" => ",\"device_class\":\"battery\"",
+        _ => "",
+    };
+    let unit = if metric.unit.is_empty() {
+        String::new()
+    } else {
+        format!(",\"unit_of_measurement\":\"{}\"", json_escape(metric.unit))
+    };
+    format!(
+        "{{\"name\":\"{}\",\"state_topic\":\"{}\",\"unique_id\":\"{}_{}\",\
+         \"state_class\":\"measurement\"{}{},\
+         \"device\":{{\"identifiers\":[\"sys-montion-{}\"],\"name\":\"{}\"}}}}",
+        id,
+        json_escape(state_topic),
+        node,
+        id,
+        unit,
+        device_class,
+        node,
+        node
+    )
+}
+
+fn publish_all(
+    client: &mut Client,
+    options: &Options,
+    node: &str,
+    battery_device: &str,
+    discovery: bool,
+) -> Result<(), io::Error> {
+    for metric in metrics::collect(battery_device) {
+        for (label, value) in &metric.samples {
+            let id = sample_id(&metric, label);
+            let topic = format!("{}/{}/{}", options.prefix, node, id);
+            if discovery {
+                let config = format!("homeassistant/sensor/{}/{}/config", node, id);
+                client.publish(
+                    &config,
+                    &discovery_payload(node, &topic, &metric, &id),
+                    true,
+                )?;
+            }
+            client.publish(&topic, &value.to_string(), false)?;
+        }
+    }
+    Ok(())
+}
+
+// 按间隔持续发布指标；连接断开时在下一个周期重连，并重新发布 discovery 配置
+pub fn run(options: &Options, battery_device: &str) {
+    let node = read_file("/proc/sys/kernel/hostname").unwrap_or_else(|_| "localhost".to_string());
+    let client_id = format!("sys-montion-{}", node);
+    let mut client: Option<Client> = None;
+    loop {
+        let discovery = options.discovery && client.is_none();
+        if client.is_none() {
+            match Client::connect(options, &client_id) {
+                Ok(connected) => client = Some(connected),
+                Err(e) => log::error!("connecting to MQTT broker {}: {}", options.addr, e),
+            }
+        }
+        if let Some(connected) = client.as_mut() {
+            if let Err(e) = publish_all(connected, options, &node, battery_device, discovery) {
+                log::error!("publishing to MQTT broker: {}", e);
+                client = None;
+            }
+        }
+        thread::sleep(options.interval);
+    }
+}
@@ -0,0 +1,385 @@
+use crate::dbus::{self, Bus};
+use crate::error::Error;
+use crate::module::{self, Builtin, MetricValue};
+use crate::render::Block;
+use crate::timeutil::now_secs;
+use crate::{counters, locale, read_file, sysfs};
+use std::collections::{BTreeMap, VecDeque};
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const NET_PATH: &str = "/sys/class/net";
+
+// 统计窗口：最近一小时
+const FLAP_WINDOW: i64 = 3600;
+
+// 各网卡的 (时间, 断开次数) 采样，watch 模式下每次刷新追加一条
+static HISTORY: Mutex<BTreeMap<String, VecDeque<(i64, i64)>>> = Mutex::new(BTreeMap::new());
+
+// 网卡的 sysfs 目录，如 /sys/class/net/wlan0
+pub fn iface_path(iface: &str) -> PathBuf {
+    Path::new(NET_PATH).join(iface)
+}
+
+// 读取网卡开机以来的断开次数：优先 carrier_down_count，旧内核用 carrier_changes / 2
+fn read_down_count(iface: &str) -> Result<i64, io::Error> {
+    let path = iface_path(iface);
+    let parse = |s: String| {
+        s.parse::<i64>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    };
+    match read_file(path.join("carrier_down_count")) {
+        Ok(count) => parse(count),
+        Err(_) => Ok(parse(read_file(path.join("carrier_changes"))?)? / 2),
+    }
+}
+
+// 输出最近一小时内网卡掉线（carrier down）的次数，如 "NET: 3 flaps/h (wlan0)"
+// 在 watch 模式中持续采样；刚启动时统计的是启动以来的次数
+pub fn get_flaps(iface: &str) -> Result<String, Error> {
+    let count = read_down_count(iface)?;
+    let now = now_secs();
+
+    let mut history = HISTORY.lock().unwrap();
+    let samples = history.entry(iface.to_string()).or_default();
+    // 网卡被重新创建时计数器归零，丢弃旧采样
+    if samples.back().is_some_and(|&(_, last)| count < last) {
+        samples.clear();
+    }
+    samples.push_back((now, count));
+    // 保留窗口起点之前的最后一个采样作为基准
+    while samples.len() > 1 && samples[1].0 <= now - FLAP_WINDOW {
+        samples.pop_front();
+    }
+    let baseline = samples.front().map_or(count, |&(_, c)| c);
+
+    Ok(format!("NET: {} flaps/h ({})", count - baseline, iface))
+}
+
+// 判断网卡是否为 VPN 隧道：WireGuard（DEVTYPE=wireguard）或 tun/tap（有 tun_flags）
+// NetworkManager 的 OpenVPN、OpenConnect 等连接同样使用 tun 设备
+fn is_tunnel(iface: &str) -> bool {
+    let path = iface_path(iface);
+    let wireguard = read_file(path.join("uevent"))
+        .is_ok_and(|uevent| uevent.lines().any(|line| line == "DEVTYPE=wireguard"));
+    wireguard || sysfs::exists(path.join("tun_flags"))
+}
+
+// 列出已启用的 VPN 网卡，没有时输出为空
+// 隧道设备没有 carrier，operstate 通常为 unknown，只排除 down
+pub fn get_vpn() -> Result<String, Error> {
+    let mut tunnels = Vec::new();
+    for entry in sysfs::read_dir(NET_PATH)? {
+        let iface = entry?.file_name().to_string_lossy().to_string();
+        let state = read_file(iface_path(&iface).join("operstate")).unwrap_or_default();
+        if state != "down" && is_tunnel(&iface) {
+            tunnels.push(iface);
+        }
+    }
+    tunnels.sort();
+    if tunnels.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(format!("VPN: {}", tunnels.join(", ")))
+}
+
+// 是否存在默认路由（/proc/net/route 中目标为 00000000 的条目）
+fn has_default_route() -> Result<bool, io::Error> {
+    let routes = read_file("/proc/net/route")?;
+    Ok(routes
+        .lines()
+        .skip(1)
+        .any(|line| line.split_whitespace().nth(1) == Some("00000000")))
+}
+
+// 输出网络连通状态：online、limited、captive portal 或 offline
+// 优先使用 NetworkManager 的连通性检测，不可用时只根据是否有默认路由判断
+pub fn get_connectivity() -> Result<String, Error> {
+    let connectivity = dbus::get_property(
+        Bus::System,
+        "org.freedesktop.NetworkManager",
+        "/org/freedesktop/NetworkManager",
+        "org.freedesktop.NetworkManager",
+        "Connectivity",
+    );
+    // NMConnectivityState：1 none，2 portal，3 limited，4 full
+    let state = match connectivity
+        .as_deref()
+        .map(|v| v.trim_start_matches("uint32 "))
+    {
+        Ok("4") => "online",
+        Ok("3") => "limited",
+        Ok("2") => "captive portal",
+        Ok("1") => "offline",
+        _ if has_default_route()? => "online",
+        _ => "offline",
+    };
+    Ok(format!("NET: {}", state))
+}
+
+// 解析 /proc/net 中的十六进制 IPv4 地址，内核按网络字节序的原始值输出
+fn parse_hex_ipv4(hex: &str) -> Option<Ipv4Addr> {
+    let raw = u32::from_str_radix(hex, 16).ok()?;
+    Some(Ipv4Addr::from(raw.to_ne_bytes()))
+}
+
+// 读取 IPv4 路由表，返回 (网卡, 目标, 掩码, metric)
+fn read_routes() -> Result<Vec<(String, Ipv4Addr, Ipv4Addr, i64)>, io::Error> {
+    let routes = read_file("/proc/net/route")?;
+    Ok(routes
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            Some((
+                fields.first()?.to_string(),
+                parse_hex_ipv4(fields.get(1)?)?,
+                parse_hex_ipv4(fields.get(7)?)?,
+                fields.get(6)?.parse().ok()?,
+            ))
+        })
+        .collect())
+}
+
+// 默认路由所在网卡，有多条时取 metric 最小的；没有 IPv4 默认路由时查 IPv6
+fn default_interface() -> Result<Option<String>, io::Error> {
+    let ipv4 = read_routes()?
+        .into_iter()
+        .filter(|(_, dest, _, _)| dest.is_unspecified())
+        .min_by_key(|(_, _, _, metric)| *metric)
+        .map(|(iface, _, _, _)| iface);
+    if ipv4.is_some() {
+        return Ok(ipv4);
+    }
+    // ipv6_route 每行：目标 前缀长度 源 源前缀 下一跳 metric 引用 使用 标志 网卡
+    let routes = read_file("/proc/net/ipv6_route").unwrap_or_default();
+    Ok(routes.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let default = fields.first()?.chars().all(|c| c == '0') && fields.get(1)? == &"00";
+        let iface = fields.get(9)?;
+        (default && *iface != "lo").then(|| iface.to_string())
+    }))
+}
+
+// 本机的 IPv4 地址：/proc/net/fib_trie 中标记为 "/32 host LOCAL" 的条目
+fn local_ipv4_addresses() -> Result<Vec<Ipv4Addr>, io::Error> {
+    let trie = read_file("/proc/net/fib_trie")?;
+    let mut addresses = Vec::new();
+    let mut last = None;
+    for line in trie.lines() {
+        let line = line.trim();
+        if let Some(addr) = line.strip_prefix("|-- ") {
+            last = addr.parse::<Ipv4Addr>().ok();
+        } else if line == "/32 host LOCAL" {
+            if let Some(addr) = last.filter(|a| !a.is_loopback() && !addresses.contains(a)) {
+                addresses.push(addr);
+            }
+        }
+    }
+    Ok(addresses)
+}
+
+// 网卡的 IPv4 地址：落在该网卡直连路由网段内的本机地址
+fn interface_ipv4(iface: &str) -> Result<Option<Ipv4Addr>, io::Error> {
+    let locals = local_ipv4_addresses()?;
+    let subnets: Vec<(u32, u32)> = read_routes()?
+        .into_iter()
+        .filter(|(name, dest, _, _)| name == iface && !dest.is_unspecified())
+        .map(|(_, dest, mask, _)| (u32::from(dest), u32::from(mask)))
+        .collect();
+    Ok(locals.into_iter().find(|addr| {
+        subnets
+            .iter()
+            .any(|&(dest, mask)| u32::from(*addr) & mask == dest)
+    }))
+}
+
+// 网卡的全局 IPv6 地址（/proc/net/if_inet6 中 scope 为 00 的条目）
+fn interface_ipv6(iface: &str) -> Option<Ipv6Addr> {
+    let addresses = read_file("/proc/net/if_inet6").ok()?;
+    addresses.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.get(3)? != &"00" || fields.get(5)? != &iface {
+            return None;
+        }
+        u128::from_str_radix(fields.first()?, 16)
+            .ok()
+            .map(Ipv6Addr::from)
+    })
+}
+
+// 输出网卡的主地址，优先 IPv4，如 "IP: 192.168.1.23 (wlan0)"
+// iface 为 "default" 时使用默认路由所在的网卡
+pub fn get_ip(iface: &str) -> Result<String, Error> {
+    let iface = if iface == "default" {
+        default_interface()?.ok_or_else(|| Error::MissingDevice("no default route".to_string()))?
+    } else {
+        iface.to_string()
+    };
+    let address = match interface_ipv4(&iface)? {
+        Some(addr) => addr.to_string(),
+        None => interface_ipv6(&iface)
+            .ok_or_else(|| Error::MissingDevice(format!("no address on {}", iface)))?
+            .to_string(),
+    };
+    Ok(format!("IP: {} ({})", address, iface))
+}
+
+// 输出网卡收发速率，如 "NET: RX 1.2 MB/s TX 0.3 MB/s"
+// iface 为 "default" 时使用默认路由所在的网卡
+pub fn get_net_rate(iface: &str) -> Result<String, Error> {
+    let iface = if iface == "default" {
+        default_interface()?.ok_or_else(|| Error::MissingDevice("no default route".to_string()))?
+    } else {
+        iface.to_string()
+    };
+    let statistics = iface_path(&iface).join("statistics");
+    let (elapsed, previous, current) = counters::delta(&format!("net:{}", iface), || {
+        let read = |file: &str| -> Result<u64, Error> {
+            let path = statistics.join(file);
+            read_file(&path)?
+                .parse()
+                .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))
+        };
+        Ok(vec![read("rx_bytes")?, read("tx_bytes")?])
+    })?;
+    let rate = |i: usize| {
+        let before = previous.get(i).copied().unwrap_or(current[i]);
+        let mb = current[i].saturating_sub(before) as f64 / 1e6 / elapsed;
+        locale::format_number(mb, 1)
+    };
+    Ok(format!("NET: RX {} MB/s TX {} MB/s", rate(0), rate(1)))
+}
+
+pub struct Flaps {
+    iface: Option<String>,
+}
+
+impl Builtin for Flaps {
+    const ID: &'static str = "flaps";
+    const BLOCK: &'static str = "net";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Flaps {
+            iface: matches.get_one::<String>("flaps").cloned(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let iface = module::required(&self.iface, "flaps", "--flaps IFACE")?;
+        Ok(Block::numeric(Self::BLOCK, get_flaps(iface)?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        match &self.iface {
+            None => Some("needs --flaps IFACE".to_string()),
+            Some(iface) => (!sysfs::exists(iface_path(iface)))
+                .then(|| format!("interface {} not found", iface)),
+        }
+    }
+}
+
+pub struct Vpn;
+
+impl Builtin for Vpn {
+    const ID: &'static str = "vpn";
+    const BLOCK: &'static str = "vpn";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Vpn
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_vpn()?))
+    }
+}
+
+pub struct Connectivity;
+
+impl Builtin for Connectivity {
+    const ID: &'static str = "connectivity";
+    const BLOCK: &'static str = "net";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Connectivity
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_connectivity()?))
+    }
+}
+
+// --ip 和 --net-rate 不带接口名时使用默认路由的接口
+fn iface_arg(matches: &clap::ArgMatches, id: &str) -> String {
+    matches
+        .get_one::<String>(id)
+        .map_or("default", String::as_str)
+        .to_string()
+}
+
+pub struct Ip {
+    iface: String,
+}
+
+impl Builtin for Ip {
+    const ID: &'static str = "ip";
+    const BLOCK: &'static str = "net";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Ip {
+            iface: iface_arg(matches, Self::ID),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_ip(&self.iface)?))
+    }
+}
+
+pub struct NetRate {
+    iface: String,
+}
+
+impl Builtin for NetRate {
+    const ID: &'static str = "net-rate";
+    const BLOCK: &'static str = "net";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        NetRate {
+            iface: iface_arg(matches, Self::ID),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, get_net_rate(&self.iface)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_active_tunnels() {
+        sysfs::use_fixtures();
+        // tun0 已关闭，eth0 不是隧道
+        assert_eq!(get_vpn().unwrap(), "VPN: wg0");
+    }
+
+    #[test]
+    fn reads_default_route() {
+        sysfs::use_fixtures();
+        assert!(has_default_route().unwrap());
+        assert_eq!(default_interface().unwrap().as_deref(), Some("eth0"));
+    }
+
+    #[test]
+    fn finds_interface_addresses() {
+        sysfs::use_fixtures();
+        assert_eq!(get_ip("default").unwrap(), "IP: 192.168.1.23 (eth0)");
+        // wg0 没有 IPv4 路由，退回到全局 IPv6 地址
+        assert_eq!(get_ip("wg0").unwrap(), "IP: fd00::2 (wg0)");
+    }
+}
@@ -0,0 +1,190 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::paths::runtime_path;
+use crate::render::Block;
+use crate::timeutil::{local_utc_offset, now_secs};
+use crate::{daylight, locale, read_file, sysfs};
+use rustix::process::{kill_process, Pid, Signal};
+use std::fs;
+use std::io;
+
+const DAEMONS: [&str; 3] = ["gammastep", "redshift", "wlsunset"];
+
+// 这些守护进程收到 SIGUSR1 时切换开关，但不提供查询接口，
+// 因此关闭时把守护进程的 PID 记录在运行时目录中，PID 不同（守护进程已重启）时视为开启
+const PAUSED_FILE: &str = "sys-montion-nightlight-paused";
+
+struct Daemon {
+    pid: i32,
+    name: &'static str,
+    // 守护进程的命令行参数，不含程序名
+    args: Vec<String>,
+}
+
+// 在 /proc 中查找正在运行的色温守护进程
+fn find_daemon() -> Option<Daemon> {
+    for entry in sysfs::read_dir("/proc").ok()?.flatten() {
+        let Ok(pid) = entry.file_name().to_string_lossy().parse::<i32>() else {
+            continue;
+        };
+        let Ok(cmdline) = read_file(format!("/proc/{}/cmdline", pid)) else {
+            continue;
+        };
+        let mut args = cmdline.split('\0').filter(|arg| !arg.is_empty());
+        let program = args.next().unwrap_or_default();
+        let program = program.rsplit('/').next().unwrap_or_default();
+        if let Some(name) = DAEMONS.iter().find(|&&name| name == program) {
+            return Some(Daemon {
+                pid,
+                name,
+                args: args.map(str::to_string).collect(),
+            });
+        }
+    }
+    None
+}
+
+fn paused(daemon: &Daemon) -> bool {
+    runtime_path(PAUSED_FILE)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .is_some_and(|pid| pid.trim() == daemon.pid.to_string())
+}
+
+// 参数 flag 之后的值，如 ["-t", "3500"] 中 -t 的值
+fn option<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    args.get(index + 1).map(String::as_str)
+}
+
+// "HH:MM" 转换为当天的分钟数
+fn parse_clock(value: &str) -> Option<i64> {
+    let (hour, minute) = value.split_once(':')?;
+    Some(hour.parse::<i64>().ok()? * 60 + minute.parse::<i64>().ok()?)
+}
+
+// gammastep 和 redshift 的 -p 按配置文件和命令行参数计算当前色温后退出，不会改变屏幕
+// 输出中包含 "Color temperature: 4500K"
+fn print_mode_temperature(daemon: &Daemon) -> Result<u32, Error> {
+    let output = locale::command(daemon.name)
+        .args(&daemon.args)
+        .arg("-p")
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                Error::BackendUnavailable(format!("{} is not in PATH", daemon.name))
+            }
+            _ => Error::from(e),
+        })?;
+    parse_temperature(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+        Error::Parse(format!(
+            "no color temperature in `{} -p` output",
+            daemon.name
+        ))
+    })
+}
+
+fn parse_temperature(output: &str) -> Option<u32> {
+    output.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("Color temperature:")?
+            .trim()
+            .strip_suffix('K')?
+            .parse()
+            .ok()
+    })
+}
+
+// wlsunset 没有打印模式，按其参数计算：-t 夜间色温（默认 4000），-T 白天色温（默认 6500），
+// 日出日落由 -S/-s 手动指定或由 -l/-L 经纬度计算；忽略日出日落前后的渐变
+fn wlsunset_temperature(args: &[String]) -> Result<u32, Error> {
+    let temperature = |flag: &str, default: u32| {
+        option(args, flag)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default)
+    };
+    let day = if let (Some(sunrise), Some(sunset)) = (
+        option(args, "-S").and_then(parse_clock),
+        option(args, "-s").and_then(parse_clock),
+    ) {
+        let minute = (now_secs() + local_utc_offset()).rem_euclid(86400) / 60;
+        (sunrise..sunset).contains(&minute)
+    } else if let (Some(lat), Some(lon)) = (
+        option(args, "-l").and_then(|value| value.parse().ok()),
+        option(args, "-L").and_then(|value| value.parse().ok()),
+    ) {
+        daylight::is_day(lat, lon)
+    } else {
+        return Err(Error::Parse(
+            "cannot tell wlsunset's schedule from its arguments".to_string(),
+        ));
+    };
+    Ok(if day {
+        temperature("-T", 6500)
+    } else {
+        temperature("-t", 4000)
+    })
+}
+
+// 输出当前色温，如 "NL: 4500K"；守护进程未运行或已用 `nightlight toggle` 关闭时为 "NL: off"
+pub fn get_nightlight() -> Result<String, Error> {
+    let Some(daemon) = find_daemon().filter(|daemon| !paused(daemon)) else {
+        return Ok("NL: off".to_string());
+    };
+    let temperature = match daemon.name {
+        "wlsunset" => wlsunset_temperature(&daemon.args)?,
+        _ => print_mode_temperature(&daemon)?,
+    };
+    Ok(format!("NL: {}K", temperature))
+}
+
+// 向守护进程发送 SIGUSR1 切换开关，返回切换后的状态
+pub fn toggle() -> Result<String, Error> {
+    let daemon = find_daemon().ok_or_else(|| {
+        Error::MissingDevice("no gammastep, redshift or wlsunset running".to_string())
+    })?;
+    let pid = Pid::from_raw(daemon.pid)
+        .ok_or_else(|| Error::Parse(format!("invalid PID {}", daemon.pid)))?;
+    kill_process(pid, Signal::USR1).map_err(io::Error::from)?;
+    let path = runtime_path(PAUSED_FILE)?;
+    if paused(&daemon) {
+        fs::remove_file(path)?;
+    } else {
+        fs::write(path, daemon.pid.to_string())?;
+    }
+    get_nightlight()
+}
+
+pub struct Nightlight;
+
+impl Builtin for Nightlight {
+    const ID: &'static str = "nightlight";
+    const BLOCK: &'static str = "nightlight";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Nightlight
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, get_nightlight()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_daemon_schedules() {
+        let gammastep = "Location: 31.23 N, 121.47 E\nPeriod: Night\nColor temperature: 4500K\nBrightness: 1.00\n";
+        assert_eq!(parse_temperature(gammastep), Some(4500));
+        let args: Vec<String> = ["-t", "3400", "-S", "00:00", "-s", "00:00"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        // 日出和日落相同时全天为夜间
+        assert_eq!(wlsunset_temperature(&args).unwrap(), 3400);
+        assert!(wlsunset_temperature(&[]).is_err());
+        assert_eq!(parse_clock("07:30"), Some(450));
+    }
+}
@@ -0,0 +1,477 @@
+use crate::dbus::{self, Bus};
+use crate::render::json_escape;
+use crate::timeutil::now_secs;
+use crate::{battery, hwmon, log, memory, script};
+use std::fs;
+use std::io;
+use std::process::Command;
+use std::time::Duration;
+
+// 电量回升超过阈值这么多后才重新允许提醒，避免在阈值附近反复通知
+const HYSTERESIS: i64 = 2;
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Urgency {
+    Low = 0,
+    Normal = 1,
+    Critical = 2,
+}
+
+impl Urgency {
+    fn name(self) -> &'static str {
+        match self {
+            Urgency::Low => "low",
+            Urgency::Normal => "normal",
+            Urgency::Critical => "critical",
+        }
+    }
+
+    // ntfy 的优先级：2 low，3 default，5 urgent
+    fn ntfy_priority(self) -> u8 {
+        match self {
+            Urgency::Low => 2,
+            Urgency::Normal => 3,
+            Urgency::Critical => 5,
+        }
+    }
+}
+
+// 告警（battery、memory、fan）的发送方式，在配置文件的 [[alerts]] 中选择
+#[derive(Debug, PartialEq)]
+pub enum Backend {
+    // 桌面通知（D-Bus）
+    Desktop,
+    // 以 JSON POST 到 URL
+    Webhook(String),
+    // 发布到 ntfy 主题，值为主题的完整 URL，如 https://ntfy.sh/my-laptop
+    Ntfy(String),
+    // 通过 sh -c 执行命令，告警内容在环境变量中
+    Command(String),
+}
+
+// 各告警的发送方式，一个告警可有多个；未配置的告警发送桌面通知
+pub struct Routes(Vec<(String, Backend)>);
+
+impl Routes {
+    pub fn new(routes: Vec<(String, Backend)>) -> Self {
+        Routes(routes)
+    }
+
+    fn backends(&self, alert: &str) -> Vec<&Backend> {
+        let backends: Vec<&Backend> = self
+            .0
+            .iter()
+            .filter(|(name, _)| name == alert)
+            .map(|(_, backend)| backend)
+            .collect();
+        if backends.is_empty() {
+            return vec![&Backend::Desktop];
+        }
+        backends
+    }
+
+    // 发送告警，失败时记录错误；webhook、ntfy 和命令在后台执行，不阻塞 watch 循环
+    fn send(&self, alert: &str, summary: &str, body: &str, urgency: Urgency) {
+        for backend in self.backends(alert) {
+            match backend {
+                Backend::Desktop => {
+                    if let Err(e) = send(summary, body, urgency) {
+                        log::error!("sending notification: {}", e);
+                    }
+                }
+                Backend::Webhook(url) => {
+                    let payload = webhook_payload(alert, summary, body, urgency);
+                    let mut command = Command::new("curl");
+                    command
+                        .args(["-fsS", "--max-time", "10"])
+                        .args(["-H", "Content-Type: application/json"])
+                        .args(["--data-raw", &payload, url]);
+                    run_in_background("webhook", command);
+                }
+                Backend::Ntfy(url) => {
+                    let mut command = Command::new("curl");
+                    command
+                        .args(["-fsS", "--max-time", "10"])
+                        .args(["-H", &format!("Title: {}", summary)])
+                        .args(["-H", &format!("Priority: {}", urgency.ntfy_priority())])
+                        .args(["--data-raw", body, url]);
+                    run_in_background("ntfy", command);
+                }
+                Backend::Command(hook) => {
+                    let mut command = Command::new("sh");
+                    command
+                        .arg("-c")
+                        .arg(hook)
+                        .env("SYS_MONTION_ALERT", alert)
+                        .env("SYS_MONTION_ALERT_SUMMARY", summary)
+                        .env("SYS_MONTION_ALERT_BODY", body)
+                        .env("SYS_MONTION_ALERT_URGENCY", urgency.name());
+                    run_in_background("alert command", command);
+                }
+            }
+        }
+    }
+}
+
+// webhook 的 JSON：{"alert":"battery","summary":...,"body":...,"urgency":"critical","host":...}
+fn webhook_payload(alert: &str, summary: &str, body: &str, urgency: Urgency) -> String {
+    let host = fs::read_to_string("/proc/sys/kernel/hostname").unwrap_or_default();
+    format!(
+        "{{\"alert\":\"{}\",\"summary\":\"{}\",\"body\":\"{}\",\"urgency\":\"{}\",\"host\":\"{}\"}}",
+        json_escape(alert),
+        json_escape(summary),
+        json_escape(body),
+        urgency.name(),
+        json_escape(host.trim())
+    )
+}
+
+// 在后台线程中运行命令并等待其退出，失败时记录错误
+fn run_in_background(what: &'static str, mut command: Command) {
+    std::thread::spawn(move || match command.output() {
+        Ok(output) if !output.status.success() => log::error!(
+            "sending {} alert: {}",
+            what,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Ok(_) => {}
+        Err(e) => log::error!("sending {} alert: {}", what, e),
+    });
+}
+
+// 通过 D-Bus 调用 org.freedesktop.Notifications 发送桌面通知
+pub fn send(summary: &str, body: &str, urgency: Urgency) -> Result<(), io::Error> {
+    notify(
+        summary,
+        body,
+        &format!("{{'urgency': <byte {}>}}", urgency as u8),
+        -1,
+    )
+}
+
+// 发送 OSD 通知：带 value 进度条提示，并用同一个 synchronous 标签替换上一条，
+// dunst/mako 等通知守护进程会显示为进度条
+pub fn send_osd(name: &str, summary: &str, value: i64) -> Result<(), io::Error> {
+    let tag = format!("sys-montion-{}", name);
+    let hints = format!(
+        "{{'urgency': <byte {}>, 'value': <int32 {}>, \
+         'x-canonical-private-synchronous': <'{}'>, 'x-dunst-stack-tag': <'{}'>}}",
+        Urgency::Low as u8,
+        value.clamp(0, 100),
+        tag,
+        tag
+    );
+    notify(summary, "", &hints, 1500)
+}
+
+fn notify(summary: &str, body: &str, hints: &str, timeout: i32) -> Result<(), io::Error> {
+    dbus::call(
+        Bus::Session,
+        "org.freedesktop.Notifications",
+        "/org/freedesktop/Notifications",
+        "org.freedesktop.Notifications.Notify",
+        &[
+            "sys-montion",
+            "0",
+            "",
+            summary,
+            body,
+            "[]",
+            hints,
+            &timeout.to_string(),
+        ],
+    )?;
+    Ok(())
+}
+
+// 低电量提醒：跨越警告/严重阈值时各提醒一次，严重状态下按间隔重复提醒
+pub struct BatteryNotifier {
+    warn: i64,
+    critical: i64,
+    repeat: Duration,
+    level: Option<Urgency>,
+    last_sent: Option<i64>,
+}
+
+impl BatteryNotifier {
+    pub fn new(warn: i64, critical: i64, repeat: Duration) -> Self {
+        BatteryNotifier {
+            warn,
+            critical,
+            repeat,
+            level: None,
+            last_sent: None,
+        }
+    }
+
+    // 每次刷新时调用，读取电量并按需发送通知
+    pub fn check(&mut self, device: &str, routes: &Routes) {
+        let capacity = battery::get_battery_capacity(device)
+            .ok()
+            .and_then(|c| c.parse::<i64>().ok());
+        let status = battery::get_battery_status(device).unwrap_or_default();
+        let Some(capacity) = capacity else {
+            return;
+        };
+
+        // 充电或电量回升后重置状态
+        if status == "Charging" || status == "Full" || capacity > self.warn + HYSTERESIS {
+            self.level = None;
+            return;
+        }
+
+        let level = if capacity <= self.critical {
+            Urgency::Critical
+        } else if capacity <= self.warn {
+            Urgency::Normal
+        } else {
+            return;
+        };
+
+        let escalated = self.level.is_none_or(|l| level > l);
+        let repeat_due = level == Urgency::Critical
+            && self
+                .last_sent
+                .is_some_and(|t| now_secs() - t >= self.repeat.as_secs() as i64);
+        if !escalated && !repeat_due {
+            return;
+        }
+
+        let summary = if level == Urgency::Critical {
+            "Battery critically low"
+        } else {
+            "Battery low"
+        };
+        routes.send(
+            "battery",
+            summary,
+            &format!("{}% remaining", capacity),
+            level,
+        );
+        self.level = Some(level);
+        self.last_sent = Some(now_secs());
+    }
+}
+
+// 内存紧张规则：可用内存低于阈值或 PSI 内存压力超过阈值时触发，
+// 可发送通知和/或执行用户命令，恢复后才会再次触发
+pub struct MemoryGuard {
+    min_available_kib: Option<i64>,
+    max_pressure: Option<f64>,
+    notify: bool,
+    hook: Option<String>,
+    triggered: bool,
+}
+
+impl MemoryGuard {
+    pub fn new(
+        min_available_mib: Option<i64>,
+        max_pressure: Option<f64>,
+        notify: bool,
+        hook: Option<String>,
+    ) -> Self {
+        MemoryGuard {
+            min_available_kib: min_available_mib.map(|mib| mib * 1024),
+            max_pressure,
+            notify,
+            hook,
+            triggered: false,
+        }
+    }
+
+    // 每次刷新时调用，检查内存状态并按需触发
+    pub fn check(&mut self, routes: &Routes) {
+        let available = memory::get_available_kib().ok();
+        let pressure = memory::get_memory_pressure().ok();
+
+        let low = matches!((self.min_available_kib, available), (Some(min), Some(a)) if a < min);
+        let stalled = matches!((self.max_pressure, pressure), (Some(max), Some(p)) if p > max);
+        if !low && !stalled {
+            self.triggered = false;
+            return;
+        }
+        if self.triggered {
+            return;
+        }
+        self.triggered = true;
+
+        let body = format!(
+            "{} MiB available, {:.1}% pressure",
+            available.unwrap_or(0) / 1024,
+            pressure.unwrap_or(0.0)
+        );
+        if self.notify {
+            routes.send("memory", "Memory running low", &body, Urgency::Critical);
+        }
+        if let Some(hook) = &self.hook {
+            script::spawn(
+                hook,
+                &[
+                    (
+                        "SYS_MONTION_MEM_AVAILABLE_KIB",
+                        available.unwrap_or(0).to_string(),
+                    ),
+                    (
+                        "SYS_MONTION_MEM_PRESSURE",
+                        pressure.unwrap_or(0.0).to_string(),
+                    ),
+                ],
+                "memory hook",
+            );
+        }
+    }
+}
+
+// 电量严重不足时的动作：放电中电量降到 level 以下时执行一次命令（如 systemctl suspend）
+// 执行后直到接上电源或电量回升才会再次触发，避免唤醒后电量仍低时立即再次挂起
+pub struct BatteryAction {
+    level: i64,
+    command: String,
+    dry_run: bool,
+    triggered: bool,
+}
+
+impl BatteryAction {
+    pub fn new(level: i64, command: String, dry_run: bool) -> Self {
+        BatteryAction {
+            level,
+            command,
+            dry_run,
+            triggered: false,
+        }
+    }
+
+    // 判断本次是否应当执行，并更新触发状态
+    fn due(&mut self, capacity: i64, status: &str) -> bool {
+        if status != "Discharging" || capacity > self.level + HYSTERESIS {
+            self.triggered = false;
+            return false;
+        }
+        if capacity > self.level || self.triggered {
+            return false;
+        }
+        self.triggered = true;
+        true
+    }
+
+    // 每次刷新时调用，读取电量并按需执行命令
+    pub fn check(&mut self, device: &str) {
+        let Some(capacity) = battery::get_battery_capacity(device)
+            .ok()
+            .and_then(|c| c.parse::<i64>().ok())
+        else {
+            return;
+        };
+        let status = battery::get_battery_status(device).unwrap_or_default();
+        if !self.due(capacity, &status) {
+            return;
+        }
+        if self.dry_run {
+            log::info!(
+                "battery at {}%, would run battery action: {}",
+                capacity,
+                self.command
+            );
+            return;
+        }
+        log::warning!(
+            "battery at {}%, running battery action: {}",
+            capacity,
+            self.command
+        );
+        script::spawn(
+            &self.command,
+            &[("SYS_MONTION_BATTERY_CAPACITY", capacity.to_string())],
+            "battery action",
+        );
+    }
+}
+
+// 风扇告警规则：风扇持续满速超过指定时长，或高温时风扇转速为 0
+pub struct FanAlert {
+    max_duration: Duration,
+    stall_temp: f64,
+    at_max_since: Option<i64>,
+    max_alerted: bool,
+    stall_alerted: bool,
+}
+
+impl FanAlert {
+    pub fn new(max_duration: Duration, stall_temp: f64) -> Self {
+        FanAlert {
+            max_duration,
+            stall_temp,
+            at_max_since: None,
+            max_alerted: false,
+            stall_alerted: false,
+        }
+    }
+
+    // 每次刷新时调用，检查风扇状态并按需发送通知
+    pub fn check(&mut self, routes: &Routes) {
+        let Ok(fans) = hwmon::read_fans() else {
+            return;
+        };
+        if fans.is_empty() {
+            return;
+        }
+        let temp = hwmon::read_max_temp().ok();
+
+        if fans.iter().any(|fan| fan.at_max) {
+            let elapsed = now_secs() - *self.at_max_since.get_or_insert_with(now_secs);
+            if !self.max_alerted && elapsed >= self.max_duration.as_secs() as i64 {
+                self.max_alerted = true;
+                let body = format!("Fan has been at full speed for {} minutes", elapsed / 60);
+                routes.send("fan", "Fan at full speed", &body, Urgency::Normal);
+            }
+        } else {
+            self.at_max_since = None;
+            self.max_alerted = false;
+        }
+
+        let stalled = temp.is_some_and(|t| t > self.stall_temp) && fans.iter().all(|f| f.rpm == 0);
+        if stalled && !self.stall_alerted {
+            let body = format!(
+                "Fan at 0 RPM while temperature is {:.0}°C",
+                temp.unwrap_or(0.0)
+            );
+            routes.send("fan", "Fan not spinning", &body, Urgency::Critical);
+        }
+        self.stall_alerted = stalled;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_battery_action_once() {
+        let mut action = BatteryAction::new(5, "systemctl suspend".to_string(), true);
+        assert!(!action.due(6, "Discharging"));
+        assert!(action.due(5, "Discharging"));
+        // 唤醒后电量仍然很低，不再重复执行
+        assert!(!action.due(4, "Discharging"));
+        assert!(!action.due(4, "Charging"));
+        assert!(action.due(4, "Discharging"));
+    }
+
+    #[test]
+    fn routes_alerts_to_backends() {
+        let routes = Routes::new(vec![
+            (
+                "fan".to_string(),
+                Backend::Ntfy("https://ntfy.sh/box".to_string()),
+            ),
+            ("fan".to_string(), Backend::Command("logger".to_string())),
+        ]);
+        assert_eq!(routes.backends("fan").len(), 2);
+        assert_eq!(routes.backends("battery"), [&Backend::Desktop]);
+        let payload = webhook_payload("memory", "Memory running low", "\"x\"", Urgency::Critical);
+        assert!(
+            payload.starts_with(r#"{"alert":"memory","summary":"Memory running low","body":"\"x\"","urgency":"critical","host":"#),
+            "{}",
+            payload
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{locale, read_file, sysfs};
+use std::io;
+use std::path::Path;
+
+const NVME_PATH: &str = "/sys/class/nvme";
+
+// 列出 NVMe 控制器，如 nvme0、nvme1
+pub fn list_controllers() -> Result<Vec<String>, io::Error> {
+    let mut controllers: Vec<String> = sysfs::read_dir(NVME_PATH)?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    controllers.sort();
+    Ok(controllers)
+}
+
+// 控制器的 hwmon 设备注册在 /sys/class/nvme/nvme0/hwmonN 下
+// temp1 为 Composite 温度，temp1_alarm 为 1 时控制器报告了温度警告
+fn read_temp(controller: &str) -> Result<(f64, bool), Error> {
+    let dir = Path::new(NVME_PATH).join(controller);
+    let hwmon = sysfs::read_dir(&dir)
+        .map_err(|_| Error::MissingDevice(format!("no NVMe controller {}", controller)))?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .find(|name| name.starts_with("hwmon"))
+        .ok_or_else(|| Error::MissingDevice(format!("{} has no temperature sensor", controller)))?;
+    let input = dir.join(&hwmon).join("temp1_input");
+    let millidegrees: i64 = read_file(&input)?
+        .parse()
+        .map_err(|e| Error::Parse(format!("{}: {}", input.display(), e)))?;
+    let alarm = read_file(dir.join(&hwmon).join("temp1_alarm")).is_ok_and(|v| v == "1");
+    Ok((millidegrees as f64 / 1000.0, alarm))
+}
+
+// 通过 `smartctl -H` 读取 SMART 健康状态，通过时为 Some(true)
+// smartctl 未安装或没有权限读取设备时为 None，只输出温度
+fn smart_passed(controller: &str) -> Option<bool> {
+    let output = locale::command("smartctl")
+        .args(["-H", &format!("/dev/{}", controller)])
+        .output()
+        .ok()?;
+    parse_smart_health(&String::from_utf8_lossy(&output.stdout))
+}
+
+// 如 "SMART overall-health self-assessment test result: PASSED"
+fn parse_smart_health(output: &str) -> Option<bool> {
+    let line = output
+        .lines()
+        .find(|line| line.contains("overall-health"))?;
+    Some(line.trim_end().ends_with("PASSED"))
+}
+
+fn reading(controller: &str) -> Result<String, Error> {
+    let (celsius, alarm) = read_temp(controller)?;
+    let mut text = format!("{:.0}°C", celsius);
+    if alarm {
+        text += " (warning)";
+    }
+    if smart_passed(controller) == Some(false) {
+        text += " FAILING";
+    }
+    Ok(text)
+}
+
+// 输出 NVMe 固态硬盘温度，如 "SSD: 45°C"，有多块时带控制器名，如 "SSD: nvme0 45°C nvme1 38°C"
+// 温度警告时追加 "(warning)"，SMART 健康检查未通过时追加 "FAILING"
+pub fn get_disk_temp(device: &str) -> Result<String, Error> {
+    let controllers = if device == "all" {
+        list_controllers().unwrap_or_default()
+    } else {
+        vec![device.to_string()]
+    };
+    let readings = match controllers.as_slice() {
+        [] => return Err(Error::MissingDevice("no NVMe drive found".to_string())),
+        [controller] => vec![reading(controller)?],
+        _ => controllers
+            .iter()
+            .map(|controller| Ok(format!("{} {}", controller, reading(controller)?)))
+            .collect::<Result<_, Error>>()?,
+    };
+    Ok(format!("SSD: {}", readings.join(" ")))
+}
+
+pub struct DiskTemp {
+    device: String,
+}
+
+impl Builtin for DiskTemp {
+    const ID: &'static str = "disk-temp";
+    const BLOCK: &'static str = "disk_temp";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        DiskTemp {
+            device: matches
+                .get_one::<String>("disk-temp")
+                .map_or("all", String::as_str)
+                .to_string(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, get_disk_temp(&self.device)?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        list_controllers()
+            .map_or(true, |controllers| controllers.is_empty())
+            .then(|| "no NVMe drives".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_nvme_temperature() {
+        sysfs::use_fixtures();
+        assert_eq!(list_controllers().unwrap(), ["nvme0"]);
+        assert_eq!(get_disk_temp("all").unwrap(), "SSD: 45°C");
+        assert!(matches!(
+            get_disk_temp("nvme1"),
+            Err(Error::MissingDevice(_))
+        ));
+        assert_eq!(
+            parse_smart_health("SMART overall-health self-assessment test result: FAILED!\n"),
+            Some(false)
+        );
+        assert_eq!(
+            parse_smart_health("SMART overall-health self-assessment test result: PASSED\n"),
+            Some(true)
+        );
+    }
+}
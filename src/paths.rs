@@ -0,0 +1,72 @@
+use crate::termux;
+use std::env;
+use std::io;
+use std::path::PathBuf;
+
+// 本程序在各基础目录下的子目录名
+const APP: &str = "sys-montion";
+
+// XDG 基础目录变量，未设置或为空时使用 $HOME 下的默认位置
+fn xdg_dir(var: &str, fallback: &str) -> Result<PathBuf, io::Error> {
+    if let Some(dir) = env::var_os(var).filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    let home = env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))?;
+    Ok(PathBuf::from(home).join(fallback))
+}
+
+// 配置目录：$XDG_CONFIG_HOME/sys-montion，默认 ~/.config/sys-montion
+pub fn config_dir() -> Result<PathBuf, io::Error> {
+    Ok(xdg_dir("XDG_CONFIG_HOME", ".config")?.join(APP))
+}
+
+// 缓存文件路径：$XDG_CACHE_HOME/sys-montion/{file}，默认 ~/.cache
+// 存放可以随时删除的数据，如计数器采样、天气和更新检查结果
+pub fn cache_path(file: &str) -> Result<PathBuf, io::Error> {
+    Ok(xdg_dir("XDG_CACHE_HOME", ".cache")?.join(APP).join(file))
+}
+
+// 状态文件路径：$XDG_STATE_HOME/sys-montion/{file}，默认 ~/.local/state
+// 存放需要跨重启保留的历史，如使用统计、电池和开机记录
+pub fn state_path(file: &str) -> Result<PathBuf, io::Error> {
+    Ok(xdg_dir("XDG_STATE_HOME", ".local/state")?
+        .join(APP)
+        .join(file))
+}
+
+// 运行时目录：$XDG_RUNTIME_DIR，没有 $HOME 下的默认位置
+// Termux 没有 /run/user，使用应用私有的 $TMPDIR（$PREFIX/tmp）
+pub fn runtime_dir() -> Result<PathBuf, io::Error> {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ if termux::detected() => Ok(env::temp_dir()),
+        _ => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "XDG_RUNTIME_DIR is not set",
+        )),
+    }
+}
+
+// 运行时文件路径：$XDG_RUNTIME_DIR/{file}，如控制套接字、计时器和 caffeine 的状态
+pub fn runtime_path(file: &str) -> Result<PathBuf, io::Error> {
+    Ok(runtime_dir()?.join(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_to_home() {
+        // 只读取环境变量，不修改，避免影响并行运行的其他测试
+        let Some(home) = env::var_os("HOME").filter(|home| !home.is_empty()) else {
+            return;
+        };
+        assert_eq!(
+            xdg_dir("SYS_MONTION_UNSET_DIR", ".local/state").unwrap(),
+            PathBuf::from(home).join(".local/state")
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+
+// 外部模块：用 dlopen 加载的共享库，按稳定的 C ABI 导出以下函数
+//
+//   uint32_t sys_montion_abi_version(void);           // 返回 ABI_VERSION
+//   int sys_montion_read(char *buf, size_t len);      // 写入输出文本（UTF-8），返回字节数；
+//                                                     // 返回负数表示读取失败，buf 中为错误信息
+//
+// sys_montion_read 可能在任意线程中调用，但同一个插件不会同时被调用两次
+pub const ABI_VERSION: u32 = 1;
+
+// 输出文本的最大长度
+const BUFFER_SIZE: usize = 4096;
+
+type ReadFn = unsafe extern "C" fn(*mut c_char, usize) -> c_int;
+type VersionFn = unsafe extern "C" fn() -> u32;
+
+// dlopen 返回的句柄，释放时 dlclose
+struct Handle(*mut c_void);
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        unsafe {
+            libc::dlclose(self.0);
+        }
+    }
+}
+
+fn dl_error() -> String {
+    let error = unsafe { libc::dlerror() };
+    if error.is_null() {
+        return "unknown dlopen error".to_string();
+    }
+    unsafe { CStr::from_ptr(error) }
+        .to_string_lossy()
+        .to_string()
+}
+
+impl Handle {
+    fn symbol(&self, name: &CStr) -> Result<*mut c_void, String> {
+        let symbol = unsafe { libc::dlsym(self.0, name.as_ptr()) };
+        if symbol.is_null() {
+            return Err(format!("missing symbol {}", name.to_string_lossy()));
+        }
+        Ok(symbol)
+    }
+}
+
+// 已加载的插件
+struct Library {
+    _handle: Handle,
+    read: ReadFn,
+}
+
+// 插件约定 sys_montion_read 可在任意线程调用
+unsafe impl Send for Library {}
+
+impl Library {
+    fn open(path: &str) -> Result<Self, String> {
+        let c_path = CString::new(path).map_err(|_| "invalid path".to_string())?;
+        let handle = unsafe { libc::dlopen(c_path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            return Err(dl_error());
+        }
+        let handle = Handle(handle);
+        let version = handle.symbol(c"sys_montion_abi_version")?;
+        let version = unsafe { std::mem::transmute::<*mut c_void, VersionFn>(version)() };
+        if version != ABI_VERSION {
+            return Err(format!(
+                "plugin ABI version {}, expected {}",
+                version, ABI_VERSION
+            ));
+        }
+        let read = handle.symbol(c"sys_montion_read")?;
+        Ok(Library {
+            read: unsafe { std::mem::transmute::<*mut c_void, ReadFn>(read) },
+            _handle: handle,
+        })
+    }
+
+    fn read(&self) -> Result<String, String> {
+        let mut buf = vec![0u8; BUFFER_SIZE];
+        let len = unsafe { (self.read)(buf.as_mut_ptr().cast(), buf.len()) };
+        let text = |len: usize| {
+            let len = len.min(buf.len());
+            // 插件可能按 C 字符串写入，截到第一个 NUL
+            let end = buf[..len].iter().position(|&b| b == 0).unwrap_or(len);
+            String::from_utf8_lossy(&buf[..end]).trim().to_string()
+        };
+        if len < 0 {
+            Err(text(buf.len()))
+        } else {
+            Ok(text(len as usize))
+        }
+    }
+}
+
+// 插件模块，加载失败时每次读取都报告加载错误
+pub struct Plugin {
+    path: String,
+    // 没有 --plugin 时为 None
+    library: Option<Result<Library, String>>,
+}
+
+impl Plugin {
+    pub fn load(path: &str) -> Self {
+        Plugin {
+            path: path.to_string(),
+            library: Some(
+                Library::open(path).map_err(|e| format!("loading plugin {}: {}", path, e)),
+            ),
+        }
+    }
+}
+
+// 每个 --plugin 各自创建一个模块实例，见 module::create；按名称创建时只加载第一个
+impl Builtin for Plugin {
+    const ID: &'static str = "plugin";
+    const BLOCK: &'static str = "plugin";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        match matches.get_one::<String>("plugin") {
+            Some(path) => Plugin::load(path),
+            None => Plugin {
+                path: String::new(),
+                library: None,
+            },
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let library = self
+            .library
+            .as_ref()
+            .ok_or_else(|| Error::MissingDevice("no plugin, use --plugin PATH".to_string()))?
+            .as_ref()
+            .map_err(|e| Error::BackendUnavailable(e.clone()))?;
+        match library.read() {
+            Ok(text) => Ok(Block::numeric(Self::BLOCK, text)),
+            Err(e) => Err(Error::BackendUnavailable(format!("{}: {}", self.path, e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::Module;
+
+    #[test]
+    fn reports_load_errors() {
+        let mut plugin = Plugin::load("/nonexistent/libplugin.so");
+        assert!(matches!(plugin.read(), Err(Error::BackendUnavailable(_))));
+    }
+}
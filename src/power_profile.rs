@@ -0,0 +1,66 @@
+use crate::dbus::{self, Bus};
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use std::io;
+
+// power-profiles-daemon 0.20 起使用 org.freedesktop 名称，旧版本为 net.hadess
+const SERVICES: [(&str, &str); 2] = [
+    (
+        "org.freedesktop.UPower.PowerProfiles",
+        "/org/freedesktop/UPower/PowerProfiles",
+    ),
+    ("net.hadess.PowerProfiles", "/net/hadess/PowerProfiles"),
+];
+
+// 依次尝试各个服务名称，返回第一个成功的结果
+fn with_service<T>(f: impl Fn(&str, &str) -> Result<T, io::Error>) -> Result<T, Error> {
+    let mut last_error = None;
+    for (name, path) in SERVICES {
+        match f(name, path) {
+            Ok(value) => return Ok(value),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(Error::BackendUnavailable(format!(
+        "power-profiles-daemon not reachable: {}",
+        last_error.map_or_else(String::new, |e| e.to_string())
+    )))
+}
+
+// 读取当前电源档位，如 "PWR: balanced"
+pub fn get_power_profile() -> Result<String, Error> {
+    let profile = with_service(|name, path| {
+        dbus::get_property(Bus::System, name, path, name, "ActiveProfile")
+    })?;
+    Ok(format!("PWR: {}", profile.trim_matches('\'')))
+}
+
+// 切换电源档位：performance、balanced 或 power-saver
+pub fn set_power_profile(profile: &str) -> Result<(), Error> {
+    with_service(|name, path| {
+        dbus::call(
+            Bus::System,
+            name,
+            path,
+            "org.freedesktop.DBus.Properties.Set",
+            &[name, "ActiveProfile", &format!("<'{}'>", profile)],
+        )
+    })?;
+    Ok(())
+}
+
+pub struct PowerProfile;
+
+impl Builtin for PowerProfile {
+    const ID: &'static str = "power-profile";
+    const BLOCK: &'static str = "power_profile";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        PowerProfile
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_power_profile()?))
+    }
+}
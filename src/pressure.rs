@@ -0,0 +1,72 @@
+use crate::error::Error;
+use crate::module::{self, Builtin, MetricValue};
+use crate::render::Block;
+use crate::{read_file, sysfs};
+use std::io;
+
+// PSI（Pressure Stall Information）资源：/proc/pressure/{cpu,memory,io}
+pub const RESOURCES: [&str; 3] = ["cpu", "memory", "io"];
+
+// 读取 some 行的 avg10：过去 10 秒内至少一个任务因等待该资源而停顿的时间百分比
+pub fn read_avg10(resource: &str) -> Result<f64, io::Error> {
+    let pressure = read_file(format!("/proc/pressure/{}", resource))?;
+    pressure
+        .lines()
+        .find(|line| line.starts_with("some"))
+        .and_then(|line| {
+            line.split_whitespace()
+                .find_map(|field| field.strip_prefix("avg10="))
+        })
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "avg10 not found"))
+}
+
+// 输出资源压力，如 "PSI cpu: 12.3%"
+// 负载均值包含等待 I/O 的任务且不随核数归一化，PSI 直接反映任务因资源不足而停顿的比例
+pub fn get_pressure(resource: &str) -> Result<String, Error> {
+    let avg10 = read_avg10(resource).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => Error::MissingDevice(
+            "/proc/pressure not found (kernel built without CONFIG_PSI or booted with psi=0)"
+                .to_string(),
+        ),
+        _ => Error::from(e),
+    })?;
+    Ok(format!("PSI {}: {:.1}%", resource, avg10))
+}
+
+pub struct Pressure {
+    resource: Option<String>,
+}
+
+impl Builtin for Pressure {
+    const ID: &'static str = "pressure";
+    const BLOCK: &'static str = "pressure";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Pressure {
+            resource: matches.get_one::<String>("pressure").cloned(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let resource = module::required(&self.resource, "pressure", "--pressure RESOURCE")?;
+        Ok(Block::percent(Self::BLOCK, get_pressure(resource)?))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        (!sysfs::exists("/proc/pressure")).then(|| "kernel has no PSI support".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sysfs;
+
+    #[test]
+    fn reads_avg10() {
+        sysfs::use_fixtures();
+        assert_eq!(get_pressure("cpu").unwrap(), "PSI cpu: 12.3%");
+        assert!(matches!(get_pressure("io"), Err(Error::MissingDevice(_))));
+    }
+}
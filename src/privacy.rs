@@ -0,0 +1,93 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{read_file, sysfs};
+use std::fs;
+use std::path::Path;
+
+// 目录下的条目名称，目录不存在或无权读取时为空
+fn entries(dir: impl AsRef<Path>) -> Vec<String> {
+    sysfs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// 有进程打开了 /dev/video* 时摄像头正在使用
+// 只能看到有权读取 /proc/PID/fd 的进程，即当前用户自己的进程
+fn camera_in_use() -> bool {
+    entries("/proc")
+        .iter()
+        .filter(|pid| pid.chars().all(|c| c.is_ascii_digit()))
+        .any(|pid| {
+            let dir = Path::new("/proc").join(pid).join("fd");
+            entries(&dir).iter().any(|fd| {
+                fs::read_link(sysfs::path(dir.join(fd)))
+                    .is_ok_and(|target| target.to_string_lossy().starts_with("/dev/video"))
+            })
+        })
+}
+
+// ALSA 录音子设备（/proc/asound/cardN/pcmMc/subK/status）处于 RUNNING 时麦克风正在使用
+// PulseAudio/PipeWire 在没有录音客户端时会关闭设备，同样适用
+fn mic_in_use() -> bool {
+    entries("/proc/asound")
+        .iter()
+        .filter(|card| card.starts_with("card"))
+        .any(|card| {
+            let card = Path::new("/proc/asound").join(card);
+            entries(&card)
+                .iter()
+                .filter(|pcm| pcm.starts_with("pcm") && pcm.ends_with('c'))
+                .any(|pcm| {
+                    let pcm = card.join(pcm);
+                    entries(&pcm).iter().any(|sub| {
+                        read_file(pcm.join(sub).join("status"))
+                            .is_ok_and(|status| status.contains("RUNNING"))
+                    })
+                })
+        })
+}
+
+// 输出隐私指示：摄像头或麦克风正在使用时为 "REC: cam mic"，都未使用时为 "REC: off"
+pub fn get_privacy() -> Result<String, Error> {
+    let devices: Vec<&str> = [(camera_in_use(), "cam"), (mic_in_use(), "mic")]
+        .into_iter()
+        .filter_map(|(in_use, name)| in_use.then_some(name))
+        .collect();
+    if devices.is_empty() {
+        return Ok("REC: off".to_string());
+    }
+    Ok(format!("REC: {}", devices.join(" ")))
+}
+
+pub struct Privacy;
+
+impl Builtin for Privacy {
+    const ID: &'static str = "privacy";
+    const BLOCK: &'static str = "privacy";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Privacy
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_privacy()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_camera_but_not_playback() {
+        sysfs::use_fixtures();
+        // 夹具中进程 200 打开了 /dev/video0，声卡只有播放子设备在运行
+        assert_eq!(get_privacy().unwrap(), "REC: cam");
+    }
+}
@@ -0,0 +1,73 @@
+use crate::log;
+use crate::metrics;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+// 每次抓取时重新读取所有指标，转换为 Prometheus 文本格式，没有样本的指标跳过
+fn collect(battery_device: &str) -> String {
+    let mut out = String::new();
+    for metric in metrics::collect(battery_device) {
+        if metric.samples.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "# HELP {} {}", metric.name, metric.help);
+        let _ = writeln!(out, "# TYPE {} gauge", metric.name);
+        for (label, value) in &metric.samples {
+            if label.is_empty() {
+                let _ = writeln!(out, "{} {}", metric.name, value);
+            } else {
+                let _ = writeln!(
+                    out,
+                    "{}{{{}=\"{}\"}} {}",
+                    metric.name, metric.label, label, value
+                );
+            }
+        }
+    }
+    out
+}
+
+fn serve_client(stream: TcpStream, battery_device: &str) -> Result<(), io::Error> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // 读完请求头，忽略内容
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("");
+    let (status, content_type, body) = if path == "/metrics" {
+        (
+            "200 OK",
+            "text/plain; version=0.0.4",
+            collect(battery_device),
+        )
+    } else {
+        ("404 Not Found", "text/plain", "Not found\n".to_string())
+    };
+    write!(
+        &stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+// 运行 HTTP 服务，在 /metrics 上以 Prometheus 文本格式输出指标
+pub fn run(addr: &str, battery_device: &str) -> Result<(), io::Error> {
+    let listener = TcpListener::bind(addr)?;
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| serve_client(stream, battery_device));
+        if let Err(e) = result {
+            log::warning!("serving metrics: {}", e);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::control::to_json;
+use crate::module::{self, Module};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::sync::Arc;
+
+// 处理一行查询，返回一行响应，出错时以 "error: " 开头
+//   MODULE [json]   读取模块（如 battery、volume、memory json）
+// 模块实例按名称保留，CPU 占用率等需要两次采样的模块在两次查询之间计算
+fn handle(
+    line: &str,
+    modules: &mut HashMap<String, Box<dyn Module>>,
+    matches: &Arc<clap::ArgMatches>,
+) -> String {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (name, json) = match words.as_slice() {
+        [name] => (*name, false),
+        [name, "json"] => (*name, true),
+        _ => return format!("error: invalid request '{}'", line),
+    };
+    if !modules.contains_key(name) {
+        let Some(module) = module::builtin(name, matches) else {
+            return format!("error: unknown module '{}'", name);
+        };
+        modules.insert(name.to_string(), module);
+    }
+    match modules.get_mut(name).unwrap().read() {
+        Ok(block) if json => to_json(&block),
+        Ok(block) => block.text,
+        Err(e) => format!("error: {}", e),
+    }
+}
+
+// 从标准输入逐行读取查询，每行输出一行结果，直到标准输入关闭
+// 父进程（状态栏、编辑器）可以保持一个子进程常驻，避免每次查询都启动新进程
+pub fn run(matches: &Arc<clap::ArgMatches>) -> Result<(), io::Error> {
+    let mut modules = HashMap::new();
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        writeln!(stdout, "{}", handle(&line, &mut modules, matches))?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sysfs;
+
+    #[test]
+    fn answers_queries() {
+        sysfs::use_fixtures();
+        let matches = Arc::new(crate::args::build_cli().get_matches_from(["sys-montion"]));
+        let mut modules = HashMap::new();
+        assert_eq!(
+            handle("memory", &mut modules, &matches),
+            "MEM: 3.8G/15.3G (25%)"
+        );
+        assert_eq!(
+            handle("memory json", &mut modules, &matches),
+            r#"{"name":"memory","text":"MEM: 3.8G/15.3G (25%)","value":25,"version":2}"#
+        );
+        assert_eq!(
+            handle("nothing", &mut modules, &matches),
+            "error: unknown module 'nothing'"
+        );
+        assert!(handle("memory yaml", &mut modules, &matches).starts_with("error: "));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::render::{field_name, json_escape, table_header, table_row, Block};
+use crate::timeutil::now_secs;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+pub const FORMATS: [&str; 2] = ["jsonl", "csv"];
+
+// 轮转后保留的旧文件数：FILE.1 为最近一次轮转，FILE.3 之前的删除
+const KEEP_ROTATED: u32 = 3;
+
+// --log-metrics：watch 模式下每次刷新把各指标追加到文件，超过大小上限时轮转
+pub struct Recorder {
+    path: PathBuf,
+    csv: bool,
+    max_size: u64,
+    file: Option<File>,
+    // csv 当前文件的表头，空文件为 None
+    header: Option<String>,
+}
+
+fn rotated(path: &Path, index: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+// jsonl 的一行：{"time":1700000000,"battery":73,"battery_state":"Charging"}
+fn json_line(blocks: &[Block], time: i64) -> String {
+    let fields: Vec<String> = std::iter::once(format!("\"time\":{}", time))
+        .chain(blocks.iter().map(|block| match block.value {
+            Some(value) => format!("\"{}\":{}", field_name(block), value),
+            None => format!("\"{}\":\"{}\"", field_name(block), json_escape(&block.text)),
+        }))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+impl Recorder {
+    // max_size 为单个文件的大小上限（字节）
+    pub fn new(path: PathBuf, format: &str, max_size: u64) -> Self {
+        Recorder {
+            path,
+            csv: format == "csv",
+            max_size,
+            file: None,
+            header: None,
+        }
+    }
+
+    // FILE -> FILE.1，FILE.1 -> FILE.2，依此类推，最旧的被覆盖
+    fn rotate(&mut self) -> Result<(), io::Error> {
+        self.file = None;
+        for index in (1..KEEP_ROTATED).rev() {
+            match fs::rename(rotated(&self.path, index), rotated(&self.path, index + 1)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        fs::rename(&self.path, rotated(&self.path, 1))
+    }
+
+    // 打开文件并返回其大小，已有的 csv 文件从第一行读取表头
+    fn open(&mut self) -> Result<u64, io::Error> {
+        if self.file.is_none() {
+            if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                fs::create_dir_all(dir)?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            self.header = None;
+            if self.csv && file.metadata()?.len() > 0 {
+                let mut header = String::new();
+                BufReader::new(File::open(&self.path)?).read_line(&mut header)?;
+                self.header = Some(header.trim_end().to_string());
+            }
+            self.file = Some(file);
+        }
+        Ok(self.file.as_ref().unwrap().metadata()?.len())
+    }
+
+    // 每个 csv 文件只有一个表头，指标集合变化时同样轮转
+    pub fn record(&mut self, blocks: &[Block]) -> Result<(), io::Error> {
+        let header = self.csv.then(|| table_header(blocks, true));
+        let size = self.open()?;
+        if size >= self.max_size || (size > 0 && header.is_some() && self.header != header) {
+            self.rotate()?;
+            self.open()?;
+        }
+        let mut text = String::new();
+        match header {
+            Some(header) => {
+                if self.header.is_none() {
+                    text.push_str(&header);
+                    text.push('\n');
+                    self.header = Some(header);
+                }
+                text.push_str(&table_row(blocks, true));
+            }
+            None => text.push_str(&json_line(blocks, now_secs())),
+        }
+        text.push('\n');
+        self.file.as_mut().unwrap().write_all(text.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_samples() {
+        let blocks = [
+            Block::numeric("battery", "Charging: 73%".to_string()),
+            Block::new("battery_state", "Charging".to_string()),
+        ];
+        assert_eq!(
+            json_line(&blocks, 1700000000),
+            "{\"time\":1700000000,\"battery\":73,\"battery_state\":\"Charging\"}"
+        );
+        assert_eq!(
+            rotated(Path::new("/tmp/metrics.jsonl"), 2),
+            Path::new("/tmp/metrics.jsonl.2")
+        );
+    }
+}
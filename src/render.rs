@@ -0,0 +1,1109 @@
+use crate::config::Config;
+use crate::error::Error;
+use crate::timeutil::now_secs;
+use crate::{i18n, icons, log, module, script, speak};
+use clap::parser::ValueSource;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use toml::Spanned;
+
+// 阈值级别
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Level {
+    Normal,
+    Warning,
+    Critical,
+}
+
+// 一个指标的输出：名称、文本以及用于阈值判断的数值
+#[derive(Clone)]
+pub struct Block {
+    pub name: &'static str,
+    // 输出该块的模块名，如 battery-capacity；多个模块共用一个块名（battery、cpu、net），按模块名区分
+    pub id: &'static str,
+    pub text: String,
+    pub value: Option<f64>,
+    // 百分比类指标（电量、音量、内存占用等）的百分比，用于 waybar 的 percentage
+    pub percentage: Option<f64>,
+    // 模块自身判断的级别（如风扇转速低于曲线），与阈值级别取较高者
+    pub level: Level,
+}
+
+impl Block {
+    pub fn new(name: &'static str, text: String) -> Self {
+        Block {
+            name,
+            id: name,
+            text,
+            value: None,
+            percentage: None,
+            level: Level::Normal,
+        }
+    }
+
+    // 取文本中的第一个数字作为数值，如 "VOL: 65%" 取 65
+    pub fn numeric(name: &'static str, text: String) -> Self {
+        let value = first_number(&text);
+        Block {
+            name,
+            id: name,
+            text,
+            value,
+            percentage: None,
+            level: Level::Normal,
+        }
+    }
+
+    // 百分比类指标，文本中的第一个数字即百分比，如 "Discharging: 80%"
+    pub fn percent(name: &'static str, text: String) -> Self {
+        let block = Block::numeric(name, text);
+        Block {
+            percentage: block.value,
+            ..block
+        }
+    }
+
+    // 文本中的第一个数字不是百分比时（如 "MEM: 3.8G/15.3G (25%)"）由模块给出，作为阈值判断的数值
+    pub fn with_percent(name: &'static str, text: String, percent: f64) -> Self {
+        Block {
+            value: Some(percent),
+            percentage: Some(percent),
+            ..Block::new(name, text)
+        }
+    }
+}
+
+fn first_number(text: &str) -> Option<f64> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let rest = &text[start..];
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+// 阈值参数，顺序与配置文件中的 warn_below、critical_below、warn_above、critical_above 相同
+const THRESHOLDS: [&str; 4] = [
+    "warn-below",
+    "critical-below",
+    "warn-above",
+    "critical-above",
+];
+
+// 阈值：低于 *_below 或高于 *_above 时进入对应级别
+#[derive(Default)]
+pub struct Thresholds {
+    pub warn_below: Option<f64>,
+    pub critical_below: Option<f64>,
+    pub warn_above: Option<f64>,
+    pub critical_above: Option<f64>,
+    // 各模块单独的阈值，按模块名；模块未设置的阈值使用上面的全局阈值
+    pub modules: HashMap<&'static str, Thresholds>,
+}
+
+impl Thresholds {
+    // 命令行参数优先，未指定的阈值使用配置文件中的值
+    pub fn from_matches(matches: &clap::ArgMatches, config: &Config) -> Self {
+        let mut thresholds = Thresholds::default();
+        thresholds.set_all([
+            config.warn_below,
+            config.critical_below,
+            config.warn_above,
+            config.critical_above,
+        ]);
+        for m in &config.modules {
+            if let Some(entry) = module::get(m.name()) {
+                thresholds.modules.entry(entry.id).or_default().set_all([
+                    m.warn_below,
+                    m.critical_below,
+                    m.warn_above,
+                    m.critical_above,
+                ]);
+            }
+        }
+        for arg in THRESHOLDS {
+            let values = matches.get_many::<(Option<String>, f64)>(arg);
+            for (module, value) in values.into_iter().flatten() {
+                let target = match module.as_deref().and_then(module::get) {
+                    Some(entry) => thresholds.modules.entry(entry.id).or_default(),
+                    None => &mut thresholds,
+                };
+                target.set(arg, Some(*value));
+            }
+        }
+        thresholds
+    }
+
+    // 设置一个阈值，value 为 None 时保留原值
+    fn set(&mut self, arg: &str, value: Option<f64>) {
+        let field = match arg {
+            "warn-below" => &mut self.warn_below,
+            "critical-below" => &mut self.critical_below,
+            "warn-above" => &mut self.warn_above,
+            _ => &mut self.critical_above,
+        };
+        if value.is_some() {
+            *field = value;
+        }
+    }
+
+    fn set_all(&mut self, values: [Option<f64>; 4]) {
+        for (arg, value) in THRESHOLDS.into_iter().zip(values) {
+            self.set(arg, value);
+        }
+    }
+
+    pub fn level(&self, value: Option<f64>) -> Level {
+        let Some(value) = value else {
+            return Level::Normal;
+        };
+        let below = |t: Option<f64>| t.is_some_and(|t| value < t);
+        let above = |t: Option<f64>| t.is_some_and(|t| value > t);
+        if below(self.critical_below) || above(self.critical_above) {
+            Level::Critical
+        } else if below(self.warn_below) || above(self.warn_above) {
+            Level::Warning
+        } else {
+            Level::Normal
+        }
+    }
+
+    // 块的级别：按数值判断的阈值级别和模块给出的级别中较高者，模块有单独的阈值时使用其阈值
+    pub fn block_level(&self, block: &Block) -> Level {
+        let level = match self.modules.get(block.id) {
+            Some(module) => Thresholds {
+                warn_below: module.warn_below.or(self.warn_below),
+                critical_below: module.critical_below.or(self.critical_below),
+                warn_above: module.warn_above.or(self.warn_above),
+                critical_above: module.critical_above.or(self.critical_above),
+                modules: HashMap::new(),
+            }
+            .level(block.value),
+            None => self.level(block.value),
+        };
+        level.max(block.level)
+    }
+}
+
+// 转义 JSON 字符串
+pub fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// JSON 输出的 API 版本，下游脚本据此判断字段，--api-version 可固定为旧版本
+// 版本 1：
+//   i3bar 块      name、full_text、color、urgent、_version（i3bar 忽略以 _ 开头的键）
+//   waybar        text、class、percentage、version
+//   --format-hook name、text、value、level、version，每个指标一行
+//   ctl get、--stdin 的 json 查询  name、text、value、version
+// 版本 2：
+//   i3bar 块      增加 instance（输出该块的模块名，点击事件据此区分同名的块）
+//   --format-hook 增加 module（模块名，区分 battery 和 battery-capacity 等同名的块）
+// 新版本只增加字段或模块，不删除、不改名、不改变含义；固定为旧版本时不输出新增的字段
+pub const API_VERSION: u32 = 2;
+
+// i3bar 协议头，watch 模式下先输出，之后每行是一个数组元素
+// 开启 click_events 后 i3bar 把点击事件写到标准输入
+pub const I3BAR_HEADER: &str = "{\"version\":1,\"click_events\":true}\n[";
+
+// 校验 `#RRGGBB` 形式的颜色
+pub fn parse_color(color: &str) -> Result<String, String> {
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if valid {
+        Ok(color.to_string())
+    } else {
+        Err(format!("invalid color '{}', expected #RRGGBB", color))
+    }
+}
+
+// 转换为 ANSI 24 位前景色转义序列
+fn ansi_color(color: &str, text: &str) -> String {
+    let channel = |i: usize| u8::from_str_radix(&color[i..i + 2], 16).unwrap_or(0);
+    format!(
+        "\x1b[38;2;{};{};{}m{}\x1b[0m",
+        channel(1),
+        channel(3),
+        channel(5),
+        text
+    )
+}
+
+// --prompt-segment：把 ANSI 转义序列标记为零宽度，否则 shell 计算提示符宽度时把转义序列算在内，
+// 换行和光标位置错乱。zsh 用 %{...%}，并把 % 写作 %%；bash 只在 PS1 本身中识别 \[...\]，
+// 命令替换的输出要用 readline 的 \x01...\x02
+fn prompt_escape(text: &str, shell: &str) -> String {
+    let (open, close) = match shell {
+        "zsh" => ("%{", "%}"),
+        "bash" => ("\x01", "\x02"),
+        _ => return text.to_string(),
+    };
+    let mut escaped = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let start = rest.find('\x1b').unwrap_or(rest.len());
+        let plain = &rest[..start];
+        if shell == "zsh" {
+            escaped.push_str(&plain.replace('%', "%%"));
+        } else {
+            escaped.push_str(plain);
+        }
+        rest = &rest[start..];
+        if rest.is_empty() {
+            break;
+        }
+        let end = rest.find('m').map_or(rest.len(), |i| i + 1);
+        escaped.push_str(open);
+        escaped.push_str(&rest[..end]);
+        escaped.push_str(close);
+        rest = &rest[end..];
+    }
+    escaped
+}
+
+// 转义 Pango 标记中的特殊字符
+fn pango_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// 超过 max_length 个字符时截断并加省略号，0 表示不限制
+pub fn truncate(text: &str, max_length: usize) -> String {
+    if max_length == 0 || text.chars().count() <= max_length {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_length.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+// --raw 的输出：块的数值，整数不带小数点；没有数值的块（如充电状态）保留原文
+fn raw_value(block: &Block) -> Block {
+    let text = match block.value.or_else(|| first_number(&block.text)) {
+        Some(value) => value.to_string(),
+        None => block.text.clone(),
+    };
+    Block {
+        text,
+        ..block.clone()
+    }
+}
+
+// 文本滚动显示：每帧左移一个字符，首尾之间留三个空格
+fn marquee(text: &str, width: usize, frame: usize) -> String {
+    let chars: Vec<char> = text.chars().chain("   ".chars()).collect();
+    chars
+        .iter()
+        .cycle()
+        .skip(frame % chars.len())
+        .take(width)
+        .collect()
+}
+
+// --warn-below 等阈值参数：N 为全局阈值，MODULE=N 只用于一个模块（模块名或输出块名，如 battery=20）
+pub fn parse_threshold(value: &str) -> Result<(Option<String>, f64), String> {
+    let (module, number) = match value.split_once('=') {
+        Some((module, number)) => {
+            let entry =
+                module::find(module).ok_or_else(|| format!("unknown module '{}'", module))?;
+            (Some(entry.id.to_string()), number)
+        }
+        None => (None, value),
+    };
+    let number = number
+        .parse()
+        .map_err(|_| format!("invalid threshold '{}', expected N or MODULE=N", number))?;
+    Ok((module, number))
+}
+
+// 解析 --max-width 的值：N 限制整行宽度，MODULE=N 限制单个模块（模块名或输出块名）
+pub fn parse_max_width(value: &str) -> Result<(Option<String>, usize), String> {
+    let (module, width) = match value.split_once('=') {
+        Some((module, width)) => (Some(module.to_string()), width),
+        None => (None, value),
+    };
+    let width = width
+        .parse()
+        .map_err(|_| format!("invalid width '{}', expected N or MODULE=N", width))?;
+    Ok((module, width))
+}
+
+// --align：数字左侧补空格到出现过的最大宽度（百分比至少 3 位，如 "VOL:   7%"），
+// 整个块的文本右侧补空格到出现过的最大宽度，在终端或等宽状态栏中刷新时各字段不左右跳动
+// widths[0] 为整个块的宽度，其后依次为块中各个数字的宽度，出现更宽的值时更新
+fn align(text: &str, widths: &mut Vec<usize>) -> String {
+    if widths.is_empty() {
+        widths.push(0);
+    }
+    let mut index = 0;
+    let tokens: Vec<String> = text
+        .split(' ')
+        .map(|token| {
+            if !token.starts_with(|c: char| c.is_ascii_digit()) {
+                return token.to_string();
+            }
+            index += 1;
+            if widths.len() <= index {
+                widths.push(0);
+            }
+            let min = if token.ends_with('%') { 4 } else { 0 };
+            widths[index] = widths[index].max(token.chars().count()).max(min);
+            format!("{:>width$}", token, width = widths[index])
+        })
+        .collect();
+    let text = tokens.join(" ");
+    widths[0] = widths[0].max(text.chars().count());
+    format!("{:<width$}", text, width = widths[0])
+}
+
+// 整行超过 max_width 个字符时反复截短当前最长的指标，通常是媒体标题
+// 阈值标记 `!` 和颜色转义不计入宽度
+fn fit_width(blocks: &mut [(Block, Level)], separator: &str, max_width: usize) {
+    let separators = separator.chars().count() * blocks.len().saturating_sub(1);
+    loop {
+        let width: usize = separators
+            + blocks
+                .iter()
+                .map(|(block, _)| block.text.chars().count())
+                .sum::<usize>();
+        let Some((longest, _)) = blocks
+            .iter_mut()
+            .max_by_key(|(block, _)| block.text.chars().count())
+        else {
+            return;
+        };
+        let length = longest.text.chars().count();
+        // 已经放得下，或截到只剩省略号仍放不下时停止
+        if width <= max_width || length <= 1 {
+            return;
+        }
+        longest.text = truncate(
+            &longest.text,
+            length.saturating_sub(width - max_width).max(1),
+        );
+    }
+}
+
+// csv/tsv/env 的字段值：有数值的块输出数值，否则输出原文（不翻译、不加图标）
+fn field_value(block: &Block) -> String {
+    match block.value {
+        Some(value) => value.to_string(),
+        None => block.text.clone(),
+    }
+}
+
+// CSV 字段含逗号、引号或换行时加双引号，内部的引号重复一次
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// TSV 没有转义规则，字段中的制表符和换行替换为空格
+fn tsv_field(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+// 单引号包裹，内部的单引号写作 '\''，可以直接 eval；只含字母数字和 .-_ 的值（如数值）不加引号
+fn shell_quote(text: &str) -> String {
+    if !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    {
+        return text.to_string();
+    }
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+// env、csv/tsv 和记录文件中指标的字段名：模块名中的 - 换成 _，如 battery-capacity 为 battery_capacity
+// 不用块名，否则 --battery 和 --battery-capacity 都输出 battery，后一个会覆盖前一个
+pub fn field_name(block: &Block) -> String {
+    block.id.replace('-', "_")
+}
+
+// env 格式：每个指标一行 name=value，如 battery_capacity=73，可通过 eval "$(...)" 导入 shell
+fn env_lines(blocks: &[Block]) -> String {
+    blocks
+        .iter()
+        .map(|block| format!("{}={}", field_name(block), shell_quote(&field_value(block))))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// csv/tsv 的表头：time 和各指标的字段名
+pub fn table_header(blocks: &[Block], csv: bool) -> String {
+    let names: Vec<String> = std::iter::once("time".to_string())
+        .chain(blocks.iter().map(field_name))
+        .collect();
+    names.join(if csv { "," } else { "\t" })
+}
+
+// csv/tsv 的一行：第一列为 Unix 时间，便于在 watch 模式下追加到日志文件
+pub fn table_row(blocks: &[Block], csv: bool) -> String {
+    let escape = if csv { csv_field } else { tsv_field };
+    let fields: Vec<String> = std::iter::once(now_secs().to_string())
+        .chain(blocks.iter().map(|block| escape(&field_value(block))))
+        .collect();
+    fields.join(if csv { "," } else { "\t" })
+}
+
+fn level_name(level: Level) -> &'static str {
+    match level {
+        Level::Normal => "normal",
+        Level::Warning => "warning",
+        Level::Critical => "critical",
+    }
+}
+
+// yambar script 模块的一次输出：每行 "标签|类型|值"，以空行结束（由调用方的换行补齐）
+// 颜色在 yambar 配置中按 state 标签设置，每个指标另有同名标签
+fn yambar(blocks: &[(Block, Level)], separator: &str) -> String {
+    let mut lines: Vec<String> = blocks
+        .iter()
+        .map(|(block, _)| format!("{}|string|{}", block.name, block.text))
+        .collect();
+    let text: Vec<&str> = blocks
+        .iter()
+        .map(|(block, _)| block.text.as_str())
+        .collect();
+    let level = blocks
+        .iter()
+        .map(|(_, level)| *level)
+        .max()
+        .unwrap_or(Level::Normal);
+    lines.push(format!("text|string|{}", text.join(separator)));
+    lines.push(format!("state|string|{}", level_name(level)));
+    lines.join("\n") + "\n"
+}
+
+// --align 记录的各块宽度，按（块名，同名块中的序号）
+type Widths = HashMap<(&'static str, usize), Vec<usize>>;
+
+// 根据命令行参数和配置文件渲染指标：阈值级别、输出格式和颜色
+pub struct Renderer {
+    thresholds: Thresholds,
+    format: String,
+    preset: String,
+    max_width: Option<usize>,
+    // 各输出块的最大宽度，按输出块名
+    widths: Vec<(&'static str, usize)>,
+    // watch 模式下超出宽度的块滚动显示而不是截断
+    scroll: bool,
+    // --raw：只输出数值
+    raw: bool,
+    // --format-hook：由外部命令根据各指标生成整行输出
+    format_hook: Option<String>,
+    color: String,
+    normal_color: Option<String>,
+    warning_color: String,
+    critical_color: String,
+    // 配置文件中各模块的颜色（normal、warning、critical），按模块名，未设置的使用上面的颜色
+    module_colors: HashMap<&'static str, [Option<String>; 3]>,
+    icons: String,
+    icon: Option<String>,
+    // --api-version：JSON 输出的版本
+    api_version: u32,
+    // --speak-format：输出供语音合成朗读的句子
+    speak: bool,
+    // --align：各块的对齐宽度
+    align: Option<Mutex<Widths>>,
+}
+
+impl Renderer {
+    pub fn from_matches(matches: &clap::ArgMatches, config: &Config) -> Self {
+        // 颜色参数有默认值，只有在命令行显式指定时才覆盖配置文件
+        let color = |id: &str, value: &Option<Spanned<String>>| match value {
+            Some(value) if matches.value_source(id) != Some(ValueSource::CommandLine) => {
+                Some(value.get_ref().clone())
+            }
+            _ => matches.get_one::<String>(id).cloned(),
+        };
+        let max_widths: Vec<&(Option<String>, usize)> = matches
+            .get_many::<(Option<String>, usize)>("max-width")
+            .map_or(Vec::new(), |widths| widths.collect());
+        // 配置文件中的宽度在前，命令行指定的同名模块在后，查找时后者优先
+        let mut widths: Vec<(&'static str, usize)> = config
+            .modules
+            .iter()
+            .filter_map(|m| Some((module::get(m.name())?.block, m.max_width?)))
+            .collect();
+        widths.extend(max_widths.iter().filter_map(|(module, width)| {
+            let module = module.as_deref()?;
+            // 模块名转换为输出块名，输出块名（如 volume）直接使用
+            Some((module::find(module)?.block, *width))
+        }));
+        Renderer {
+            thresholds: Thresholds::from_matches(matches, config),
+            format: matches.get_one::<String>("format").unwrap().clone(),
+            preset: if matches.get_flag("tmux") {
+                "tmux".to_string()
+            } else {
+                matches.get_one::<String>("format-preset").unwrap().clone()
+            },
+            max_width: max_widths
+                .iter()
+                .rev()
+                .find_map(|(module, width)| module.is_none().then_some(*width))
+                .or(config.max_width)
+                .or(matches.get_flag("tmux").then_some(80)),
+            widths,
+            scroll: matches.get_flag("scroll"),
+            raw: matches.get_flag("raw"),
+            format_hook: matches.get_one::<String>("format-hook").cloned(),
+            color: matches.get_one::<String>("color").unwrap().clone(),
+            normal_color: color("color-normal", &config.color_normal),
+            warning_color: color("color-warning", &config.color_warning).unwrap(),
+            critical_color: color("color-critical", &config.color_critical).unwrap(),
+            module_colors: config
+                .modules
+                .iter()
+                .filter_map(|m| {
+                    let colors = [&m.color_normal, &m.color_warning, &m.color_critical]
+                        .map(|color| color.as_ref().map(|color| color.get_ref().clone()));
+                    Some((module::get(m.name())?.id, colors))
+                })
+                .collect(),
+            icons: matches.get_one::<String>("icons").unwrap().clone(),
+            icon: matches.get_one::<String>("icon").cloned(),
+            api_version: matches
+                .get_one::<u32>("api-version")
+                .copied()
+                .unwrap_or(API_VERSION),
+            speak: matches.get_flag("speak-format"),
+            align: matches.get_flag("align").then(Mutex::default),
+        }
+    }
+
+    // 未配置分隔符时使用，tmux 状态栏空间有限，只用空格分隔
+    pub fn default_separator(&self) -> &'static str {
+        if self.preset == "tmux" {
+            " "
+        } else {
+            " | "
+        }
+    }
+
+    fn color_for(&self, block: &Block, level: Level) -> Option<&str> {
+        let module = self
+            .module_colors
+            .get(block.id)
+            .and_then(|colors| colors[level as usize].as_deref());
+        module.or(match level {
+            Level::Normal => self.normal_color.as_deref(),
+            Level::Warning => Some(&self.warning_color),
+            Level::Critical => Some(&self.critical_color),
+        })
+    }
+
+    fn width_for(&self, block: &Block) -> Option<usize> {
+        self.widths
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == block.name)
+            .map(|(_, width)| *width)
+            .filter(|&width| width > 0)
+    }
+
+    // 是否有块超出宽度需要滚动，watch 模式据此定时重绘
+    pub fn scrolls(&self, blocks: &[Block]) -> bool {
+        self.scroll
+            && blocks.iter().any(|block| {
+                self.width_for(block)
+                    .is_some_and(|width| block.text.chars().count() > width)
+            })
+    }
+
+    // 按 --max-width MODULE=N 截断块的文本，滚动模式下显示第 frame 帧
+    fn fit_text(&self, block: &Block, text: &str, frame: Option<usize>) -> String {
+        match (self.width_for(block), frame) {
+            (Some(width), Some(frame)) if self.scroll && text.chars().count() > width => {
+                marquee(text, width, frame)
+            }
+            (Some(width), _) => truncate(text, width),
+            (None, _) => text.to_string(),
+        }
+    }
+
+    // 按 --icons/--icon 为 text 添加图标前缀，图标按块的英文原文选择
+    fn with_icon(&self, block: &Block, text: String) -> Block {
+        let icon = match &self.icon {
+            Some(icon) => Some(icon.clone()),
+            None => icons::icon(&self.icons, block),
+        };
+        Block {
+            text: match icon {
+                Some(icon) if !icon.is_empty() => format!("{} {}", icon, text),
+                _ => text,
+            },
+            ..block.clone()
+        }
+    }
+
+    // i3bar 协议中的一个 block 对象，设置 color 和 urgent
+    // instance 为模块名，点击事件据此区分同名的块（如 cpu 和 throttle 都是 cpu 块）
+    fn i3bar_block(&self, block: &Block, level: Level) -> String {
+        let mut fields = vec![
+            format!("\"name\":\"{}\"", block.name),
+            format!("\"full_text\":\"{}\"", json_escape(&block.text)),
+        ];
+        if self.api_version >= 2 {
+            fields.insert(1, format!("\"instance\":\"{}\"", block.id));
+        }
+        if let Some(color) = self.color_for(block, level) {
+            fields.push(format!("\"color\":\"{}\"", color));
+        }
+        if level == Level::Critical {
+            fields.push("\"urgent\":true".to_string());
+        }
+        fields.push(format!("\"_version\":{}", self.api_version));
+        format!("{{{}}}", fields.join(","))
+    }
+
+    // 纯文本：超过阈值时追加 `!`，--raw 时原样输出数值，按 --format-preset 使用对应状态栏的颜色语法，否则按 --color 着色
+    fn plain_text(&self, block: &Block, level: Level) -> String {
+        if self.raw {
+            return block.text.clone();
+        }
+        // 对齐时正常级别用空格代替 `!`，级别变化不改变宽度
+        let text = match level {
+            Level::Normal if self.align.is_some() => format!("{} ", block.text),
+            Level::Normal => block.text.clone(),
+            _ => format!("{}!", block.text),
+        };
+        let color = self.color_for(block, level);
+        match (self.preset.as_str(), color) {
+            ("polybar", Some(color)) => return format!("%{{F{}}}{}%{{F-}}", color, text),
+            ("dzen2", Some(color)) => {
+                return format!("^fg({}){}^fg()", color, text.replace('^', "^^"))
+            }
+            ("dzen2", None) => return text.replace('^', "^^"),
+            ("tmux", Some(color)) => {
+                return format!("#[fg={}]{}#[default]", color, text.replace('#', "##"))
+            }
+            ("tmux", None) => return text.replace('#', "##"),
+            _ => {}
+        }
+        match (self.color.as_str(), color) {
+            ("ansi", Some(color)) => ansi_color(color, &text),
+            ("pango", Some(color)) => format!(
+                "<span foreground=\"{}\">{}</span>",
+                color,
+                pango_escape(&text)
+            ),
+            ("pango", None) => pango_escape(&text),
+            _ => text,
+        }
+    }
+
+    // --prompt-segment 的输出：单个块的纯文本，--color ansi 时着色并按 shell 的语法转义
+    pub fn prompt_segment(&self, block: &Block, shell: &str) -> String {
+        if block.text.is_empty() {
+            return String::new();
+        }
+        let level = self.thresholds.block_level(block);
+        let text = self.fit_text(block, &i18n::localize(&block.text), None);
+        let block = self.with_icon(block, text);
+        let text = match (self.color.as_str(), self.color_for(&block, level)) {
+            ("ansi", Some(color)) => ansi_color(color, &block.text),
+            _ => block.text,
+        };
+        prompt_escape(&text, shell)
+    }
+
+    // 把多个指标渲染为一行状态
+    // plain: 用 separator 连接；i3bar: 每个指标一个 block；waybar: 合并文本，class 取最高级别
+    // csv/tsv 在数据行之前输出表头
+    pub fn render_line(&self, blocks: &[Block], separator: &str) -> String {
+        let line = self.render_frame(blocks, separator, None);
+        match self.header(blocks) {
+            Some(header) => format!("{}\n{}", header, line),
+            None => line,
+        }
+    }
+
+    // csv/tsv 的表头，watch 模式在指标集合变化时重新输出
+    pub fn header(&self, blocks: &[Block]) -> Option<String> {
+        match self.format.as_str() {
+            "csv" => Some(table_header(blocks, true)),
+            "tsv" => Some(table_header(blocks, false)),
+            _ => None,
+        }
+    }
+
+    // 每个指标一行 JSON 写入 hook 命令的标准输入，命令输出的第一行即为整行状态
+    // 如 {"name":"battery","module":"battery","text":"Charging: 97%","value":97,"level":"normal"}
+    // 用外部命令而不是内嵌 rhai/mlua 脚本引擎：不增加依赖和二进制体积，hook 可以用任何语言编写，
+    // 与 --script、点击动作一样通过 sh -c 运行，超时和输出读取由 script::run_with_input 处理
+    fn run_hook(&self, hook: &str, blocks: &[Block]) -> Result<String, Error> {
+        let input: String = blocks
+            .iter()
+            .map(|block| {
+                let value = block
+                    .value
+                    .map(|value| format!(",\"value\":{}", value))
+                    .unwrap_or_default();
+                let module = if self.api_version >= 2 {
+                    format!(",\"module\":\"{}\"", block.id)
+                } else {
+                    String::new()
+                };
+                format!(
+                    "{{\"name\":\"{}\"{},\"text\":\"{}\"{},\"level\":\"{}\",\"version\":{}}}\n",
+                    block.name,
+                    module,
+                    json_escape(&block.text),
+                    value,
+                    level_name(self.thresholds.block_level(block)),
+                    self.api_version
+                )
+            })
+            .collect();
+        script::run_with_input(hook, Some(&input))
+    }
+
+    // watch 模式按帧渲染，--scroll 时超出宽度的块逐帧滚动
+    pub fn render_frame(&self, blocks: &[Block], separator: &str, frame: Option<usize>) -> String {
+        // hook 失败时按默认方式输出，状态栏不会因脚本错误而空白
+        if let Some(hook) = self.format_hook.as_deref() {
+            match self.run_hook(hook, blocks) {
+                Ok(line) => return line,
+                Err(e) => log::error!("format hook: {}", e),
+            }
+        }
+        if self.speak {
+            return speak::sentences(blocks);
+        }
+        // 机器可读的格式不截断、不加图标，文本为空的指标也保留，列保持固定
+        match self.format.as_str() {
+            "csv" => return table_row(blocks, true),
+            "tsv" => return table_row(blocks, false),
+            "env" => return env_lines(blocks),
+            _ => {}
+        }
+        // 文本为空的指标（如未连接 VPN）不占位置
+        let mut seen: HashMap<&'static str, usize> = HashMap::new();
+        let mut blocks: Vec<(Block, Level)> = blocks
+            .iter()
+            .filter(|block| !block.text.is_empty())
+            .map(|block| {
+                let level = self.thresholds.block_level(block);
+                if self.raw {
+                    return (raw_value(block), level);
+                }
+                let mut text = self.fit_text(block, &i18n::localize(&block.text), frame);
+                if let Some(widths) = &self.align {
+                    let nth = seen.entry(block.name).or_default();
+                    let mut widths = widths.lock().unwrap();
+                    text = align(&text, widths.entry((block.name, *nth)).or_default());
+                    *nth += 1;
+                }
+                (self.with_icon(block, text), level)
+            })
+            .collect();
+        if let Some(max_width) = self.max_width.filter(|_| self.format == "plain") {
+            fit_width(&mut blocks, separator, max_width);
+        }
+        match self.format.as_str() {
+            "i3bar" => {
+                let objects: Vec<String> = blocks
+                    .iter()
+                    .map(|(block, level)| self.i3bar_block(block, *level))
+                    .collect();
+                format!("[{}]", objects.join(","))
+            }
+            "waybar" => {
+                let text: Vec<&str> = blocks
+                    .iter()
+                    .map(|(block, _)| block.text.as_str())
+                    .collect();
+                let mut class: Vec<&str> = blocks.iter().map(|(block, _)| block.name).collect();
+                match blocks.iter().map(|(_, level)| *level).max() {
+                    Some(Level::Warning) => class.push("warning"),
+                    Some(Level::Critical) => class.push("critical"),
+                    _ => {}
+                }
+                let mut fields = vec![
+                    format!("\"text\":\"{}\"", json_escape(&text.join(separator))),
+                    format!("\"class\":\"{}\"", class.join(" ")),
+                ];
+                // percentage 只对单个百分比类指标有意义
+                if let [(block, _)] = &blocks[..] {
+                    if let Some(percent) = block.percentage {
+                        fields.push(format!("\"percentage\":{}", percent.round() as i64));
+                    }
+                }
+                fields.push(format!("\"version\":{}", self.api_version));
+                format!("{{{}}}", fields.join(","))
+            }
+            _ if self.preset == "yambar" => yambar(&blocks, separator),
+            _ => {
+                let texts: Vec<String> = blocks
+                    .iter()
+                    .map(|(block, level)| self.plain_text(block, *level))
+                    .collect();
+                texts.join(separator)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_long_titles() {
+        assert_eq!(truncate("Daft Punk - Get Lucky", 9), "Daft Pun…");
+        assert_eq!(truncate("Daft Punk", 9), "Daft Punk");
+        assert_eq!(
+            truncate("Daft Punk - Get Lucky", 0),
+            "Daft Punk - Get Lucky"
+        );
+    }
+
+    #[test]
+    fn fits_line_to_max_width() {
+        let mut blocks = vec![
+            (
+                Block::new("media", "MEDIA: Daft Punk - Get Lucky".to_string()),
+                Level::Normal,
+            ),
+            (Block::new("battery", "BAT: 80%".to_string()), Level::Normal),
+        ];
+        // 28 + 1 + 8 = 37，截短最长的媒体标题
+        fit_width(&mut blocks, " ", 30);
+        assert_eq!(blocks[0].0.text, "MEDIA: Daft Punk - G…");
+        assert_eq!(blocks[1].0.text, "BAT: 80%");
+    }
+
+    #[test]
+    fn prints_machine_readable_formats() {
+        crate::sysfs::use_fixtures();
+        let matches =
+            crate::args::build_cli().get_matches_from(["sys-montion", "--battery-device", "BAT0"]);
+        // 同为 battery 块的两个模块各有一个字段
+        let blocks: Vec<Block> = ["battery", "battery-capacity"]
+            .iter()
+            .map(|name| module::read(name, &matches).unwrap())
+            .chain([Block::new(
+                "media",
+                "MEDIA: Rock 'n' Roll, Vol. 1".to_string(),
+            )])
+            .collect();
+        assert_eq!(
+            env_lines(&blocks),
+            "battery=80\nbattery_capacity=80\nmedia='MEDIA: Rock '\\''n'\\'' Roll, Vol. 1'"
+        );
+        assert_eq!(
+            table_header(&blocks, true),
+            "time,battery,battery_capacity,media"
+        );
+        assert_eq!(csv_field("Rock, \"Roll\""), "\"Rock, \"\"Roll\"\"\"");
+        assert_eq!(tsv_field("a\tb"), "a b");
+    }
+
+    #[test]
+    fn versions_json_output() {
+        crate::sysfs::use_fixtures();
+        let matches = crate::args::build_cli().get_matches_from(["sys-montion"]);
+        // percentage 为已用内存的百分比，而不是文本中的第一个数字 3.8
+        let blocks = [module::read("memory", &matches).unwrap()];
+        let render = |format: &str, version: &str| {
+            let matches = crate::args::build_cli().get_matches_from([
+                "sys-montion",
+                "--format",
+                format,
+                "--api-version",
+                version,
+            ]);
+            Renderer::from_matches(&matches, &Config::default()).render_line(&blocks, " | ")
+        };
+        assert_eq!(
+            render("waybar", "2"),
+            r#"{"text":"MEM: 3.8G/15.3G (25%)","class":"memory","percentage":25,"version":2}"#
+        );
+        assert_eq!(
+            render("i3bar", "2"),
+            r#"[{"name":"memory","instance":"memory","full_text":"MEM: 3.8G/15.3G (25%)","_version":2}]"#
+        );
+        // 固定为版本 1 时不输出 instance
+        assert_eq!(
+            render("i3bar", "1"),
+            r#"[{"name":"memory","full_text":"MEM: 3.8G/15.3G (25%)","_version":1}]"#
+        );
+    }
+
+    #[test]
+    fn applies_module_thresholds() {
+        crate::sysfs::use_fixtures();
+        let levels = |args: &[&str], config: &Config| {
+            let matches = crate::args::build_cli().get_matches_from(
+                ["sys-montion", "--battery-device", "BAT0"]
+                    .iter()
+                    .chain(args),
+            );
+            let thresholds = Thresholds::from_matches(&matches, config);
+            ["battery", "memory"].map(|name| {
+                let block = module::read(name, &matches).unwrap();
+                thresholds.block_level(&block)
+            })
+        };
+        // 电量 80%、内存已用 25%：内存的数值是百分比而不是 "3.8G" 中的 3.8
+        let config = Config::default();
+        let args = ["--warn-below", "20", "--critical-below", "10"];
+        assert_eq!(levels(&args, &config), [Level::Normal, Level::Normal]);
+        let args = [
+            "--warn-below",
+            "battery=90",
+            "--critical-above",
+            "memory=20",
+        ];
+        assert_eq!(levels(&args, &config), [Level::Warning, Level::Critical]);
+        // 模块未设置的阈值使用全局阈值
+        let args = ["--warn-above", "70", "--warn-above", "memory=30"];
+        assert_eq!(levels(&args, &config), [Level::Warning, Level::Normal]);
+        let config: Config =
+            toml::from_str("warn_above = 70\n[[modules]]\nname = \"battery\"\nwarn_above = 90\n")
+                .unwrap();
+        assert_eq!(levels(&[], &config), [Level::Normal, Level::Normal]);
+        assert_eq!(
+            levels(&["--warn-above", "battery=50"], &config),
+            [Level::Warning, Level::Normal]
+        );
+        assert_eq!(
+            parse_threshold("volume=5"),
+            Ok((Some("volume-level".to_string()), 5.0))
+        );
+        assert!(parse_threshold("nope=5").is_err());
+        assert!(parse_threshold("battery=x").is_err());
+    }
+
+    #[test]
+    fn applies_module_colors() {
+        let config: Config = toml::from_str(
+            "color_normal = \"#FFFFFF\"\n[[modules]]\nname = \"battery-capacity\"\ncolor_warning = \"#FF8800\"\n",
+        )
+        .unwrap();
+        let matches = crate::args::build_cli().get_matches_from([
+            "sys-montion",
+            "--format",
+            "i3bar",
+            "--warn-below",
+            "50",
+        ]);
+        let renderer = Renderer::from_matches(&matches, &config);
+        let low = |id| Block {
+            id,
+            ..Block::percent("battery", "20%".to_string())
+        };
+        // 模块的颜色只用于该模块，未设置的级别使用全局颜色
+        assert_eq!(
+            renderer.color_for(&low("battery-capacity"), Level::Warning),
+            Some("#FF8800")
+        );
+        assert_eq!(
+            renderer.color_for(&low("battery-capacity"), Level::Normal),
+            Some("#FFFFFF")
+        );
+        assert_eq!(
+            renderer.color_for(&low("battery"), Level::Warning),
+            Some("#FFFF00")
+        );
+        assert!(renderer
+            .render_line(&[low("battery-capacity")], " ")
+            .contains("\"color\":\"#FF8800\""));
+    }
+
+    #[test]
+    fn passes_modules_to_format_hook() {
+        let blocks = [
+            Block::numeric("memory", "MEM: 25%".to_string()),
+            Block {
+                id: "battery-capacity",
+                ..Block::numeric("battery", "80%".to_string())
+            },
+        ];
+        let matches = crate::args::build_cli().get_matches_from([
+            "sys-montion",
+            "--format-hook",
+            "tail -n 1",
+        ]);
+        assert_eq!(
+            Renderer::from_matches(&matches, &Config::default()).render_line(&blocks, " | "),
+            r#"{"name":"battery","module":"battery-capacity","text":"80%","value":80,"level":"normal","version":2}"#
+        );
+    }
+
+    #[test]
+    fn aligns_fields() {
+        let mut widths = Vec::new();
+        assert_eq!(align("VOL: 7%", &mut widths), "VOL:   7%");
+        assert_eq!(align("VOL: 100%", &mut widths), "VOL: 100%");
+        let mut widths = Vec::new();
+        assert_eq!(
+            align("Discharging: 80% 2h 5m", &mut widths),
+            "Discharging:  80% 2h 5m"
+        );
+        // 数字和整个块都不会变窄
+        assert_eq!(
+            align("Charging: 9% 12h 30m", &mut widths),
+            "Charging:   9% 12h 30m "
+        );
+        assert_eq!(align("Full: 100%", &mut widths), "Full: 100%             ");
+    }
+
+    #[test]
+    fn escapes_prompt_segments() {
+        let colored = ansi_color("#FF0000", "BAT: 9%");
+        assert_eq!(
+            prompt_escape(&colored, "zsh"),
+            "%{\x1b[38;2;255;0;0m%}BAT: 9%%%{\x1b[0m%}"
+        );
+        assert_eq!(
+            prompt_escape(&colored, "bash"),
+            "\x01\x1b[38;2;255;0;0m\x02BAT: 9%\x01\x1b[0m\x02"
+        );
+        assert_eq!(prompt_escape("BAT: 80%", "zsh"), "BAT: 80%%");
+        assert_eq!(prompt_escape(&colored, "none"), colored);
+    }
+
+    #[test]
+    fn prints_raw_values() {
+        let block = Block::numeric("battery", "Charging: 73%".to_string());
+        assert_eq!(raw_value(&block).text, "73");
+        let block = Block::new("cpu_freq", "CPU: 1.5GHz powersave".to_string());
+        assert_eq!(raw_value(&block).text, "1.5");
+        let block = Block::new("battery_state", "Charging".to_string());
+        assert_eq!(raw_value(&block).text, "Charging");
+    }
+
+    #[test]
+    fn scrolls_long_blocks() {
+        assert_eq!(marquee("Get Lucky", 5, 0), "Get L");
+        assert_eq!(marquee("Get Lucky", 5, 6), "cky  ");
+        assert_eq!(marquee("Get Lucky", 5, 11), " Get ");
+        assert_eq!(marquee("Get Lucky", 5, 12), "Get L");
+        assert_eq!(
+            parse_max_width("media=30"),
+            Ok((Some("media".to_string()), 30))
+        );
+        assert_eq!(parse_max_width("80"), Ok((None, 80)));
+        assert!(parse_max_width("media=").is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use crate::error::Error;
+use crate::log;
+use crate::module::{self, Builtin, MetricValue};
+use crate::render::Block;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 命令超过这个时间未退出就结束它，避免卡住整个刷新循环
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// 通过 `sh -c` 运行自定义命令，取标准输出的第一行，有 label 时加在前面
+// 如 command = "~/bin/weather.sh"、label = "W:" 输出 "W: 12°C"
+pub fn run(command: &str, label: Option<&str>) -> Result<String, Error> {
+    let text = run_with_input(command, None)?;
+    Ok(match label {
+        Some(label) if !text.is_empty() => format!("{} {}", label, text),
+        _ => text,
+    })
+}
+
+// 通过 `sh -c` 在后台运行点击动作、hook 等命令，不等待结果，env 为额外的环境变量
+// 由一个线程等待命令退出并回收，watch 模式下不会积累僵尸进程；what 用于日志，如 "sink hook"
+pub fn spawn(command: &str, env: &[(&str, String)], what: &str) -> Option<thread::JoinHandle<()>> {
+    let mut child = match Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            log::error!("running {}: {}", what, e);
+            return None;
+        }
+    };
+    let what = what.to_string();
+    Some(thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() => log::debug!("{} exited ({})", what, status),
+        Ok(_) => {}
+        Err(e) => log::error!("waiting for {}: {}", what, e),
+    }))
+}
+
+// 运行命令并把 input 写入其标准输入，返回标准输出的第一行
+pub fn run_with_input(command: &str, input: Option<&str>) -> Result<String, Error> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // 标准输入、输出和错误各用一个线程读写，命令输出超过管道缓冲区（约 64 KiB）时不会因为没人读取而卡住
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        let input = input.to_string();
+        thread::spawn(move || {
+            // 写完后关闭标准输入让命令读到末尾
+            let _ = stdin.write_all(input.as_bytes());
+        });
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if start.elapsed() >= TIMEOUT {
+            // 命令启动的后台进程可能仍持有管道，不等待读取线程
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(Error::BackendUnavailable(format!(
+                "'{}' timed out after {}s",
+                command,
+                TIMEOUT.as_secs()
+            )));
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    if !status.success() {
+        let stderr = stderr.join().unwrap_or_default();
+        return Err(Error::BackendUnavailable(format!(
+            "'{}' failed ({}): {}",
+            command,
+            status,
+            stderr.trim()
+        )));
+    }
+
+    Ok(stdout.lines().next().unwrap_or("").trim().to_string())
+}
+
+// 在线程中读完管道，返回读到的文本（非 UTF-8 部分替换为 U+FFFD）
+fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+// script 模块：运行 --script 或配置文件中 command 的命令，输出加上 label 前缀
+pub struct Script {
+    command: Option<String>,
+    label: Option<String>,
+}
+
+impl Script {
+    // 配置文件中带 command 的 script 模块
+    pub fn command(command: &str, label: Option<&str>) -> Self {
+        Script {
+            command: Some(command.to_string()),
+            label: label.map(str::to_string),
+        }
+    }
+}
+
+impl Builtin for Script {
+    const ID: &'static str = "script";
+    const BLOCK: &'static str = "script";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Script {
+            command: matches.get_one::<String>("script").cloned(),
+            label: None,
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let command = module::required(&self.command, "script", "--script COMMAND")?;
+        Ok(Block::numeric(
+            Self::BLOCK,
+            run(command, self.label.as_deref())?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_commands() {
+        assert_eq!(run("printf '12°C\\nmore'", Some("W:")).unwrap(), "W: 12°C");
+        assert_eq!(run("true", Some("W:")).unwrap(), "");
+        assert!(matches!(
+            run("echo oops >&2; exit 3", None),
+            Err(Error::BackendUnavailable(_))
+        ));
+        assert_eq!(
+            run_with_input("grep -c battery", Some("battery\nvolume\nbattery\n")).unwrap(),
+            "2"
+        );
+        // 输出超过管道缓冲区时边运行边读取，不会卡到超时
+        assert_eq!(run("yes line | head -c 200000", None).unwrap(), "line");
+        assert!(matches!(
+            run("yes oops | head -c 200000 >&2; exit 1", None),
+            Err(Error::BackendUnavailable(e)) if e.contains("failed")
+        ));
+    }
+
+    // 后台命令退出后被回收，不留下僵尸进程
+    #[test]
+    fn reaps_background_commands() {
+        let path = std::env::temp_dir().join(format!("sys-montion-spawn-{}", std::process::id()));
+        let handle = spawn(
+            "echo $$ > \"$PIDFILE\"",
+            &[("PIDFILE", path.display().to_string())],
+            "test",
+        );
+        handle.unwrap().join().unwrap();
+        let pid = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(!std::path::Path::new("/proc").join(pid.trim()).exists());
+    }
+}
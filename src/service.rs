@@ -0,0 +1,92 @@
+use crate::log;
+use crate::render::Block;
+use zbus::blocking::connection;
+use zbus::blocking::object_server::InterfaceRef;
+use zbus::{block_on, interface};
+
+const BUS_NAME: &str = "org.sysmontion.Monitor";
+const OBJECT_PATH: &str = "/org/sysmontion/Monitor";
+
+// 导出到 D-Bus 的数值，未运行对应模块或无法读取时为 -1
+struct Monitor {
+    battery_capacity: i32,
+    volume: i32,
+    brightness: i32,
+}
+
+#[interface(name = "org.sysmontion.Monitor")]
+impl Monitor {
+    #[zbus(property)]
+    fn battery_capacity(&self) -> i32 {
+        self.battery_capacity
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> i32 {
+        self.volume
+    }
+
+    #[zbus(property)]
+    fn brightness(&self) -> i32 {
+        self.brightness
+    }
+}
+
+// 会话总线上的 org.sysmontion.Monitor 服务，值来自 watch 模式中各模块的输出
+pub struct Service {
+    // 连接需要保持打开，服务才会继续存在
+    _connection: zbus::blocking::Connection,
+    monitor: InterfaceRef<Monitor>,
+}
+
+impl Service {
+    pub fn start() -> zbus::Result<Self> {
+        let monitor = Monitor {
+            battery_capacity: -1,
+            volume: -1,
+            brightness: -1,
+        };
+        let connection = connection::Builder::session()?
+            .name(BUS_NAME)?
+            .serve_at(OBJECT_PATH, monitor)?
+            .build()?;
+        let monitor = connection
+            .object_server()
+            .interface::<_, Monitor>(OBJECT_PATH)?;
+        Ok(Service {
+            _connection: connection,
+            monitor,
+        })
+    }
+
+    // 根据模块输出更新属性，值有变化时发送 PropertiesChanged 信号
+    pub fn update(&self, blocks: &[Block]) {
+        let value = |name: &str| {
+            blocks
+                .iter()
+                .find(|block| block.name == name)
+                .and_then(|block| block.value)
+                .map_or(-1, |v| v as i32)
+        };
+        let emitter = self.monitor.signal_emitter();
+        let mut monitor = self.monitor.get_mut();
+        let result = (|| {
+            if monitor.battery_capacity != value("battery") {
+                monitor.battery_capacity = value("battery");
+                block_on(monitor.battery_capacity_changed(emitter))?;
+            }
+            if monitor.volume != value("volume") {
+                monitor.volume = value("volume");
+                block_on(monitor.volume_changed(emitter))?;
+            }
+            if monitor.brightness != value("backlight") {
+                monitor.brightness = value("backlight");
+                block_on(monitor.brightness_changed(emitter))?;
+            }
+            zbus::Result::Ok(())
+        })();
+        if let Err(e) = result {
+            log::error!("emitting D-Bus signal: {}", e);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::event_loop::EventLoop;
+use crate::watch::Wake;
+use rustix::event::epoll::EventFlags;
+use signal_hook::consts::{SIGHUP, SIGUSR1};
+use signal_hook::low_level::pipe;
+use std::io::{self, Read};
+use std::os::unix::net::UnixStream;
+
+// 检查 SIGRTMIN+n 是否在实时信号范围内
+pub fn check_offset(offset: i32) -> Result<(), io::Error> {
+    if offset < 0 || libc::SIGRTMIN() + offset > libc::SIGRTMAX() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "signal SIGRTMIN+{} is out of range (0-{})",
+                offset,
+                libc::SIGRTMAX() - libc::SIGRTMIN()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// 信号处理函数向各自的套接字对写入一个字节，由事件循环读取
+fn add(events: &mut EventLoop, signal: i32, wake: fn(i32) -> Wake) -> Result<(), io::Error> {
+    let (reader, writer) = UnixStream::pair()?;
+    reader.set_nonblocking(true)?;
+    pipe::register(signal, writer)?;
+    let mut buf = [0u8; 64];
+    events.add(reader, EventFlags::IN, move |reader, woken| {
+        // 连续多次的同一信号合并为一次刷新
+        while reader.read(&mut buf).is_ok_and(|len| len > 0) {}
+        woken.push(wake(signal));
+        Ok(())
+    })
+}
+
+// SIGUSR1 刷新全部模块，SIGHUP 重新加载配置文件
+pub fn register(events: &mut EventLoop) -> Result<(), io::Error> {
+    add(events, SIGUSR1, |_| Wake::All)?;
+    add(events, SIGHUP, |_| Wake::Reload)
+}
+
+// SIGRTMIN+n 只刷新配置了 n 的模块
+// 与 i3blocks 相同，可在按键绑定中执行 `pkill -RTMIN+2 sys-montion`
+pub fn register_realtime(events: &mut EventLoop, offsets: &[i32]) -> Result<(), io::Error> {
+    for &offset in offsets {
+        add(events, libc::SIGRTMIN() + offset, |signal| {
+            Wake::Signal(signal - libc::SIGRTMIN())
+        })?;
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::event_loop::EventLoop;
+use crate::log;
+use crate::watch::Wake;
+use std::io;
+
+const SIGNAL: &str = "org.freedesktop.login1.Manager.PrepareForSleep";
+
+// 解析 `gdbus monitor` 输出的 PrepareForSleep 信号，返回 true 表示即将挂起，false 表示已唤醒
+// 信号行形如 "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (false,)"
+pub fn parse_signal(line: &str) -> Option<bool> {
+    let args = line.split_once(SIGNAL)?.1.trim();
+    match args.trim_start_matches('(').trim_end_matches([')', ',']) {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+// 监听 logind 的 PrepareForSleep 信号，从挂起中唤醒后立即刷新全部模块
+// 挂起期间单调时钟停止，不刷新的话时钟、电量和网络要等到下一次定时刷新才更新
+pub fn register(events: &mut EventLoop) -> Result<(), io::Error> {
+    events.add_command(
+        "gdbus",
+        &[
+            "monitor",
+            "--system",
+            "--dest",
+            "org.freedesktop.login1",
+            "--object-path",
+            "/org/freedesktop/login1",
+        ],
+        |line, woken| match parse_signal(line) {
+            Some(true) => log::debug!("suspending"),
+            Some(false) => {
+                log::info!("resumed from suspend, refreshing all modules");
+                woken.push(Wake::All);
+            }
+            None => {}
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sleep_signal() {
+        assert_eq!(
+            parse_signal(
+                "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (true,)"
+            ),
+            Some(true)
+        );
+        assert_eq!(
+            parse_signal(
+                "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (false,)"
+            ),
+            Some(false)
+        );
+        assert_eq!(
+            parse_signal("/org/freedesktop/login1: org.freedesktop.login1.Manager.SessionNew ('3', objectpath '/org/freedesktop/login1/session/_33')"),
+            None
+        );
+    }
+}
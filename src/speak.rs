@@ -0,0 +1,237 @@
+use crate::render::Block;
+
+// --speak-format：把各模块的输出改写为完整的英文句子，便于交给 espeak、festival 或屏幕阅读器朗读
+// 如 "Battery at 73 percent, discharging, about 2 hours 14 minutes left."
+
+// 输出前缀的读法，未列出的前缀原样朗读
+const LABELS: &[(&str, &str)] = &[
+    ("AC", "AC power"),
+    ("BL", "Backlight"),
+    ("BT", "Bluetooth"),
+    ("CAF", "Caffeine"),
+    ("CHG", "Charge threshold"),
+    ("CLIP", "Clipboard"),
+    ("COUNT", "Count"),
+    ("CPU", "CPU"),
+    ("CTR", "Containers"),
+    ("DISK", "Disk"),
+    ("DND", "Do not disturb"),
+    ("DOCK", "Docked with"),
+    ("GM", "Game mode"),
+    ("GPU", "GPU"),
+    ("IDLE", "Idle for"),
+    ("IP", "IP address"),
+    ("KBD", "Keyboard backlight"),
+    ("LID", "Lid"),
+    ("MEDIA", "Playing"),
+    ("MEM", "Memory"),
+    ("MIC", "Microphone"),
+    ("NET", "Network"),
+    ("NL", "Night light"),
+    ("PER", "Peripherals"),
+    ("PWR", "Power"),
+    ("REC", "Recording"),
+    ("SCR", "Screen time"),
+    ("SSD", "Disk temperature"),
+    ("TEMP", "Temperature"),
+    ("THR", "Throttled"),
+    ("TIMER", "Timer"),
+    ("TOP", "Top process"),
+    ("UPD", "Updates"),
+    ("USB", "Removable drives"),
+    ("VOL", "Volume"),
+    ("VPN", "VPN"),
+    ("WIFI", "Wi-Fi"),
+    ("WS", "Workspaces"),
+];
+
+// 缩写单词的读法
+const WORDS: &[(&str, &str)] = &[
+    ("MB/s", "megabytes per second"),
+    ("flaps/h", "flaps per hour"),
+    ("RX", "received"),
+    ("TX", "sent"),
+];
+
+fn plural(number: &str, singular: &str, plural: &str) -> String {
+    if number == "1" {
+        format!("{} {}", number, singular)
+    } else {
+        format!("{} {}", number, plural)
+    }
+}
+
+// 朗读一个带单位的数字，如 73% -> "73 percent"、2h -> "2 hours"、3.8G -> "3.8 gigabytes"
+// nightlight 的 K 为色温（开尔文），其余模块的 K 为 KiB
+fn number(token: &str, block: &str) -> Option<String> {
+    let end = token
+        .find(|c: char| !c.is_ascii_digit() && c != '.' && c != '-')
+        .unwrap_or(token.len());
+    let (value, unit) = token.split_at(end);
+    if !value.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    // 范围，如充电阈值 75-80%
+    let value = value.replace('-', " to ");
+    let value = value.as_str();
+    Some(match unit {
+        "" => value.to_string(),
+        "%" => format!("{} percent", value),
+        "°C" => format!("{} degrees", value),
+        "W" => plural(value, "watt", "watts"),
+        "GHz" => format!("{} gigahertz", value),
+        "K" if block == "nightlight" => format!("{} kelvin", value),
+        "B" => plural(value, "byte", "bytes"),
+        "K" => format!("{} kilobytes", value),
+        "M" => format!("{} megabytes", value),
+        "G" => format!("{} gigabytes", value),
+        "T" => format!("{} terabytes", value),
+        "d" => plural(value, "day", "days"),
+        "h" => plural(value, "hour", "hours"),
+        "m" => plural(value, "minute", "minutes"),
+        "s" => plural(value, "second", "seconds"),
+        _ => return None,
+    })
+}
+
+fn word(token: &str, block: &str) -> String {
+    // 工作区编号后的 ! 表示有紧急窗口
+    if let Some(token) = token.strip_suffix('!').filter(|t| !t.is_empty()) {
+        return format!("{} urgent", word(token, block));
+    }
+    if let Some(spoken) = number(token, block) {
+        return spoken;
+    }
+    // 已用/总量，如 3.8G/15.3G -> "3.8 gigabytes of 15.3 gigabytes"
+    if let Some((used, total)) = token.split_once('/') {
+        if let (Some(used), Some(total)) = (number(used, block), number(total, block)) {
+            return format!("{} of {}", used, total);
+        }
+    }
+    match (block, token) {
+        ("diskio", "R") => return "read".to_string(),
+        ("diskio", "W") => return "write".to_string(),
+        _ => {}
+    }
+    WORDS
+        .iter()
+        .find(|(short, _)| *short == token)
+        .map_or(token.to_string(), |(_, spoken)| spoken.to_string())
+}
+
+fn words(text: &str, block: &str) -> String {
+    let text = text.replace(" (", ", ").replace([')', '[', ']'], "");
+    text.split_whitespace()
+        .map(|token| {
+            // 逗号留在词尾，朗读时作为停顿
+            match token.strip_suffix(',') {
+                Some(token) => format!("{},", word(token, block)),
+                None => word(token, block),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// 电池各模块的输出：状态和电量 "Discharging: 73%"、状态 "Charging"、
+// 剩余时间 "2h 14m left"、功率 "10.0W"、健康度 "83% (17% wear)"
+fn battery(text: &str) -> String {
+    if let Some((status, capacity)) = text.split_once(": ") {
+        return format!(
+            "battery at {}, {}",
+            words(capacity, "battery"),
+            status.to_lowercase()
+        );
+    }
+    if let Some(time) = text.strip_suffix(" left") {
+        return format!("about {} left", words(time, "battery"));
+    }
+    if let Some(time) = text.strip_suffix(" until full") {
+        return format!("about {} until full", words(time, "battery"));
+    }
+    if text.ends_with('W') {
+        return format!("battery drawing {}", words(text, "battery"));
+    }
+    if text.starts_with(|c: char| c.is_ascii_digit()) {
+        return format!("battery {}", words(text, "battery"));
+    }
+    format!("battery {}", text.to_lowercase())
+}
+
+fn phrase(block: &Block) -> String {
+    if block.name == "battery" {
+        return battery(&block.text);
+    }
+    match block.text.split_once(": ") {
+        Some((label, rest)) => {
+            let label = LABELS
+                .iter()
+                .find(|(short, _)| *short == label)
+                .map_or(label, |(_, spoken)| spoken);
+            format!("{} {}", label, words(rest, block.name))
+        }
+        None => words(&block.text, block.name),
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// 每个模块一句话，相邻的电池模块（电量、剩余时间）合为一句
+pub fn sentences(blocks: &[Block]) -> String {
+    let mut sentences: Vec<String> = Vec::new();
+    let mut previous = "";
+    for block in blocks.iter().filter(|block| !block.text.is_empty()) {
+        let phrase = phrase(block);
+        match sentences.last_mut() {
+            Some(last) if block.name == "battery" && previous == "battery" => {
+                last.push_str(", ");
+                last.push_str(&phrase);
+            }
+            _ => sentences.push(capitalize(&phrase)),
+        }
+        previous = block.name;
+    }
+    sentences
+        .iter()
+        .map(|sentence| format!("{}.", sentence))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speaks_blocks_as_sentences() {
+        let blocks = [
+            Block::numeric("battery", "Discharging: 73%".to_string()),
+            Block::new("battery", "2h 14m left".to_string()),
+            Block::numeric("memory", "MEM: 3.8G/15.3G (25%)".to_string()),
+            Block::numeric("nightlight", "NL: 4500K".to_string()),
+            Block::new("vpn", String::new()),
+            Block::numeric("diskio", "DISK: R 1.2 MB/s W 0.3 MB/s".to_string()),
+        ];
+        assert_eq!(
+            sentences(&blocks),
+            "Battery at 73 percent, discharging, about 2 hours 14 minutes left. \
+             Memory 3.8 gigabytes of 15.3 gigabytes, 25 percent. \
+             Night light 4500 kelvin. \
+             Disk read 1.2 megabytes per second write 0.3 megabytes per second."
+        );
+        assert_eq!(
+            phrase(&Block::new("charge_threshold", "CHG: 75-80%".to_string())),
+            "Charge threshold 75 to 80 percent"
+        );
+        assert_eq!(
+            phrase(&Block::new("workspace", "WS: 1 [2: web] 3!".to_string())),
+            "Workspaces 1 2: web 3 urgent"
+        );
+    }
+}
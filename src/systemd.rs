@@ -0,0 +1,164 @@
+use crate::dbus::{self, Bus};
+use crate::error::Error;
+use crate::locale;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use std::env;
+use std::io;
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::time::Duration;
+
+// 向 systemd 发送状态通知（sd_notify 协议），如 "READY=1"、"WATCHDOG=1"
+// 未由 systemd 以 Type=notify 启动（没有 NOTIFY_SOCKET）时什么也不做
+pub fn notify(state: &str) -> Result<(), io::Error> {
+    let Some(path) = env::var_os("NOTIFY_SOCKET") else {
+        return Ok(());
+    };
+    let path = path.to_string_lossy().to_string();
+    // 以 @ 开头的是抽象命名空间套接字
+    let addr = match path.strip_prefix('@') {
+        Some(name) => SocketAddr::from_abstract_name(name)?,
+        None => SocketAddr::from_pathname(&path)?,
+    };
+    let socket = UnixDatagram::unbound()?;
+    socket.send_to_addr(state.as_bytes(), &addr)?;
+    Ok(())
+}
+
+// 看门狗心跳间隔：WATCHDOG_USEC 的一半，未启用看门狗或 WATCHDOG_PID 不是本进程时为 None
+pub fn watchdog_interval() -> Option<Duration> {
+    if let Ok(pid) = env::var("WATCHDOG_PID") {
+        if pid.parse() != Ok(std::process::id()) {
+            return None;
+        }
+    }
+    let usec: u64 = env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    (usec > 0).then(|| Duration::from_micros(usec / 2))
+}
+
+// 服务管理器中处于 failed 状态的单元数（Manager 的 NFailedUnits 属性，如 "uint32 2"）
+// gdbus 不可用时退回 `systemctl --failed`
+fn failed_count(bus: Bus) -> Result<u32, io::Error> {
+    match dbus::get_property(
+        bus,
+        "org.freedesktop.systemd1",
+        "/org/freedesktop/systemd1",
+        "org.freedesktop.systemd1.Manager",
+        "NFailedUnits",
+    ) {
+        Ok(value) => value
+            .rsplit(' ')
+            .next()
+            .and_then(|count| count.parse().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, value)),
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        Err(_) => {
+            let scope = match bus {
+                Bus::System => "--system",
+                Bus::Session => "--user",
+            };
+            let output = locale::command("systemctl")
+                .args([scope, "--failed", "--plain", "--no-legend"])
+                .output()?;
+            if !output.status.success() {
+                return Err(io::Error::other(
+                    String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                ));
+            }
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .count() as u32)
+        }
+    }
+}
+
+// 输出系统和用户服务管理器中失败的单元数：没有时为 "OK"，否则如 "✗ 2 failed"
+// 没有用户会话（如在系统服务中运行）时只统计系统单元
+pub fn get_failed_units() -> Result<String, Error> {
+    let system = failed_count(Bus::System)
+        .map_err(|e| Error::BackendUnavailable(format!("querying systemd: {}", e)))?;
+    let user = failed_count(Bus::Session).unwrap_or(0);
+    Ok(match system + user {
+        0 => "OK".to_string(),
+        failed => format!("✗ {} failed", failed),
+    })
+}
+
+// 按 systemd 的规则为 ExecStart 中的参数加引号
+fn quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || "\"'\\;$%".contains(c)) {
+        return arg.to_string();
+    }
+    let escaped = arg
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "$$")
+        .replace('%', "%%");
+    format!("\"{}\"", escaped)
+}
+
+// 生成用户服务文件，安装到 ~/.config/systemd/user/sys-montion.service
+// args 为 watch 模式的参数，未包含 --systemd 时自动加上
+pub fn unit_file(exe: &str, args: &[String]) -> String {
+    let mut command = vec![quote(exe)];
+    command.extend(args.iter().map(|arg| quote(arg)));
+    if !args.iter().any(|arg| arg == "--systemd") {
+        command.push("--systemd".to_string());
+    }
+    format!(
+        "[Unit]
+Description=sys-montion status monitor
+PartOf=graphical-session.target
+After=graphical-session.target
+
+[Service]
+Type=notify
+ExecStart={}
+WatchdogSec=30
+Restart=on-failure
+
+[Install]
+WantedBy=graphical-session.target
+",
+        command.join(" ")
+    )
+}
+
+pub struct FailedUnits;
+
+impl Builtin for FailedUnits {
+    const ID: &'static str = "failed-units";
+    const BLOCK: &'static str = "systemd";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        FailedUnits
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, get_failed_units()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_unit_file() {
+        let unit = unit_file(
+            "/usr/bin/sys-montion",
+            &[
+                "--watch".to_string(),
+                "5".to_string(),
+                "--script".to_string(),
+                "echo 50%".to_string(),
+            ],
+        );
+        assert!(unit.contains(
+            "\nExecStart=/usr/bin/sys-montion --watch 5 --script \"echo 50%%\" --systemd\n"
+        ));
+        assert!(unit.contains("\nType=notify\n"));
+    }
+}
@@ -0,0 +1,147 @@
+use crate::error::Error;
+use crate::locale;
+use std::env;
+use std::io;
+
+// 是否运行在 Android 的 Termux 中：Termux 设置 TERMUX_VERSION，PREFIX 位于应用数据目录下
+// Termux 中没有 /sys/class/power_supply 和 backlight 的读取权限，电池数据来自 Termux:API
+pub fn detected() -> bool {
+    env::var_os("TERMUX_VERSION").is_some()
+        || env::var("PREFIX").is_ok_and(|prefix| prefix.contains("/com.termux/"))
+}
+
+// termux-battery-status 输出的电池状态
+#[derive(Debug, PartialEq)]
+struct Status {
+    percentage: i64,
+    // CHARGING、DISCHARGING、FULL、NOT_CHARGING、UNKNOWN
+    status: String,
+    // UNPLUGGED、PLUGGED_AC、PLUGGED_USB、PLUGGED_WIRELESS
+    plugged: String,
+    // GOOD、OVERHEAT、DEAD、OVER_VOLTAGE、COLD 等
+    health: String,
+}
+
+// 取出 JSON 对象中 "key": 之后的值
+fn field<'a>(json: &'a str, key: &str) -> Option<&'a str> {
+    let start = json.find(&format!("\"{}\"", key))? + key.len() + 2;
+    json[start..]
+        .trim_start()
+        .strip_prefix(':')
+        .map(str::trim_start)
+}
+
+fn json_string(json: &str, key: &str) -> Option<String> {
+    let value = field(json, key)?.strip_prefix('"')?;
+    Some(value[..value.find('"')?].to_string())
+}
+
+fn json_number(json: &str, key: &str) -> Option<f64> {
+    let value = field(json, key)?;
+    let end = value
+        .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+fn parse_status(json: &str) -> Result<Status, Error> {
+    let invalid = || Error::Parse("unexpected termux-battery-status output".to_string());
+    Ok(Status {
+        percentage: json_number(json, "percentage").ok_or_else(invalid)? as i64,
+        status: json_string(json, "status").ok_or_else(invalid)?,
+        plugged: json_string(json, "plugged").unwrap_or_default(),
+        health: json_string(json, "health").unwrap_or_default(),
+    })
+}
+
+// 运行 termux-battery-status（termux-api 软件包，需要安装 Termux:API 应用）
+fn read_status() -> Result<Status, Error> {
+    let output = locale::command("termux-battery-status")
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => Error::BackendUnavailable(
+                "termux-battery-status is not installed (pkg install termux-api)".to_string(),
+            ),
+            _ => Error::from(e),
+        })?;
+    if !output.status.success() {
+        return Err(Error::BackendUnavailable(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    parse_status(&String::from_utf8_lossy(&output.stdout))
+}
+
+// "NOT_CHARGING" -> "Not charging"，与 sysfs 的状态名一致
+fn title_case(name: &str) -> String {
+    let lower = name.to_lowercase().replace('_', " ");
+    let mut chars = lower.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => lower,
+    }
+}
+
+pub fn get_battery_capacity() -> Result<String, Error> {
+    Ok(read_status()?.percentage.to_string())
+}
+
+pub fn get_battery_status() -> Result<String, Error> {
+    Ok(title_case(&read_status()?.status))
+}
+
+// Android 不提供剩余能量和功率，无法估算剩余时间，只返回状态
+pub fn get_battery_time() -> Result<String, Error> {
+    get_battery_status()
+}
+
+pub fn read_power_watts() -> Result<f64, Error> {
+    Err(Error::MissingDevice(
+        "termux-battery-status does not report battery voltage".to_string(),
+    ))
+}
+
+// Android 只提供健康状态，不提供设计容量，如 "Good"、"Overheat"
+pub fn get_battery_health() -> Result<String, Error> {
+    Ok(title_case(&read_status()?.health))
+}
+
+pub fn get_ac() -> Result<String, Error> {
+    let status = read_status()?;
+    Ok(match status.plugged.as_str() {
+        "UNPLUGGED" | "" => "AC: off".to_string(),
+        _ => "AC: on".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_battery_status() {
+        let json = r#"{
+  "health": "GOOD",
+  "percentage": 85,
+  "plugged": "PLUGGED_USB",
+  "status": "NOT_CHARGING",
+  "temperature": 29.799999237060547,
+  "current": -245000
+}"#;
+        let status = parse_status(json).unwrap();
+        assert_eq!(
+            status,
+            Status {
+                percentage: 85,
+                status: "NOT_CHARGING".to_string(),
+                plugged: "PLUGGED_USB".to_string(),
+                health: "GOOD".to_string(),
+            }
+        );
+        assert_eq!(title_case(&status.status), "Not charging");
+        assert!(matches!(
+            parse_status("termux-api is not running"),
+            Err(Error::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::paths::runtime_path;
+use crate::render::Block;
+use crate::timeutil::now_secs;
+use std::fs;
+use std::io;
+
+// 计时器的结束时间（Unix 秒）记录在运行时目录中，一次性输出和 watch 模式都能读到
+const STATE_FILE: &str = "sys-montion-timer";
+
+// 未启动时为 None
+fn load() -> Option<i64> {
+    fs::read_to_string(runtime_path(STATE_FILE).ok()?)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+// 剩余时间格式为 "MM:SS"，超过一小时为 "H:MM:SS"
+fn format_remaining(seconds: i64) -> String {
+    match seconds {
+        0..=3599 => format!("{:02}:{:02}", seconds / 60, seconds % 60),
+        _ => format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        ),
+    }
+}
+
+fn describe(end: Option<i64>, now: i64) -> String {
+    match end {
+        None => "TIMER: off".to_string(),
+        Some(end) if end <= now => "TIMER: done".to_string(),
+        Some(end) => format!("TIMER: {}", format_remaining(end - now)),
+    }
+}
+
+// 输出计时器的剩余时间，如 "TIMER: 24:59"；到时后为 "TIMER: done"，直到 `timer stop`
+pub fn get_timer() -> Result<String, Error> {
+    Ok(describe(load(), now_secs()))
+}
+
+// 启动计时器，已有计时器时重新开始
+pub fn start(duration: i64) -> Result<String, Error> {
+    let path = runtime_path(STATE_FILE)?;
+    fs::write(path, format!("{}\n", now_secs() + duration))?;
+    get_timer()
+}
+
+pub fn stop() -> Result<String, Error> {
+    match fs::remove_file(runtime_path(STATE_FILE)?) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(Error::from(e)),
+        _ => {}
+    }
+    get_timer()
+}
+
+pub struct Timer;
+
+impl Builtin for Timer {
+    const ID: &'static str = "timer";
+    const BLOCK: &'static str = "timer";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Timer
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_timer()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shows_remaining_time() {
+        assert_eq!(describe(Some(2500), 1001), "TIMER: 24:59");
+        assert_eq!(describe(Some(8000), 800), "TIMER: 2:00:00");
+        assert_eq!(describe(Some(2500), 2500), "TIMER: done");
+        assert_eq!(describe(None, 0), "TIMER: off");
+    }
+}
@@ -0,0 +1,183 @@
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 时间来源，所有模块都通过 now_secs() 获取当前时间，
+// 以便用固定时钟复现与时间相关的问题
+pub trait Clock: Send + Sync {
+    fn now(&self) -> i64;
+}
+
+// 系统时钟
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0)
+    }
+}
+
+// 固定时钟，始终返回同一时刻（--now）
+pub struct FixedClock(pub i64);
+
+impl Clock for FixedClock {
+    fn now(&self) -> i64 {
+        self.0
+    }
+}
+
+static CLOCK: OnceLock<Box<dyn Clock>> = OnceLock::new();
+
+// 设置全局时间来源，需在读取时间前调用
+pub fn set_clock(clock: Box<dyn Clock>) {
+    let _ = CLOCK.set(clock);
+}
+
+// 当前 Unix 时间（秒）
+pub fn now_secs() -> i64 {
+    CLOCK.get_or_init(|| Box::new(SystemClock)).now()
+}
+
+// 解析 --now 参数：Unix 秒数或 UTC 时间 "2024-10-06T12:00:00Z"
+pub fn parse_timestamp(value: &str) -> Result<i64, String> {
+    if let Ok(timestamp) = value.parse::<i64>() {
+        return Ok(timestamp);
+    }
+    let invalid = || {
+        format!(
+            "invalid timestamp '{}', expected Unix seconds or YYYY-MM-DDTHH:MM:SSZ",
+            value
+        )
+    };
+    let value = value.strip_suffix('Z').unwrap_or(value);
+    let (date, time) = value.split_once('T').unwrap_or((value, "00:00:00"));
+    let date: Vec<i64> = date
+        .split('-')
+        .map(|p| p.parse().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    let time: Vec<i64> = time
+        .split(':')
+        .map(|p| p.parse().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    match (&date[..], &time[..]) {
+        ([year, month, day], [hour, minute, second]) => {
+            Ok(days_from_civil(*year, *month, *day) * 86400 + hour * 3600 + minute * 60 + second)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+// 解析时长，如 "90s"、"30m"、"1h"、"2d"，没有单位时按秒计算
+pub fn parse_duration(value: &str) -> Result<i64, String> {
+    let invalid = || format!("invalid duration '{}', expected e.g. 30m, 1h or 2d", value);
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let number: i64 = number.parse().map_err(|_| invalid())?;
+    let scale = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(invalid()),
+    };
+    match number * scale {
+        0 => Err(invalid()),
+        seconds => Ok(seconds),
+    }
+}
+
+// 公历日期转换为自 1970-01-01 起的天数
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// 自 1970-01-01 起的天数转换为公历日期 (年, 月, 日)
+pub fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// 解析 `date +%z` 的输出，如 "+0800"、"-0430"
+fn parse_offset(output: &[u8]) -> Option<i64> {
+    let offset = String::from_utf8_lossy(output).trim().to_string();
+    if offset.len() != 5 {
+        return None;
+    }
+    let sign = if offset.starts_with('-') { -1 } else { 1 };
+    let hours: i64 = offset[1..3].parse().ok()?;
+    let minutes: i64 = offset[3..5].parse().ok()?;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+// 通过 `date +%z` 获取本地时区偏移（秒），失败时按 UTC 处理
+pub fn local_utc_offset() -> i64 {
+    let Ok(output) = Command::new("date").arg("+%z").output() else {
+        return 0;
+    };
+    parse_offset(&output.stdout).unwrap_or(0)
+}
+
+// 时区 tz（如 Europe/Berlin）在 timestamp 时刻的 UTC 偏移（秒），通过 `TZ=tz date -d @timestamp +%z` 获取，
+// 夏令时按该时刻计算；系统时区数据库中没有该时区时返回 None（date 会静默按 UTC 处理）
+pub fn zone_utc_offset(tz: &str, timestamp: i64) -> Option<i64> {
+    let zoneinfo = std::env::var("TZDIR").unwrap_or_else(|_| "/usr/share/zoneinfo".to_string());
+    if tz.is_empty() || tz.contains("..") || !Path::new(&zoneinfo).join(tz).is_file() {
+        return None;
+    }
+    let output = Command::new("date")
+        .env("TZ", tz)
+        .arg("-d")
+        .arg(format!("@{}", timestamp))
+        .arg("+%z")
+        .output()
+        .ok()?;
+    parse_offset(&output.stdout)
+}
+
+// 本地日期字符串，如 "2024-10-06"
+pub fn local_date(timestamp: i64) -> String {
+    let (year, month, day) = civil_from_days((timestamp + local_utc_offset()).div_euclid(86400));
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("1h"), Ok(3600));
+        assert_eq!(parse_duration("30m"), Ok(1800));
+        assert_eq!(parse_duration("90"), Ok(90));
+        assert!(parse_duration("1w").is_err());
+        assert!(parse_duration("0h").is_err());
+    }
+
+    #[test]
+    fn parses_utc_offsets() {
+        assert_eq!(parse_offset(b"+0800\n"), Some(28800));
+        assert_eq!(parse_offset(b"-0430"), Some(-16200));
+        assert_eq!(parse_offset(b""), None);
+        assert_eq!(zone_utc_offset("No/Such_Zone", 0), None);
+        assert_eq!(zone_utc_offset("../etc/passwd", 0), None);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::{counters, format_size, read_file, sysfs};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// 排序依据
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SortBy {
+    Cpu,
+    Rss,
+}
+
+impl SortBy {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "cpu" => Some(SortBy::Cpu),
+            "rss" => Some(SortBy::Rss),
+            _ => None,
+        }
+    }
+}
+
+// 读取 /proc/stat 中的总 CPU 时间和 CPU 数量
+fn read_total_ticks() -> Result<(u64, u64), Error> {
+    let stat = read_file("/proc/stat")?;
+    let mut total = None;
+    let mut cpus = 0;
+    for line in stat.lines() {
+        if let Some(rest) = line.strip_prefix("cpu ") {
+            total = Some(
+                rest.split_whitespace()
+                    .filter_map(|v| v.parse::<u64>().ok())
+                    .sum(),
+            );
+        } else if line.starts_with("cpu") {
+            cpus += 1;
+        }
+    }
+    let total =
+        total.ok_or_else(|| Error::Parse("cpu line not found in /proc/stat".to_string()))?;
+    Ok((total, cpus.max(1)))
+}
+
+// 从 /proc/<pid>/stat 解析 utime + stime
+// 进程名可能包含空格和括号，所以从最后一个 ')' 之后开始按字段切分
+fn parse_stat_ticks(stat: &str) -> Option<u64> {
+    let fields: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+// 从 /proc/<pid>/status 读取进程名和 VmRSS（KiB），内核线程没有 VmRSS
+fn parse_status(status: &str) -> (Option<String>, u64) {
+    let mut name = None;
+    let mut rss = 0;
+    for line in status.lines() {
+        if let Some(value) = line.strip_prefix("Name:") {
+            name = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("VmRSS:") {
+            rss = value
+                .split_whitespace()
+                .next()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+        }
+    }
+    (name, rss)
+}
+
+// 扫描 /proc 下所有进程，返回 pid -> (名称, CPU 时间, RSS)
+// 扫描期间退出的进程直接跳过
+fn read_processes() -> Result<HashMap<u32, (String, u64, u64)>, Error> {
+    let mut processes = HashMap::new();
+    for entry in sysfs::read_dir("/proc")? {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|n| n.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let (Ok(stat), Ok(status)) = (
+            read_file(format!("/proc/{}/stat", pid)),
+            read_file(format!("/proc/{}/status", pid)),
+        ) else {
+            continue;
+        };
+        let (Some(ticks), (Some(name), rss)) = (parse_stat_ticks(&stat), parse_status(&status))
+        else {
+            continue;
+        };
+        processes.insert(pid, (name, ticks, rss));
+    }
+    Ok(processes)
+}
+
+// 与上一次采样比较，返回各进程名的 CPU 占用百分比（按单核计，可超过 100%）
+// 采样保存为 [总 CPU 时间, CPU 数量, pid, CPU 时间, pid, CPU 时间, ...]
+fn cpu_usage() -> Result<HashMap<String, f64>, Error> {
+    let names: RefCell<HashMap<u64, String>> = RefCell::new(HashMap::new());
+    let (_, previous, current) = counters::delta("top", || {
+        let (total, cpus) = read_total_ticks()?;
+        let mut values = vec![total, cpus];
+        let mut names = names.borrow_mut();
+        names.clear();
+        for (pid, (name, ticks, _)) in read_processes()? {
+            values.extend([pid as u64, ticks]);
+            names.insert(pid as u64, name);
+        }
+        Ok(values)
+    })?;
+
+    let mut usage: HashMap<String, f64> = HashMap::new();
+    let (Some(&before), Some(&total), Some(&cpus)) =
+        (previous.first(), current.first(), current.get(1))
+    else {
+        return Ok(usage);
+    };
+    let elapsed = total.saturating_sub(before);
+    if elapsed == 0 {
+        return Ok(usage);
+    }
+    let last: HashMap<u64, u64> = previous
+        .get(2..)
+        .unwrap_or_default()
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .collect();
+    let names = names.borrow();
+    for pair in current[2..].chunks_exact(2) {
+        let (pid, ticks) = (pair[0], pair[1]);
+        let Some(name) = names.get(&pid) else {
+            continue;
+        };
+        // 新出现的进程从 0 开始计算
+        let before = last.get(&pid).copied().unwrap_or(0);
+        let percent = ticks.saturating_sub(before) as f64 * 100.0 * cpus as f64 / elapsed as f64;
+        *usage.entry(name.clone()).or_default() += percent;
+    }
+    Ok(usage)
+}
+
+// 按进程名合计 RSS（KiB），同名的多个进程（如浏览器的各个子进程）合并计算
+fn rss_usage() -> Result<HashMap<String, u64>, Error> {
+    let mut usage: HashMap<String, u64> = HashMap::new();
+    for (name, _, rss) in read_processes()?.into_values() {
+        *usage.entry(name).or_default() += rss;
+    }
+    Ok(usage)
+}
+
+// 列出 CPU 占用或内存占用最高的 count 个进程，如 "TOP: firefox 21%"
+pub fn get_top(count: usize, by: SortBy) -> Result<String, Error> {
+    let entries: Vec<String> = match by {
+        SortBy::Cpu => {
+            let mut usage: Vec<(String, f64)> = cpu_usage()?.into_iter().collect();
+            usage.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            usage
+                .into_iter()
+                .take(count)
+                .map(|(name, percent)| format!("{} {:.0}%", name, percent))
+                .collect()
+        }
+        SortBy::Rss => {
+            let mut usage: Vec<(String, u64)> = rss_usage()?.into_iter().collect();
+            usage.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            usage
+                .into_iter()
+                .take(count)
+                .map(|(name, rss)| format!("{} {}", name, format_size(rss * 1024)))
+                .collect()
+        }
+    };
+
+    if entries.is_empty() {
+        return Err(Error::MissingDevice(
+            "no processes found in /proc".to_string(),
+        ));
+    }
+    Ok(format!("TOP: {}", entries.join(", ")))
+}
+
+pub struct Top {
+    count: usize,
+    by: SortBy,
+}
+
+impl Builtin for Top {
+    const ID: &'static str = "top";
+    const BLOCK: &'static str = "top";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Top {
+            count: matches.get_one::<usize>("top").copied().unwrap_or(1),
+            by: SortBy::parse(matches.get_one::<String>("top-by").unwrap()).unwrap(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_top(self.count, self.by)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_stat_with_odd_names() {
+        let stat = "1234 (Web Content (x)) S 1 1234 1234 0 -1 4194560 100 0 0 0 150 50 0 0 20 0";
+        assert_eq!(parse_stat_ticks(stat), Some(200));
+        assert_eq!(parse_stat_ticks("garbage"), None);
+    }
+
+    #[test]
+    fn sums_rss_by_name() {
+        sysfs::use_fixtures();
+        // 两个 firefox 进程合计 1.5G，内核线程没有 VmRSS
+        assert_eq!(
+            get_top(2, SortBy::Rss).unwrap(),
+            "TOP: firefox 1.5G, Xorg 200.0M"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use crate::event_loop::EventLoop;
+use crate::watch::Wake;
+use rustix::event::epoll::EventFlags;
+use rustix::fd::OwnedFd;
+use rustix::net::netlink::{self, SocketAddrNetlink};
+use rustix::net::{bind, recv, socket_with, AddressFamily, RecvFlags, SocketFlags, SocketType};
+use std::io;
+
+// 打开内核 uevent 套接字（NETLINK_KOBJECT_UEVENT）
+fn open_socket() -> Result<OwnedFd, io::Error> {
+    let fd = socket_with(
+        AddressFamily::NETLINK,
+        SocketType::DGRAM,
+        SocketFlags::CLOEXEC,
+        Some(netlink::KOBJECT_UEVENT),
+    )?;
+    // 组 1 为内核广播的 uevent
+    bind(&fd, &SocketAddrNetlink::new(0, 1))?;
+    Ok(fd)
+}
+
+// 监听 uevent，关注的子系统（如 power_supply、backlight）产生事件时刷新全部模块
+pub fn register(events: &mut EventLoop, subsystems: &[&'static str]) -> Result<(), io::Error> {
+    let subsystems = subsystems.to_vec();
+    let mut buf = [0u8; 8192];
+    events.add(open_socket()?, EventFlags::IN, move |fd, woken| {
+        let (len, _) = recv(&*fd, &mut buf, RecvFlags::empty())?;
+        // 消息由 NUL 分隔，形如 "change@/devices/...\0ACTION=change\0SUBSYSTEM=power_supply\0..."
+        let matched = buf[..len]
+            .split(|&b| b == 0)
+            .filter_map(|field| field.strip_prefix(b"SUBSYSTEM="))
+            .any(|subsystem| subsystems.iter().any(|s| s.as_bytes() == subsystem));
+        if matched {
+            woken.push(Wake::All);
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,212 @@
+use crate::capabilities::command_exists;
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::paths::cache_path;
+use crate::render::Block;
+use crate::timeutil::now_secs;
+use crate::{locale, log};
+use std::fs;
+use std::io;
+
+// 查询可用更新的包管理器
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Backend {
+    // Arch：pacman-contrib 的 checkupdates，使用临时数据库，不需要 root
+    Pacman,
+    // Debian/Ubuntu：模拟 apt-get upgrade，基于上次 apt update 的软件包列表
+    Apt,
+    // Fedora：dnf check-update，会按需刷新元数据
+    Dnf,
+}
+
+impl Backend {
+    pub const NAMES: [&'static str; 4] = ["auto", "pacman", "apt", "dnf"];
+
+    // `auto` 按 PATH 中存在的命令选择
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "auto" => [Backend::Pacman, Backend::Apt, Backend::Dnf]
+                .into_iter()
+                .find(|backend| command_exists(backend.command())),
+            "pacman" => Some(Backend::Pacman),
+            "apt" => Some(Backend::Apt),
+            "dnf" => Some(Backend::Dnf),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Backend::Pacman => "pacman",
+            Backend::Apt => "apt",
+            Backend::Dnf => "dnf",
+        }
+    }
+
+    pub fn command(self) -> &'static str {
+        match self {
+            Backend::Pacman => "checkupdates",
+            Backend::Apt => "apt-get",
+            Backend::Dnf => "dnf",
+        }
+    }
+
+    fn args(self) -> &'static [&'static str] {
+        match self {
+            Backend::Pacman => &[],
+            Backend::Apt => &["-s", "-o", "Debug::NoLocking=true", "upgrade"],
+            Backend::Dnf => &["-q", "check-update"],
+        }
+    }
+
+    // 成功的退出码：checkupdates 没有更新时返回 2，dnf 有更新时返回 100
+    fn succeeded(self, code: Option<i32>) -> bool {
+        matches!(
+            (self, code),
+            (_, Some(0)) | (Backend::Pacman, Some(2)) | (Backend::Dnf, Some(100))
+        )
+    }
+
+    // 从命令输出中统计待更新的软件包数
+    fn count(self, output: &str) -> usize {
+        match self {
+            // 每行 "name 1.0-1 -> 1.1-1"
+            Backend::Pacman => output.lines().filter(|line| line.contains(" -> ")).count(),
+            // 每个待安装的软件包一行 "Inst name [1.0] (1.1 ...)"
+            Backend::Apt => output
+                .lines()
+                .filter(|line| line.starts_with("Inst "))
+                .count(),
+            // 每行 "name.arch version repo"，之后可能有 "Obsoleting Packages" 段
+            Backend::Dnf => output
+                .lines()
+                .take_while(|line| !line.starts_with("Obsoleting"))
+                .filter(|line| line.split_whitespace().count() == 3)
+                .count(),
+        }
+    }
+
+    fn check(self) -> Result<usize, Error> {
+        let output = locale::command(self.command())
+            .args(self.args())
+            .output()
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => {
+                    Error::BackendUnavailable(format!("{} is not installed", self.command()))
+                }
+                _ => Error::from(e),
+            })?;
+        if !self.succeeded(output.status.code()) {
+            return Err(Error::BackendUnavailable(format!(
+                "{}: {}",
+                self.command(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(self.count(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+// 缓存文件每行 "包管理器\t检查时间（秒）\t更新数"
+fn load_cache(backend: Backend) -> Option<(i64, usize)> {
+    let content = fs::read_to_string(cache_path("updates.tsv").ok()?).ok()?;
+    content.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [name, time, count] = fields[..] else {
+            return None;
+        };
+        (name == backend.name()).then_some(())?;
+        Some((time.parse().ok()?, count.parse().ok()?))
+    })
+}
+
+fn save_cache(backend: Backend, count: usize) -> Result<(), io::Error> {
+    let path = cache_path("updates.tsv")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut content: String = fs::read_to_string(&path)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| !line.starts_with(&format!("{}\t", backend.name())))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    content.push_str(&format!("{}\t{}\t{}\n", backend.name(), now_secs(), count));
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, content)?;
+    fs::rename(tmp, path)
+}
+
+// 输出待更新的软件包数，如 "UPD: 12"
+// 检查较慢且会访问网络，结果缓存 ttl 秒；检查失败（如离线）时退回上一次缓存的结果
+pub fn get_updates(backend: Option<Backend>, ttl: u64) -> Result<String, Error> {
+    let backend = backend.ok_or_else(|| {
+        Error::MissingDevice(
+            "no supported package manager (checkupdates, apt-get, dnf)".to_string(),
+        )
+    })?;
+    let cached = load_cache(backend);
+    if let Some((time, count)) = cached {
+        if now_secs().saturating_sub(time) < ttl as i64 {
+            return Ok(format!("UPD: {}", count));
+        }
+    }
+    match backend.check() {
+        Ok(count) => {
+            if let Err(e) = save_cache(backend, count) {
+                log::warning!("cannot cache update count: {}", e);
+            }
+            Ok(format!("UPD: {}", count))
+        }
+        Err(e) => match cached {
+            Some((_, count)) => {
+                log::warning!("checking updates, using cached value: {}", e);
+                Ok(format!("UPD: {}", count))
+            }
+            None => Err(e),
+        },
+    }
+}
+
+pub struct Updates {
+    backend: Option<Backend>,
+    ttl: u64,
+}
+
+impl Builtin for Updates {
+    const ID: &'static str = "updates";
+    const BLOCK: &'static str = "updates";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Updates {
+            backend: Backend::parse(matches.get_one::<String>("updates-backend").unwrap()),
+            ttl: *matches.get_one::<u64>("updates-ttl").unwrap(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(
+            Self::BLOCK,
+            get_updates(self.backend, self.ttl)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_pending_updates() {
+        assert_eq!(
+            Backend::Pacman.count("linux 6.11.1-1 -> 6.11.2-1\nmesa 1:24.2.3-1 -> 1:24.2.4-1\n"),
+            2
+        );
+        let apt = "Reading package lists...\nThe following packages will be upgraded:\n  curl libcurl4\nInst curl [8.5.0-2] (8.5.0-2ubuntu1 Ubuntu:24.04/noble-updates [amd64])\nInst libcurl4 [8.5.0-2] (8.5.0-2ubuntu1 Ubuntu:24.04/noble-updates [amd64])\nConf curl (8.5.0-2ubuntu1 Ubuntu:24.04/noble-updates [amd64])\n";
+        assert_eq!(Backend::Apt.count(apt), 2);
+        let dnf = "\nkernel.x86_64    6.11.2-300.fc41    updates\nvim-minimal.x86_64    2:9.1.719-1.fc41    updates\nObsoleting Packages\ngrub2-tools.x86_64    1:2.12-10.fc41    updates\n";
+        assert_eq!(Backend::Dnf.count(dnf), 2);
+        assert!(Backend::Pacman.succeeded(Some(2)));
+        assert!(!Backend::Apt.succeeded(Some(100)));
+    }
+}
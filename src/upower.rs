@@ -0,0 +1,248 @@
+use crate::dbus::{self, Bus};
+use crate::error::Error;
+use crate::event_loop::EventLoop;
+use crate::format_duration;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::watch::Wake;
+use std::io;
+
+const UPOWER: &str = "org.freedesktop.UPower";
+const DEVICE_INTERFACE: &str = "org.freedesktop.UPower.Device";
+// UPower 合并所有电池得到的虚拟设备
+const DISPLAY_DEVICE: &str = "/org/freedesktop/UPower/devices/DisplayDevice";
+
+// 设备对象路径：all 为 DisplayDevice，BAT0 为 .../devices/battery_BAT0
+fn device_path(device: &str) -> String {
+    if device == "all" {
+        DISPLAY_DEVICE.to_string()
+    } else {
+        format!("/org/freedesktop/UPower/devices/battery_{}", device)
+    }
+}
+
+fn unavailable(e: io::Error) -> Error {
+    Error::BackendUnavailable(format!("UPower is not available: {}", e))
+}
+
+// 属性值文本取最后一个词，如 "double 80.0" -> "80.0"、"uint32 2" -> "2"
+fn parse_value<T: std::str::FromStr>(value: &str, property: &str) -> Result<T, Error> {
+    value
+        .split_whitespace()
+        .last()
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| Error::Parse(format!("UPower {}: unexpected value '{}'", property, value)))
+}
+
+fn get<T: std::str::FromStr>(path: &str, property: &str) -> Result<T, Error> {
+    let value =
+        dbus::get_property(Bus::System, UPOWER, path, DEVICE_INTERFACE, property).map_err(|e| {
+            if e.to_string().contains("UnknownObject") {
+                Error::MissingDevice(format!("UPower device {} not found", path))
+            } else {
+                unavailable(e)
+            }
+        })?;
+    parse_value(&value, property)
+}
+
+// 检查设备存在且为电池，DisplayDevice 在没有电池时 IsPresent 为 false
+fn battery(device: &str) -> Result<String, Error> {
+    let path = device_path(device);
+    if !get::<bool>(&path, "IsPresent")? {
+        return Err(Error::MissingDevice("no battery found".to_string()));
+    }
+    Ok(path)
+}
+
+// UPower 管理的所有设备的对象路径，不含 DisplayDevice
+fn device_paths() -> Result<Vec<String>, Error> {
+    let reply = dbus::call(
+        Bus::System,
+        UPOWER,
+        "/org/freedesktop/UPower",
+        "org.freedesktop.UPower.EnumerateDevices",
+        &[],
+    )
+    .map_err(unavailable)?;
+    Ok(reply
+        .split('\'')
+        .filter(|path| path.starts_with("/org/freedesktop/UPower/devices/"))
+        .map(str::to_string)
+        .collect())
+}
+
+// 所有电池设备的对象路径，用于需要逐块相加的数值
+fn battery_paths(device: &str) -> Result<Vec<String>, Error> {
+    if device != "all" {
+        return Ok(vec![battery(device)?]);
+    }
+    let paths: Vec<String> = device_paths()?
+        .into_iter()
+        .filter(|path| path.contains("/battery_"))
+        .collect();
+    if paths.is_empty() {
+        return Err(Error::MissingDevice("no battery found".to_string()));
+    }
+    Ok(paths)
+}
+
+// UPower 的 State 枚举转换为 sysfs 的状态文本，与 sysfs 后端输出一致
+fn state_name(state: u32) -> &'static str {
+    match state {
+        1 => "Charging",
+        2 | 6 => "Discharging",
+        3 | 5 => "Not charging",
+        4 => "Full",
+        _ => "Unknown",
+    }
+}
+
+pub fn get_battery_capacity(device: &str) -> Result<String, Error> {
+    let percentage: f64 = get(&battery(device)?, "Percentage")?;
+    Ok((percentage.round() as i64).to_string())
+}
+
+pub fn get_battery_status(device: &str) -> Result<String, Error> {
+    let state: u32 = get(&battery(device)?, "State")?;
+    Ok(state_name(state).to_string())
+}
+
+// UPower 自己估算剩余时间，未知时为 0，此时与 sysfs 后端一样只输出状态
+pub fn get_battery_time(device: &str) -> Result<String, Error> {
+    let path = battery(device)?;
+    let status = state_name(get(&path, "State")?);
+    let (seconds, suffix): (i64, _) = match status {
+        "Charging" => (get(&path, "TimeToFull")?, "until full"),
+        "Discharging" => (get(&path, "TimeToEmpty")?, "left"),
+        _ => (0, ""),
+    };
+    if seconds <= 0 {
+        return Ok(status.to_string());
+    }
+    Ok(format!("{} {}", format_duration(seconds), suffix))
+}
+
+pub fn read_power_watts(device: &str) -> Result<f64, Error> {
+    let mut total = 0.0;
+    for path in battery_paths(device)? {
+        total += get::<f64>(&path, "EnergyRate")?.abs();
+    }
+    Ok(total)
+}
+
+pub fn get_battery_health(device: &str) -> Result<String, Error> {
+    let mut total_full = 0.0;
+    let mut total_design = 0.0;
+    for path in battery_paths(device)? {
+        total_full += get::<f64>(&path, "EnergyFull")?;
+        total_design += get::<f64>(&path, "EnergyFullDesign")?;
+    }
+    if total_design <= 0.0 {
+        return Err(Error::MissingDevice(
+            "battery does not report design capacity".to_string(),
+        ));
+    }
+    let health = (total_full * 100.0 / total_design) as i64;
+    Ok(format!("{}% ({}% wear)", health, (100 - health).max(0)))
+}
+
+// UPower 的 Type 枚举中的无线外设，返回显示名称；笔记本电池、电源和 UPS 等返回 None
+fn peripheral_name(kind: u32) -> Option<&'static str> {
+    match kind {
+        5 => Some("mouse"),
+        6 => Some("keyboard"),
+        12 => Some("gamepad"),
+        13 => Some("pen"),
+        14 => Some("touchpad"),
+        17 => Some("headset"),
+        18 => Some("speakers"),
+        19 => Some("headphones"),
+        22 => Some("remote"),
+        26 => Some("wearable"),
+        _ => None,
+    }
+}
+
+fn format_peripherals(mut devices: Vec<(&str, f64)>) -> String {
+    // 电量最低的外设排在最前，输出的第一个数值即为阈值判断使用的值
+    devices.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let items: Vec<String> = devices
+        .iter()
+        .map(|(name, percentage)| format!("{} {:.0}%", name, percentage))
+        .collect();
+    format!("PER: {}", items.join(" "))
+}
+
+// 输出无线鼠标、键盘、耳机等外设的电量，如 "PER: headset 15% mouse 80%"
+// 外设通过蓝牙 Battery Service 或厂商驱动（如 Logitech HID++）上报给 UPower
+pub fn get_peripherals() -> Result<String, Error> {
+    let mut devices = Vec::new();
+    for path in device_paths()? {
+        let Some(name) = peripheral_name(get(&path, "Type")?) else {
+            continue;
+        };
+        devices.push((name, get::<f64>(&path, "Percentage")?));
+    }
+    if devices.is_empty() {
+        return Err(Error::MissingDevice(
+            "no wireless peripherals with a battery found".to_string(),
+        ));
+    }
+    Ok(format_peripherals(devices))
+}
+
+// 监听 UPower 设备的属性变化信号，电量或状态变化时立即刷新
+pub fn subscribe(events: &mut EventLoop) -> Result<(), io::Error> {
+    events.add_command(
+        "gdbus",
+        &["monitor", "--system", "--dest", UPOWER],
+        |line, woken| {
+            // 信号行形如 "/org/freedesktop/UPower/devices/battery_BAT0: org.freedesktop.DBus.Properties.PropertiesChanged (...)"
+            if line.starts_with('/') {
+                woken.push(Wake::All);
+            }
+        },
+    )
+}
+
+pub struct Peripherals;
+
+impl Builtin for Peripherals {
+    const ID: &'static str = "peripherals";
+    const BLOCK: &'static str = "peripherals";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Peripherals
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::numeric(Self::BLOCK, get_peripherals()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_property_values() {
+        assert_eq!(
+            parse_value::<f64>("double 80.5", "Percentage").unwrap(),
+            80.5
+        );
+        assert_eq!(parse_value::<u32>("uint32 2", "State").unwrap(), 2);
+        assert!(parse_value::<bool>("boolean true", "IsPresent").unwrap());
+        assert!(matches!(
+            parse_value::<i64>("", "TimeToEmpty"),
+            Err(Error::Parse(_))
+        ));
+        assert_eq!(state_name(6), "Discharging");
+        assert_eq!(state_name(4), "Full");
+        assert_eq!(peripheral_name(2), None);
+        assert_eq!(
+            format_peripherals(vec![("mouse", 80.0), ("headset", 15.0)]),
+            "PER: headset 15% mouse 80%"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use crate::error::Error;
+use crate::timeutil::{civil_from_days, local_utc_offset, now_secs};
+use crate::{format_duration, paths, read_file};
+use std::fs;
+use std::io;
+
+// 保留的开机记录条数
+const KEEP_BOOTS: usize = 500;
+
+// 一次开机：启动时间和最后一次记录的时间（近似关机时间）
+struct Boot {
+    id: String,
+    boot_time: i64,
+    last_seen: i64,
+}
+
+// 读取开机记录，每行格式为 "boot_id\t开机时间\t最后记录时间"
+fn load() -> Result<Vec<Boot>, io::Error> {
+    let content = match fs::read_to_string(paths::state_path("uptime.tsv")?) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut boots = Vec::new();
+    for line in content.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if let [id, boot_time, last_seen] = fields[..] {
+            let (Ok(boot_time), Ok(last_seen)) = (boot_time.parse(), last_seen.parse()) else {
+                continue;
+            };
+            boots.push(Boot {
+                id: id.to_string(),
+                boot_time,
+                last_seen,
+            });
+        }
+    }
+    Ok(boots)
+}
+
+fn save(boots: &[Boot]) -> Result<(), io::Error> {
+    let path = paths::state_path("uptime.tsv")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let skip = boots.len().saturating_sub(KEEP_BOOTS);
+    let content: String = boots[skip..]
+        .iter()
+        .map(|boot| format!("{}\t{}\t{}\n", boot.id, boot.boot_time, boot.last_seen))
+        .collect();
+    fs::write(path, content)
+}
+
+// 本次开机的启动时间（/proc/stat 中的 btime）
+fn boot_time() -> Result<i64, io::Error> {
+    read_file("/proc/stat")?
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "btime not found"))
+}
+
+// watch 模式下定期调用，记录本次开机并更新最后记录时间
+pub fn record() -> Result<(), io::Error> {
+    let id = read_file("/proc/sys/kernel/random/boot_id")?;
+    let now = now_secs();
+    let mut boots = load()?;
+    match boots.iter_mut().find(|boot| boot.id == id) {
+        Some(boot) => boot.last_seen = now,
+        None => boots.push(Boot {
+            id,
+            boot_time: boot_time()?,
+            last_seen: now,
+        }),
+    }
+    save(&boots)
+}
+
+// 本地时间所在周的周一日期
+fn week_start(timestamp: i64) -> String {
+    let days = (timestamp + local_utc_offset()).div_euclid(86400);
+    // 1970-01-01 是周四
+    let (year, month, day) = civil_from_days(days - (days + 3).rem_euclid(7));
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// 输出开机历史：最近 8 周每周的开机次数、最长和当前运行时间
+pub fn get_uptime_history() -> Result<String, Error> {
+    let boots = load()?;
+    let Some(first) = boots.first() else {
+        return Ok("No boots recorded yet, run --watch with --track-usage".to_string());
+    };
+    let now = now_secs();
+    let current = read_file("/proc/sys/kernel/random/boot_id").unwrap_or_default();
+    let uptime = |boot: &Boot| {
+        let end = if boot.id == current {
+            now
+        } else {
+            boot.last_seen
+        };
+        end - boot.boot_time
+    };
+
+    let mut lines = vec![format!(
+        "{} boots recorded since week of {}",
+        boots.len(),
+        week_start(first.boot_time)
+    )];
+    for offset in (0..8).rev() {
+        let week = week_start(now - offset * 7 * 86400);
+        let count = boots
+            .iter()
+            .filter(|boot| week_start(boot.boot_time) == week)
+            .count();
+        lines.push(format!("week of {}  {:>3} boots", week, count));
+    }
+    if let Some(longest) = boots.iter().max_by_key(|boot| uptime(boot)) {
+        lines.push(format!(
+            "longest uptime  {} (week of {})",
+            format_duration(uptime(longest)),
+            week_start(longest.boot_time)
+        ));
+    }
+    if let Some(boot) = boots.iter().find(|boot| boot.id == current) {
+        lines.push(format!("current uptime  {}", format_duration(uptime(boot))));
+    }
+    Ok(lines.join("\n"))
+}
@@ -0,0 +1,145 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::render::Block;
+use crate::timeutil::{local_date, now_secs};
+use crate::{battery, format_duration, paths, read_file, sysfs};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// 数据库中保留的天数
+const KEEP_DAYS: usize = 60;
+
+// 使用统计数据库路径：$XDG_STATE_HOME/sys-montion/usage.tsv
+fn database_path() -> Result<PathBuf, io::Error> {
+    paths::state_path("usage.tsv")
+}
+
+// 读取数据库，每行格式为 "日期\t亮屏秒数\t电池供电秒数"
+fn load() -> Result<BTreeMap<String, (i64, i64)>, io::Error> {
+    let mut days = BTreeMap::new();
+    let content = match fs::read_to_string(database_path()?) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(days),
+        Err(e) => return Err(e),
+    };
+    for line in content.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if let [date, screen, battery] = fields[..] {
+            let screen = screen.parse().unwrap_or(0);
+            let battery = battery.parse().unwrap_or(0);
+            days.insert(date.to_string(), (screen, battery));
+        }
+    }
+    Ok(days)
+}
+
+fn save(days: &BTreeMap<String, (i64, i64)>) -> Result<(), io::Error> {
+    let path = database_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content: String = days
+        .iter()
+        .rev()
+        .take(KEEP_DAYS)
+        .map(|(date, (screen, battery))| format!("{}\t{}\t{}\n", date, screen, battery))
+        .collect();
+    fs::write(path, content)
+}
+
+// 判断屏幕是否点亮：任一已连接的 DRM 输出处于 DPMS On 状态
+fn screen_on() -> bool {
+    let Ok(entries) = sysfs::read_dir("/sys/class/drm/") else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let path = Path::new("/sys/class/drm").join(entry.file_name());
+        read_file(path.join("status")).is_ok_and(|s| s == "connected")
+            && read_file(path.join("dpms")).is_ok_and(|s| s == "On")
+    })
+}
+
+// watch 模式下每次刷新调用，把经过的秒数累加到今天的统计
+pub fn record(elapsed: i64, battery_device: &str) -> Result<(), io::Error> {
+    let screen = screen_on();
+    let on_battery = battery::get_battery_status(battery_device).is_ok_and(|s| s == "Discharging");
+    if !screen && !on_battery {
+        return Ok(());
+    }
+
+    let mut days = load()?;
+    let today = days.entry(local_date(now_secs())).or_insert((0, 0));
+    if screen {
+        today.0 += elapsed;
+    }
+    if on_battery {
+        today.1 += elapsed;
+    }
+    save(&days)
+}
+
+// 输出今天的亮屏时间
+pub fn get_screen_time() -> Result<String, Error> {
+    let days = load()?;
+    let (screen, _) = days.get(&local_date(now_secs())).copied().unwrap_or((0, 0));
+    Ok(format!("SCR: {}", format_duration(screen)))
+}
+
+// 输出最近 7 天的使用统计报告
+pub fn get_usage_report() -> Result<String, Error> {
+    let days = load()?;
+    let now = now_secs();
+    let mut lines = Vec::new();
+    let mut total_screen = 0;
+    let mut total_battery = 0;
+    for offset in (0..7).rev() {
+        let date = local_date(now - offset * 86400);
+        let (screen, battery) = days.get(&date).copied().unwrap_or((0, 0));
+        total_screen += screen;
+        total_battery += battery;
+        lines.push(format!(
+            "{}  screen {:>7}  battery {:>7}",
+            date,
+            format_duration(screen),
+            format_duration(battery)
+        ));
+    }
+    lines.push(format!(
+        "total       screen {:>7}  battery {:>7}",
+        format_duration(total_screen),
+        format_duration(total_battery)
+    ));
+    Ok(lines.join("\n"))
+}
+
+pub struct ScreenTime;
+
+impl Builtin for ScreenTime {
+    const ID: &'static str = "screen-time";
+    const BLOCK: &'static str = "usage";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        ScreenTime
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_screen_time()?))
+    }
+}
+
+pub struct UsageReport;
+
+impl Builtin for UsageReport {
+    const ID: &'static str = "usage-report";
+    const BLOCK: &'static str = "usage";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        UsageReport
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_usage_report()?))
+    }
+}
@@ -0,0 +1,213 @@
+use crate::battery;
+use crate::render::Block;
+
+// 配置文件中模块的 show_if / hide_if 条件，watch 模式下每次输出时判断，隐藏的模块不输出但照常刷新
+//
+// [[modules]]
+// name = "battery"
+// hide_if = "ac and value > 95"
+//
+// [[modules]]
+// name = "mic"
+// hide_if = 'text contains "muted"'
+//
+// 条件为用 and、or 连接的项（and 优先），项前加 not 取反：
+//   value OP N          块的数值，如电量、音量百分比，OP 为 < <= > >= == !=；块没有数值时不成立
+//   text == "S"         文本完全相同，!= 为不同，contains 为包含
+//   empty               文本为空（如 VPN 未连接）
+//   ac                  接通了外部电源
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Op {
+    fn parse(token: &str) -> Option<Self> {
+        Some(match token {
+            "<" => Op::Less,
+            "<=" => Op::LessEqual,
+            ">" => Op::Greater,
+            ">=" => Op::GreaterEqual,
+            "==" => Op::Equal,
+            "!=" => Op::NotEqual,
+            _ => return None,
+        })
+    }
+
+    fn compare(self, left: f64, right: f64) -> bool {
+        match self {
+            Op::Less => left < right,
+            Op::LessEqual => left <= right,
+            Op::Greater => left > right,
+            Op::GreaterEqual => left >= right,
+            Op::Equal => left == right,
+            Op::NotEqual => left != right,
+        }
+    }
+}
+
+enum Test {
+    Value(Op, f64),
+    TextEquals(String),
+    TextContains(String),
+    Empty,
+    Ac,
+}
+
+struct Term {
+    negate: bool,
+    test: Test,
+}
+
+// 解析后的条件：外层为 or，内层为 and
+pub struct Condition(Vec<Vec<Term>>);
+
+// 按空白分词，双引号内的空白保留，引号本身去掉
+fn tokenize(text: &str) -> Result<Vec<(String, bool)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut token = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => token.push(c),
+                    None => return Err("unterminated string".to_string()),
+                }
+            }
+            tokens.push((token, true));
+        } else {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek().filter(|c| !c.is_whitespace()) {
+                token.push(c);
+                chars.next();
+            }
+            tokens.push((token, false));
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_term(tokens: &[(String, bool)]) -> Result<Term, String> {
+    let (negate, tokens) = match tokens {
+        [(not, false), rest @ ..] if not == "not" => (true, rest),
+        _ => (false, tokens),
+    };
+    let words: Vec<(&str, bool)> = tokens
+        .iter()
+        .map(|(token, quoted)| (token.as_str(), *quoted))
+        .collect();
+    let test = match words.as_slice() {
+        [("empty", false)] => Test::Empty,
+        [("ac", false)] => Test::Ac,
+        [("value", false), (op, false), (number, false)] => Test::Value(
+            Op::parse(op).ok_or_else(|| format!("unknown operator '{}'", op))?,
+            number
+                .parse()
+                .map_err(|_| format!("'{}' is not a number", number))?,
+        ),
+        [("text", false), ("==", false), (text, true)] => Test::TextEquals(text.to_string()),
+        [("text", false), ("!=", false), (text, true)] => {
+            return Ok(Term {
+                negate: !negate,
+                test: Test::TextEquals(text.to_string()),
+            })
+        }
+        [("text", false), ("contains", false), (text, true)] => {
+            Test::TextContains(text.to_string())
+        }
+        [] => return Err("empty condition".to_string()),
+        _ => {
+            let term: Vec<&str> = words.iter().map(|(word, _)| *word).collect();
+            return Err(format!("invalid condition '{}'", term.join(" ")));
+        }
+    };
+    Ok(Term { negate, test })
+}
+
+impl Condition {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        let mut any = Vec::new();
+        for alternative in tokens.split(|(token, quoted)| !quoted && token == "or") {
+            let all = alternative
+                .split(|(token, quoted)| !quoted && token == "and")
+                .map(parse_term)
+                .collect::<Result<Vec<_>, _>>()?;
+            any.push(all);
+        }
+        Ok(Condition(any))
+    }
+
+    pub fn matches(&self, block: &Block) -> bool {
+        self.0.iter().any(|all| {
+            all.iter().all(|term| {
+                let result = match &term.test {
+                    Test::Value(op, number) => {
+                        block.value.is_some_and(|value| op.compare(value, *number))
+                    }
+                    Test::TextEquals(text) => block.text == *text,
+                    Test::TextContains(text) => block.text.contains(text.as_str()),
+                    Test::Empty => block.text.is_empty(),
+                    Test::Ac => battery::on_ac(),
+                };
+                result != term.negate
+            })
+        })
+    }
+}
+
+// 模块的显示规则：show_if 成立时才显示，hide_if 成立时隐藏
+#[derive(Default)]
+pub struct Rule {
+    pub show_if: Option<Condition>,
+    pub hide_if: Option<Condition>,
+}
+
+impl Rule {
+    pub fn visible(&self, block: &Block) -> bool {
+        self.show_if.as_ref().is_none_or(|show| show.matches(block))
+            && !self
+                .hide_if
+                .as_ref()
+                .is_some_and(|hide| hide.matches(block))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_conditions() {
+        let battery = Block::numeric("battery", "Charging: 97%".to_string());
+        let condition = |text| Condition::parse(text).unwrap();
+        assert!(condition("value > 95").matches(&battery));
+        assert!(!condition("value > 95 and not value >= 97").matches(&battery));
+        assert!(condition("value < 10 or text contains \"Charging\"").matches(&battery));
+        assert!(condition("text != \"Full: 100%\"").matches(&battery));
+        assert!(!condition("empty").matches(&battery));
+        assert!(condition("empty").matches(&Block::new("vpn", String::new())));
+        // 没有数值的块不满足数值比较
+        assert!(!condition("value < 10").matches(&Block::new("vpn", "VPN: wg0".to_string())));
+        let rule = Rule {
+            show_if: None,
+            hide_if: Some(condition("text == \"MIC: muted\"")),
+        };
+        assert!(!rule.visible(&Block::new("mic", "MIC: muted".to_string())));
+        assert!(rule.visible(&Block::new("mic", "MIC: 40%".to_string())));
+        assert!(Condition::parse("value >> 3").is_err());
+        assert!(Condition::parse("text == \"open").is_err());
+        assert!(Condition::parse("value > 3 and").is_err());
+    }
+}
@@ -0,0 +1,657 @@
+use crate::adaptive::Adaptive;
+use crate::config::{self, Config};
+use crate::event_loop::EventLoop;
+use crate::fifo::Fifo;
+use crate::module::{self, EventInterest, Module};
+use crate::recorder::Recorder;
+use crate::render::{self, Block, Renderer};
+use crate::{
+    audio, battery, cli_schedules, click, config_schedules, control, count, history, locks, log,
+    module_output, mounts, notify, print_help, service, signals, sleep, systemd, termux, uevent,
+    upower, uptime, usage, visibility, wm, xsetroot,
+};
+use std::path::PathBuf;
+use std::process;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use visibility::Condition;
+
+// --scroll 时滚动一个字符的间隔
+const SCROLL_STEP: Duration = Duration::from_millis(500);
+
+// 唤醒 watch 循环的事件
+#[derive(Debug)]
+pub enum Wake {
+    // 系统事件或 SIGUSR1：刷新全部模块
+    All,
+    // SIGRTMIN+n：只刷新配置了该信号的模块
+    Signal(i32),
+    // 控制套接字请求：只刷新指定模块（模块名或输出块名）
+    Module(String),
+    // i3bar 点击事件：执行块的动作后刷新该模块，instance 为块所属的模块名
+    Click {
+        name: String,
+        instance: Option<String>,
+        button: u8,
+    },
+    // 配置文件被修改或 SIGHUP：重新加载配置文件
+    Reload,
+}
+
+// 模块的刷新间隔（秒）和可选的刷新信号 SIGRTMIN+n
+// script 模块另外带有要运行的命令和输出前缀，plugin 模块带有共享库路径，actions 为配置的点击命令
+pub struct Schedule<'a> {
+    pub name: &'a str,
+    pub interval: u64,
+    pub signal: Option<i32>,
+    pub command: Option<&'a str>,
+    pub path: Option<&'a str>,
+    pub label: Option<&'a str>,
+    pub actions: [Option<&'a str>; 5],
+    // 配置的显示条件（show_if、hide_if）
+    pub show_if: Option<&'a str>,
+    pub hide_if: Option<&'a str>,
+}
+
+impl Schedule<'_> {
+    // 条件在加载配置时已检查过，这里不再报错
+    fn rule(&self) -> visibility::Rule {
+        let parse = |condition: Option<&str>| condition.and_then(|c| Condition::parse(c).ok());
+        visibility::Rule {
+            show_if: parse(self.show_if),
+            hide_if: parse(self.hide_if),
+        }
+    }
+}
+
+// 由命令行参数和配置文件得到的设置，重新加载配置文件时整体替换
+struct Settings<'a> {
+    modules: Vec<Schedule<'a>>,
+    renderer: Renderer,
+    separator: String,
+    skip_missing: bool,
+}
+
+impl<'a> Settings<'a> {
+    fn new(matches: &'a clap::ArgMatches, config: &'a Config, interval: u64) -> Self {
+        // 命令行指定了模块时只刷新这些模块，否则按配置文件中各模块的间隔刷新
+        let mut modules = cli_schedules(matches, interval);
+        if modules.is_empty() {
+            modules = config_schedules(config, config.interval.unwrap_or(interval));
+        }
+        let renderer = Renderer::from_matches(matches, config);
+        let separator = config
+            .separator
+            .clone()
+            .unwrap_or_else(|| renderer.default_separator().to_string());
+        battery::set_smoothing(
+            matches
+                .get_one::<f64>("battery-smoothing")
+                .copied()
+                .or(config.battery_smoothing.as_ref().map(|f| *f.get_ref()))
+                .unwrap_or(battery::DEFAULT_SMOOTHING),
+        );
+        Settings {
+            modules,
+            renderer,
+            separator,
+            skip_missing: matches.get_flag("skip-missing")
+                || config.skip_missing
+                || matches.subcommand_name() == Some("all"),
+        }
+    }
+}
+
+// 已注册的与模块相关的事件源，重新加载配置后只为新增的模块注册
+#[derive(Default)]
+struct Sources {
+    mounts: bool,
+    count: bool,
+    audio: bool,
+    wm: bool,
+    locks: bool,
+    signals: Vec<i32>,
+}
+
+impl Sources {
+    fn register(
+        &mut self,
+        events: &mut EventLoop,
+        matches: &clap::ArgMatches,
+        slots: &[Slot],
+        sink_hook: &mut Option<String>,
+    ) {
+        let interests = slots.iter().fold(EventInterest::NONE, |interests, slot| {
+            interests | slot.instance.interests()
+        });
+        if interests.contains(EventInterest::MOUNTS) && !self.mounts {
+            self.mounts = true;
+            if let Err(e) = mounts::register(events) {
+                log::warning!("cannot watch mount table: {}", e);
+            }
+        }
+        if let Some(dir) = matches
+            .get_many::<String>("count")
+            .and_then(|mut args| args.next())
+            .filter(|_| interests.contains(EventInterest::FILES) && !self.count)
+        {
+            self.count = true;
+            if let Err(e) = count::register(events, dir) {
+                log::warning!("cannot watch {}: {}", dir, e);
+            }
+        }
+        if (interests.contains(EventInterest::AUDIO) || sink_hook.is_some()) && !self.audio {
+            self.audio = true;
+            audio::subscribe(events, sink_hook.take());
+        }
+        if interests.contains(EventInterest::WM) && !self.wm {
+            self.wm = true;
+            if let Err(e) = wm::subscribe(events) {
+                log::warning!("cannot watch window manager events: {}", e);
+            }
+        }
+        if interests.contains(EventInterest::LOCKS) && !self.locks {
+            self.locks = true;
+            if let Err(e) = locks::register(events) {
+                log::info!("not watching lock keys, polling instead: {}", e);
+            }
+        }
+        let mut offsets: Vec<i32> = slots
+            .iter()
+            .filter_map(|slot| slot.module.signal)
+            .filter(|offset| !self.signals.contains(offset))
+            .collect();
+        offsets.sort();
+        offsets.dedup();
+        if let Err(e) = signals::register_realtime(events, &offsets) {
+            log::warning!("cannot install signal handlers: {}", e);
+        }
+        self.signals.extend(offsets);
+    }
+}
+
+// watch 模式中的一个模块及其刷新计划
+struct Slot<'a> {
+    module: &'a Schedule<'a>,
+    instance: Box<dyn Module>,
+    interval: Duration,
+    next: Instant,
+    block: Option<Block>,
+    rule: visibility::Rule,
+    // --adaptive 受限时暂停定时刷新
+    paused: bool,
+}
+
+// 点击事件是否属于该模块：有 instance 时按块名和模块名匹配，同名的块（cpu 和 throttle）不会混淆；
+// 不带 instance 的事件（其他程序输出的块）按模块名或块名匹配第一个
+fn clicked(slot: &Slot, name: &str, instance: Option<&str>) -> bool {
+    match instance {
+        Some(instance) => slot
+            .block
+            .as_ref()
+            .is_some_and(|block| block.name == name && block.id == instance),
+        None => slot.module.name == name || slot.block.as_ref().is_some_and(|b| b.name == name),
+    }
+}
+
+// 按 --adaptive 的当前状态设置各模块的刷新间隔，恢复刷新的模块不再等待放慢后的间隔
+fn adapt(slots: &mut [Slot], adaptive: &Adaptive, now: Instant) {
+    for slot in slots {
+        let interval = Duration::from_secs(slot.module.interval);
+        match adaptive.interval(slot.module.name, interval) {
+            Some(interval) => {
+                slot.interval = interval;
+                slot.next = if slot.paused {
+                    now
+                } else {
+                    slot.next.min(now + interval)
+                };
+                slot.paused = false;
+            }
+            None => slot.paused = true,
+        }
+    }
+}
+
+// 持续运行：各模块按各自的间隔刷新，任一模块更新后重新输出整行
+// 后台规则（低电量、内存、风扇、使用统计）每隔 interval 秒检查一次
+// 配置文件保存后或收到 SIGHUP 时重新加载，无需重启
+pub fn run(matches: &Arc<clap::ArgMatches>, config: Config, interval: u64) {
+    let battery_device = matches.get_one::<String>("battery-device").unwrap();
+    let mut notifier = matches.get_flag("notify").then(|| {
+        notify::BatteryNotifier::new(
+            *matches.get_one::<i64>("battery-warn").unwrap(),
+            *matches.get_one::<i64>("battery-critical").unwrap(),
+            Duration::from_secs(*matches.get_one::<u64>("notify-repeat").unwrap()),
+        )
+    });
+    let mut battery_action = matches.get_one::<String>("battery-action").map(|command| {
+        notify::BatteryAction::new(
+            *matches.get_one::<i64>("battery-action-level").unwrap(),
+            command.clone(),
+            matches.get_flag("battery-action-dry-run"),
+        )
+    });
+    let memory_low = matches.get_one::<i64>("memory-low").copied();
+    let memory_pressure = matches.get_one::<f64>("memory-pressure").copied();
+    let mut memory_guard = (memory_low.is_some() || memory_pressure.is_some()).then(|| {
+        notify::MemoryGuard::new(
+            memory_low,
+            memory_pressure,
+            matches.get_flag("notify"),
+            matches.get_one::<String>("memory-hook").cloned(),
+        )
+    });
+    let mut adaptive = matches.get_flag("adaptive").then(|| {
+        Adaptive::new(
+            *matches.get_one::<i64>("battery-warn").unwrap(),
+            *matches.get_one::<f64>("adaptive-pressure").unwrap(),
+        )
+    });
+    let mut fan_alert = matches.get_flag("fan-alert").then(|| {
+        notify::FanAlert::new(
+            Duration::from_secs(*matches.get_one::<u64>("fan-max-minutes").unwrap() * 60),
+            *matches.get_one::<f64>("fan-stall-temp").unwrap(),
+        )
+    });
+    let mut recorder = matches.get_one::<PathBuf>("log-metrics").map(|path| {
+        Recorder::new(
+            path.clone(),
+            matches.get_one::<String>("log-metrics-format").unwrap(),
+            *matches.get_one::<u64>("log-metrics-max-size").unwrap() * 1024 * 1024,
+        )
+    });
+    let track_usage = matches.get_flag("track-usage");
+    let battery_history = matches.get_flag("battery-history");
+    let mut sink_hook = matches.get_one::<String>("sink-hook").cloned();
+    let media_player = matches
+        .get_one::<String>("media-player")
+        .map(String::as_str);
+    let config_path = matches.get_one::<String>("config").map(String::as_str);
+
+    if Settings::new(matches, &config, interval).modules.is_empty()
+        && notifier.is_none()
+        && battery_action.is_none()
+        && memory_guard.is_none()
+        && fan_alert.is_none()
+        && !track_usage
+        && !battery_history
+        && sink_hook.is_none()
+    {
+        print_help();
+        return;
+    }
+
+    let root_window = if matches.get_flag("xsetroot") {
+        match xsetroot::RootWindow::connect() {
+            Ok(root_window) => Some(root_window),
+            Err(e) => {
+                eprintln!("Error connecting to X server: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+
+    let service = if matches.get_flag("dbus") {
+        match service::Service::start() {
+            Ok(service) => Some(service),
+            Err(e) => {
+                eprintln!("Error starting D-Bus service: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+
+    let format = matches.get_one::<String>("format").unwrap().as_str();
+    let min_interval =
+        Duration::from_millis(matches.get_one::<u64>("min-interval").copied().unwrap_or(0));
+    if format == "i3bar" && root_window.is_none() {
+        println!("{}", render::I3BAR_HEADER);
+    }
+    let header = (format == "i3bar").then(|| render::I3BAR_HEADER.to_string());
+    let mut fifos = Vec::new();
+    for path in matches.get_many::<PathBuf>("output").into_iter().flatten() {
+        match Fifo::create(path.clone(), header.clone()) {
+            Ok(fifo) => fifos.push(fifo),
+            Err(e) => {
+                eprintln!("Error creating {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        }
+    }
+
+    // 电源、背光、音量和挂载表变化时立即刷新，无法监听时退回定时轮询
+    // 所有事件源与定时刷新共用一个单线程事件循环
+    let mut events = match EventLoop::new() {
+        Ok(events) => events,
+        Err(e) => {
+            eprintln!("Error creating event loop: {}", e);
+            process::exit(1);
+        }
+    };
+    // Android 不允许普通应用监听 uevent，Termux 中直接按间隔轮询
+    if termux::detected() {
+        log::debug!("running in Termux, polling instead of listening for uevents");
+    } else if let Err(e) = uevent::register(&mut events, &["power_supply", "backlight"]) {
+        log::warning!("cannot listen for uevents, falling back to polling: {}", e);
+    }
+    // UPower 的属性变化信号比 uevent 更及时地反映合并后的电量，无线外设的电量也只能从 UPower 得知
+    if battery::backend() == battery::Backend::Upower || matches.get_flag("peripherals") {
+        if let Err(e) = upower::subscribe(&mut events) {
+            log::warning!("cannot watch UPower: {}", e);
+        }
+    }
+    if let Err(e) = cli_schedules(matches, interval)
+        .iter()
+        .filter_map(|module| module.signal)
+        .chain(config.modules.iter().filter_map(|module| module.signal()))
+        .try_for_each(signals::check_offset)
+    {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+    if let Err(e) = sleep::register(&mut events) {
+        log::info!("not watching for suspend/resume: {}", e);
+    }
+    if let Err(e) = signals::register(&mut events) {
+        log::warning!("cannot install signal handlers: {}", e);
+    }
+    if let Some(path) = config::path(config_path) {
+        if let Err(e) = config::register(&mut events, &path) {
+            log::info!("not watching {}: {}", path.display(), e);
+        }
+    }
+    // 作为 i3bar 的 status_command 运行时，点击事件从标准输入送达
+    if format == "i3bar" && root_window.is_none() {
+        if let Err(e) = click::register(&mut events) {
+            log::warning!("cannot read click events: {}", e);
+        }
+    }
+    let cache = control::Cache::default();
+    if let Err(e) = control::serve(&mut events, cache.clone()) {
+        log::warning!("cannot open control socket: {}", e);
+    }
+    let mut sources = Sources::default();
+
+    let start = Instant::now();
+    let check_interval = Duration::from_secs(interval);
+    let mut next_check = start;
+    let mut last_tick = start;
+    let mut usage_carry = 0.0;
+    let mut woken: Vec<Wake> = Vec::new();
+
+    // 由 systemd 以 Type=notify 启动时，初始化完成后通知就绪，并按 WatchdogSec 发送心跳
+    let systemd = matches.get_flag("systemd");
+    let watchdog = systemd.then(systemd::watchdog_interval).flatten();
+    let mut next_watchdog = start;
+    if systemd {
+        if let Err(e) = systemd::notify("READY=1") {
+            log::warning!("notifying systemd: {}", e);
+        }
+    }
+
+    let mut config = config;
+    loop {
+        let settings = Settings::new(matches, &config, interval);
+        let alert_routes = config.alert_routes();
+        let modules = &settings.modules;
+        for module in modules {
+            log::info!("module {} every {}s", module.name, module.interval);
+        }
+        if modules
+            .iter()
+            .any(|module| module.name.starts_with("battery"))
+        {
+            let batteries = battery::list_batteries().unwrap_or_default();
+            log::info!(
+                "battery backend {}, batteries found in sysfs: {}",
+                battery::backend().name(),
+                if batteries.is_empty() {
+                    "none".to_string()
+                } else {
+                    batteries.join(", ")
+                }
+            );
+        }
+
+        // 重新加载后重新创建模块实例，所有模块立即刷新，已删除模块的缓存值一并清除
+        let now = Instant::now();
+        cache.borrow_mut().clear();
+        let mut slots: Vec<Slot> = modules
+            .iter()
+            .filter_map(|schedule| {
+                Some(Slot {
+                    module: schedule,
+                    instance: module::create(schedule, matches)?,
+                    interval: Duration::from_secs(schedule.interval),
+                    next: now,
+                    block: None,
+                    rule: schedule.rule(),
+                    paused: false,
+                })
+            })
+            .collect();
+        if let Some(adaptive) = &adaptive {
+            adapt(&mut slots, adaptive, now);
+        }
+        sources.register(&mut events, matches, &slots, &mut sink_hook);
+        // 滚动显示的当前帧和下一帧的时间，没有需要滚动的块时为 None
+        let mut frame = 0;
+        let mut next_scroll: Option<Instant> = None;
+        // csv/tsv 上次输出的表头
+        let mut last_header: Option<String> = None;
+        // 上次输出的行和时间：输出不变时不再打印，--min-interval 内的变化推迟到间隔结束时合并输出
+        let mut last_line: Option<String> = None;
+        let mut last_output: Option<Instant> = None;
+        let mut next_output: Option<Instant> = None;
+
+        config = loop {
+            let now = Instant::now();
+
+            // 刷新到期的模块，以及被事件或信号唤醒的模块，多个模块并行读取
+            let due: Vec<usize> = (0..slots.len())
+                .filter(|&i| {
+                    let slot = &slots[i];
+                    let signaled = woken.iter().any(|wake| match wake {
+                        Wake::All => true,
+                        Wake::Signal(n) => slot.module.signal == Some(*n),
+                        Wake::Module(name) => {
+                            slot.module.name == name
+                                || slot.block.as_ref().is_some_and(|b| b.name == name)
+                        }
+                        Wake::Click { name, instance, .. } => {
+                            clicked(slot, name, instance.as_deref())
+                        }
+                        Wake::Reload => false,
+                    });
+                    signaled || !slot.paused && slot.next <= now
+                })
+                .collect();
+            if !due.is_empty() {
+                let names: Vec<&str> = due.iter().map(|&i| slots[i].module.name).collect();
+                log::debug!("refreshing {}", names.join(", "));
+            }
+            let results = module::read_parallel(
+                slots
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(i, _)| due.contains(i))
+                    .map(|(_, slot)| &mut slot.instance)
+                    .collect(),
+            );
+            let updated = !due.is_empty();
+            for (i, result) in due.into_iter().zip(results) {
+                let slot = &mut slots[i];
+                slot.block = module_output(slot.module.name, result, settings.skip_missing);
+                slot.next = now + slot.interval;
+                if let Some(block) = &slot.block {
+                    cache
+                        .borrow_mut()
+                        .insert(slot.module.name.to_string(), block.clone());
+                }
+            }
+            let scroll_due = next_scroll.is_some_and(|next| next <= now);
+            if scroll_due {
+                frame += 1;
+            }
+            let output_due = next_output.is_some_and(|next| next <= now);
+            if updated || scroll_due || output_due {
+                let blocks: Vec<Block> =
+                    slots.iter().filter_map(|slot| slot.block.clone()).collect();
+                if updated {
+                    if let Some(service) = &service {
+                        service.update(&blocks);
+                    }
+                    if let Some(recorder) = recorder.as_mut() {
+                        if let Err(e) = recorder.record(&blocks) {
+                            log::error!("logging metrics: {}", e);
+                        }
+                    }
+                }
+                // 显示条件每次输出时判断，隐藏的块仍然记录和导出
+                let blocks: Vec<Block> = slots
+                    .iter()
+                    .filter_map(|slot| slot.block.clone().filter(|block| slot.rule.visible(block)))
+                    .collect();
+                next_scroll = settings
+                    .renderer
+                    .scrolls(&blocks)
+                    .then(|| match next_scroll {
+                        Some(next) if !scroll_due => next,
+                        _ => now + SCROLL_STEP,
+                    });
+                let line =
+                    settings
+                        .renderer
+                        .render_frame(&blocks, &settings.separator, Some(frame));
+                let changed = last_line.as_ref() != Some(&line);
+                let earliest = last_output.map(|last| last + min_interval);
+                next_output = None;
+                if changed && earliest.is_some_and(|earliest| earliest > now) {
+                    next_output = earliest;
+                } else {
+                    if changed {
+                        let header = settings.renderer.header(&blocks);
+                        if root_window.is_none() && header.is_some() && header != last_header {
+                            println!("{}", header.as_deref().unwrap());
+                            last_header = header;
+                        }
+                        match &root_window {
+                            Some(root_window) => {
+                                if let Err(e) = root_window.set_name(&line) {
+                                    log::error!("setting root window name: {}", e);
+                                }
+                            }
+                            None if format == "i3bar" => println!("{},", line),
+                            None => println!("{}", line),
+                        }
+                        last_output = Some(now);
+                    }
+                    for fifo in fifos.iter_mut().filter(|fifo| changed || !fifo.is_open()) {
+                        if format == "i3bar" {
+                            fifo.write_line(&format!("{},", line));
+                        } else {
+                            fifo.write_line(&line);
+                        }
+                    }
+                    last_line = Some(line);
+                }
+            }
+
+            if let Some(interval) = watchdog.filter(|_| next_watchdog <= now) {
+                next_watchdog = now + interval;
+                if let Err(e) = systemd::notify("WATCHDOG=1") {
+                    log::warning!("sending watchdog ping: {}", e);
+                }
+            }
+
+            if next_check <= now {
+                next_check = now + check_interval;
+                if let Some(notifier) = notifier.as_mut() {
+                    notifier.check(battery_device, &alert_routes);
+                }
+                if let Some(action) = battery_action.as_mut() {
+                    action.check(battery_device);
+                }
+                if let Some(guard) = memory_guard.as_mut() {
+                    guard.check(&alert_routes);
+                }
+                if let Some(alert) = fan_alert.as_mut() {
+                    alert.check(&alert_routes);
+                }
+                if let Some(adaptive) = adaptive.as_mut() {
+                    if adaptive.check(battery_device) {
+                        adapt(&mut slots, adaptive, now);
+                    }
+                }
+                if track_usage {
+                    // 事件唤醒时间隔不固定，按实际经过的时间累计
+                    usage_carry += last_tick.elapsed().as_secs_f64();
+                    last_tick = Instant::now();
+                    let elapsed = usage_carry.floor();
+                    usage_carry -= elapsed;
+                    if let Err(e) = usage::record(elapsed as i64, battery_device) {
+                        log::error!("recording usage: {}", e);
+                    }
+                    if let Err(e) = uptime::record() {
+                        log::error!("recording uptime: {}", e);
+                    }
+                }
+                if battery_history {
+                    if let Err(e) = history::record(battery_device) {
+                        log::error!("recording battery history: {}", e);
+                    }
+                }
+            }
+
+            // 等待到最近一个模块到期，或被事件提前唤醒
+            let deadline = slots
+                .iter()
+                .filter(|slot| !slot.paused)
+                .map(|slot| slot.next)
+                .chain([next_check])
+                .chain(watchdog.map(|_| next_watchdog))
+                .chain(next_scroll)
+                .chain(next_output)
+                .min()
+                .unwrap_or(next_check);
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            woken.clear();
+            events.wait(timeout, &mut woken);
+            for wake in &woken {
+                log::debug!("woken by {:?}", wake);
+                if let Wake::Click {
+                    name,
+                    instance,
+                    button,
+                } = wake
+                {
+                    let Some(slot) = slots
+                        .iter()
+                        .find(|slot| clicked(slot, name, instance.as_deref()))
+                    else {
+                        continue;
+                    };
+                    let command = usize::from(*button)
+                        .checked_sub(1)
+                        .and_then(|i| slot.module.actions.get(i).copied().flatten());
+                    click::dispatch(name, *button, command, media_player);
+                }
+            }
+            // 新配置有错误时保留当前配置继续运行
+            if woken.iter().any(|wake| matches!(wake, Wake::Reload)) {
+                match config::load(config_path) {
+                    Ok(config) => {
+                        log::info!("configuration reloaded");
+                        break config;
+                    }
+                    Err(e) => log::error!("reloading config: {}", e),
+                }
+            }
+        };
+    }
+}
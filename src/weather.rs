@@ -0,0 +1,324 @@
+use crate::daylight;
+use crate::error::Error;
+use crate::log;
+use crate::module::{self, Builtin, MetricValue};
+use crate::paths::cache_path;
+use crate::render::Block;
+use std::fs;
+use std::io;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 天气数据来源
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Provider {
+    // api.open-meteo.com，地名先经 geocoding-api.open-meteo.com 解析为经纬度
+    OpenMeteo,
+    // wttr.in，直接接受地名或经纬度
+    Wttr,
+}
+
+impl Provider {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "open-meteo" => Some(Provider::OpenMeteo),
+            "wttr" => Some(Provider::Wttr),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Provider::OpenMeteo => "open-meteo",
+            Provider::Wttr => "wttr",
+        }
+    }
+
+    fn fetch(self, location: &str) -> Result<Report, Error> {
+        match self {
+            Provider::OpenMeteo => {
+                let (lat, lon) = match daylight::parse_location(location) {
+                    Ok(coordinates) => coordinates,
+                    Err(_) => geocode(location)?,
+                };
+                parse_open_meteo(&http_get(&format!(
+                    "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&current=temperature_2m,weather_code",
+                    lat, lon
+                ))?)
+            }
+            Provider::Wttr => parse_wttr(&http_get(&format!(
+                "https://wttr.in/{}?m&format=%t|%C",
+                url_encode(location)
+            ))?),
+        }
+    }
+}
+
+// 当前气温（摄氏度）和天气状况
+#[derive(Debug, PartialEq)]
+struct Report {
+    temperature: f64,
+    condition: String,
+}
+
+// 使用 `curl` 发送 HTTP 请求，返回响应正文
+fn http_get(url: &str) -> Result<String, Error> {
+    let output = Command::new("curl")
+        .args(["-fsS", "--max-time", "10", url])
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                Error::BackendUnavailable("curl is not installed".to_string())
+            }
+            _ => Error::from(e),
+        })?;
+    if !output.status.success() {
+        return Err(Error::BackendUnavailable(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn url_encode(text: &str) -> String {
+    let mut encoded = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b',' => {
+                encoded.push(byte as char)
+            }
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+// 取出 JSON 中第一个 "key": 数值，只用于 open-meteo 的扁平响应
+fn json_number(json: &str, key: &str) -> Option<f64> {
+    let start = json.find(&format!("\"{}\":", key))? + key.len() + 3;
+    let value = json[start..].trim_start();
+    let end = value
+        .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+// 地名解析为经纬度，取第一个结果
+fn geocode(name: &str) -> Result<(f64, f64), Error> {
+    let json = http_get(&format!(
+        "https://geocoding-api.open-meteo.com/v1/search?name={}&count=1",
+        url_encode(name)
+    ))?;
+    let results = json
+        .find("\"results\"")
+        .map(|start| &json[start..])
+        .ok_or_else(|| Error::MissingDevice(format!("location '{}' not found", name)))?;
+    match (
+        json_number(results, "latitude"),
+        json_number(results, "longitude"),
+    ) {
+        (Some(lat), Some(lon)) => Ok((lat, lon)),
+        _ => Err(Error::Parse(format!(
+            "unexpected geocoding response for '{}'",
+            name
+        ))),
+    }
+}
+
+// WMO 天气代码（open-meteo 的 weather_code）
+fn describe_code(code: u32) -> &'static str {
+    match code {
+        0 => "Clear",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 | 48 => "Fog",
+        51 | 53 | 55 | 56 | 57 => "Drizzle",
+        61 | 63 | 65 | 66 | 67 => "Rain",
+        71 | 73 | 75 | 77 => "Snow",
+        80..=82 => "Rain showers",
+        85 | 86 => "Snow showers",
+        95..=99 => "Thunderstorm",
+        _ => "Unknown",
+    }
+}
+
+// 响应中 current_units 也带有同名字段，只在 "current" 对象中查找
+fn parse_open_meteo(json: &str) -> Result<Report, Error> {
+    let invalid = || Error::Parse("unexpected open-meteo response".to_string());
+    let current = &json[json.find("\"current\":{").ok_or_else(invalid)?..];
+    let temperature = json_number(current, "temperature_2m").ok_or_else(invalid)?;
+    let code = json_number(current, "weather_code").ok_or_else(invalid)?;
+    Ok(Report {
+        temperature,
+        condition: describe_code(code as u32).to_string(),
+    })
+}
+
+// wttr.in 的 format=%t|%C 输出形如 "+12°C|Partly cloudy"
+fn parse_wttr(text: &str) -> Result<Report, Error> {
+    let invalid = || Error::Parse(format!("unexpected wttr.in response '{}'", text.trim()));
+    let (temperature, condition) = text.trim().split_once('|').ok_or_else(invalid)?;
+    let temperature = temperature.trim().trim_end_matches("°C");
+    Ok(Report {
+        temperature: temperature.parse().map_err(|_| invalid())?,
+        condition: condition.trim().to_string(),
+    })
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+// 缓存文件每行 "来源\t地点\t获取时间（秒）\t气温\t天气状况"，按来源和地点各保留一条
+fn parse_cache(content: &str, provider: Provider, location: &str) -> Option<(u64, Report)> {
+    content.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [name, place, time, temperature, condition] = fields[..] else {
+            return None;
+        };
+        (name == provider.name() && place == location).then_some(())?;
+        Some((
+            time.parse().ok()?,
+            Report {
+                temperature: temperature.parse().ok()?,
+                condition: condition.to_string(),
+            },
+        ))
+    })
+}
+
+fn load_cache(provider: Provider, location: &str) -> Option<(u64, Report)> {
+    let content = fs::read_to_string(cache_path("weather.tsv").ok()?).ok()?;
+    parse_cache(&content, provider, location)
+}
+
+fn save_cache(provider: Provider, location: &str, report: &Report) -> Result<(), io::Error> {
+    let path = cache_path("weather.tsv")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut content: String = fs::read_to_string(&path)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| !line.starts_with(&format!("{}\t{}\t", provider.name(), location)))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    content.push_str(&format!(
+        "{}\t{}\t{}\t{}\t{}\n",
+        provider.name(),
+        location,
+        now_secs(),
+        report.temperature,
+        report.condition
+    ));
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, content)?;
+    fs::rename(tmp, path)
+}
+
+fn format_report(report: &Report) -> String {
+    format!("Weather: {:.0}°C {}", report.temperature, report.condition)
+}
+
+// 输出当前气温和天气状况，如 "Weather: 12°C Partly cloudy"
+// 缓存的结果在 ttl 秒内直接使用；获取失败（如离线）时退回上一次缓存的结果
+pub fn get_weather(location: &str, provider: Provider, ttl: u64) -> Result<String, Error> {
+    let cached = load_cache(provider, location);
+    if let Some((time, report)) = &cached {
+        if now_secs().saturating_sub(*time) < ttl {
+            return Ok(format_report(report));
+        }
+    }
+    match provider.fetch(location) {
+        Ok(report) => {
+            if let Err(e) = save_cache(provider, location, &report) {
+                log::warning!("cannot cache weather: {}", e);
+            }
+            Ok(format_report(&report))
+        }
+        Err(e) => match cached {
+            Some((_, report)) => {
+                log::warning!("fetching weather, using cached value: {}", e);
+                Ok(format_report(&report))
+            }
+            None => Err(e),
+        },
+    }
+}
+
+pub struct Weather {
+    location: Option<String>,
+    provider: Provider,
+    ttl: u64,
+}
+
+impl Builtin for Weather {
+    const ID: &'static str = "weather";
+    const BLOCK: &'static str = "weather";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        Weather {
+            location: matches.get_one::<String>("weather").cloned(),
+            provider: Provider::parse(matches.get_one::<String>("weather-provider").unwrap())
+                .unwrap(),
+            ttl: *matches.get_one::<u64>("weather-ttl").unwrap(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        let location = module::required(&self.location, "weather", "--weather LOCATION")?;
+        Ok(Block::new(
+            Self::BLOCK,
+            get_weather(location, self.provider, self.ttl)?,
+        ))
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        self.location
+            .is_none()
+            .then(|| "needs --weather LOCATION".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_provider_responses() {
+        let json = r#"{"latitude":52.52,"current_units":{"temperature_2m":"°C","weather_code":"wmo code"},"current":{"time":"2026-10-15T12:00","interval":900,"temperature_2m":12.4,"weather_code":2}}"#;
+        assert_eq!(
+            parse_open_meteo(json).unwrap(),
+            Report {
+                temperature: 12.4,
+                condition: "Partly cloudy".to_string()
+            }
+        );
+        assert_eq!(
+            parse_wttr("+12°C|Light rain\n").unwrap(),
+            Report {
+                temperature: 12.0,
+                condition: "Light rain".to_string()
+            }
+        );
+        assert!(matches!(
+            parse_wttr("Unknown location"),
+            Err(Error::Parse(_))
+        ));
+        assert_eq!(url_encode("New York"), "New+York");
+    }
+
+    #[test]
+    fn reads_cached_report() {
+        let content =
+            "wttr\tBerlin\t1760000000\t8\tFog\nopen-meteo\tBerlin\t1760000100\t9.5\tOvercast\n";
+        let (time, report) = parse_cache(content, Provider::OpenMeteo, "Berlin").unwrap();
+        assert_eq!(time, 1760000100);
+        assert_eq!(format_report(&report), "Weather: 10°C Overcast");
+        assert!(parse_cache(content, Provider::Wttr, "Paris").is_none());
+    }
+}
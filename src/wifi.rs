@@ -0,0 +1,385 @@
+use crate::error::Error;
+use crate::module::{Builtin, MetricValue};
+use crate::net::iface_path;
+use crate::render::Block;
+use crate::{read_file, sysfs};
+use rustix::fd::OwnedFd;
+use rustix::net::netlink::{self, SocketAddrNetlink};
+use rustix::net::{
+    recv, sendto, socket_with, AddressFamily, RecvFlags, SendFlags, SocketFlags, SocketType,
+};
+use std::io;
+
+// 无线网卡的连接详情通过 nl80211（generic netlink）查询，与 iw dev IFACE link 相同：
+// 当前 AP 的频率和信息元素（SSID、加密方式）来自扫描结果中已关联的 BSS，发送速率来自 AP 的 station 信息
+
+const NET_PATH: &str = "/sys/class/net";
+
+// linux/netlink.h
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+const NLM_F_REQUEST: u16 = 0x1;
+const NLM_F_ACK: u16 = 0x4;
+const NLM_F_DUMP: u16 = 0x300;
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+// linux/genetlink.h：通过控制器按名称查询 nl80211 的 family id
+const GENL_ID_CTRL: u16 = 0x10;
+const CTRL_CMD_GETFAMILY: u8 = 3;
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+// linux/nl80211.h
+const NL80211_CMD_GET_STATION: u8 = 17;
+const NL80211_CMD_GET_SCAN: u8 = 32;
+const NL80211_ATTR_IFINDEX: u16 = 3;
+const NL80211_ATTR_STA_INFO: u16 = 21;
+const NL80211_ATTR_BSS: u16 = 47;
+const NL80211_STA_INFO_TX_BITRATE: u16 = 8;
+const NL80211_RATE_INFO_BITRATE: u16 = 1;
+const NL80211_RATE_INFO_BITRATE32: u16 = 5;
+const NL80211_BSS_FREQUENCY: u16 = 2;
+const NL80211_BSS_CAPABILITY: u16 = 5;
+const NL80211_BSS_INFORMATION_ELEMENTS: u16 = 6;
+const NL80211_BSS_STATUS: u16 = 9;
+const NL80211_BSS_STATUS_ASSOCIATED: u32 = 1;
+
+// 802.11 信息元素
+const IE_SSID: u8 = 0;
+const IE_RSN: u8 = 48;
+const IE_VENDOR: u8 = 221;
+// 能力字段中的 Privacy 位，没有 RSN/WPA 信息元素时表示 WEP
+const CAPABILITY_PRIVACY: u16 = 0x0010;
+
+// 按 netlink 属性格式（长度、类型、内容，4 字节对齐）拆分，返回 (类型, 内容)
+fn attributes(mut data: &[u8]) -> Vec<(u16, &[u8])> {
+    let mut attrs = Vec::new();
+    while data.len() >= 4 {
+        let len = usize::from(u16::from_ne_bytes([data[0], data[1]]));
+        let kind = u16::from_ne_bytes([data[2], data[3]]) & NLA_TYPE_MASK;
+        if len < 4 || len > data.len() {
+            break;
+        }
+        attrs.push((kind, &data[4..len]));
+        data = &data[((len + 3) & !3).min(data.len())..];
+    }
+    attrs
+}
+
+fn attribute(data: &[u8], kind: u16) -> Option<&[u8]> {
+    attributes(data)
+        .into_iter()
+        .find_map(|(k, value)| (k == kind).then_some(value))
+}
+
+fn attribute_u32(data: &[u8], kind: u16) -> Option<u32> {
+    Some(u32::from_ne_bytes(
+        attribute(data, kind)?.get(..4)?.try_into().ok()?,
+    ))
+}
+
+fn attribute_u16(data: &[u8], kind: u16) -> Option<u16> {
+    Some(u16::from_ne_bytes(
+        attribute(data, kind)?.get(..2)?.try_into().ok()?,
+    ))
+}
+
+fn push_attribute(buf: &mut Vec<u8>, kind: u16, value: &[u8]) {
+    buf.extend_from_slice(&((4 + value.len()) as u16).to_ne_bytes());
+    buf.extend_from_slice(&kind.to_ne_bytes());
+    buf.extend_from_slice(value);
+    buf.resize((buf.len() + 3) & !3, 0);
+}
+
+struct Netlink(OwnedFd);
+
+impl Netlink {
+    fn open() -> Result<Self, io::Error> {
+        Ok(Netlink(socket_with(
+            AddressFamily::NETLINK,
+            SocketType::RAW,
+            SocketFlags::CLOEXEC,
+            Some(netlink::GENERIC),
+        )?))
+    }
+
+    // 发送一条 generic netlink 请求，返回各应答消息的属性部分（去掉消息头和 genl 头）
+    fn request(
+        &self,
+        family: u16,
+        command: u8,
+        flags: u16,
+        attrs: &[(u16, &[u8])],
+    ) -> Result<Vec<Vec<u8>>, io::Error> {
+        let mut message = vec![0u8; 16];
+        message.extend_from_slice(&[command, 1, 0, 0]);
+        for (kind, value) in attrs {
+            push_attribute(&mut message, *kind, value);
+        }
+        let len = message.len() as u32;
+        message[..4].copy_from_slice(&len.to_ne_bytes());
+        message[4..6].copy_from_slice(&family.to_ne_bytes());
+        message[6..8].copy_from_slice(&(NLM_F_REQUEST | NLM_F_ACK | flags).to_ne_bytes());
+        message[8..12].copy_from_slice(&1u32.to_ne_bytes());
+        sendto(
+            &self.0,
+            &message,
+            SendFlags::empty(),
+            &SocketAddrNetlink::new(0, 0),
+        )?;
+
+        // 应答可能分为多个数据报，以 NLMSG_DONE 或确认（错误码为 0 的 NLMSG_ERROR）结束
+        let mut replies = Vec::new();
+        let mut buf = vec![0u8; 65536];
+        loop {
+            let (received, _) = recv(&self.0, &mut buf, RecvFlags::empty())?;
+            let mut data = &buf[..received];
+            while data.len() >= 16 {
+                let len = u32::from_ne_bytes(data[..4].try_into().unwrap()) as usize;
+                let kind = u16::from_ne_bytes([data[4], data[5]]);
+                if len < 16 || len > data.len() {
+                    break;
+                }
+                match kind {
+                    NLMSG_DONE => return Ok(replies),
+                    NLMSG_ERROR => {
+                        let code = i32::from_ne_bytes(data[16..20].try_into().unwrap());
+                        if code == 0 {
+                            return Ok(replies);
+                        }
+                        return Err(io::Error::from_raw_os_error(-code));
+                    }
+                    _ if len >= 20 => replies.push(data[20..len].to_vec()),
+                    _ => {}
+                }
+                data = &data[((len + 3) & !3).min(data.len())..];
+            }
+        }
+    }
+
+    fn family(&self, name: &str) -> Result<u16, Error> {
+        let mut name = name.as_bytes().to_vec();
+        name.push(0);
+        let replies = self
+            .request(
+                GENL_ID_CTRL,
+                CTRL_CMD_GETFAMILY,
+                0,
+                &[(CTRL_ATTR_FAMILY_NAME, &name)],
+            )
+            .map_err(|e| Error::BackendUnavailable(format!("nl80211 not available: {}", e)))?;
+        replies
+            .iter()
+            .find_map(|reply| attribute_u16(reply, CTRL_ATTR_FAMILY_ID))
+            .ok_or_else(|| Error::BackendUnavailable("nl80211 not available".to_string()))
+    }
+}
+
+// 第一块无线网卡（uevent 中 DEVTYPE=wlan），如 wlan0、wlp3s0
+fn wireless_interface() -> Result<String, Error> {
+    let mut ifaces: Vec<String> = sysfs::read_dir(NET_PATH)?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|iface| {
+            read_file(iface_path(iface).join("uevent"))
+                .is_ok_and(|uevent| uevent.lines().any(|line| line == "DEVTYPE=wlan"))
+        })
+        .collect();
+    ifaces.sort();
+    ifaces
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::MissingDevice("no wireless interface found".to_string()))
+}
+
+// 频段，6 GHz 信道的中心频率从 5955 MHz 开始
+fn band(frequency: u32) -> &'static str {
+    match frequency {
+        0..=3000 => "2.4 GHz",
+        3001..=5930 => "5 GHz",
+        5931..=7125 => "6 GHz",
+        _ => "60 GHz",
+    }
+}
+
+// 信息元素为 (编号, 长度, 内容) 序列
+fn elements(mut data: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut elements = Vec::new();
+    while data.len() >= 2 {
+        let len = usize::from(data[1]);
+        if data.len() < 2 + len {
+            break;
+        }
+        elements.push((data[0], &data[2..2 + len]));
+        data = &data[2 + len..];
+    }
+    elements
+}
+
+// RSN 信息元素中的认证方式（AKM）：版本、组密码、成对密码列表之后，每项为 OUI 00-0F-AC 加类型
+fn rsn_akms(rsn: &[u8]) -> Vec<u8> {
+    let pairwise = match rsn.get(6..8) {
+        Some(count) => usize::from(u16::from_le_bytes([count[0], count[1]])),
+        None => return Vec::new(),
+    };
+    let start = 8 + 4 * pairwise;
+    let count = match rsn.get(start..start + 2) {
+        Some(count) => usize::from(u16::from_le_bytes([count[0], count[1]])),
+        None => return Vec::new(),
+    };
+    rsn.get(start + 2..)
+        .unwrap_or_default()
+        .chunks_exact(4)
+        .take(count)
+        .filter(|suite| suite[..3] == [0x00, 0x0F, 0xAC])
+        .map(|suite| suite[3])
+        .collect()
+}
+
+// 加密方式：WPA3（SAE）、WPA2（PSK）、两者兼容的过渡模式、企业版（802.1X）、OWE、WPA、WEP 或 open
+fn security(ies: &[u8], capability: u16) -> &'static str {
+    let elements = elements(ies);
+    if let Some((_, rsn)) = elements.iter().find(|(id, _)| *id == IE_RSN) {
+        let akms = rsn_akms(rsn);
+        let has = |types: &[u8]| akms.iter().any(|akm| types.contains(akm));
+        let sae = has(&[8, 24]);
+        let psk = has(&[2, 6]);
+        return match () {
+            _ if has(&[12, 13]) => "WPA3-Enterprise",
+            _ if has(&[1, 5]) => "WPA2-Enterprise",
+            _ if sae && psk => "WPA2/WPA3",
+            _ if sae => "WPA3",
+            _ if has(&[18]) => "OWE",
+            _ => "WPA2",
+        };
+    }
+    let wpa = elements
+        .iter()
+        .any(|(id, data)| *id == IE_VENDOR && data.starts_with(&[0x00, 0x50, 0xF2, 0x01]));
+    match () {
+        _ if wpa => "WPA",
+        _ if capability & CAPABILITY_PRIVACY != 0 => "WEP",
+        _ => "open",
+    }
+}
+
+// 发送速率，单位 100 kbit/s
+fn tx_bitrate(station: &[u8]) -> Option<u32> {
+    let info = attribute(station, NL80211_ATTR_STA_INFO)?;
+    let rate = attribute(info, NL80211_STA_INFO_TX_BITRATE)?;
+    attribute_u32(rate, NL80211_RATE_INFO_BITRATE32)
+        .or_else(|| attribute_u16(rate, NL80211_RATE_INFO_BITRATE).map(u32::from))
+}
+
+// 由已关联 BSS 的属性和发送速率生成输出
+fn describe(bss: &[u8], bitrate: Option<u32>) -> String {
+    let ies = attribute(bss, NL80211_BSS_INFORMATION_ELEMENTS).unwrap_or_default();
+    let ssid = elements(ies)
+        .into_iter()
+        .find_map(|(id, data)| (id == IE_SSID).then(|| String::from_utf8_lossy(data).to_string()))
+        .filter(|ssid| !ssid.is_empty())
+        .unwrap_or_else(|| "hidden".to_string());
+    let mut fields = vec![ssid];
+    if let Some(frequency) = attribute_u32(bss, NL80211_BSS_FREQUENCY) {
+        fields.push(band(frequency).to_string());
+    }
+    if let Some(rate) = bitrate {
+        fields.push(format!("{}.{} Mb/s", rate / 10, rate % 10));
+    }
+    let capability = attribute_u16(bss, NL80211_BSS_CAPABILITY).unwrap_or(0);
+    fields.push(security(ies, capability).to_string());
+    format!("WIFI: {}", fields.join(" "))
+}
+
+// 输出无线连接的 SSID、频段、发送速率和加密方式，如 "WIFI: HomeNet 5 GHz 866.7 Mb/s WPA2"
+// iface 为 "auto" 时使用第一块无线网卡；未连接时输出为空
+pub fn get_wifi_detail(iface: &str) -> Result<String, Error> {
+    let iface = if iface == "auto" {
+        wireless_interface()?
+    } else {
+        iface.to_string()
+    };
+    let index: u32 = read_file(iface_path(&iface).join("ifindex"))?
+        .parse()
+        .map_err(|_| Error::Parse(format!("{}: invalid ifindex", iface)))?;
+    let index = index.to_ne_bytes();
+
+    let socket = Netlink::open()?;
+    let family = socket.family("nl80211")?;
+    let request = |command| {
+        socket
+            .request(
+                family,
+                command,
+                NLM_F_DUMP,
+                &[(NL80211_ATTR_IFINDEX, &index)],
+            )
+            .map_err(|e| Error::BackendUnavailable(format!("nl80211 on {}: {}", iface, e)))
+    };
+    let scan = request(NL80211_CMD_GET_SCAN)?;
+    let associated = scan.iter().find_map(|reply| {
+        let bss = attribute(reply, NL80211_ATTR_BSS)?;
+        (attribute_u32(bss, NL80211_BSS_STATUS)? == NL80211_BSS_STATUS_ASSOCIATED).then_some(bss)
+    });
+    let Some(bss) = associated else {
+        return Ok(String::new());
+    };
+    let stations = request(NL80211_CMD_GET_STATION)?;
+    let bitrate = stations.iter().find_map(|station| tx_bitrate(station));
+    Ok(describe(bss, bitrate))
+}
+
+pub struct WifiDetail {
+    iface: String,
+}
+
+impl Builtin for WifiDetail {
+    const ID: &'static str = "wifi-detail";
+    const BLOCK: &'static str = "wifi";
+
+    fn new(matches: &clap::ArgMatches) -> Self {
+        WifiDetail {
+            iface: matches
+                .get_one::<String>("wifi-detail")
+                .map_or("auto", String::as_str)
+                .to_string(),
+        }
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_wifi_detail(&self.iface)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_association() {
+        // SSID "HomeNet"，RSN：CCMP，AKM 为 PSK 和 SAE
+        let mut ies = vec![IE_SSID, 7];
+        ies.extend_from_slice(b"HomeNet");
+        ies.extend_from_slice(&[
+            IE_RSN, 24, 1, 0, 0x00, 0x0F, 0xAC, 4, 1, 0, 0x00, 0x0F, 0xAC, 4, 2, 0, 0x00, 0x0F,
+            0xAC, 2, 0x00, 0x0F, 0xAC, 8, 0, 0,
+        ]);
+        let mut bss = Vec::new();
+        push_attribute(&mut bss, NL80211_BSS_FREQUENCY, &5180u32.to_ne_bytes());
+        push_attribute(&mut bss, NL80211_BSS_INFORMATION_ELEMENTS, &ies);
+        push_attribute(&mut bss, NL80211_BSS_STATUS, &1u32.to_ne_bytes());
+        assert_eq!(attribute_u32(&bss, NL80211_BSS_STATUS), Some(1));
+        assert_eq!(
+            describe(&bss, Some(8667)),
+            "WIFI: HomeNet 5 GHz 866.7 Mb/s WPA2/WPA3"
+        );
+
+        assert_eq!(band(2437), "2.4 GHz");
+        assert_eq!(band(5955), "6 GHz");
+        assert_eq!(security(&[], CAPABILITY_PRIVACY), "WEP");
+        assert_eq!(security(&[IE_VENDOR, 4, 0x00, 0x50, 0xF2, 0x01], 0), "WPA");
+
+        sysfs::use_fixtures();
+        assert_eq!(wireless_interface().unwrap(), "wlan0");
+    }
+}
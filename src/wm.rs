@@ -0,0 +1,364 @@
+use crate::error::Error;
+use crate::event_loop::EventLoop;
+use crate::module::{Builtin, EventInterest, MetricValue};
+use crate::render::Block;
+use crate::watch::Wake;
+use rustix::event::epoll;
+use std::env;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// sway（以及 i3）IPC 消息类型
+const GET_WORKSPACES: u32 = 1;
+const SUBSCRIBE: u32 = 2;
+const GET_TREE: u32 = 4;
+
+const MAGIC: &[u8] = b"i3-ipc";
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+// 窗口管理器的 IPC 套接字
+enum Ipc {
+    // $SWAYSOCK 或 $I3SOCK，二进制的 i3-ipc 协议
+    Sway(PathBuf),
+    // Hyprland 的请求套接字 .socket.sock，事件套接字为同目录的 .socket2.sock
+    Hyprland(PathBuf),
+}
+
+fn detect() -> Result<Ipc, Error> {
+    for var in ["SWAYSOCK", "I3SOCK"] {
+        if let Some(path) = env::var_os(var).filter(|path| !path.is_empty()) {
+            return Ok(Ipc::Sway(PathBuf::from(path)));
+        }
+    }
+    if let Some(signature) = env::var_os("HYPRLAND_INSTANCE_SIGNATURE") {
+        // Hyprland 0.40 起套接字位于 $XDG_RUNTIME_DIR/hypr，之前位于 /tmp/hypr
+        let mut dirs: Vec<PathBuf> = env::var_os("XDG_RUNTIME_DIR")
+            .map(|dir| PathBuf::from(dir).join("hypr"))
+            .into_iter()
+            .collect();
+        dirs.push(PathBuf::from("/tmp/hypr"));
+        let dir = dirs
+            .into_iter()
+            .map(|dir| dir.join(&signature))
+            .find(|dir| dir.join(".socket.sock").exists())
+            .ok_or_else(|| Error::MissingDevice("Hyprland IPC socket not found".to_string()))?;
+        return Ok(Ipc::Hyprland(dir));
+    }
+    Err(Error::MissingDevice(
+        "not running under sway, i3 or Hyprland".to_string(),
+    ))
+}
+
+fn connect(path: &Path) -> Result<UnixStream, Error> {
+    let stream = UnixStream::connect(path).map_err(|e| {
+        Error::BackendUnavailable(format!("cannot connect to {}: {}", path.display(), e))
+    })?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    Ok(stream)
+}
+
+// i3-ipc 消息："i3-ipc" + 长度（u32）+ 类型（u32）+ 正文，整数为本机字节序
+fn sway_send(stream: &mut UnixStream, kind: u32, payload: &str) -> Result<(), io::Error> {
+    let mut message = MAGIC.to_vec();
+    message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    message.extend_from_slice(&kind.to_ne_bytes());
+    message.extend_from_slice(payload.as_bytes());
+    stream.write_all(&message)
+}
+
+fn sway_request(path: &Path, kind: u32) -> Result<String, Error> {
+    let mut stream = connect(path)?;
+    sway_send(&mut stream, kind, "")?;
+    let mut header = [0u8; 14];
+    stream.read_exact(&mut header)?;
+    if &header[..6] != MAGIC {
+        return Err(Error::Parse("invalid i3-ipc reply".to_string()));
+    }
+    let len = u32::from_ne_bytes(header[6..10].try_into().unwrap()) as usize;
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload)?;
+    Ok(String::from_utf8_lossy(&payload).to_string())
+}
+
+// Hyprland 的请求为一行文本，"j/" 前缀表示返回 JSON，回复后服务端关闭连接
+fn hyprland_request(dir: &Path, command: &str) -> Result<String, Error> {
+    let mut stream = connect(&dir.join(".socket.sock"))?;
+    stream.write_all(format!("j/{}", command).as_bytes())?;
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+    Ok(reply)
+}
+
+// 跳过 json[start..] 开头的一个 JSON 值，返回值结束的位置
+fn skip_value(json: &str, start: usize) -> usize {
+    let bytes = json.as_bytes();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut i = start;
+    while i < bytes.len() {
+        match (in_string, bytes[i]) {
+            (true, b'\\') => i += 1,
+            (true, b'"') => {
+                in_string = false;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            (true, _) => {}
+            (false, b'"') => in_string = true,
+            (false, b'{' | b'[') => depth += 1,
+            (false, b'}' | b']') if depth == 0 => return i,
+            (false, b'}' | b']') => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            (false, b',') if depth == 0 => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+// 对象或数组的直接成员：对象为 (键, 值)，数组的键为空
+fn members(json: &str) -> Vec<(String, &str)> {
+    let json = json.trim();
+    let mut members = Vec::new();
+    let mut i = 1;
+    while i < json.len().saturating_sub(1) {
+        let rest = &json[i..];
+        let offset = rest.len() - rest.trim_start_matches([' ', '\n', '\t', '\r', ',']).len();
+        i += offset;
+        if i >= json.len() - 1 {
+            break;
+        }
+        let mut key = String::new();
+        if json.starts_with('{') {
+            let end = skip_value(json, i);
+            key = string(&json[i..end]).unwrap_or_default();
+            i = end + json[end..].find(':').map_or(0, |colon| colon + 1);
+            i += json[i..].len() - json[i..].trim_start().len();
+        }
+        let end = skip_value(json, i);
+        members.push((key, json[i..end].trim()));
+        i = end;
+    }
+    members
+}
+
+fn field<'a>(object: &'a str, key: &str) -> Option<&'a str> {
+    members(object)
+        .into_iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value)
+}
+
+// 解码 JSON 字符串，null 等非字符串值返回 None
+fn string(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+    let mut text = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => text.push('\n'),
+            't' => text.push('\t'),
+            'u' => {
+                let code: String = chars.by_ref().take(4).collect();
+                text.push(
+                    char::from_u32(u32::from_str_radix(&code, 16).ok()?).unwrap_or('\u{fffd}'),
+                );
+            }
+            other => text.push(other),
+        }
+    }
+    Some(text)
+}
+
+// 工作区名称，聚焦的工作区加方括号，有紧急窗口的加 "!"，如 "WS: 1 [2] 3!"
+fn format_workspaces(workspaces: &[(String, bool, bool)]) -> String {
+    let names: Vec<String> = workspaces
+        .iter()
+        .map(|(name, focused, urgent)| match (focused, urgent) {
+            (true, _) => format!("[{}]", name),
+            (false, true) => format!("{}!", name),
+            (false, false) => name.clone(),
+        })
+        .collect();
+    format!("WS: {}", names.join(" "))
+}
+
+pub fn get_workspaces() -> Result<String, Error> {
+    let mut workspaces = Vec::new();
+    match detect()? {
+        Ipc::Sway(path) => {
+            for (_, workspace) in members(&sway_request(&path, GET_WORKSPACES)?) {
+                workspaces.push((
+                    field(workspace, "name")
+                        .and_then(string)
+                        .unwrap_or_default(),
+                    field(workspace, "focused") == Some("true"),
+                    field(workspace, "urgent") == Some("true"),
+                ));
+            }
+        }
+        Ipc::Hyprland(dir) => {
+            let active = hyprland_request(&dir, "activeworkspace")?;
+            let active = field(&active, "id");
+            let reply = hyprland_request(&dir, "workspaces")?;
+            let mut list: Vec<(i64, &str)> = members(&reply)
+                .into_iter()
+                .filter_map(|(_, workspace)| {
+                    Some((field(workspace, "id")?.parse().ok()?, workspace))
+                })
+                // 负数 id 为 special 工作区（scratchpad）
+                .filter(|(id, _)| *id > 0)
+                .collect();
+            list.sort_by_key(|(id, _)| *id);
+            for (_, workspace) in list {
+                workspaces.push((
+                    field(workspace, "name")
+                        .and_then(string)
+                        .unwrap_or_default(),
+                    field(workspace, "id") == active,
+                    false,
+                ));
+            }
+        }
+    }
+    Ok(format_workspaces(&workspaces))
+}
+
+// 在 sway 的布局树中查找聚焦的节点，平铺和浮动窗口都是子节点
+fn focused_node(node: &str) -> Option<&str> {
+    if field(node, "focused") == Some("true") {
+        return Some(node);
+    }
+    ["nodes", "floating_nodes"].iter().find_map(|key| {
+        members(field(node, key)?)
+            .into_iter()
+            .find_map(|(_, child)| focused_node(child))
+    })
+}
+
+// 聚焦窗口的标题；空工作区聚焦时聚焦节点是工作区本身，此时为 None
+fn focused_title(tree: &str) -> Option<String> {
+    let node = focused_node(tree)?;
+    if field(node, "type").and_then(string).as_deref() == Some("workspace") {
+        return None;
+    }
+    field(node, "name").and_then(string)
+}
+
+// 输出聚焦窗口的标题，没有聚焦窗口时为空
+pub fn get_window_title() -> Result<String, Error> {
+    Ok(match detect()? {
+        Ipc::Sway(path) => focused_title(&sway_request(&path, GET_TREE)?).unwrap_or_default(),
+        Ipc::Hyprland(dir) => {
+            let window = hyprland_request(&dir, "activewindow")?;
+            field(&window, "title").and_then(string).unwrap_or_default()
+        }
+    })
+}
+
+// 监听工作区和窗口事件，切换工作区或窗口标题变化时立即刷新
+pub fn subscribe(events: &mut EventLoop) -> Result<(), Error> {
+    match detect()? {
+        Ipc::Sway(path) => {
+            let mut stream = UnixStream::connect(&path)?;
+            sway_send(&mut stream, SUBSCRIBE, r#"["workspace","window"]"#)?;
+            let mut buf = [0u8; 4096];
+            // 事件为二进制消息，不需要解析，有数据即刷新
+            events.add(stream, epoll::EventFlags::IN, move |stream, woken| {
+                if stream.read(&mut buf)? == 0 {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                woken.push(Wake::All);
+                Ok(())
+            })?;
+        }
+        Ipc::Hyprland(dir) => {
+            // 事件每行形如 "workspace>>2"、"activewindow>>kitty,~"
+            let stream = UnixStream::connect(dir.join(".socket2.sock"))?;
+            events.add_lines(stream, |line, woken| {
+                if line.starts_with("workspace") || line.starts_with("activewindow") {
+                    woken.push(Wake::All);
+                }
+            })?;
+        }
+    }
+    Ok(())
+}
+
+pub struct Workspace;
+
+impl Builtin for Workspace {
+    const ID: &'static str = "workspace";
+    const BLOCK: &'static str = "workspace";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        Workspace
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_workspaces()?))
+    }
+
+    fn interests(&self) -> EventInterest {
+        EventInterest::WM
+    }
+}
+
+pub struct WindowTitle;
+
+impl Builtin for WindowTitle {
+    const ID: &'static str = "window-title";
+    const BLOCK: &'static str = "window_title";
+
+    fn new(_: &clap::ArgMatches) -> Self {
+        WindowTitle
+    }
+
+    fn read_block(&mut self) -> MetricValue {
+        Ok(Block::new(Self::BLOCK, get_window_title()?))
+    }
+
+    fn interests(&self) -> EventInterest {
+        EventInterest::WM
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ipc_replies() {
+        let workspaces = r#"[{"num":1,"name":"1","visible":false,"focused":false,"urgent":false,"rect":{"x":0,"y":0}},{"num":2,"name":"2: web","visible":true,"focused":true,"urgent":false},{"num":3,"name":"3","focused":false,"urgent":true}]"#;
+        let parsed: Vec<(String, bool, bool)> = members(workspaces)
+            .into_iter()
+            .map(|(_, ws)| {
+                (
+                    field(ws, "name").and_then(string).unwrap(),
+                    field(ws, "focused") == Some("true"),
+                    field(ws, "urgent") == Some("true"),
+                )
+            })
+            .collect();
+        assert_eq!(format_workspaces(&parsed), "WS: 1 [2: web] 3!");
+        let tree = r#"{"name":"root","type":"root","focused":false,"nodes":[{"name":"eDP-1","type":"output","focused":false,"nodes":[{"name":"1","type":"workspace","focused":false,"nodes":[{"name":"vim \"main.rs\"","type":"con","focused":true,"nodes":[]}],"floating_nodes":[]}]}]}"#;
+        assert_eq!(focused_title(tree).as_deref(), Some("vim \"main.rs\""));
+        let empty = r#"{"name":"root","type":"root","focused":false,"nodes":[{"name":"4","type":"workspace","focused":true,"nodes":[]}]}"#;
+        assert_eq!(focused_title(empty), None);
+        assert_eq!(string(r#""café""#).as_deref(), Some("café"));
+    }
+}
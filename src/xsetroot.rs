@@ -0,0 +1,34 @@
+use std::io;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, PropMode};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt;
+
+// X11 根窗口，dwm 从根窗口名称（WM_NAME）读取状态栏文本
+pub struct RootWindow {
+    conn: RustConnection,
+    root: u32,
+}
+
+impl RootWindow {
+    // 连接 $DISPLAY 指定的 X 服务器
+    pub fn connect() -> Result<Self, io::Error> {
+        let (conn, screen) = x11rb::connect(None).map_err(io::Error::other)?;
+        let root = conn.setup().roots[screen].root;
+        Ok(RootWindow { conn, root })
+    }
+
+    // 设置根窗口名称，效果等同于 `xsetroot -name`
+    pub fn set_name(&self, name: &str) -> Result<(), io::Error> {
+        self.conn
+            .change_property8(
+                PropMode::REPLACE,
+                self.root,
+                AtomEnum::WM_NAME,
+                AtomEnum::STRING,
+                name.as_bytes(),
+            )
+            .map_err(io::Error::other)?;
+        self.conn.flush().map_err(io::Error::other)
+    }
+}
@@ -0,0 +1,333 @@
+// 集成测试：在临时目录中构造假的 /sys、/proc 文件树，用 --sysfs-root 运行程序，检查输出和退出码
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+// 临时文件树，测试结束时删除
+struct Tree {
+    root: PathBuf,
+}
+
+// 一次运行的标准输出（去掉末尾换行）、标准错误和退出码
+struct Output {
+    stdout: String,
+    stderr: String,
+    code: i32,
+}
+
+impl Tree {
+    fn new() -> Self {
+        let root = std::env::temp_dir().join(format!(
+            "sys-montion-test-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("home")).unwrap();
+        Tree { root }
+    }
+
+    // 写入 sysfs/procfs 属性文件，path 为绝对路径，如 /sys/class/power_supply/BAT0/status
+    fn file(&self, path: &str, content: &str) -> &Self {
+        let path = self.root.join(path.trim_start_matches('/'));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, format!("{}\n", content)).unwrap();
+        self
+    }
+
+    fn battery(&self, name: &str, status: &str, capacity: i64) -> &Self {
+        let dir = format!("/sys/class/power_supply/{}", name);
+        self.file(&format!("{}/type", dir), "Battery")
+            .file(&format!("{}/status", dir), status)
+            .file(&format!("{}/capacity", dir), &capacity.to_string())
+            .file(&format!("{}/energy_full", dir), "50000000")
+            .file(&format!("{}/energy_full_design", dir), "60000000")
+            .file(
+                &format!("{}/energy_now", dir),
+                &(capacity * 500000).to_string(),
+            )
+            .file(&format!("{}/power_now", dir), "10000000")
+    }
+
+    fn ac(&self, online: bool) -> &Self {
+        self.file("/sys/class/power_supply/AC/type", "Mains").file(
+            "/sys/class/power_supply/AC/online",
+            if online { "1" } else { "0" },
+        )
+    }
+
+    fn meminfo(&self, content: &str) -> &Self {
+        self.file("/proc/meminfo", content)
+    }
+
+    // 不读取用户的配置文件和环境变量，外部命令（amixer、gdbus 等）都不可用
+    fn run(&self, args: &[&str]) -> Output {
+        let output = Command::new(env!("CARGO_BIN_EXE_sys-montion"))
+            .env_clear()
+            .env("HOME", self.root.join("home"))
+            .arg("--sysfs-root")
+            .arg(&self.root)
+            .args(args)
+            .output()
+            .unwrap();
+        Output {
+            stdout: String::from_utf8_lossy(&output.stdout)
+                .trim_end()
+                .to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            code: output.status.code().unwrap_or(-1),
+        }
+    }
+
+    // 运行并要求成功，返回标准输出
+    fn output(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+        assert_eq!(output.code, 0, "{:?} failed: {}", args, output.stderr);
+        output.stdout
+    }
+}
+
+impl Drop for Tree {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+const MEMINFO: &str = "MemTotal:       16000000 kB
+MemFree:         2000000 kB
+MemAvailable:   12000000 kB
+Buffers:          500000 kB
+Cached:          6000000 kB
+SwapTotal:       8000000 kB
+SwapFree:        8000000 kB";
+
+#[test]
+fn discharging_battery() {
+    let tree = Tree::new();
+    tree.battery("BAT0", "Discharging", 80).ac(false);
+    assert_eq!(tree.output(&["--battery"]), "Discharging: 80%");
+    assert_eq!(tree.output(&["--battery-state"]), "Discharging");
+    assert_eq!(tree.output(&["--battery-capacity"]), "80%");
+    assert_eq!(tree.output(&["--battery-time"]), "4h 0m left");
+    assert_eq!(tree.output(&["--battery-power"]), "10.0W");
+    assert_eq!(tree.output(&["--battery-health"]), "83% (17% wear)");
+    assert_eq!(tree.output(&["--ac"]), "AC: off");
+    assert_eq!(tree.output(&["--charger-info"]), "PWR: off");
+}
+
+#[test]
+fn charging_and_full_batteries() {
+    let tree = Tree::new();
+    tree.battery("BAT0", "Charging", 40).ac(true);
+    assert_eq!(tree.output(&["--battery"]), "Charging: 40%");
+    assert_eq!(tree.output(&["--ac"]), "AC: on");
+    tree.battery("BAT1", "Full", 100);
+    assert_eq!(
+        tree.output(&["--battery-device", "BAT1", "--battery-state"]),
+        "Full"
+    );
+    // 两块电池按能量合计：40% 和 100% 平均为 70%
+    assert_eq!(tree.output(&["--battery-capacity"]), "70%");
+}
+
+#[test]
+fn battery_details() {
+    let tree = Tree::new();
+    tree.battery("BAT0", "Discharging", 80)
+        .file("/sys/class/power_supply/BAT0/manufacturer", "SMP")
+        .file("/sys/class/power_supply/BAT0/technology", "Li-ion")
+        .file("/sys/class/power_supply/BAT0/cycle_count", "12")
+        .file(
+            "/sys/class/power_supply/BAT0/charge_control_start_threshold",
+            "75",
+        )
+        .file(
+            "/sys/class/power_supply/BAT0/charge_control_end_threshold",
+            "80",
+        );
+    assert_eq!(
+        tree.output(&["--battery-info"]),
+        "BAT0: SMP Li-ion, 12 cycles"
+    );
+    assert_eq!(tree.output(&["--charge-threshold"]), "CHG: 75-80%");
+}
+
+#[test]
+fn missing_battery() {
+    let tree = Tree::new();
+    tree.file("/sys/class/power_supply/.keep", "");
+    let output = tree.run(&["--battery"]);
+    assert_eq!(output.stdout, "Unknown");
+    assert_eq!(output.code, 3);
+    // --skip-missing 时不输出缺少硬件的模块，正常退出
+    let output = tree.run(&["--battery", "--skip-missing"]);
+    assert_eq!((output.stdout.as_str(), output.code), ("", 0));
+}
+
+#[test]
+fn memory() {
+    let tree = Tree::new();
+    tree.meminfo(MEMINFO);
+    assert_eq!(tree.output(&["--memory"]), "MEM: 3.8G/15.3G (25%)");
+    assert_eq!(
+        tree.output(&["--memory", "--memory-format", "percent"]),
+        "MEM: 25%"
+    );
+}
+
+#[test]
+fn malformed_meminfo() {
+    let tree = Tree::new();
+    tree.meminfo("MemTotal: lots\nMemAvailable: some");
+    let output = tree.run(&["--memory"]);
+    assert_eq!(output.stdout, "Unknown");
+    assert_eq!(output.code, 4);
+    assert!(output.stderr.contains("memory"), "{}", output.stderr);
+}
+
+#[test]
+fn backlights() {
+    let tree = Tree::new();
+    tree.file("/sys/class/backlight/amdgpu_bl1/brightness", "128")
+        .file("/sys/class/backlight/amdgpu_bl1/max_brightness", "255")
+        .file("/sys/class/leds/tpacpi::kbd_backlight/brightness", "1")
+        .file("/sys/class/leds/tpacpi::kbd_backlight/max_brightness", "2");
+    assert_eq!(tree.output(&["--backlight"]), "BL: 50%");
+    assert_eq!(
+        tree.output(&["--backlight", "--backlight-format", "raw"]),
+        "BL: 128/255"
+    );
+    assert_eq!(tree.output(&["--kbd-backlight"]), "KBD: 50%");
+}
+
+#[test]
+fn missing_backlight() {
+    let tree = Tree::new();
+    tree.file("/sys/class/backlight/.keep", "");
+    let output = tree.run(&["--backlight"]);
+    assert_eq!(output.stdout, "Unknown");
+    assert_eq!(output.code, 3);
+}
+
+#[test]
+fn malformed_backlight() {
+    let tree = Tree::new();
+    tree.file("/sys/class/backlight/amdgpu_bl1/brightness", "bright")
+        .file("/sys/class/backlight/amdgpu_bl1/max_brightness", "255");
+    assert_eq!(tree.run(&["--backlight"]).code, 4);
+}
+
+#[test]
+fn temperatures() {
+    let tree = Tree::new();
+    tree.file("/sys/class/hwmon/hwmon0/name", "coretemp")
+        .file("/sys/class/hwmon/hwmon0/temp1_label", "Package id 0")
+        .file("/sys/class/hwmon/hwmon0/temp1_input", "66000")
+        .file("/sys/class/hwmon/hwmon0/temp2_label", "Core 0")
+        .file("/sys/class/hwmon/hwmon0/temp2_input", "58000")
+        .file("/sys/class/hwmon/hwmon0/temp3_label", "Core 1")
+        .file("/sys/class/hwmon/hwmon0/temp3_input", "64000")
+        .file("/sys/class/hwmon/hwmon1/name", "nvme")
+        .file("/sys/class/hwmon/hwmon1/temp1_label", "Composite")
+        .file("/sys/class/hwmon/hwmon1/temp1_input", "45000");
+    assert_eq!(tree.output(&["--temp"]), "TEMP: 66°C");
+    assert_eq!(tree.output(&["--temp", "nvme:Composite"]), "TEMP: 45°C");
+    assert_eq!(
+        tree.output(&["--temp", "cpu", "--cpu-temp-agg", "per-core"]),
+        "TEMP: C0 58°C C1 64°C"
+    );
+    assert_eq!(tree.run(&["--temp", "k10temp"]).code, 3);
+}
+
+#[test]
+fn lid_dock_and_locks() {
+    let tree = Tree::new();
+    tree.file("/proc/acpi/button/lid/LID/state", "state:      closed")
+        .file("/sys/class/drm/card0-eDP-1/status", "connected")
+        .file("/sys/class/drm/card0-DP-2/status", "connected")
+        .file("/sys/class/leds/input5::capslock/brightness", "1")
+        .file("/sys/class/leds/input5::numlock/brightness", "0");
+    assert_eq!(tree.output(&["--lid"]), "LID: closed");
+    assert_eq!(tree.output(&["--docked"]), "DOCK: DP-2");
+    assert_eq!(tree.output(&["--locks"]), "CAPS");
+    tree.file("/sys/class/drm/card0-DP-2/status", "disconnected");
+    assert_eq!(tree.output(&["--docked"]), "");
+}
+
+#[test]
+fn combined_output_and_formats() {
+    let tree = Tree::new();
+    tree.battery("BAT0", "Discharging", 80).meminfo(MEMINFO);
+    assert_eq!(
+        tree.output(&["--battery", "--memory"]),
+        "Discharging: 80% | MEM: 3.8G/15.3G (25%)"
+    );
+    let output = tree.output(&["--battery", "--format", "waybar"]);
+    assert!(
+        output.contains("\"text\":\"Discharging: 80%\""),
+        "{}",
+        output
+    );
+    // 部分模块失败时其余模块照常输出，退出码取第一个错误
+    let output = tree.run(&["--memory", "--backlight"]);
+    assert_eq!(output.stdout, "MEM: 3.8G/15.3G (25%) | Unknown");
+    assert_eq!(output.code, 3);
+}
+
+#[test]
+fn module_thresholds() {
+    let tree = Tree::new();
+    tree.battery("BAT0", "Discharging", 80).meminfo(MEMINFO);
+    let check = |args: &[&str]| {
+        let mut all = vec!["--battery", "--memory", "--check"];
+        all.extend(args);
+        tree.run(&all).code
+    };
+    // 内存的数值是已用内存的百分比 25，而不是 "3.8G" 中的 3.8
+    assert_eq!(check(&["--warn-below", "20", "--critical-below", "10"]), 0);
+    assert_eq!(check(&["--warn-below", "battery=90"]), 1);
+    assert_eq!(check(&["--critical-above", "memory=20"]), 2);
+    assert_eq!(tree.run(&["--warn-below", "nope=5"]).code, 2);
+    let output = tree.output(&["--memory", "--format", "waybar"]);
+    assert!(output.contains("\"percentage\":25,"), "{}", output);
+}
+
+#[test]
+fn prompt_segments() {
+    let tree = Tree::new();
+    tree.battery("BAT0", "Discharging", 80);
+    assert_eq!(
+        tree.output(&["--prompt-segment", "battery"]),
+        "Discharging: 80%"
+    );
+    // 提示符中出错时不输出任何内容
+    let output = tree.run(&["--prompt-segment", "backlight"]);
+    assert_eq!(
+        (output.stdout.as_str(), output.stderr.as_str(), output.code),
+        ("", "", 3)
+    );
+}
+
+#[test]
+fn invalid_arguments() {
+    let tree = Tree::new();
+    assert_eq!(tree.run(&["--no-such-option"]).code, 2);
+    assert_eq!(tree.run(&["--memory-pressure", "101"]).code, 2);
+    // 设备名按 `list` 列出的设备检查
+    tree.battery("BAT0", "Full", 100);
+    let output = tree.run(&["--battery-device", "BAT9", "--battery"]);
+    assert_eq!(output.code, 2);
+    assert!(
+        output.stderr.contains("available: BAT0"),
+        "{}",
+        output.stderr
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_sys-montion"))
+        .args(["--sysfs-root", "/nonexistent/sys-montion", "--battery"])
+        .output()
+        .unwrap();
+    assert_ne!(output.status.code(), Some(0));
+}